@@ -3,6 +3,12 @@
 
 # RISC0 XMSS Benchmark Script (CPU Only)
 # This script runs benchmarks without CUDA acceleration
+#
+# Set FEATURES=poseidon2 to benchmark the Poseidon2 hash backend instead of Keccak.
+#
+# Set BENCH_SWEEP=1 to prove every validator count of {1, 4, 16, 64} at heights 8 and 13 once
+# instead, writing the results to target/bench-results as CSV and JSON. BENCH_SWEEP_VALIDATORS and
+# BENCH_SWEEP_TREE_HEIGHTS take comma-separated lists to override the grid.
 
 set -e
 
@@ -16,7 +22,7 @@ export RUST_LOG=info
 export RISC0_PROVER=local  # Use local prover
 
 echo "📦 Building without CUDA support..."
-cargo build --release -p risc0-host
+cargo build --release -p risc0-host ${FEATURES:+--features $FEATURES}
 
 echo
 echo "💻 Running benchmarks on CPU..."
@@ -24,7 +30,7 @@ echo "   Sample size: 10 iterations per benchmark"
 echo
 
 # Run the Criterion benchmark
-cargo bench -p risc0-host
+cargo bench -p risc0-host ${FEATURES:+--features $FEATURES}
 
 echo
 echo "📊 Benchmark results saved to:"
@@ -3,6 +3,12 @@
 
 # SP1 XMSS Benchmark Script (CPU Only)
 # This script runs benchmarks without CUDA acceleration
+#
+# Set FEATURES=poseidon2 to benchmark the Poseidon2 hash backend instead of Keccak.
+#
+# Set BENCH_SWEEP=1 to prove every validator count of {1, 4, 16, 64} at heights 8 and 13 once
+# instead, writing the results to target/bench-results as CSV and JSON. BENCH_SWEEP_VALIDATORS and
+# BENCH_SWEEP_TREE_HEIGHTS take comma-separated lists to override the grid.
 
 set -e
 
@@ -16,11 +22,11 @@ export RUST_LOG=info
 
 echo "🔨 Building SP1 guest program..."
 cd crates/sp1/guest
-cargo prove build
+cargo prove build ${FEATURES:+--features $FEATURES}
 cd ../../..
 
 echo "📦 Building SP1 host..."
-cargo build --release -p sp1-host
+cargo build --release -p sp1-host ${FEATURES:+--features $FEATURES}
 
 echo
 echo "💻 Running benchmarks on CPU..."
@@ -28,7 +34,7 @@ echo "   Sample size: 10 iterations per benchmark"
 echo
 
 # Run the Criterion benchmark
-cargo bench -p sp1-host
+cargo bench -p sp1-host ${FEATURES:+--features $FEATURES}
 
 echo
 echo "📊 Benchmark results saved to:"
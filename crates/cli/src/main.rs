@@ -0,0 +1,181 @@
+// Copyright 2025 Irreducible Inc.
+//! Generates XMSS keys, signs messages and verifies signatures with `leansig-core`.
+//!
+//! `keygen` writes the state of a signer to a file, from which `sign` recreates the signer and
+//! to which it records the epochs signed at. Every command prints its results as text, or as a
+//! JSON object with `--json`. Hashes, params and messages are hex, with an optional `0x` prefix.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, bail};
+use clap::{Parser, Subcommand};
+use leansig_core::{
+    Message, Param, Signature, SignerState,
+    hash::Hash,
+    spec::{SPEC_1, SPEC_2, Spec},
+    verify_signature,
+};
+use leansig_shared::print_report;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use serde_json::json;
+
+#[derive(Parser)]
+#[command(name = "leansig", about = "Signs and verifies messages with XMSS")]
+struct Cli {
+    /// Print the results as a JSON object
+    #[arg(long, global = true)]
+    json: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Generates the keys of a signer, writes its state to a file and prints its root and param
+    Keygen {
+        /// Where to write the signer state, which must be kept secret
+        #[arg(long)]
+        state: PathBuf,
+        /// The spec of the signature scheme, `1` or `2`
+        #[arg(long, default_value = "2", value_parser = parse_spec)]
+        spec: Spec,
+        /// The height of the XMSS tree, for a lifetime of `2^tree_height` epochs
+        #[arg(long, default_value_t = 4)]
+        tree_height: u32,
+        /// Derive the keys from this seed instead of from the randomness of the system, for
+        /// reproducible keys that are not secret
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+    /// Signs a message at an epoch after every epoch signed at before, and writes the signature
+    /// to a file
+    Sign {
+        /// The signer state written by `keygen`, updated with the epoch
+        #[arg(long)]
+        state: PathBuf,
+        /// The epoch to sign at
+        #[arg(long)]
+        epoch: u64,
+        /// The 32-byte message, in hex
+        #[arg(long)]
+        message_hex: Message,
+        /// Where to write the signature
+        #[arg(long)]
+        output: PathBuf,
+    },
+    /// Verifies a signature of a message against the root and param of a signer, failing if it
+    /// does not verify
+    Verify {
+        /// The root printed by `keygen`, in hex
+        #[arg(long)]
+        root: Hash,
+        /// The param printed by `keygen`, in hex
+        #[arg(long)]
+        param: Param,
+        /// The spec of the signature scheme, `1` or `2`
+        #[arg(long, default_value = "2", value_parser = parse_spec)]
+        spec: Spec,
+        /// The 32-byte message, in hex
+        #[arg(long)]
+        message_hex: Message,
+        /// The signature written by `sign`
+        #[arg(long)]
+        signature: PathBuf,
+    },
+}
+
+fn parse_spec(name: &str) -> Result<Spec, String> {
+    match name {
+        "1" | "SPEC_1" => Ok(SPEC_1),
+        "2" | "SPEC_2" => Ok(SPEC_2),
+        _ => Err(format!("unknown spec {name}, expected 1 or 2")),
+    }
+}
+
+fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    let report = match cli.command {
+        Command::Keygen {
+            state,
+            spec,
+            tree_height,
+            seed,
+        } => {
+            let Some(lifetime) = 1usize.checked_shl(tree_height) else {
+                bail!("tree height {tree_height} is too large");
+            };
+            let seed = match seed {
+                Some(seed) => ChaCha20Rng::seed_from_u64(seed).random(),
+                None => rand::random(),
+            };
+            let signer_state = SignerState::new(spec, lifetime, seed);
+            let signer = signer_state.signer()?;
+            write_state(&signer_state, &state)?;
+            json!({
+                "root": signer.root.to_string(),
+                "param": signer.param.to_string(),
+                "lifetime": lifetime,
+            })
+        }
+        Command::Sign {
+            state,
+            epoch,
+            message_hex,
+            output,
+        } => {
+            let mut signer_state = SignerState::read_from_file(&state)
+                .with_context(|| format!("failed to read {}", state.display()))?;
+            if epoch >= signer_state.lifetime {
+                bail!(
+                    "epoch {epoch} is past the lifetime of {} epochs",
+                    signer_state.lifetime
+                );
+            }
+            if !signer_state.consume(epoch) {
+                bail!(
+                    "epoch {epoch} is before the next epoch {}, so it may have been signed at",
+                    signer_state.next_epoch
+                );
+            }
+            let mut signer = signer_state.signer()?;
+            // Record the epoch before signing, so that a crash cannot lead to signing twice.
+            write_state(&signer_state, &state)?;
+            let signature = signer.sign(epoch as usize, &message_hex)?;
+            signature
+                .write_to_file(&output)
+                .with_context(|| format!("failed to write {}", output.display()))?;
+            json!({ "epoch": epoch, "root": signer.root.to_string() })
+        }
+        Command::Verify {
+            root,
+            param,
+            spec,
+            message_hex,
+            signature,
+        } => {
+            let signature = Signature::read_from_file(&signature, &spec)
+                .with_context(|| format!("failed to read {}", signature.display()))?;
+            let valid = verify_signature(&spec, &param, &message_hex, &signature, &root);
+            print_report(
+                cli.json,
+                &json!({
+                    "valid": valid,
+                    "epoch": signature.hash_tree_proof.leaf_index(),
+                }),
+            );
+            if !valid {
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+    };
+    print_report(cli.json, &report);
+    Ok(())
+}
+
+fn write_state(state: &SignerState, path: &Path) -> anyhow::Result<()> {
+    state
+        .write_to_file(path)
+        .with_context(|| format!("failed to write {}", path.display()))
+}
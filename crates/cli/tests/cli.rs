@@ -0,0 +1,120 @@
+// Copyright 2025 Irreducible Inc.
+//! Drives the `leansig` binary end to end, through the files it writes.
+
+use std::path::Path;
+
+use assert_cmd::Command;
+use serde_json::Value;
+
+const MESSAGE: &str = "0x2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a";
+const OTHER_MESSAGE: &str = "2b2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a";
+
+fn leansig() -> Command {
+    Command::new(env!("CARGO_BIN_EXE_leansig"))
+}
+
+/// Runs `leansig --json` with `args`, expecting it to succeed, and returns its report
+fn run(args: &[&str]) -> Value {
+    let output = leansig().arg("--json").args(args).assert().success();
+    serde_json::from_slice(&output.get_output().stdout).unwrap()
+}
+
+fn path(path: &Path) -> &str {
+    path.to_str().unwrap()
+}
+
+fn verify(keys: &Value, message: &str, signature: &Path) -> assert_cmd::assert::Assert {
+    leansig()
+        .arg("--json")
+        .args(["verify", "--root", keys["root"].as_str().unwrap()])
+        .args(["--param", keys["param"].as_str().unwrap()])
+        .args(["--message-hex", message, "--signature", path(signature)])
+        .assert()
+}
+
+#[test]
+fn test_keygen_sign_verify() {
+    let dir = tempfile::tempdir().unwrap();
+    let state = dir.path().join("state");
+    let signature = dir.path().join("signature");
+    let keys = run(&[
+        "keygen",
+        "--state",
+        path(&state),
+        "--tree-height",
+        "2",
+        "--seed",
+        "1",
+    ]);
+    assert_eq!(keys["lifetime"], 4);
+
+    let signed = run(&[
+        "sign",
+        "--state",
+        path(&state),
+        "--epoch",
+        "1",
+        "--message-hex",
+        MESSAGE,
+        "--output",
+        path(&signature),
+    ]);
+    assert_eq!(signed["root"], keys["root"]);
+
+    let output = verify(&keys, MESSAGE, &signature).success();
+    let report: Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    assert_eq!(report, serde_json::json!({ "valid": true, "epoch": 1 }));
+
+    let output = verify(&keys, OTHER_MESSAGE, &signature).failure();
+    let report: Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    assert_eq!(report["valid"], false);
+}
+
+#[test]
+fn test_sign_rejects_used_and_past_epochs() {
+    let dir = tempfile::tempdir().unwrap();
+    let state = dir.path().join("state");
+    let signature = dir.path().join("signature");
+    run(&[
+        "keygen",
+        "--state",
+        path(&state),
+        "--tree-height",
+        "2",
+        "--seed",
+        "2",
+    ]);
+    let sign = |epoch: &str| {
+        leansig()
+            .args(["sign", "--state", path(&state), "--epoch", epoch])
+            .args(["--message-hex", MESSAGE, "--output", path(&signature)])
+            .assert()
+    };
+
+    sign("2").success();
+    let stderr = |assert: assert_cmd::assert::Assert| {
+        String::from_utf8(assert.get_output().stderr.clone()).unwrap()
+    };
+    assert!(stderr(sign("2").failure()).contains("epoch 2 is before the next epoch 3"));
+    assert!(stderr(sign("0").failure()).contains("epoch 0 is before the next epoch 3"));
+    assert!(stderr(sign("4").failure()).contains("past the lifetime of 4 epochs"));
+    sign("3").success();
+}
+
+#[test]
+fn test_rejects_files_of_another_kind() {
+    let dir = tempfile::tempdir().unwrap();
+    let state = dir.path().join("state");
+    let keys = run(&[
+        "keygen",
+        "--state",
+        path(&state),
+        "--tree-height",
+        "2",
+        "--seed",
+        "3",
+    ]);
+    let output = verify(&keys, MESSAGE, &state).failure();
+    let stderr = String::from_utf8(output.get_output().stderr.clone()).unwrap();
+    assert!(stderr.contains("payload kind signer state instead of signature"));
+}
@@ -0,0 +1,37 @@
+// Copyright 2025 Irreducible Inc.
+use criterion::{Criterion, black_box, criterion_group, criterion_main};
+use leansig_core::{
+    Signer,
+    hash::{Blake3Backend, KeccakBackend, TweakableHash},
+    spec::SPEC_2,
+};
+use rand::{SeedableRng, rngs::StdRng};
+
+const MAX_RETRIES: usize = 1_000_000;
+const TREE_HEIGHT: usize = 13;
+
+fn keygen<H: TweakableHash>() -> Signer<H> {
+    Signer::new_with_backend(
+        StdRng::seed_from_u64(0),
+        MAX_RETRIES,
+        SPEC_2,
+        1 << TREE_HEIGHT,
+    )
+    .unwrap()
+}
+
+/// Compares key generation for a tree of height 13 across hash backends.
+fn backend_benchmarks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("keygen");
+    group.sample_size(10);
+    group.bench_function(KeccakBackend::NAME, |b| {
+        b.iter(|| black_box(keygen::<KeccakBackend>()));
+    });
+    group.bench_function(Blake3Backend::NAME, |b| {
+        b.iter(|| black_box(keygen::<Blake3Backend>()));
+    });
+    group.finish();
+}
+
+criterion_group!(backends, backend_benchmarks);
+criterion_main!(backends);
@@ -0,0 +1,110 @@
+// Copyright 2025 Irreducible Inc.
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use criterion::{Criterion, black_box, criterion_group, criterion_main};
+use leansig_core::{
+    Message, Nonce, Param, Pk, Signer,
+    hash::{Hash, KeccakBackend, TweakableHash},
+    hash_tree::HashTreeProof,
+    spec::SPEC_2,
+    verify_signature, verify_signatures_same_root,
+};
+use rand::{SeedableRng, rngs::StdRng};
+
+const HEIGHT: usize = 13;
+const NUM_EPOCHS: usize = 16;
+
+/// The number of tree nodes hashed by [`CountingKeccak`].
+static TREE_HASHES: AtomicUsize = AtomicUsize::new(0);
+
+/// Keccak that counts the tree nodes it hashes.
+#[derive(Clone)]
+struct CountingKeccak(KeccakBackend);
+
+impl TweakableHash for CountingKeccak {
+    const NAME: &'static str = "counting-keccak256";
+
+    fn new(param: &Param) -> Self {
+        Self(KeccakBackend::new(param))
+    }
+
+    fn hash_message(&self, epoch: u32, message: &Message, nonce: &Nonce) -> Hash {
+        self.0.hash_message(epoch, message, nonce)
+    }
+
+    fn hash_chain_step(&self, epoch: u32, chain_index: u32, pos_in_chain: u32, hash: Hash) -> Hash {
+        self.0
+            .hash_chain_step(epoch, chain_index, pos_in_chain, hash)
+    }
+
+    fn hash_tree_node(&self, left: &Hash, right: &Hash, level: u32, index: u32) -> Hash {
+        TREE_HASHES.fetch_add(1, Ordering::Relaxed);
+        self.0.hash_tree_node(left, right, level, index)
+    }
+
+    fn hash_public_key(&self, epoch: u32, public_key: &Pk) -> Hash {
+        self.0.hash_public_key(epoch, public_key)
+    }
+}
+
+/// Compares verifying 16 consecutive epochs of one validator one by one against verifying them
+/// together, which hashes the ancestors the leaves share once.
+fn batch_verify_benchmarks(c: &mut Criterion) {
+    let spec = SPEC_2;
+    let mut signer = Signer::new(
+        StdRng::seed_from_u64(0),
+        1_000_000,
+        spec.clone(),
+        1 << HEIGHT,
+    )
+    .unwrap();
+    let (param, root) = (signer.param.clone(), signer.root);
+    let items: Vec<_> = (0..NUM_EPOCHS)
+        .map(|epoch| {
+            let message = Message([epoch as u8; 32]);
+            (message, signer.sign(epoch, &message).unwrap())
+        })
+        .collect();
+
+    // 16 separate paths of 13 nodes, against the 15 nodes below the common ancestor of the
+    // leaves and the 9 above it.
+    let hasher = CountingKeccak::new(&param);
+    for (_, signature) in &items {
+        let epoch = signature.hash_tree_proof.leaf_index() as u32;
+        let leaf = hasher.hash_public_key(epoch, &signature.public_key);
+        assert!(
+            signature
+                .hash_tree_proof
+                .verify_with_backend(&hasher, &leaf, &root)
+        );
+    }
+    let independent = TREE_HASHES.swap(0, Ordering::Relaxed);
+    let leaves: Vec<_> = (items.iter())
+        .map(|(_, signature)| {
+            let epoch = signature.hash_tree_proof.leaf_index() as u32;
+            let leaf = hasher.hash_public_key(epoch, &signature.public_key);
+            (&signature.hash_tree_proof, leaf)
+        })
+        .collect();
+    assert!(HashTreeProof::verify_batch_with_backend(
+        &hasher, &leaves, &root
+    ));
+    let joint = TREE_HASHES.swap(0, Ordering::Relaxed);
+    println!("tree hashes for {NUM_EPOCHS} epochs: {independent} independent, {joint} joint");
+
+    let mut group = c.benchmark_group("same_root_verify");
+    group.bench_function("independent", |b| {
+        b.iter(|| {
+            for (message, signature) in &items {
+                black_box(verify_signature(&spec, &param, message, signature, &root));
+            }
+        });
+    });
+    group.bench_function("joint", |b| {
+        b.iter(|| black_box(verify_signatures_same_root(&spec, &param, &root, &items)));
+    });
+    group.finish();
+}
+
+criterion_group!(batch_verify, batch_verify_benchmarks);
+criterion_main!(batch_verify);
@@ -0,0 +1,48 @@
+// Copyright 2025 Irreducible Inc.
+use criterion::{Criterion, black_box, criterion_group, criterion_main};
+use leansig_core::{
+    Message, Param,
+    code::{grind_parallel, grind_seeded},
+    spec::{SPEC_1, SPEC_2},
+};
+use rand::{SeedableRng, rngs::StdRng};
+
+const MAX_RETRIES: usize = 1_000_000;
+
+/// Compares serial and parallel nonce grinding over the same candidate sequences.
+fn grind_benchmarks(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(0);
+    let message = Message([42; 32]);
+
+    for (name, spec) in [("SPEC_1", SPEC_1), ("SPEC_2", SPEC_2)] {
+        let param = Param::random(spec.param_len, &mut rng);
+        let mut group = c.benchmark_group(format!("grind_{name}"));
+
+        // Vary the seed between iterations so that we measure the average number of attempts
+        // rather than a single lucky or unlucky sequence.
+        let mut counter = 0u64;
+        group.bench_function("serial", |b| {
+            b.iter(|| {
+                counter += 1;
+                let mut seed = [0; 32];
+                seed[..8].copy_from_slice(&counter.to_le_bytes());
+                black_box(grind_seeded(&spec, MAX_RETRIES, &param, &message, &seed));
+            });
+        });
+
+        let mut counter = 0u64;
+        group.bench_function("parallel", |b| {
+            b.iter(|| {
+                counter += 1;
+                let mut seed = [0; 32];
+                seed[..8].copy_from_slice(&counter.to_le_bytes());
+                black_box(grind_parallel(&spec, MAX_RETRIES, &param, &message, &seed));
+            });
+        });
+
+        group.finish();
+    }
+}
+
+criterion_group!(grind, grind_benchmarks);
+criterion_main!(grind);
@@ -0,0 +1,24 @@
+// Copyright 2025 Irreducible Inc.
+use criterion::{BenchmarkId, Criterion, black_box, criterion_group, criterion_main};
+use leansig_core::{Param, hash::Hash, hash_tree::HashTree};
+use rand::{SeedableRng, rngs::StdRng};
+
+/// Measures building a hash tree over random leaves.
+///
+/// Run with and without `--features parallel` to compare the serial and parallel constructions.
+fn hash_tree_benchmarks(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(0);
+    let param = Param::random(18, &mut rng);
+
+    let mut group = c.benchmark_group("hash_tree_new");
+    for height in [10, 13] {
+        let leaves: Vec<_> = (0..1 << height).map(|_| Hash::random(&mut rng)).collect();
+        group.bench_with_input(BenchmarkId::from_parameter(height), &leaves, |b, leaves| {
+            b.iter(|| black_box(HashTree::new(&param, leaves.clone())));
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(hash_tree, hash_tree_benchmarks);
+criterion_main!(hash_tree);
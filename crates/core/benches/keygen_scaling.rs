@@ -0,0 +1,48 @@
+// Copyright 2025 Irreducible Inc.
+//! Times `Signer::new` over tree heights 8 to 14, to show how key generation scales with the
+//! lifetime.
+//!
+//! Prints `spec,tree_height,lifetime,seconds` lines as CSV on stdout, and the progress of the
+//! key generation on stderr, e.g. `cargo bench -p leansig-core --bench keygen_scaling >
+//! keygen.csv`.
+use std::time::Instant;
+
+use leansig_core::{
+    KeygenPhase, Signer,
+    spec::{SPEC_1, SPEC_2},
+};
+use rand::{SeedableRng, rngs::StdRng};
+
+const MAX_RETRIES: usize = 1_000_000;
+
+fn main() {
+    // `cargo test --benches` runs this binary too, which should not take minutes
+    if std::env::args().any(|arg| arg == "--test") {
+        return;
+    }
+
+    println!("spec,tree_height,lifetime,seconds");
+    for (name, spec) in [("SPEC_1", SPEC_1), ("SPEC_2", SPEC_2)] {
+        for tree_height in 8..=14 {
+            let lifetime = 1 << tree_height;
+            let start = Instant::now();
+            let signer = Signer::new_with_progress(
+                StdRng::seed_from_u64(0),
+                MAX_RETRIES,
+                spec.clone(),
+                lifetime,
+                |progress| match progress.phase {
+                    KeygenPhase::DeriveKeys => eprint!(
+                        "\r{name} h{tree_height}: {}/{} key pairs",
+                        progress.epochs_done, progress.epochs_total
+                    ),
+                    KeygenPhase::BuildTree => eprintln!(", building the tree"),
+                },
+            )
+            .unwrap();
+            let seconds = start.elapsed().as_secs_f64();
+            std::hint::black_box(signer.root);
+            println!("{name},{tree_height},{lifetime},{seconds:.3}");
+        }
+    }
+}
@@ -0,0 +1,159 @@
+// Copyright 2025 Irreducible Inc.
+//! Native costs of the validator hot paths, outside of any zkVM.
+//!
+//! The benchmark ids are `native_<operation>/<spec>/h<tree height>`, or
+//! `native_<operation>/<spec>` for the operations that don't depend on the tree height, so that
+//! the results of two commits can be diffed id by id.
+use criterion::{BenchmarkId, Criterion, black_box, criterion_group, criterion_main};
+use leansig_core::{
+    AggregatedSignature, AggregatedVerifier, Message, Param, Signer, ValidatorSignature,
+    code::grind_seeded,
+    hash::Hash,
+    hash_tree::HashTree,
+    spec::{SPEC_1, SPEC_2, Spec},
+    verify_signature,
+};
+use rand::{SeedableRng, rngs::StdRng};
+
+const SPECS: [(&str, Spec); 2] = [("SPEC_1", SPEC_1), ("SPEC_2", SPEC_2)];
+const TREE_HEIGHTS: [usize; 3] = [4, 8, 13];
+const MAX_RETRIES: usize = 1_000_000;
+const NUM_VALIDATORS: usize = 16;
+/// The seeds the grind benchmark cycles through, so that every run tries the same nonces.
+const NUM_GRIND_SEEDS: u64 = 64;
+
+fn height_id(name: &str, height: usize) -> BenchmarkId {
+    BenchmarkId::new(name, format!("h{height}"))
+}
+
+/// Measures `Signer::new`, which derives the public key of every epoch and builds their tree.
+fn keygen_benchmarks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("native_keygen");
+    group.sample_size(10);
+    for (name, spec) in SPECS {
+        for height in TREE_HEIGHTS {
+            group.bench_function(height_id(name, height), |b| {
+                b.iter(|| {
+                    let rng = StdRng::seed_from_u64(0);
+                    black_box(Signer::new(rng, MAX_RETRIES, spec.clone(), 1 << height).unwrap())
+                });
+            });
+        }
+    }
+    group.finish();
+}
+
+/// Measures `Signer::sign` and `verify_signature`, whose authentication paths grow with the
+/// tree height.
+fn sign_verify_benchmarks(c: &mut Criterion) {
+    let message = Message([42; 32]);
+    for (name, spec) in SPECS {
+        for height in TREE_HEIGHTS {
+            let rng = StdRng::seed_from_u64(0);
+            let mut signer = Signer::new(rng, MAX_RETRIES, spec.clone(), 1 << height).unwrap();
+
+            let mut group = c.benchmark_group("native_sign");
+            group.bench_function(height_id(name, height), |b| {
+                b.iter(|| black_box(signer.sign(0, &message)));
+            });
+            group.finish();
+
+            let signature = signer.sign(0, &message).unwrap();
+            let mut group = c.benchmark_group("native_verify");
+            group.bench_function(height_id(name, height), |b| {
+                b.iter(|| {
+                    black_box(verify_signature(
+                        &spec,
+                        &signer.param,
+                        &message,
+                        &signature,
+                        &signer.root,
+                    ))
+                });
+            });
+            group.finish();
+        }
+    }
+}
+
+/// Measures `HashTree::new` over as many random leaves as a tree of each height has.
+fn hash_tree_benchmarks(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut group = c.benchmark_group("native_hash_tree");
+    for (name, spec) in SPECS {
+        let param = Param::random(spec.param_len, &mut rng);
+        for height in TREE_HEIGHTS {
+            let leaves: Vec<_> = (0..1 << height).map(|_| Hash::random(&mut rng)).collect();
+            group.bench_function(height_id(name, height), |b| {
+                b.iter(|| black_box(HashTree::new(&param, leaves.clone())));
+            });
+        }
+    }
+    group.finish();
+}
+
+/// Measures `code::grind` over a fixed cycle of seeds, i.e. the average cost of the attempts
+/// needed to hit the target sum rather than that of a single lucky or unlucky sequence.
+fn grind_benchmarks(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(0);
+    let message = Message([42; 32]);
+    let mut group = c.benchmark_group("native_grind");
+    for (name, spec) in SPECS {
+        let param = Param::random(spec.param_len, &mut rng);
+        let mut counter = 0u64;
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                counter = (counter + 1) % NUM_GRIND_SEEDS;
+                let mut seed = [0; 32];
+                seed[..8].copy_from_slice(&counter.to_le_bytes());
+                black_box(grind_seeded(&spec, MAX_RETRIES, &param, &message, &seed));
+            });
+        });
+    }
+    group.finish();
+}
+
+/// Measures `AggregatedVerifier::verify` on an aggregate of 16 validators.
+fn aggregate_verify_benchmarks(c: &mut Criterion) {
+    let message = Message([42; 32]);
+    let mut group = c.benchmark_group("native_aggregate_verify");
+    group.sample_size(20);
+    for (name, spec) in SPECS {
+        for height in TREE_HEIGHTS {
+            let mut validators: Vec<_> = (0..NUM_VALIDATORS as u64)
+                .map(|seed| {
+                    let rng = StdRng::seed_from_u64(seed);
+                    Signer::new(rng, MAX_RETRIES, spec.clone(), 1 << height).unwrap()
+                })
+                .collect();
+            let signatures = (validators.iter_mut())
+                .map(|validator| ValidatorSignature {
+                    epoch: 0,
+                    signature: validator.sign(0, &message).unwrap().into(),
+                    xmss_root: validator.root,
+                    param: validator.param.clone(),
+                    validator_index: None,
+                })
+                .collect();
+            let registry = (validators.iter())
+                .map(|v| (v.root, v.param.clone()))
+                .collect();
+            let verifier = AggregatedVerifier::new(registry, spec.clone());
+            let aggregated = AggregatedSignature::new(signatures);
+            group.bench_function(height_id(name, height), |b| {
+                b.iter(|| black_box(verifier.verify(&message, &aggregated)));
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(
+    native,
+    keygen_benchmarks,
+    sign_verify_benchmarks,
+    hash_tree_benchmarks,
+    grind_benchmarks,
+    aggregate_verify_benchmarks
+);
+criterion_main!(native);
@@ -0,0 +1,44 @@
+// Copyright 2025 Irreducible Inc.
+use criterion::{Criterion, black_box, criterion_group, criterion_main};
+use leansig_core::{Message, Param, Pk, Signer, Sk, spec::SPEC_2, verify_signature};
+use rand::{SeedableRng, rngs::StdRng};
+
+const MAX_RETRIES: usize = 1_000_000;
+
+/// Measures one-time key derivation, signing and verification.
+///
+/// Run with and without `--features parallel` to compare the serial and parallel chain walks.
+fn signing_benchmarks(c: &mut Criterion) {
+    let spec = SPEC_2;
+    let mut rng = StdRng::seed_from_u64(0);
+    let message = Message([42; 32]);
+
+    let mut group = c.benchmark_group("signing");
+    let param = Param::random(spec.param_len, &mut rng);
+    let sk = Sk::random(&mut rng, param, &spec);
+    group.bench_function("derive", |b| {
+        b.iter(|| black_box(Pk::derive(&sk, &spec)));
+    });
+
+    let mut signer = Signer::new(StdRng::seed_from_u64(1), MAX_RETRIES, spec.clone(), 8).unwrap();
+    group.bench_function("sign", |b| {
+        b.iter(|| black_box(signer.sign(0, &message)));
+    });
+
+    let signature = signer.sign(0, &message).unwrap();
+    group.bench_function("verify", |b| {
+        b.iter(|| {
+            black_box(verify_signature(
+                &spec,
+                &signer.param,
+                &message,
+                &signature,
+                &signer.root,
+            ))
+        });
+    });
+    group.finish();
+}
+
+criterion_group!(signing, signing_benchmarks);
+criterion_main!(signing);
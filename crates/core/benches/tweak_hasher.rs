@@ -0,0 +1,83 @@
+// Copyright 2025 Irreducible Inc.
+use criterion::{Criterion, black_box, criterion_group, criterion_main};
+use leansig_core::{
+    Message, Nonce, Param,
+    code::{grind, new_valid},
+    hash::{Hash, KeccakBackend, TweakableHash, tweak_hash_chain},
+    hash_chain::hash_chain_with,
+    spec::SPEC_2,
+};
+use rand::{SeedableRng, rngs::StdRng};
+
+const MAX_RETRIES: usize = 1_000_000;
+
+/// Compares reusing a `TweakHasher` prefix state against absorbing `param` for every hash, and
+/// scalar against eight-way chain walks.
+fn tweak_hasher_benchmarks(c: &mut Criterion) {
+    let spec = SPEC_2;
+    let mut rng = StdRng::seed_from_u64(0);
+    let param = Param::random(spec.param_len, &mut rng);
+    let message = Message([42; 32]);
+
+    let mut group = c.benchmark_group("pk_derive");
+    let start_hashes: Vec<_> = (0..spec.dimension())
+        .map(|_| Hash::random(&mut rng))
+        .collect();
+    let hasher = KeccakBackend::new(&param);
+    let chain_len = spec.chain_len() as u32;
+    group.bench_function("prefixed", |b| {
+        b.iter(|| {
+            for (chain_index, &start_hash) in (0..).zip(&start_hashes) {
+                black_box(hash_chain_with(
+                    &hasher,
+                    /* epoch */ 0,
+                    chain_index,
+                    start_hash,
+                    0,
+                    chain_len - 1,
+                ));
+            }
+        });
+    });
+    // `Pk::derive` walks eight chains at a time when the `simd` feature is enabled.
+    #[cfg(feature = "simd")]
+    {
+        use leansig_core::{Pk, Sk};
+
+        let sk = Sk::random(&mut rng, param.clone(), &spec);
+        group.bench_function("simd", |b| {
+            b.iter(|| black_box(Pk::derive(&sk, &spec)));
+        });
+    }
+    group.bench_function("from_scratch", |b| {
+        b.iter(|| {
+            for (chain_index, &start_hash) in (0..).zip(&start_hashes) {
+                let mut current = start_hash;
+                for pos_in_chain in 1..chain_len {
+                    current = tweak_hash_chain(&param, chain_index, pos_in_chain, current);
+                }
+                black_box(current);
+            }
+        });
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("grind");
+    let mut grind_rng = StdRng::seed_from_u64(1);
+    group.bench_function("prefixed", |b| {
+        b.iter(|| black_box(grind(&spec, MAX_RETRIES, &param, &message, &mut grind_rng)));
+    });
+    let mut grind_rng = StdRng::seed_from_u64(1);
+    group.bench_function("from_scratch", |b| {
+        b.iter(|| {
+            black_box((0..MAX_RETRIES).find_map(|_| {
+                let nonce = Nonce::random(&mut grind_rng);
+                new_valid(&spec, &param, &message, &nonce)
+            }))
+        });
+    });
+    group.finish();
+}
+
+criterion_group!(tweak_hasher, tweak_hasher_benchmarks);
+criterion_main!(tweak_hasher);
@@ -0,0 +1,70 @@
+// Copyright 2025 Irreducible Inc.
+use criterion::{BenchmarkId, Criterion, black_box, criterion_group, criterion_main};
+use leansig_core::{
+    AggregatedSignature, AggregatedVerifier, Message, Param, Signer, ValidatorSignature,
+    hash::Hash, spec::SPEC_2,
+};
+use rand::{SeedableRng, rngs::StdRng};
+
+const NUM_ROOTS: usize = 10_000;
+const MAX_RETRIES: usize = 1_000_000;
+
+/// Compares looking a root up among 10k registered validators by binary search, as
+/// `AggregatedVerifier` does, against a linear scan.
+fn root_lookup_benchmarks(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(0);
+    let roots: Vec<_> = (0..NUM_ROOTS).map(|_| Hash::random(&mut rng)).collect();
+    let param = Param::random(SPEC_2.param_len, &mut rng);
+    let registry = roots.iter().map(|root| (*root, param.clone())).collect();
+    let verifier = AggregatedVerifier::new(registry, SPEC_2);
+    // The last registered root is the worst case of the scan.
+    let needle = roots[NUM_ROOTS - 1];
+
+    let mut group = c.benchmark_group("root_lookup");
+    group.bench_function("sorted", |b| {
+        b.iter(|| black_box(verifier.contains_root(black_box(&needle))));
+    });
+    group.bench_function("linear", |b| {
+        b.iter(|| black_box(roots.contains(black_box(&needle))));
+    });
+    group.finish();
+}
+
+/// Measures verifying aggregates of 16, 64 and 128 validators.
+///
+/// Run with and without `--features parallel` to compare the serial and parallel verification.
+fn aggregate_benchmarks(c: &mut Criterion) {
+    let message = Message([42; 32]);
+    let mut validators: Vec<_> = (0..128)
+        .map(|seed| Signer::new(StdRng::seed_from_u64(seed), MAX_RETRIES, SPEC_2, 1).unwrap())
+        .collect();
+    let signatures: Vec<_> = (validators.iter_mut())
+        .map(|validator| ValidatorSignature {
+            epoch: 0,
+            signature: validator.sign(0, &message).unwrap().into(),
+            xmss_root: validator.root,
+            param: validator.param.clone(),
+            validator_index: None,
+        })
+        .collect();
+
+    let mut group = c.benchmark_group("aggregate_verify");
+    group.sample_size(20);
+    for num_validators in [16, 64, 128] {
+        let registry = validators[..num_validators]
+            .iter()
+            .map(|v| (v.root, v.param.clone()))
+            .collect();
+        let verifier = AggregatedVerifier::new(registry, SPEC_2);
+        let aggregated = AggregatedSignature::new(signatures[..num_validators].to_vec());
+        group.bench_with_input(
+            BenchmarkId::from_parameter(num_validators),
+            &aggregated,
+            |b, aggregated| b.iter(|| black_box(verifier.verify(&message, aggregated))),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(verifier, root_lookup_benchmarks, aggregate_benchmarks);
+criterion_main!(verifier);
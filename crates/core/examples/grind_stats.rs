@@ -0,0 +1,76 @@
+// Copyright 2025 Irreducible Inc.
+//! Prints the distribution of the nonce grinding attempts of SPEC_1, SPEC_2 and any spec given
+//! on the command line, next to the `max_retries` suggested for them.
+//!
+//! ```text
+//! cargo run --release -p leansig-core --example grind_stats -- [--samples N] [SPEC...]
+//! ```
+//!
+//! A spec is given as `message_hash_len,coordinate_resolution_bits,param_len,target_sum`, e.g.
+//! `18,4,18,280`.
+use leansig_core::{
+    code::attempt_statistics,
+    spec::{SPEC_1, SPEC_2, Spec},
+};
+use rand::{SeedableRng, rngs::StdRng};
+
+const DEFAULT_SAMPLES: usize = 10_000;
+
+fn parse_spec(arg: &str) -> Spec {
+    let values: Vec<usize> = (arg.split(','))
+        .map(|value| value.parse().expect("spec values are integers"))
+        .collect();
+    let [
+        message_hash_len,
+        coordinate_resolution_bits,
+        param_len,
+        target_sum,
+    ] = values[..]
+    else {
+        panic!("expected a spec of 4 values, got {arg}");
+    };
+    Spec::new(
+        message_hash_len,
+        coordinate_resolution_bits,
+        param_len,
+        target_sum,
+    )
+    .unwrap_or_else(|err| panic!("invalid spec {arg}: {err}"))
+}
+
+fn main() {
+    let mut samples = DEFAULT_SAMPLES;
+    let mut specs = vec![
+        ("SPEC_1".to_string(), SPEC_1),
+        ("SPEC_2".to_string(), SPEC_2),
+    ];
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--samples" {
+            let value = args.next().expect("--samples takes a number");
+            samples = value.parse().expect("--samples takes a number");
+        } else {
+            specs.push((arg.clone(), parse_spec(&arg)));
+        }
+    }
+
+    println!(
+        "{:<14} {:>8} {:>6} {:>6} {:>6} {:>7} {:>8} {:>9}",
+        "spec", "mean", "p50", "p95", "p99", "max", "failures", "suggested"
+    );
+    let mut rng = StdRng::seed_from_u64(0);
+    for (name, spec) in specs {
+        let stats = attempt_statistics(&spec, samples, &mut rng);
+        println!(
+            "{:<14} {:>8.1} {:>6} {:>6} {:>6} {:>7} {:>8} {:>9}",
+            name,
+            stats.mean,
+            stats.p50,
+            stats.p95,
+            stats.p99,
+            stats.max,
+            stats.failures,
+            spec.suggested_max_retries()
+        );
+    }
+}
@@ -0,0 +1,41 @@
+// Copyright 2025 Irreducible Inc.
+//! Regenerates the known-answer test vectors checked in under `tests/fixtures`, for when their
+//! format or the scheme changes on purpose.
+//!
+//! ```text
+//! cargo run -p leansig-core --features compat-hashsig --example kat_vectors
+//! ```
+//!
+//! Without the `compat-hashsig` feature, the `hashsig_compat_*.json` vectors are left as they are.
+//!
+//! See [`leansig_core::kat`] for what the vectors pin.
+use std::path::Path;
+
+use leansig_core::{
+    kat::{KatFile, generate_vectors},
+    spec::{SPEC_1, SPEC_2},
+};
+
+/// The seed of the signers of the checked-in vectors
+const SEED: u64 = 0;
+
+fn write(name: &str, vectors: &KatFile) {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let json = serde_json::to_string_pretty(vectors).unwrap();
+    let path = fixtures.join(format!("{name}.json"));
+    std::fs::write(&path, json + "\n").expect("the fixtures directory is writable");
+    println!("wrote {}", path.display());
+}
+
+fn main() {
+    for (name, spec) in [("spec_1", SPEC_1), ("spec_2", SPEC_2)] {
+        write(&format!("kat_{name}"), &generate_vectors(&spec, SEED));
+        #[cfg(feature = "compat-hashsig")]
+        write(
+            &format!("hashsig_compat_{name}"),
+            &leansig_core::kat::generate_vectors_with_backend::<leansig_core::hash::HashSigCompat>(
+                &spec, SEED,
+            ),
+        );
+    }
+}
@@ -0,0 +1,49 @@
+// Copyright 2025 Irreducible Inc.
+//! Prints the memory a signer holds for tree heights 8 to 14, for capacity planning.
+//!
+//! ```text
+//! cargo run --release -p leansig-core --example memory_footprint -- [VALIDATORS]
+//! ```
+//!
+//! The last column is the memory of `VALIDATORS` signers, 64 by default, as held while test data
+//! is generated.
+use leansig_core::{Signer, spec::SPEC_2};
+use rand::{SeedableRng, rngs::StdRng};
+
+const MAX_RETRIES: usize = 10_000;
+const DEFAULT_VALIDATORS: usize = 64;
+
+fn mib(bytes: usize) -> f64 {
+    bytes as f64 / (1 << 20) as f64
+}
+
+fn main() {
+    let validators = match std::env::args().nth(1) {
+        Some(arg) => arg.parse().expect("the number of validators is an integer"),
+        None => DEFAULT_VALIDATORS,
+    };
+
+    println!(
+        "{:>6} {:>12} {:>12} {:>12} {:>12} {:>16}",
+        "height", "secret MiB", "public MiB", "tree MiB", "total MiB", "validators MiB"
+    );
+    for tree_height in 8..=14 {
+        let signer = Signer::new(
+            StdRng::seed_from_u64(0),
+            MAX_RETRIES,
+            SPEC_2,
+            1 << tree_height,
+        )
+        .unwrap();
+        let footprint = signer.memory_footprint();
+        println!(
+            "{:>6} {:>12.2} {:>12.2} {:>12.2} {:>12.2} {:>16.1}",
+            tree_height,
+            mib(footprint.secret_bytes),
+            mib(footprint.public_key_bytes),
+            mib(footprint.tree_bytes),
+            mib(footprint.total()),
+            mib(validators * footprint.total())
+        );
+    }
+}
@@ -0,0 +1,55 @@
+// Copyright 2025 Irreducible Inc.
+//! Prints signatures signed natively as a JSON test vector, for the verifiers that run elsewhere
+//! such as `leansig-wasm`.
+//!
+//! ```text
+//! cargo run --release -p leansig-core --example test_vector -- [VALIDATORS] [TREE_HEIGHT]
+//! ```
+//!
+//! Every validator signs the same message over SPEC_2 at epoch 1, with a signer seeded by its
+//! index, so the output only changes when the scheme does. The signatures are hex-encoded
+//! [`leansig_core::Signature::to_bytes`].
+use leansig_core::{Message, Signer, spec::SPEC_2};
+use rand::{SeedableRng, rngs::StdRng};
+use serde_json::json;
+
+const MAX_RETRIES: usize = 100_000;
+const EPOCH: usize = 1;
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let validators: u64 = args
+        .next()
+        .map_or(2, |arg| arg.parse().expect("a validator count"));
+    let tree_height: usize = args
+        .next()
+        .map_or(2, |arg| arg.parse().expect("a tree height"));
+
+    let message = Message([42; 32]);
+    let validators: Vec<_> = (0..validators)
+        .map(|seed| {
+            let rng = StdRng::seed_from_u64(seed);
+            let mut signer =
+                Signer::new(rng, MAX_RETRIES, SPEC_2, 1 << tree_height).expect("SPEC_2 is valid");
+            let signature = signer.sign(EPOCH, &message).expect("grinding succeeds");
+            json!({
+                "param": signer.param,
+                "root": signer.root,
+                "signature": to_hex(&signature.to_bytes()),
+            })
+        })
+        .collect();
+
+    let vector = json!({
+        "spec": SPEC_2,
+        "message": to_hex(&message.0),
+        "epoch": EPOCH,
+        "tree_height": tree_height,
+        "validators": validators,
+    });
+    println!("{}", serde_json::to_string_pretty(&vector).unwrap());
+}
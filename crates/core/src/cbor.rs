@@ -0,0 +1,378 @@
+// Copyright 2025 Irreducible Inc.
+//! Canonical CBOR encodings of signatures and aggregates, for systems that sign or hash the
+//! serialized bytes and so need every value to have exactly one encoding.
+//!
+//! The encoding is the serde layout of a type in binary formats, written under the deterministic
+//! rules COSE uses (RFC 7049 section 3.9, RFC 8949 section 4.2.3):
+//!
+//! - integers and lengths take the shortest form of their argument;
+//! - arrays, maps, byte strings and text strings have definite lengths;
+//! - map keys are sorted by the length of their encoding, then bytewise, and are distinct.
+//!
+//! Decoding accepts only bytes in that encoding, so that re-encoding a decoded value gives back
+//! the same bytes. It returns an error on any other input, never panics, and bounds the nesting
+//! it follows.
+
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+use ciborium::Value;
+use ciborium_ll::{Decoder, Header};
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::{AggregatedSignature, Signature, ValidatorSignature};
+
+/// The reasons bytes can fail to decode by [`CanonicalCbor::from_cbor`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CborError {
+    /// The bytes are not well-formed CBOR.
+    Malformed(String),
+    /// An array, map, byte string or text string has an indefinite length.
+    IndefiniteLength,
+    /// A map has the same key twice.
+    DuplicateKey,
+    /// The bytes are well-formed, but not the canonical encoding of their value.
+    NotCanonical,
+    /// Bytes follow the encoded value.
+    TrailingBytes,
+    /// The value does not have the layout of the type.
+    InvalidValue(String),
+}
+
+impl fmt::Display for CborError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CborError::Malformed(err) => write!(f, "malformed CBOR: {err}"),
+            CborError::IndefiniteLength => write!(f, "indefinite-length item"),
+            CborError::DuplicateKey => write!(f, "duplicate map key"),
+            CborError::NotCanonical => write!(f, "not in canonical encoding"),
+            CborError::TrailingBytes => write!(f, "trailing bytes after the encoded value"),
+            CborError::InvalidValue(err) => write!(f, "invalid value: {err}"),
+        }
+    }
+}
+
+impl core::error::Error for CborError {}
+
+/// A type with a canonical CBOR encoding.
+pub trait CanonicalCbor: Serialize + DeserializeOwned {
+    /// The canonical CBOR encoding of the value.
+    fn to_canonical_cbor(&self) -> Vec<u8> {
+        let value = Value::serialized(self).expect("the value serializes to CBOR");
+        encode(&canonicalize(value).expect("serde layouts have distinct map keys"))
+    }
+
+    /// Decodes the canonical CBOR encoding of a value, as given by
+    /// [`CanonicalCbor::to_canonical_cbor`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bytes are not the canonical encoding of a value of the type.
+    fn from_cbor(bytes: &[u8]) -> Result<Self, CborError> {
+        check_definite_lengths(bytes)?;
+        let mut reader = bytes;
+        let value: Value = ciborium::from_reader(&mut reader)
+            .map_err(|err| CborError::Malformed(err.to_string()))?;
+        if !reader.is_empty() {
+            return Err(CborError::TrailingBytes);
+        }
+        if encode(&canonicalize(value.clone())?) != bytes {
+            return Err(CborError::NotCanonical);
+        }
+        value
+            .deserialized()
+            .map_err(|err| CborError::InvalidValue(err.to_string()))
+    }
+}
+
+impl CanonicalCbor for Signature {}
+impl CanonicalCbor for ValidatorSignature {}
+impl CanonicalCbor for AggregatedSignature {}
+
+fn encode(value: &Value) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    ciborium::into_writer(value, &mut bytes).expect("writing to a Vec cannot fail");
+    bytes
+}
+
+/// Sorts the keys of every map of `value` in canonical order, rejecting duplicates.
+///
+/// The other rules hold for any value written by `ciborium`.
+fn canonicalize(value: Value) -> Result<Value, CborError> {
+    Ok(match value {
+        Value::Array(items) => Value::Array(
+            (items.into_iter())
+                .map(canonicalize)
+                .collect::<Result<_, _>>()?,
+        ),
+        Value::Map(entries) => {
+            let mut entries = (entries.into_iter())
+                .map(|(key, value)| {
+                    let key = canonicalize(key)?;
+                    Ok((encode(&key), key, canonicalize(value)?))
+                })
+                .collect::<Result<Vec<_>, CborError>>()?;
+            entries.sort_by(|(a, ..), (b, ..)| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+            if entries.windows(2).any(|pair| pair[0].0 == pair[1].0) {
+                return Err(CborError::DuplicateKey);
+            }
+            Value::Map(
+                (entries.into_iter())
+                    .map(|(_, key, value)| (key, value))
+                    .collect(),
+            )
+        }
+        Value::Tag(tag, value) => Value::Tag(tag, canonicalize(*value)?.into()),
+        value => value,
+    })
+}
+
+/// Walks the items of `bytes`, rejecting any of indefinite length before the nesting of a value
+/// hides how it was encoded.
+fn check_definite_lengths(bytes: &[u8]) -> Result<(), CborError> {
+    let malformed = |err: ciborium_ll::Error<_>| {
+        CborError::Malformed(match err {
+            ciborium_ll::Error::Io(_) => "unexpected end of input".into(),
+            ciborium_ll::Error::Syntax(offset) => format!("syntax error at byte {offset}"),
+        })
+    };
+    let mut decoder = Decoder::from(bytes);
+    let mut buffer = [0; 256];
+    while decoder.offset() < bytes.len() {
+        match decoder.pull().map_err(malformed)? {
+            Header::Break
+            | Header::Array(None)
+            | Header::Map(None)
+            | Header::Bytes(None)
+            | Header::Text(None) => return Err(CborError::IndefiniteLength),
+            Header::Bytes(len @ Some(_)) => {
+                let mut segments = decoder.bytes(len);
+                while let Some(mut segment) = segments.pull().map_err(malformed)? {
+                    while segment.pull(&mut buffer).map_err(malformed)?.is_some() {}
+                }
+            }
+            Header::Text(len @ Some(_)) => {
+                let mut segments = decoder.text(len);
+                while let Some(mut segment) = segments.pull().map_err(malformed)? {
+                    while segment.pull(&mut buffer).map_err(malformed)?.is_some() {}
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    use super::*;
+    use crate::{
+        Message, Nonce, OtsSignature, Param, Pk, RAND_LEN, Signer, hash::Hash,
+        hash_tree::HashTreeProof, spec::SPEC_1, verify_signature,
+    };
+
+    fn from_hex(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    /// A signature with one chain and a tree of height one, small enough to pin its encoding.
+    fn fixed_signature() -> Signature {
+        Signature {
+            signature: OtsSignature {
+                nonce: Nonce([0; RAND_LEN]),
+                hashes: vec![Hash([1; 32])],
+            },
+            hash_tree_proof: HashTreeProof::new(1, vec![Hash([2; 32])]),
+            public_key: Pk {
+                param: Param::from(&[3, 4][..]),
+                end_hashes: vec![Hash([5; 32])],
+            },
+        }
+    }
+
+    /// The encoding of `Hash([byte; 32])`, an array of 32 one-byte integers
+    fn hash_hex(byte: u8) -> String {
+        format!("9820{}", format!("{byte:02x}").repeat(32))
+    }
+
+    /// The encoding of a map key shorter than 24 bytes
+    fn key_hex(key: &str) -> String {
+        let hex: String = key.bytes().map(|byte| format!("{byte:02x}")).collect();
+        format!("{:02x}{hex}", 0x60 + key.len())
+    }
+
+    #[test]
+    fn test_golden_vectors() {
+        let signature = fixed_signature();
+        // Every map sorts its keys by length, e.g. "signature", "public_key", "hash_tree_proof"
+        let param_hex = [&key_hex("data"), "820304"].concat();
+        let signature_hex = [
+            "a3",
+            &key_hex("signature"),
+            "a2",
+            &key_hex("nonce"),
+            "97",
+            &"00".repeat(RAND_LEN),
+            &key_hex("hashes"),
+            "81",
+            &hash_hex(1),
+            &key_hex("public_key"),
+            "a2",
+            &key_hex("param"),
+            "a1",
+            &param_hex,
+            &key_hex("end_hashes"),
+            "81",
+            &hash_hex(5),
+            &key_hex("hash_tree_proof"),
+            "a2",
+            &key_hex("path"),
+            "81",
+            &hash_hex(2),
+            &key_hex("leaf_index"),
+            "01",
+        ]
+        .concat();
+        let bytes = signature.to_canonical_cbor();
+        assert_eq!(bytes, from_hex(&signature_hex));
+        assert_eq!(
+            Signature::from_cbor(&bytes).unwrap().to_canonical_cbor(),
+            bytes
+        );
+
+        let validator_signature = ValidatorSignature {
+            epoch: 1,
+            signature: signature.into(),
+            xmss_root: Hash([6; 32]),
+            param: Param::from(&[3, 4][..]),
+            validator_index: Some(7),
+        };
+        let validator_signature_hex = [
+            "a5",
+            &key_hex("epoch"),
+            "01",
+            &key_hex("param"),
+            "a1",
+            &param_hex,
+            &key_hex("signature"),
+            "a1",
+            &key_hex("Xmss"),
+            &signature_hex,
+            &key_hex("xmss_root"),
+            &hash_hex(6),
+            &key_hex("validator_index"),
+            "07",
+        ]
+        .concat();
+        let bytes = validator_signature.to_canonical_cbor();
+        assert_eq!(bytes, from_hex(&validator_signature_hex));
+        let decoded = ValidatorSignature::from_cbor(&bytes).unwrap();
+        assert_eq!(decoded.validator_index, Some(7));
+
+        let aggregated = AggregatedSignature::new(vec![validator_signature]);
+        let aggregated_hex =
+            ["a1", &key_hex("signatures"), "81", &validator_signature_hex].concat();
+        let bytes = aggregated.to_canonical_cbor();
+        assert_eq!(bytes, from_hex(&aggregated_hex));
+        assert_eq!(
+            AggregatedSignature::from_cbor(&bytes)
+                .unwrap()
+                .signatures
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let message = Message([42; 32]);
+        let mut signer = Signer::new(StdRng::seed_from_u64(0), 100_000, SPEC_1, 4).unwrap();
+        let signature = signer.sign(1, &message).unwrap();
+        let bytes = signature.to_canonical_cbor();
+        let decoded = Signature::from_cbor(&bytes).unwrap();
+        assert!(verify_signature(
+            &SPEC_1,
+            &signer.param,
+            &message,
+            &decoded,
+            &signer.root
+        ));
+        assert_eq!(decoded.to_canonical_cbor(), bytes);
+    }
+
+    #[test]
+    fn test_rejects_non_canonical() {
+        let bytes = fixed_signature().to_canonical_cbor();
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert_eq!(
+            Signature::from_cbor(&trailing).map(drop),
+            Err(CborError::TrailingBytes)
+        );
+
+        // An indefinite-length array holding the definite-length one
+        let indefinite = [&[0x9f][..], &bytes, &[0xff]].concat();
+        assert_eq!(
+            Signature::from_cbor(&indefinite).map(drop),
+            Err(CborError::IndefiniteLength)
+        );
+        // The text string "a" in two indefinite-length chunks
+        assert_eq!(
+            Signature::from_cbor(&from_hex("7f6161ff")).map(drop),
+            Err(CborError::IndefiniteLength)
+        );
+
+        // {"a": 0, "a": 1}
+        assert_eq!(
+            Signature::from_cbor(&from_hex("a2616100616101")).map(drop),
+            Err(CborError::DuplicateKey)
+        );
+        // {"bb": 0, "a": 1}, with the longer key first
+        assert_eq!(
+            Signature::from_cbor(&from_hex("a262626200616101")).map(drop),
+            Err(CborError::NotCanonical)
+        );
+        // 1 in two bytes instead of one
+        assert_eq!(
+            Signature::from_cbor(&from_hex("1801")).map(drop),
+            Err(CborError::NotCanonical)
+        );
+        // Canonical, but not a signature
+        assert!(matches!(
+            Signature::from_cbor(&from_hex("01")),
+            Err(CborError::InvalidValue(_))
+        ));
+        // A byte string claiming far more bytes than there are
+        assert!(matches!(
+            Signature::from_cbor(&from_hex("5bffffffffffffffff00")),
+            Err(CborError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn test_fuzz_never_panics() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let bytes = fixed_signature().to_canonical_cbor();
+        for _ in 0..2000 {
+            let mut mutated = bytes.clone();
+            for _ in 0..rng.random_range(1..4) {
+                let index = rng.random_range(0..mutated.len());
+                mutated[index] = rng.random();
+            }
+            let len = rng.random_range(0..=mutated.len());
+            let _ = Signature::from_cbor(&mutated[..len]);
+            let _ = AggregatedSignature::from_cbor(&mutated);
+
+            let random: Vec<u8> = (0..rng.random_range(0..64)).map(|_| rng.random()).collect();
+            let _ = ValidatorSignature::from_cbor(&random);
+        }
+        // Nesting deeper than the recursion limit of the decoder
+        let nested = [vec![0x81; 10_000], vec![0]].concat();
+        assert!(Signature::from_cbor(&nested).is_err());
+    }
+}
@@ -2,38 +2,430 @@
 
 //! Encoding related stuff.
 
+use alloc::{vec, vec::Vec};
 use bitvec::prelude::*;
-use rand::rngs::StdRng;
+#[cfg(feature = "std")]
+use rand::{RngCore as _, rngs::StdRng};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use tiny_keccak::{Hasher, Keccak};
 
-use crate::{Message, Nonce, Param, hash::tweak_hash_message, spec::Spec};
+use crate::{
+    Message, Nonce, Param, RAND_LEN,
+    hash::{HASH_LEN, Hash, KECCAK_EPOCH, KeccakBackend, TweakableHash},
+    spec::{Spec, SpecError},
+};
+
+/// The number of candidates evaluated per parallel batch in [`grind_parallel`].
+///
+/// Batches are processed in order, so at most one batch of work is wasted past the winner.
+#[cfg(feature = "parallel")]
+const PARALLEL_CHUNK_LEN: usize = 1024;
+
+/// How [`crate::Signer`] searches for a nonce producing a valid codeword.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GrindStrategy {
+    /// Draw nonces from the signer's rng one at a time, see [`grind`].
+    #[default]
+    Serial,
+    /// Derive candidates from a seed drawn from the signer's rng and evaluate them across
+    /// threads, see [`grind_parallel`].
+    #[cfg(feature = "parallel")]
+    Parallel,
+}
+
+#[cfg(feature = "std")]
+impl GrindStrategy {
+    /// Runs the grinding strategy for a signature at `epoch`, drawing any randomness it needs
+    /// from `rng`.
+    pub fn grind<H: TweakableHash>(
+        self,
+        spec: &Spec,
+        max_retries: usize,
+        hasher: &H,
+        epoch: u32,
+        message: &Message,
+        rng: &mut StdRng,
+    ) -> Option<GrindResult> {
+        match self {
+            GrindStrategy::Serial => {
+                grind_observed(spec, max_retries, hasher, epoch, message, rng, |_| {})
+            }
+            #[cfg(feature = "parallel")]
+            GrindStrategy::Parallel => {
+                let mut seed = [0; 32];
+                rng.fill_bytes(&mut seed);
+                grind_parallel_with_backend(spec, max_retries, hasher, epoch, message, &seed)
+            }
+        }
+    }
+}
+
+/// The outcome of a successful [`grind`].
+pub struct GrindResult {
+    /// The valid codeword.
+    pub codeword: Codeword,
+    /// The nonce producing `codeword`.
+    pub nonce: Nonce,
+    /// The number of nonces tried, including the successful one.
+    pub attempts: usize,
+    /// The sums of all the codewords tried, if requested through [`grind_verbose`].
+    pub histogram: Option<SumHistogram>,
+}
+
+/// A histogram of the codeword sums observed while grinding.
+///
+/// Useful for tuning the target sum of a new spec: it shows how far off the rejected codewords
+/// were.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SumHistogram {
+    counts: Vec<usize>,
+}
+
+impl SumHistogram {
+    /// Creates an empty histogram covering every sum reachable under `spec`.
+    pub fn new(spec: &Spec) -> Self {
+        Self {
+            counts: vec![0; spec.max_sum() + 1],
+        }
+    }
+
+    /// Records one observed sum.
+    pub fn record(&mut self, sum: usize) {
+        if sum >= self.counts.len() {
+            self.counts.resize(sum + 1, 0);
+        }
+        self.counts[sum] += 1;
+    }
+
+    /// The number of times `sum` was observed.
+    pub fn count(&self, sum: usize) -> usize {
+        self.counts.get(sum).copied().unwrap_or(0)
+    }
+
+    /// The number of observations, that is, the number of grinding attempts.
+    pub fn total(&self) -> usize {
+        self.counts.iter().sum()
+    }
+
+    /// The counts indexed by sum.
+    pub fn counts(&self) -> &[usize] {
+        &self.counts
+    }
+}
+
+/// The attempts [`attempt_statistics`] grinds for at most before counting a failure.
+pub const STATISTICS_MAX_RETRIES: usize = 1_000_000;
+
+/// The distribution of the number of attempts [`grind`] needs under a spec, see
+/// [`attempt_statistics`].
+///
+/// The percentiles are over the successful samples, with the nearest-rank method.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AttemptStats {
+    /// The mean number of attempts.
+    pub mean: f64,
+    /// The median number of attempts.
+    pub p50: usize,
+    /// The 95th percentile of the number of attempts.
+    pub p95: usize,
+    /// The 99th percentile of the number of attempts.
+    pub p99: usize,
+    /// The largest number of attempts of a sample.
+    pub max: usize,
+    /// The number of samples that gave up after [`STATISTICS_MAX_RETRIES`] attempts.
+    pub failures: usize,
+}
+
+/// Grinds `samples` random messages under random params, and reports the distribution of the
+/// number of attempts they needed.
+///
+/// This is the empirical counterpart of [`Spec::suggested_max_retries`], for choosing the
+/// `max_retries` of a signer.
+#[cfg(feature = "std")]
+pub fn attempt_statistics(spec: &Spec, samples: usize, rng: &mut StdRng) -> AttemptStats {
+    let mut attempts = Vec::with_capacity(samples);
+    let mut failures = 0;
+    for _ in 0..samples {
+        let param = Param::random(spec.param_len, rng);
+        let mut message = Message([0; 32]);
+        rng.fill_bytes(&mut message.0);
+        match grind(spec, STATISTICS_MAX_RETRIES, &param, &message, rng) {
+            Some(result) => attempts.push(result.attempts),
+            None => failures += 1,
+        }
+    }
+
+    attempts.sort_unstable();
+    let percentile = |percent: usize| match attempts.len() {
+        0 => 0,
+        len => attempts[(len * percent).div_ceil(100).max(1) - 1],
+    };
+    let mean = match attempts.len() {
+        0 => 0.0,
+        len => attempts.iter().sum::<usize>() as f64 / len as f64,
+    };
+    AttemptStats {
+        mean,
+        p50: percentile(50),
+        p95: percentile(95),
+        p99: percentile(99),
+        max: attempts.last().copied().unwrap_or(0),
+        failures,
+    }
+}
 
 /// Try to find a suitable encoding to fit into the target sum.
 ///
 /// For this we are going to try different random parameter values until we find a valid encoding.
 /// It should not take too many iterations, but in case it does, we will give up and return `None`.
+#[cfg(feature = "std")]
 pub fn grind(
     spec: &Spec,
     max_retries: usize,
     param: &Param,
     message: &Message,
     rng: &mut StdRng,
-) -> Option<(Codeword, Nonce)> {
-    for _ in 0..max_retries {
+) -> Option<GrindResult> {
+    let hasher = KeccakBackend::new(param);
+    grind_observed(
+        spec,
+        max_retries,
+        &hasher,
+        KECCAK_EPOCH,
+        message,
+        rng,
+        |_| {},
+    )
+}
+
+/// Same as [`grind`], but also returns the histogram of all the sums observed.
+#[cfg(feature = "std")]
+pub fn grind_verbose(
+    spec: &Spec,
+    max_retries: usize,
+    param: &Param,
+    message: &Message,
+    rng: &mut StdRng,
+) -> Option<GrindResult> {
+    let hasher = KeccakBackend::new(param);
+    let mut histogram = SumHistogram::new(spec);
+    let result = grind_observed(
+        spec,
+        max_retries,
+        &hasher,
+        KECCAK_EPOCH,
+        message,
+        rng,
+        |codeword| histogram.record(codeword.sum()),
+    )?;
+    Some(GrindResult {
+        histogram: Some(histogram),
+        ..result
+    })
+}
+
+/// Same as [`grind`], but with any hash backend for a signature at `epoch`, and calls `observe`
+/// with every codeword tried, valid or not.
+///
+/// Unlike [`grind_verbose`], the observer sees all the attempts even when grinding gives up.
+#[cfg(feature = "std")]
+pub fn grind_observed<H: TweakableHash>(
+    spec: &Spec,
+    max_retries: usize,
+    hasher: &H,
+    epoch: u32,
+    message: &Message,
+    rng: &mut StdRng,
+    mut observe: impl FnMut(&Codeword),
+) -> Option<GrindResult> {
+    for attempt in 0..max_retries {
         let rho = Nonce::random(rng);
-        match new_valid(spec, param, message, &rho) {
-            Some(codeword) => return Some((codeword, rho)),
-            None => continue,
+        let codeword = Codeword::new_with_backend(spec, hasher, epoch, message, &rho);
+        observe(&codeword);
+        if codeword.is_valid(spec) {
+            return Some(GrindResult {
+                codeword,
+                nonce: rho,
+                attempts: attempt + 1,
+                histogram: None,
+            });
         }
     }
     // give up because we couldn't find a valid encoding in a reasonable number of attempts.
     None
 }
 
+/// Like [`grind`], but tries the candidates derived from `seed` in order instead of drawing
+/// nonces from an rng.
+///
+/// This is the serial reference for [`grind_parallel`] and returns exactly the same result.
+pub fn grind_seeded(
+    spec: &Spec,
+    max_retries: usize,
+    param: &Param,
+    message: &Message,
+    seed: &[u8; 32],
+) -> Option<GrindResult> {
+    let hasher = KeccakBackend::new(param);
+    let candidates = SeededCandidates::new(spec, &hasher, KECCAK_EPOCH, message, seed);
+    (0..max_retries)
+        .step_by(GROUP_LEN)
+        .find_map(|start| candidates.first_valid(start, max_retries.min(start + GROUP_LEN)))
+}
+
+/// Evaluates the candidates derived from `seed` across threads.
+///
+/// Candidates are processed in batches of [`PARALLEL_CHUNK_LEN`] and the successful candidate
+/// with the smallest index wins, so the result does not depend on thread scheduling and matches
+/// [`grind_seeded`].
+#[cfg(feature = "parallel")]
+pub fn grind_parallel(
+    spec: &Spec,
+    max_retries: usize,
+    param: &Param,
+    message: &Message,
+    seed: &[u8; 32],
+) -> Option<GrindResult> {
+    let hasher = KeccakBackend::new(param);
+    grind_parallel_with_backend(spec, max_retries, &hasher, KECCAK_EPOCH, message, seed)
+}
+
+/// Same as [`grind_parallel`], but with any hash backend for a signature at `epoch`.
+#[cfg(feature = "parallel")]
+pub fn grind_parallel_with_backend<H: TweakableHash>(
+    spec: &Spec,
+    max_retries: usize,
+    hasher: &H,
+    epoch: u32,
+    message: &Message,
+    seed: &[u8; 32],
+) -> Option<GrindResult> {
+    let candidates = SeededCandidates::new(spec, hasher, epoch, message, seed);
+    (0..max_retries)
+        .step_by(PARALLEL_CHUNK_LEN)
+        .find_map(|chunk_start| {
+            let chunk_end = max_retries.min(chunk_start + PARALLEL_CHUNK_LEN);
+            (0..(chunk_end - chunk_start).div_ceil(GROUP_LEN))
+                .into_par_iter()
+                .find_map_first(|group| {
+                    let start = chunk_start + group * GROUP_LEN;
+                    candidates.first_valid(start, chunk_end.min(start + GROUP_LEN))
+                })
+        })
+}
+
+/// The number of candidates hashed together by [`SeededCandidates::first_valid`].
+#[cfg(all(feature = "multi_buffer", not(target_os = "zkvm")))]
+const GROUP_LEN: usize = 4;
+#[cfg(not(all(feature = "multi_buffer", not(target_os = "zkvm"))))]
+const GROUP_LEN: usize = 1;
+
+/// The candidate nonces derived from a grinding seed for one message.
+struct SeededCandidates<'a, H> {
+    spec: &'a Spec,
+    hasher: &'a H,
+    epoch: u32,
+    message: &'a Message,
+    seed: &'a [u8; 32],
+}
+
+impl<'a, H: TweakableHash> SeededCandidates<'a, H> {
+    fn new(
+        spec: &'a Spec,
+        hasher: &'a H,
+        epoch: u32,
+        message: &'a Message,
+        seed: &'a [u8; 32],
+    ) -> Self {
+        Self {
+            spec,
+            hasher,
+            epoch,
+            message,
+            seed,
+        }
+    }
+
+    fn result(&self, index: usize, nonce: Nonce, codeword: Codeword) -> Option<GrindResult> {
+        codeword.is_valid(self.spec).then_some(GrindResult {
+            codeword,
+            nonce,
+            attempts: index + 1,
+            histogram: None,
+        })
+    }
+
+    /// Evaluates the candidates `start..end`, at most [`GROUP_LEN`] of them, and returns the
+    /// first valid one.
+    ///
+    /// A full group is hashed at once with [`TweakableHash::hash_message_x4`] when the
+    /// multi-buffer keccak is available.
+    fn first_valid(&self, start: usize, end: usize) -> Option<GrindResult> {
+        #[cfg(all(feature = "multi_buffer", not(target_os = "zkvm")))]
+        if end - start == GROUP_LEN {
+            let nonces: [Nonce; GROUP_LEN] =
+                core::array::from_fn(|offset| candidate_nonce(self.seed, start + offset));
+            let hashes = self
+                .hasher
+                .hash_message_x4(self.epoch, self.message, &nonces);
+            return (start..end).zip(nonces.into_iter().zip(hashes)).find_map(
+                |(index, (nonce, hash))| {
+                    let codeword = Codeword::from_message_hash(self.spec, &hash);
+                    self.result(index, nonce, codeword)
+                },
+            );
+        }
+
+        (start..end).find_map(|index| {
+            let nonce = candidate_nonce(self.seed, index);
+            let codeword = Codeword::new_with_backend(
+                self.spec,
+                self.hasher,
+                self.epoch,
+                self.message,
+                &nonce,
+            );
+            self.result(index, nonce, codeword)
+        })
+    }
+}
+
+/// Derives the `index`-th candidate nonce from a grinding seed.
+fn candidate_nonce(seed: &[u8; 32], index: usize) -> Nonce {
+    let mut hasher = Keccak::v256();
+    hasher.update(seed);
+    hasher.update(&(index as u64).to_be_bytes());
+    let mut digest = [0u8; 32];
+    hasher.finalize(&mut digest);
+    let mut nonce = Nonce([0; RAND_LEN]);
+    nonce.0.copy_from_slice(&digest[..RAND_LEN]);
+    nonce
+}
+
 /// Creates a new codeword and returns `Some` only if the codeword valid, that is, the sum
-/// of chunks is equal to the target sum dictated by the spec.
+/// of chunks is accepted by the target sum rule dictated by the spec (see [`Spec::accepts`]).
 pub fn new_valid(spec: &Spec, param: &Param, message: &Message, nonce: &Nonce) -> Option<Codeword> {
-    let codeword = Codeword::new(spec, param, message, nonce);
-    if codeword.sum() == spec.target_sum {
+    new_valid_with_backend(
+        spec,
+        &KeccakBackend::new(param),
+        KECCAK_EPOCH,
+        message,
+        nonce,
+    )
+}
+
+/// Same as [`new_valid`], but with any hash backend for a signature at `epoch`.
+pub fn new_valid_with_backend<H: TweakableHash>(
+    spec: &Spec,
+    hasher: &H,
+    epoch: u32,
+    message: &Message,
+    nonce: &Nonce,
+) -> Option<Codeword> {
+    let codeword = Codeword::new_with_backend(spec, hasher, epoch, message, nonce);
+    if codeword.is_valid(spec) {
         Some(codeword)
     } else {
         None
@@ -43,19 +435,88 @@ pub fn new_valid(spec: &Spec, param: &Param, message: &Message, nonce: &Nonce) -
 /// Codeword is basically a coordinate on this hypercube structure.
 ///
 /// The origin of this structure is where the private key is stored.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Codeword {
     coords: Vec<u8>,
 }
 
 impl Codeword {
+    /// Derives the codeword for `message` from the first `spec.message_hash_len` bytes of the
+    /// message hash.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `spec.message_hash_len` exceeds the hash output length.
     pub fn new(spec: &Spec, param: &Param, message: &Message, nonce: &Nonce) -> Codeword {
-        let full_hash = tweak_hash_message(param, message, nonce);
+        Self::new_with_backend(
+            spec,
+            &KeccakBackend::new(param),
+            KECCAK_EPOCH,
+            message,
+            nonce,
+        )
+    }
+
+    /// Same as [`Codeword::new`], but with any hash backend for a signature at `epoch`.
+    pub fn new_with_backend<H: TweakableHash>(
+        spec: &Spec,
+        hasher: &H,
+        epoch: u32,
+        message: &Message,
+        nonce: &Nonce,
+    ) -> Codeword {
+        Self::from_message_hash(spec, &hasher.hash_message(epoch, message, nonce))
+    }
+
+    fn from_message_hash(spec: &Spec, full_hash: &Hash) -> Codeword {
+        assert!(
+            spec.message_hash_len <= HASH_LEN,
+            "message hash length must not exceed {HASH_LEN} bytes"
+        );
         let trunc_hash = &full_hash.as_ref()[0..spec.message_hash_len];
         let coords = bytes_to_coordinates(trunc_hash, spec.coordinate_resolution_bits);
         assert_eq!(coords.len(), spec.dimension());
         Self { coords }
     }
 
+    /// Reconstructs a codeword from raw coordinates.
+    ///
+    /// Fails if the number of coordinates is not the dimension of `spec`, or if any coordinate is
+    /// past the end of its chain.
+    pub fn from_coords(coords: Vec<u8>, spec: &Spec) -> Result<Codeword, SpecError> {
+        if coords.len() != spec.dimension() {
+            return Err(SpecError::DimensionMismatch {
+                expected: spec.dimension(),
+                actual: coords.len(),
+            });
+        }
+        let chain_len = spec.chain_len();
+        if let Some((index, &coordinate)) = coords
+            .iter()
+            .enumerate()
+            .find(|&(_, &coordinate)| coordinate as usize >= chain_len)
+        {
+            return Err(SpecError::CoordinateOutOfRange {
+                index,
+                coordinate,
+                chain_len,
+            });
+        }
+        Ok(Self { coords })
+    }
+
+    /// Builds a codeword without checking the coordinates, to exercise the verifier on malformed
+    /// input.
+    #[cfg(test)]
+    pub(crate) fn from_coords_unchecked(coords: Vec<u8>) -> Codeword {
+        Self { coords }
+    }
+
+    /// Returns `true` if the codeword has the dimension of `spec` and its sum is accepted by it.
+    pub fn is_valid(&self, spec: &Spec) -> bool {
+        self.dimension() == spec.dimension() && spec.accepts(self.sum())
+    }
+
     /// Returns the sum over all the coordinates.
     ///
     /// You can think about it as a distance from the source, the where the secret key is stored.
@@ -76,6 +537,25 @@ impl Codeword {
     pub fn coords(&self) -> &[u8] {
         &self.coords
     }
+
+    /// Returns the `i`-th coordinate, or `None` if `i` is not less than the dimension.
+    pub fn coordinate(&self, i: usize) -> Option<u8> {
+        self.coords.get(i).copied()
+    }
+
+    /// Iterates over the coordinates, that is, the positions in each chain.
+    pub fn iter(&self) -> core::iter::Copied<core::slice::Iter<'_, u8>> {
+        self.coords.iter().copied()
+    }
+}
+
+impl<'a> IntoIterator for &'a Codeword {
+    type Item = u8;
+    type IntoIter = core::iter::Copied<core::slice::Iter<'a, u8>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
 }
 
 /// Chops bytes into coordinates of a given resolution.
@@ -91,7 +571,10 @@ fn bytes_to_coordinates(bytes: &[u8], resolution_bits: usize) -> Vec<u8> {
 
 #[cfg(test)]
 mod tests {
-    use super::bytes_to_coordinates;
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::spec::SPEC_2;
 
     #[test]
     fn test_bytes_to_coordinates() {
@@ -104,4 +587,147 @@ mod tests {
         let coords = bytes_to_coordinates(&[0b01101100, 0b10100110], 8);
         assert_eq!(coords, vec![0b01101100, 0b10100110]);
     }
+
+    #[test]
+    fn test_grind_attempts() {
+        // Pinned for the seeded rng below; a change here means the encoding has changed.
+        const ATTEMPTS: usize = 16;
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let param = Param::random(SPEC_2.param_len, &mut rng);
+        let message = Message([42; 32]);
+
+        let result = grind(&SPEC_2, 10000, &param, &message, &mut rng).unwrap();
+        assert_eq!(result.attempts, ATTEMPTS);
+        assert_eq!(result.codeword.sum(), SPEC_2.target_sum);
+
+        // Giving up one attempt early must fail.
+        let mut rng = StdRng::seed_from_u64(0);
+        let param = Param::random(SPEC_2.param_len, &mut rng);
+        assert!(grind(&SPEC_2, ATTEMPTS - 1, &param, &message, &mut rng).is_none());
+    }
+
+    #[test]
+    fn test_attempt_statistics() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let stats = attempt_statistics(&SPEC_2, 40, &mut rng);
+        assert_eq!(stats.failures, 0);
+        assert!(1 <= stats.p50 && stats.p50 <= stats.p95);
+        assert!(stats.p95 <= stats.p99 && stats.p99 <= stats.max);
+        assert!(1.0 <= stats.mean && stats.mean <= stats.max as f64);
+        // With 40 samples, the 99th percentile is the largest one
+        assert_eq!(stats.p99, stats.max);
+        assert!(stats.max <= SPEC_2.suggested_max_retries());
+    }
+
+    #[test]
+    fn test_grind_histogram() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let param = Param::random(SPEC_2.param_len, &mut rng);
+        let message = Message([42; 32]);
+
+        let result = grind_verbose(&SPEC_2, 10000, &param, &message, &mut rng).unwrap();
+        let histogram = result.histogram.unwrap();
+        assert_eq!(histogram.total(), result.attempts);
+        assert_eq!(result.codeword.sum(), SPEC_2.target_sum);
+        // Only the last attempt hits the target exactly.
+        assert_eq!(histogram.count(SPEC_2.target_sum), 1);
+        assert_eq!(histogram.counts().len(), SPEC_2.max_sum() + 1);
+    }
+
+    #[test]
+    fn test_from_coords() {
+        let dimension = SPEC_2.dimension();
+        let mut coords = vec![8u8; dimension];
+        coords[0] = 15;
+
+        let codeword = Codeword::from_coords(coords.clone(), &SPEC_2).unwrap();
+        assert_eq!(codeword.coords(), &coords[..]);
+        assert_eq!(codeword.coordinate(0), Some(15));
+        assert_eq!(codeword.coordinate(dimension), None);
+        assert_eq!(codeword.iter().collect::<Vec<_>>(), coords);
+        assert_eq!(codeword.sum(), 8 * (dimension - 1) + 15);
+        assert!(!codeword.is_valid(&SPEC_2));
+
+        coords[3] = 16;
+        assert_eq!(
+            Codeword::from_coords(coords, &SPEC_2),
+            Err(SpecError::CoordinateOutOfRange {
+                index: 3,
+                coordinate: 16,
+                chain_len: 16
+            })
+        );
+        assert_eq!(
+            Codeword::from_coords(vec![0; dimension + 1], &SPEC_2),
+            Err(SpecError::DimensionMismatch {
+                expected: dimension,
+                actual: dimension + 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_is_valid_matches_new_valid() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let param = Param::random(SPEC_2.param_len, &mut rng);
+        let message = Message([42; 32]);
+
+        let result = grind(&SPEC_2, 10000, &param, &message, &mut rng).unwrap();
+        let codeword = Codeword::from_coords(result.codeword.coords().to_vec(), &SPEC_2).unwrap();
+        assert!(codeword.is_valid(&SPEC_2));
+        assert_eq!(codeword, result.codeword);
+    }
+
+    #[cfg(all(feature = "multi_buffer", not(target_os = "zkvm")))]
+    #[test]
+    fn test_multi_buffer_grind_matches_scalar() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let param = Param::random(SPEC_2.param_len, &mut rng);
+        let message = Message([42; 32]);
+
+        for seed in [[0; 32], [1; 32], [0xab; 32]] {
+            let (index, codeword) = (0..)
+                .find_map(|index| {
+                    let nonce = candidate_nonce(&seed, index);
+                    new_valid(&SPEC_2, &param, &message, &nonce).map(|codeword| (index, codeword))
+                })
+                .unwrap();
+
+            // Budgets that end mid-group must still stop at exactly the right candidate.
+            for max_retries in index..index + GROUP_LEN + 1 {
+                let result = grind_seeded(&SPEC_2, max_retries, &param, &message, &seed);
+                if max_retries <= index {
+                    assert!(result.is_none());
+                } else {
+                    let result = result.unwrap();
+                    assert_eq!(result.attempts, index + 1);
+                    assert_eq!(result.codeword, codeword);
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_grind_matches_serial() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let param = Param::random(SPEC_2.param_len, &mut rng);
+        let message = Message([42; 32]);
+
+        for seed in [[0; 32], [1; 32], [0xab; 32]] {
+            let serial = grind_seeded(&SPEC_2, 10000, &param, &message, &seed).unwrap();
+            let parallel = grind_parallel(&SPEC_2, 10000, &param, &message, &seed).unwrap();
+            assert_eq!(parallel.nonce.0, serial.nonce.0);
+            assert_eq!(parallel.attempts, serial.attempts);
+            assert_eq!(parallel.codeword.coords(), serial.codeword.coords());
+        }
+
+        // Exhausting the budget fails the same way.
+        let seed = [0; 32];
+        let attempts = grind_seeded(&SPEC_2, 10000, &param, &message, &seed)
+            .unwrap()
+            .attempts;
+        assert!(grind_parallel(&SPEC_2, attempts - 1, &param, &message, &seed).is_none());
+    }
 }
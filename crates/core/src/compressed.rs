@@ -0,0 +1,204 @@
+// Copyright 2025 Irreducible Inc.
+//! A compact wire form of [`AggregatedSignature`] for validators that share params.
+//!
+//! Every [`ValidatorSignature`] names the param of its tree, and every public key inside it names
+//! the param again, so an aggregate over validators on a common genesis param repeats it several
+//! times per signature. [`CompressedAggregate`] keeps each distinct param once and replaces every
+//! occurrence with an index into that table.
+
+use alloc::collections::BTreeMap;
+use alloc::{vec, vec::Vec};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{AggregatedSignature, AnySignature, DecodeError, Param, ValidatorSignature};
+
+/// An [`AggregatedSignature`] with its params deduplicated into a table.
+///
+/// It only holds vectors, integers and byte strings, so it deserializes wherever the aggregate
+/// itself does, including inside the zkVM guests.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CompressedAggregate {
+    /// The distinct params, in the order they first occur
+    pub params: Vec<Param>,
+    /// The signatures, with every param left empty
+    pub signatures: Vec<ValidatorSignature>,
+    /// The index into `params` of every param that was left empty, signature by signature
+    pub param_indices: Vec<u32>,
+}
+
+/// The params of a signature, in the order their indices are stored.
+fn params_mut(sig: &mut ValidatorSignature) -> Vec<&mut Param> {
+    let ValidatorSignature {
+        param, signature, ..
+    } = sig;
+    match signature {
+        AnySignature::Xmss(signature) => vec![param, &mut signature.public_key.param],
+        AnySignature::Hyper(signature) => vec![
+            param,
+            &mut signature.bottom.public_key.param,
+            &mut signature.bottom_param,
+            &mut signature.top.public_key.param,
+        ],
+    }
+}
+
+impl AggregatedSignature {
+    /// Deduplicates the params of the signatures, see [`CompressedAggregate`].
+    pub fn to_compressed(&self) -> CompressedAggregate {
+        let mut table: BTreeMap<Vec<u8>, u32> = BTreeMap::new();
+        let mut params = Vec::new();
+        let mut param_indices = Vec::new();
+        let mut signatures = self.signatures.clone();
+        for sig in &mut signatures {
+            for param in params_mut(sig) {
+                let param = core::mem::replace(param, Param { data: Vec::new() });
+                let index = *table.entry(param.data.clone()).or_insert_with(|| {
+                    params.push(param);
+                    (params.len() - 1) as u32
+                });
+                param_indices.push(index);
+            }
+        }
+        CompressedAggregate {
+            params,
+            signatures,
+            param_indices,
+        }
+    }
+
+    /// Restores an aggregate from [`AggregatedSignature::to_compressed`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an index is out of range, or if there are more or fewer indices than
+    /// params left out of the signatures.
+    pub fn from_compressed(compressed: CompressedAggregate) -> Result<Self, DecodeError> {
+        let CompressedAggregate {
+            params,
+            mut signatures,
+            param_indices,
+        } = compressed;
+        let expected: usize = signatures.iter_mut().map(|sig| params_mut(sig).len()).sum();
+        if param_indices.len() != expected {
+            return Err(DecodeError::ParamCountMismatch {
+                expected,
+                actual: param_indices.len(),
+            });
+        }
+        let mut param_indices = param_indices.into_iter();
+        for sig in &mut signatures {
+            for param in params_mut(sig) {
+                let index = param_indices.next().expect("one index per param");
+                *param = (params.get(index as usize).cloned()).ok_or(
+                    DecodeError::ParamIndexOutOfRange {
+                        index,
+                        num_params: params.len(),
+                    },
+                )?;
+            }
+        }
+        Ok(Self::new(signatures))
+    }
+
+    /// The number of bytes the aggregate takes up when encoded with bincode, as the zkVM guests
+    /// read it.
+    #[cfg(feature = "std")]
+    pub fn encoded_size(&self) -> usize {
+        bincode::serialized_size(self).expect("aggregates serialize") as usize
+    }
+}
+
+impl CompressedAggregate {
+    /// The number of bytes the compressed aggregate takes up when encoded with bincode, for
+    /// comparison with [`AggregatedSignature::encoded_size`].
+    #[cfg(feature = "std")]
+    pub fn encoded_size(&self) -> usize {
+        bincode::serialized_size(self).expect("aggregates serialize") as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::*;
+    use crate::{Message, Signer, hypertree::HyperSigner, spec::SPEC_2};
+
+    #[test]
+    fn test_round_trip() {
+        let message = Message([42; 32]);
+        // Validators created from the same seed share their param.
+        let mut signatures = Vec::new();
+        for seed in [1, 1, 1, 2] {
+            let mut signer = Signer::new(StdRng::seed_from_u64(seed), 10000, SPEC_2, 4).unwrap();
+            signatures.push(ValidatorSignature {
+                epoch: 0,
+                signature: signer.sign(0, &message).unwrap().into(),
+                xmss_root: signer.root,
+                param: signer.param.clone(),
+                validator_index: None,
+            });
+        }
+        let mut hyper_signer =
+            HyperSigner::new(StdRng::seed_from_u64(3), 10000, SPEC_2, 1, 1).unwrap();
+        signatures.push(ValidatorSignature {
+            epoch: 1,
+            signature: hyper_signer.sign(1, &message).unwrap().into(),
+            xmss_root: hyper_signer.root,
+            param: hyper_signer.param.clone(),
+            validator_index: None,
+        });
+        let aggregated = AggregatedSignature::new(signatures);
+
+        let compressed = aggregated.to_compressed();
+        // Seeds 1 and 2, and the top and bottom params of the hypertree.
+        assert_eq!(compressed.params.len(), 4);
+        assert_eq!(compressed.param_indices.len(), 4 * 2 + 4);
+        assert!(compressed.encoded_size() < aggregated.encoded_size());
+
+        // The compressed form goes through bincode like any guest input.
+        let bytes = bincode::serialize(&compressed).unwrap();
+        assert_eq!(bytes.len(), compressed.encoded_size());
+        let decoded: CompressedAggregate = bincode::deserialize(&bytes).unwrap();
+        let restored = AggregatedSignature::from_compressed(decoded).unwrap();
+        assert_eq!(
+            bincode::serialize(&restored).unwrap(),
+            bincode::serialize(&aggregated).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_rejects_malformed() {
+        let message = Message([42; 32]);
+        let mut signer = Signer::new(StdRng::seed_from_u64(1), 10000, SPEC_2, 4).unwrap();
+        let aggregated = AggregatedSignature::new(vec![ValidatorSignature {
+            epoch: 0,
+            signature: signer.sign(0, &message).unwrap().into(),
+            xmss_root: signer.root,
+            param: signer.param.clone(),
+            validator_index: None,
+        }]);
+        let compressed = aggregated.to_compressed();
+
+        let mut out_of_range = compressed.clone();
+        out_of_range.param_indices[1] = 1;
+        assert_eq!(
+            AggregatedSignature::from_compressed(out_of_range).map(drop),
+            Err(DecodeError::ParamIndexOutOfRange {
+                index: 1,
+                num_params: 1
+            })
+        );
+
+        let mut extra = compressed;
+        extra.param_indices.push(0);
+        assert_eq!(
+            AggregatedSignature::from_compressed(extra).map(drop),
+            Err(DecodeError::ParamCountMismatch {
+                expected: 2,
+                actual: 3
+            })
+        );
+    }
+}
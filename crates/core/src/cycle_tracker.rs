@@ -0,0 +1,46 @@
+// Copyright 2025 Irreducible Inc.
+//! Cycle counts of the phases of signature verification in the SP1 zkVM.
+//!
+//! With the `cycle_tracker` feature, every phase of [`verify_signature`](crate::verify_signature)
+//! is announced on stdout as a region of the SP1 cycle tracker, which adds up the cycles of the
+//! region over the whole execution in its report. Without the feature the phases compile to plain
+//! calls.
+
+/// The reconstruction of the codeword from the message and the nonce
+pub const CODEWORD: &str = "codeword";
+/// The completion of the hash chains from the signed hashes to their ends
+pub const HASH_CHAINS: &str = "hash_chains";
+/// The verification of the Merkle proof of the public key against the root
+pub const MERKLE_PROOF: &str = "merkle_proof";
+
+/// Every phase, in the order of verification
+pub const PHASES: [&str; 3] = [CODEWORD, HASH_CHAINS, MERKLE_PROOF];
+
+/// Runs `phase` as the region `region` of the cycle tracker
+#[cfg(feature = "cycle_tracker")]
+pub(crate) fn track<T>(region: &str, phase: impl FnOnce() -> T) -> T {
+    println!("cycle-tracker-report-start: {region}");
+    let result = phase();
+    println!("cycle-tracker-report-end: {region}");
+    result
+}
+
+/// Runs `phase`, without tracking its cycles
+#[cfg(not(feature = "cycle_tracker"))]
+#[inline(always)]
+pub(crate) fn track<T>(_region: &str, phase: impl FnOnce() -> T) -> T {
+    phase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_track() {
+        // Tracking changes nothing of the result, with or without the feature
+        for region in PHASES {
+            assert_eq!(track(region, || region.len()), region.len());
+        }
+    }
+}
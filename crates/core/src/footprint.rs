@@ -0,0 +1,135 @@
+// Copyright 2025 Irreducible Inc.
+
+//! How much memory keys, trees and signatures hold, for capacity planning.
+//!
+//! The counts are computed from the lengths of the vectors, so they ignore the spare capacity
+//! of the vectors and the overhead of the allocator.
+
+use alloc::vec::Vec;
+use core::mem::size_of;
+
+use crate::{
+    AggregatedSignature, AnySignature, OtsSignature, Param, Pk, Signature, Sk, ValidatorSignature,
+    hash::Hash, hypertree::HyperSignature,
+};
+
+/// The bytes a value holds in memory.
+pub trait MemoryFootprint {
+    /// The bytes the value owns on the heap.
+    fn heap_bytes(&self) -> usize;
+
+    /// The bytes of the value itself and of everything it owns on the heap.
+    fn memory_footprint(&self) -> usize
+    where
+        Self: Sized,
+    {
+        size_of::<Self>() + self.heap_bytes()
+    }
+}
+
+impl<T: MemoryFootprint> MemoryFootprint for Vec<T> {
+    fn heap_bytes(&self) -> usize {
+        self.iter().map(T::memory_footprint).sum()
+    }
+}
+
+impl MemoryFootprint for Hash {
+    fn heap_bytes(&self) -> usize {
+        0
+    }
+}
+
+impl MemoryFootprint for Param {
+    fn heap_bytes(&self) -> usize {
+        self.data.len()
+    }
+}
+
+impl MemoryFootprint for Sk {
+    fn heap_bytes(&self) -> usize {
+        self.param.heap_bytes() + self.start_hashes.heap_bytes()
+    }
+}
+
+impl MemoryFootprint for Pk {
+    fn heap_bytes(&self) -> usize {
+        self.param.heap_bytes() + self.end_hashes.heap_bytes()
+    }
+}
+
+impl MemoryFootprint for OtsSignature {
+    fn heap_bytes(&self) -> usize {
+        self.hashes.heap_bytes()
+    }
+}
+
+impl MemoryFootprint for Signature {
+    fn heap_bytes(&self) -> usize {
+        self.signature.heap_bytes()
+            + self.hash_tree_proof.path.heap_bytes()
+            + self.public_key.heap_bytes()
+    }
+}
+
+impl MemoryFootprint for HyperSignature {
+    fn heap_bytes(&self) -> usize {
+        self.bottom.heap_bytes() + self.bottom_param.heap_bytes() + self.top.heap_bytes()
+    }
+}
+
+impl MemoryFootprint for AnySignature {
+    fn heap_bytes(&self) -> usize {
+        match self {
+            AnySignature::Xmss(signature) => signature.heap_bytes(),
+            AnySignature::Hyper(signature) => signature.heap_bytes(),
+        }
+    }
+}
+
+impl MemoryFootprint for ValidatorSignature {
+    fn heap_bytes(&self) -> usize {
+        self.signature.heap_bytes() + self.param.heap_bytes()
+    }
+}
+
+impl MemoryFootprint for AggregatedSignature {
+    fn heap_bytes(&self) -> usize {
+        self.signatures.heap_bytes()
+    }
+}
+
+/// The memory held by a [`crate::Signer`], see [`crate::Signer::memory_footprint`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SignerFootprint {
+    /// The bytes of the secret keys of all epochs.
+    pub secret_bytes: usize,
+    /// The bytes of the one-time public keys of all epochs.
+    pub public_key_bytes: usize,
+    /// The bytes of the hash tree, or of the traversal state that replaces it.
+    pub tree_bytes: usize,
+}
+
+impl SignerFootprint {
+    /// The bytes of the keys and of the tree.
+    pub fn total(&self) -> usize {
+        self.secret_bytes + self.public_key_bytes + self.tree_bytes
+    }
+}
+
+impl core::ops::Add for SignerFootprint {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            secret_bytes: self.secret_bytes + other.secret_bytes,
+            public_key_bytes: self.public_key_bytes + other.public_key_bytes,
+            tree_bytes: self.tree_bytes + other.tree_bytes,
+        }
+    }
+}
+
+impl core::iter::Sum for SignerFootprint {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), |total, footprint| total + footprint)
+    }
+}
@@ -1,24 +1,109 @@
 // Copyright 2025 Irreducible Inc.
 //! Definition of various tweaked hash functions used in the project.
+//!
+//! There is no zkVM-specific keccak in this crate. The SP1 and RISC0 guests patch `tiny-keccak`
+//! with their precompile-backed forks, so the `Keccak::v256` states below are accelerated there
+//! and plain software keccak everywhere else.
+//!
+//! # Interoperability
+//!
+//! [`KeccakBackend`], [`Sha3Backend`] and the Blake3 and Poseidon2 backends share only the tweak
+//! separator bytes with the `b-wagn/hash-sig` reference implementation, not the layout of the
+//! tweaked inputs, so their signatures do not verify there or the other way round, for any spec.
+//! They ignore the epoch. [`KeccakBackend`] and [`Sha3Backend`] hash `param || tweak || input`,
+//! and the other two key by the param and tweak instead, with:
+//! * the message input as `nonce || message`;
+//! * the chain input as `hash || chain_index || pos_in_chain`, both big-endian `u64`;
+//! * the tree node input as `level || index || left || right`, both big-endian `u32`;
+//! * the leaf input as the end hashes of the public key, under a tweak of its own.
+//!
+//! `HashSigCompat`, behind the `compat-hashsig` feature, lays the hashes out as the reference's
+//! SHA3 tweakable hash and message hash instead. Each is a SHA3-256 of
+//! * `param || 0x00 || epoch || chain_index || pos_in_chain || hash` for a chain step, with the
+//!   epoch as a big-endian `u32` and the chain index and position of the output as bytes;
+//! * `param || 0x01 || level || index || left || right` for a tree node, with the level of the
+//!   parent as a byte, counting the leaves as level 0, and its index as a big-endian `u32`;
+//! * `param || 0x01 || 0x00 || epoch || end_hashes` for the leaf of a public key, i.e. the tree
+//!   tweak of the leaf at index `epoch`;
+//! * `nonce || param || 0x02 || epoch || message` for a message, with the epoch as a big-endian
+//!   `u32`.
+//!
+//! This covers the reference's SHA3 instantiations with 32-byte hashes ([`HASH_LEN`]), a
+//! parameter of `spec.param_len` bytes, [`crate::RAND_LEN`] bytes of randomness and 32-byte
+//! messages, over trees of at most `2^32` leaves and chains of every valid
+//! [`crate::spec::Spec`], which has at most 256 chains of at most 256 positions. The codeword is
+//! still chopped from the message hash as described in [`crate::code`], not by the reference's
+//! encodings, so it is the chains, leaves and tree paths under a given codeword that agree, not
+//! the choice of codeword. The vectors under `tests/fixtures/hashsig_compat_*.json` pin the
+//! layout; see [`crate::kat`] for how they are generated.
 
-use rand::{RngCore as _, rngs::StdRng};
-use serde::{Deserialize, Serialize};
-use tiny_keccak::{Hasher, Keccak};
+use core::{fmt, str::FromStr};
 
-use crate::{Message, Nonce, Param, Pk};
+#[cfg(feature = "std")]
+use rand::RngCore;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use tiny_keccak::{Hasher, Keccak, Sha3};
 
-// Taken from:
+use crate::{
+    Message, Nonce, Param, Pk,
+    hex::{self, ParseHexError},
+};
+
+// Taken from, without the layout of the inputs they prefix, see the module docs:
 // https://github.com/b-wagn/hash-sig/blob/34fa36886d2942f851f26345c49f92fdb96ac7eb/src/lib.rs#L4-L6
 const TWEAK_CHAIN: u8 = 0x00;
 const TWEAK_TREE: u8 = 0x01;
 const TWEAK_MESSAGE: u8 = 0x02;
+/// Separates public-key leaves from inner tree nodes, which used to share [`TWEAK_TREE`].
+const TWEAK_LEAF: u8 = 0x03;
+
+/// The length of the hash output in bytes.
+pub const HASH_LEN: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Hash(pub [u8; HASH_LEN]);
+
+/// The serde layout of [`Hash`] in binary formats.
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Hash")]
+struct HashRepr([u8; HASH_LEN]);
+
+impl fmt::Display for Hash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        hex::write(f, &self.0)
+    }
+}
+
+impl fmt::LowerHex for Hash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        hex::write(f, &self.0)
+    }
+}
+
+impl FromStr for Hash {
+    type Err = ParseHexError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        hex::decode_array(s).map(Hash)
+    }
+}
+
+impl Serialize for Hash {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        hex::serialize(self, || HashRepr(self.0), serializer)
+    }
+}
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
-pub struct Hash(pub [u8; 32]);
+impl<'de> Deserialize<'de> for Hash {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        hex::deserialize(deserializer, |HashRepr(bytes)| Hash(bytes))
+    }
+}
 
 impl Hash {
-    pub fn random(rng: &mut StdRng) -> Self {
-        let mut hash = [0u8; 32];
+    #[cfg(feature = "std")]
+    pub fn random(rng: &mut (impl RngCore + ?Sized)) -> Self {
+        let mut hash = [0u8; HASH_LEN];
         rng.fill_bytes(&mut hash);
         Hash(hash)
     }
@@ -30,33 +115,529 @@ impl AsRef<[u8]> for Hash {
     }
 }
 
+/// A family of tweaked hash functions instantiating the scheme.
+///
+/// An instance is created for one [`Param`] and may absorb whatever is shared between invocations
+/// up front, so callers should create it once and reuse it for all the hashes under that param.
+pub trait TweakableHash: Clone + Send + Sync {
+    /// A unique name for the instantiation, bound into [`crate::spec::Spec::id_with_backend`] so
+    /// that proofs cannot mix hash functions.
+    const NAME: &'static str;
+
+    /// Creates the hash functions keyed by `param`.
+    fn new(param: &Param) -> Self;
+
+    /// Hashes a message together with the nonce for a signature at `epoch`, see
+    /// [`tweak_hash_message`].
+    ///
+    /// The epoch is also the index of the signature's leaf in the tree. Only `HashSigCompat`
+    /// binds it; the other backends of this crate ignore it.
+    fn hash_message(&self, epoch: u32, message: &Message, nonce: &Nonce) -> Hash;
+
+    /// Hashes a message with four nonces.
+    ///
+    /// Backends with a multi-buffer implementation can override this to speed up grinding.
+    fn hash_message_x4(&self, epoch: u32, message: &Message, nonces: &[Nonce; 4]) -> [Hash; 4] {
+        core::array::from_fn(|i| self.hash_message(epoch, message, &nonces[i]))
+    }
+
+    /// Advances a hash chain of the key of `epoch` by one step, see [`tweak_hash_chain`].
+    fn hash_chain_step(&self, epoch: u32, chain_index: u32, pos_in_chain: u32, hash: Hash) -> Hash;
+
+    /// Walks consecutive chains of the key of `epoch` in place, see
+    /// [`crate::hash_chain::hash_chain_with`].
+    ///
+    /// `hashes[i]` is at `start_pos` of chain `first_chain_index + i` and is advanced by `steps`.
+    /// Backends with a multi-buffer implementation can override this to speed up key generation.
+    fn hash_chains(
+        &self,
+        epoch: u32,
+        first_chain_index: u32,
+        hashes: &mut [Hash],
+        start_pos: u32,
+        steps: u32,
+    ) {
+        for (i, hash) in hashes.iter_mut().enumerate() {
+            for pos_in_chain in start_pos + 1..=start_pos + steps {
+                let chain_index = first_chain_index + i as u32;
+                *hash = self.hash_chain_step(epoch, chain_index, pos_in_chain, *hash);
+            }
+        }
+    }
+
+    /// Hashes two sibling tree nodes into their parent, see [`tweak_hash_tree_node`].
+    fn hash_tree_node(&self, left: &Hash, right: &Hash, level: u32, index: u32) -> Hash;
+
+    /// Hashes the public key of `epoch` into its tree leaf, see [`tweak_public_key_hash`].
+    fn hash_public_key(&self, epoch: u32, public_key: &Pk) -> Hash;
+}
+
+/// The epoch the Keccak-only helpers pass to [`KeccakBackend`], which ignores it
+pub(crate) const KECCAK_EPOCH: u32 = 0;
+
+/// The default instantiation of the tweaked hash functions with Keccak-256.
+#[derive(Clone)]
+pub struct KeccakBackend {
+    message: TweakHasher,
+    chain: TweakHasher,
+    tree: TweakHasher,
+    leaf: TweakHasher,
+    #[cfg(all(feature = "multi_buffer", not(target_os = "zkvm")))]
+    param: Param,
+    #[cfg(all(feature = "simd", not(target_os = "zkvm")))]
+    chain_x8: crate::keccak_x4::KeccakX8,
+}
+
+impl fmt::Debug for KeccakBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeccakBackend").finish_non_exhaustive()
+    }
+}
+
+impl TweakableHash for KeccakBackend {
+    const NAME: &'static str = "keccak256";
+
+    fn new(param: &Param) -> Self {
+        Self {
+            message: TweakHasher::message(param),
+            chain: TweakHasher::chain(param),
+            tree: TweakHasher::tree(param),
+            leaf: TweakHasher::leaf(param),
+            #[cfg(all(feature = "multi_buffer", not(target_os = "zkvm")))]
+            param: param.clone(),
+            #[cfg(all(feature = "simd", not(target_os = "zkvm")))]
+            chain_x8: {
+                let mut prefix = crate::keccak_x4::KeccakX8::new();
+                prefix.update_all(param.as_ref());
+                prefix.update_all(&[TWEAK_CHAIN]);
+                prefix
+            },
+        }
+    }
+
+    fn hash_message(&self, _epoch: u32, message: &Message, nonce: &Nonce) -> Hash {
+        self.message.hash_message(message, nonce)
+    }
+
+    #[cfg(all(feature = "multi_buffer", not(target_os = "zkvm")))]
+    fn hash_message_x4(&self, _epoch: u32, message: &Message, nonces: &[Nonce; 4]) -> [Hash; 4] {
+        tweak_hash_message_x4(&self.param, message, nonces)
+    }
+
+    fn hash_chain_step(
+        &self,
+        _epoch: u32,
+        chain_index: u32,
+        pos_in_chain: u32,
+        hash: Hash,
+    ) -> Hash {
+        self.chain.hash_chain(chain_index, pos_in_chain, hash)
+    }
+
+    /// Walks groups of eight chains in lockstep with an eight-way keccak.
+    #[cfg(all(feature = "simd", not(target_os = "zkvm")))]
+    fn hash_chains(
+        &self,
+        epoch: u32,
+        first_chain_index: u32,
+        hashes: &mut [Hash],
+        start_pos: u32,
+        steps: u32,
+    ) {
+        const LANES: usize = 8;
+        let grouped = hashes.len() / LANES * LANES;
+        let (groups, rest) = hashes.split_at_mut(grouped);
+        for (group_index, group) in groups.as_chunks_mut::<LANES>().0.iter_mut().enumerate() {
+            let first = first_chain_index + (group_index * LANES) as u32;
+            let chain_indices: [[u8; 8]; LANES] =
+                core::array::from_fn(|lane| u64::from(first + lane as u32).to_be_bytes());
+            for pos_in_chain in start_pos + 1..=start_pos + steps {
+                let mut hasher = self.chain_x8.clone();
+                hasher.update(core::array::from_fn(|lane| group[lane].as_ref()));
+                hasher.update(core::array::from_fn(|lane| &chain_indices[lane][..]));
+                hasher.update_all(&u64::from(pos_in_chain).to_be_bytes());
+                for (hash, digest) in group.iter_mut().zip(hasher.finalize()) {
+                    *hash = Hash(digest);
+                }
+            }
+        }
+        for (i, hash) in rest.iter_mut().enumerate() {
+            for pos_in_chain in start_pos + 1..=start_pos + steps {
+                let chain_index = first_chain_index + (grouped + i) as u32;
+                *hash = self.hash_chain_step(epoch, chain_index, pos_in_chain, *hash);
+            }
+        }
+    }
+
+    fn hash_tree_node(&self, left: &Hash, right: &Hash, level: u32, index: u32) -> Hash {
+        self.tree.hash_tree_node(left, right, level, index)
+    }
+
+    fn hash_public_key(&self, _epoch: u32, public_key: &Pk) -> Hash {
+        self.leaf.hash_public_key(public_key)
+    }
+}
+
+/// The tweaked hash functions instantiated with SHA3-256.
+///
+/// The inputs are laid out exactly as for [`KeccakBackend`]; only the padding differs.
+#[derive(Clone)]
+pub struct Sha3Backend {
+    message: TweakHasher<Sha3>,
+    chain: TweakHasher<Sha3>,
+    tree: TweakHasher<Sha3>,
+    leaf: TweakHasher<Sha3>,
+}
+
+impl fmt::Debug for Sha3Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sha3Backend").finish_non_exhaustive()
+    }
+}
+
+impl TweakableHash for Sha3Backend {
+    const NAME: &'static str = "sha3-256";
+
+    fn new(param: &Param) -> Self {
+        Self {
+            message: TweakHasher::with_state(Sha3::v256(), param, TWEAK_MESSAGE),
+            chain: TweakHasher::with_state(Sha3::v256(), param, TWEAK_CHAIN),
+            tree: TweakHasher::with_state(Sha3::v256(), param, TWEAK_TREE),
+            leaf: TweakHasher::with_state(Sha3::v256(), param, TWEAK_LEAF),
+        }
+    }
+
+    fn hash_message(&self, _epoch: u32, message: &Message, nonce: &Nonce) -> Hash {
+        self.message.hash_message(message, nonce)
+    }
+
+    fn hash_chain_step(
+        &self,
+        _epoch: u32,
+        chain_index: u32,
+        pos_in_chain: u32,
+        hash: Hash,
+    ) -> Hash {
+        self.chain.hash_chain(chain_index, pos_in_chain, hash)
+    }
+
+    fn hash_tree_node(&self, left: &Hash, right: &Hash, level: u32, index: u32) -> Hash {
+        self.tree.hash_tree_node(left, right, level, index)
+    }
+
+    fn hash_public_key(&self, _epoch: u32, public_key: &Pk) -> Hash {
+        self.leaf.hash_public_key(public_key)
+    }
+}
+
+/// The tweaked hash functions instantiated with keyed Blake3.
+///
+/// Each tweak gets its own key, derived from `param` in a context naming the tweak, so the
+/// inputs need no prefix. Blake3 is fast natively but expensive in a zkVM, so this backend is
+/// meant for host-only deployments.
+#[cfg(feature = "blake3")]
+#[derive(Clone)]
+pub struct Blake3Backend {
+    message: blake3::Hasher,
+    chain: blake3::Hasher,
+    tree: blake3::Hasher,
+    leaf: blake3::Hasher,
+}
+
+#[cfg(feature = "blake3")]
+impl Blake3Backend {
+    fn keyed(param: &Param, tweak: u8) -> blake3::Hasher {
+        let context = format!("leansig 2025 tweaked hash, tweak {tweak:#04x}");
+        blake3::Hasher::new_keyed(&blake3::derive_key(&context, param.as_ref()))
+    }
+
+    fn finish(prefix: &blake3::Hasher, parts: &[&[u8]]) -> Hash {
+        let mut hasher = prefix.clone();
+        for part in parts {
+            hasher.update(part);
+        }
+        Hash(*hasher.finalize().as_bytes())
+    }
+}
+
+#[cfg(feature = "blake3")]
+impl fmt::Debug for Blake3Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Blake3Backend").finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "blake3")]
+impl TweakableHash for Blake3Backend {
+    const NAME: &'static str = "blake3";
+
+    fn new(param: &Param) -> Self {
+        Self {
+            message: Self::keyed(param, TWEAK_MESSAGE),
+            chain: Self::keyed(param, TWEAK_CHAIN),
+            tree: Self::keyed(param, TWEAK_TREE),
+            leaf: Self::keyed(param, TWEAK_LEAF),
+        }
+    }
+
+    fn hash_message(&self, _epoch: u32, message: &Message, nonce: &Nonce) -> Hash {
+        Self::finish(&self.message, &[nonce.as_ref(), message.as_ref()])
+    }
+
+    fn hash_chain_step(
+        &self,
+        _epoch: u32,
+        chain_index: u32,
+        pos_in_chain: u32,
+        hash: Hash,
+    ) -> Hash {
+        Self::finish(
+            &self.chain,
+            &[
+                hash.as_ref(),
+                &u64::from(chain_index).to_be_bytes(),
+                &u64::from(pos_in_chain).to_be_bytes(),
+            ],
+        )
+    }
+
+    fn hash_tree_node(&self, left: &Hash, right: &Hash, level: u32, index: u32) -> Hash {
+        Self::finish(
+            &self.tree,
+            &[
+                &level.to_be_bytes(),
+                &index.to_be_bytes(),
+                left.as_ref(),
+                right.as_ref(),
+            ],
+        )
+    }
+
+    fn hash_public_key(&self, _epoch: u32, public_key: &Pk) -> Hash {
+        let mut hasher = self.leaf.clone();
+        for h in public_key.end_hashes.iter() {
+            hasher.update(h.as_ref());
+        }
+        Hash(*hasher.finalize().as_bytes())
+    }
+}
+
+/// The tweaked hash functions laid out as the SHA3-256 instantiation of the `b-wagn/hash-sig`
+/// reference implementation, see the [module docs](self#interoperability) for the layout and the
+/// parameter sets it covers.
+///
+/// Unlike the other backends, it binds the epoch into the chain, leaf and message hashes.
+///
+/// # Panics
+/// The methods panic for chain indices, chain positions or tree levels that do not fit in a byte,
+/// which no valid spec or tree produces.
+#[cfg(feature = "compat-hashsig")]
+#[derive(Clone)]
+pub struct HashSigCompat {
+    param: Param,
+    /// SHA3-256 after absorbing `param`, the prefix of every hash but the message hash
+    prefix: Sha3,
+}
+
+#[cfg(feature = "compat-hashsig")]
+impl HashSigCompat {
+    fn finish(&self, tweak: &[u8], absorb: impl FnOnce(&mut Sha3)) -> Hash {
+        let mut hasher = self.prefix.clone();
+        hasher.update(tweak);
+        absorb(&mut hasher);
+        let mut result = [0; HASH_LEN];
+        hasher.finalize(&mut result);
+        Hash(result)
+    }
+
+    /// `0x01 || level || pos_in_level`, with the level as a byte and the position as a
+    /// big-endian `u32`
+    fn tree_tweak(level: u32, pos_in_level: u32) -> [u8; 6] {
+        let level = u8::try_from(level).expect("trees are at most 32 levels high");
+        let mut tweak = [TWEAK_TREE, level, 0, 0, 0, 0];
+        tweak[2..].copy_from_slice(&pos_in_level.to_be_bytes());
+        tweak
+    }
+
+    /// `0x00 || epoch || chain_index || pos_in_chain`, with the epoch as a big-endian `u32` and
+    /// the chain index and position as bytes
+    fn chain_tweak(epoch: u32, chain_index: u32, pos_in_chain: u32) -> [u8; 7] {
+        let chain_index = u8::try_from(chain_index).expect("a valid spec has at most 256 chains");
+        let pos_in_chain = u8::try_from(pos_in_chain).expect("chains have at most 256 positions");
+        let mut tweak = [TWEAK_CHAIN, 0, 0, 0, 0, chain_index, pos_in_chain];
+        tweak[1..5].copy_from_slice(&epoch.to_be_bytes());
+        tweak
+    }
+}
+
+#[cfg(feature = "compat-hashsig")]
+impl fmt::Debug for HashSigCompat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HashSigCompat").finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "compat-hashsig")]
+impl TweakableHash for HashSigCompat {
+    const NAME: &'static str = "hash-sig-sha3-256";
+
+    fn new(param: &Param) -> Self {
+        let mut prefix = Sha3::v256();
+        prefix.update(param.as_ref());
+        Self {
+            param: param.clone(),
+            prefix,
+        }
+    }
+
+    fn hash_message(&self, epoch: u32, message: &Message, nonce: &Nonce) -> Hash {
+        // The randomness comes first here, so there is no prefix state to reuse.
+        let mut hasher = Sha3::v256();
+        hasher.update(nonce.as_ref());
+        hasher.update(self.param.as_ref());
+        hasher.update(&[TWEAK_MESSAGE]);
+        hasher.update(&epoch.to_be_bytes());
+        hasher.update(message.as_ref());
+        let mut result = [0; HASH_LEN];
+        hasher.finalize(&mut result);
+        Hash(result)
+    }
+
+    fn hash_chain_step(&self, epoch: u32, chain_index: u32, pos_in_chain: u32, hash: Hash) -> Hash {
+        let tweak = Self::chain_tweak(epoch, chain_index, pos_in_chain);
+        self.finish(&tweak, |hasher| hasher.update(hash.as_ref()))
+    }
+
+    fn hash_tree_node(&self, left: &Hash, right: &Hash, level: u32, index: u32) -> Hash {
+        // `level` is the level of the children, the reference tweaks with that of the parent.
+        let tweak = Self::tree_tweak(level + 1, index);
+        self.finish(&tweak, |hasher| {
+            hasher.update(left.as_ref());
+            hasher.update(right.as_ref());
+        })
+    }
+
+    fn hash_public_key(&self, epoch: u32, public_key: &Pk) -> Hash {
+        let tweak = Self::tree_tweak(0, epoch);
+        self.finish(&tweak, |hasher| absorb_public_key(hasher, public_key))
+    }
+}
+
+/// A sponge state that has already absorbed the `param || tweak` prefix shared by every
+/// invocation of one tweaked hash function.
+///
+/// Cloning the prefix state is cheaper than re-absorbing `param` for every hash, which adds up
+/// when grinding nonces or walking hash chains. Construct one with [`TweakHasher::message`],
+/// [`TweakHasher::chain`] or [`TweakHasher::tree`] and use the matching `hash_*` methods.
+#[derive(Clone)]
+pub struct TweakHasher<S = Keccak> {
+    prefix: S,
+    tweak: u8,
+}
+
+impl TweakHasher {
+    fn new(param: &Param, tweak: u8) -> Self {
+        Self::with_state(Keccak::v256(), param, tweak)
+    }
+
+    /// Prefix state for [`TweakHasher::hash_message`].
+    pub fn message(param: &Param) -> Self {
+        Self::new(param, TWEAK_MESSAGE)
+    }
+
+    /// Prefix state for [`TweakHasher::hash_chain`].
+    pub fn chain(param: &Param) -> Self {
+        Self::new(param, TWEAK_CHAIN)
+    }
+
+    /// Prefix state for [`TweakHasher::hash_tree_node`].
+    pub fn tree(param: &Param) -> Self {
+        Self::new(param, TWEAK_TREE)
+    }
+
+    /// Prefix state for [`TweakHasher::hash_public_key`].
+    pub fn leaf(param: &Param) -> Self {
+        Self::new(param, TWEAK_LEAF)
+    }
+}
+
+impl<S: Hasher + Clone> TweakHasher<S> {
+    fn with_state(mut prefix: S, param: &Param, tweak: u8) -> Self {
+        prefix.update(param.as_ref());
+        prefix.update(&[tweak]);
+        Self { prefix, tweak }
+    }
+
+    fn finish(&self, tweak: u8, absorb: impl FnOnce(&mut S)) -> Hash {
+        debug_assert_eq!(self.tweak, tweak, "hasher used with the wrong tweak");
+        let mut hasher = self.prefix.clone();
+        absorb(&mut hasher);
+        let mut result = [0u8; HASH_LEN];
+        hasher.finalize(&mut result);
+        Hash(result)
+    }
+
+    /// See [`tweak_hash_message`].
+    pub fn hash_message(&self, message: &Message, nonce: &Nonce) -> Hash {
+        self.finish(TWEAK_MESSAGE, |hasher| {
+            hasher.update(nonce.as_ref());
+            hasher.update(message.as_ref());
+        })
+    }
+
+    /// See [`tweak_hash_chain`].
+    pub fn hash_chain(&self, chain_index: u32, pos_in_chain: u32, hash: Hash) -> Hash {
+        self.finish(TWEAK_CHAIN, |hasher| {
+            hasher.update(hash.as_ref());
+            hasher.update(&u64::from(chain_index).to_be_bytes());
+            hasher.update(&u64::from(pos_in_chain).to_be_bytes());
+        })
+    }
+
+    /// See [`tweak_hash_tree_node`].
+    pub fn hash_tree_node(&self, left: &Hash, right: &Hash, level: u32, index: u32) -> Hash {
+        self.finish(TWEAK_TREE, |hasher| {
+            hasher.update(&level.to_be_bytes());
+            hasher.update(&index.to_be_bytes());
+            hasher.update(left.as_ref());
+            hasher.update(right.as_ref());
+        })
+    }
+
+    /// See [`tweak_public_key_hash`].
+    pub fn hash_public_key(&self, public_key: &Pk) -> Hash {
+        self.finish(TWEAK_LEAF, |hasher| absorb_public_key(hasher, public_key))
+    }
+}
+
+fn absorb_public_key(hasher: &mut impl Hasher, public_key: &Pk) {
+    for h in public_key.end_hashes.iter() {
+        hasher.update(h.as_ref());
+    }
+}
+
 pub fn tweak_hash_message(param: &Param, message: &Message, nonce: &Nonce) -> Hash {
-    let mut hasher = Keccak::v256();
-    hasher.update(param.as_ref());
-    hasher.update(&[TWEAK_MESSAGE]);
-    hasher.update(nonce.as_ref());
-    hasher.update(message.as_ref());
-    let mut hash = [0u8; 32];
-    hasher.finalize(&mut hash);
-    Hash(hash)
+    TweakHasher::message(param).hash_message(message, nonce)
+}
+
+/// Computes [`tweak_hash_message`] for four nonces at once with a multi-buffer keccak.
+#[cfg(all(feature = "multi_buffer", not(target_os = "zkvm")))]
+pub fn tweak_hash_message_x4(param: &Param, message: &Message, nonces: &[Nonce; 4]) -> [Hash; 4] {
+    let mut hasher = crate::keccak_x4::KeccakX4::new();
+    hasher.update_all(param.as_ref());
+    hasher.update_all(&[TWEAK_MESSAGE]);
+    hasher.update(core::array::from_fn(|lane| nonces[lane].as_ref()));
+    hasher.update_all(message.as_ref());
+    hasher.finalize().map(Hash)
 }
 
 /// Returns a hash that is meant to be used for chain hash.
-pub fn tweak_hash_chain(
-    param: &Param,
-    chain_index: usize,
-    pos_in_chain: usize,
-    hash: Hash,
-) -> Hash {
-    let mut hasher = Keccak::v256();
-    hasher.update(param.as_ref());
-    hasher.update(&[TWEAK_CHAIN]);
-    hasher.update(hash.as_ref());
-    hasher.update(&(chain_index as u64).to_be_bytes());
-    hasher.update(&(pos_in_chain as u64).to_be_bytes());
-    let mut result = [0u8; 32];
-    hasher.finalize(&mut result);
-    Hash(result)
+///
+/// The chain index and position are absorbed as big-endian `u64` words. They are `u32` in the API
+/// so that hosts and 32-bit zkVM guests agree on their range, and widening them keeps the bytes
+/// identical to the encoding that earlier roots and signatures were produced with.
+pub fn tweak_hash_chain(param: &Param, chain_index: u32, pos_in_chain: u32, hash: Hash) -> Hash {
+    TweakHasher::chain(param).hash_chain(chain_index, pos_in_chain, hash)
 }
 /// Computes the hash of a HashTree node from its two children.
 ///
@@ -78,16 +659,7 @@ pub fn tweak_hash_tree_node(
     level: u32,
     index: u32,
 ) -> Hash {
-    let mut hasher = Keccak::v256();
-    hasher.update(param.as_ref());
-    hasher.update(&[TWEAK_TREE]);
-    hasher.update(&level.to_be_bytes());
-    hasher.update(&index.to_be_bytes());
-    hasher.update(left.as_ref());
-    hasher.update(right.as_ref());
-    let mut result = [0u8; 32];
-    hasher.finalize(&mut result);
-    Hash(result)
+    TweakHasher::tree(param).hash_tree_node(left, right, level, index)
 }
 
 /// Computes the hash associated to a public key
@@ -99,13 +671,236 @@ pub fn tweak_hash_tree_node(
 /// * `param` - Cryptographic parameter
 /// * `public_key` - The public key
 pub fn tweak_public_key_hash(param: &Param, public_key: &Pk) -> Hash {
-    let mut hasher = Keccak::v256();
-    hasher.update(param.as_ref());
-    hasher.update(&[TWEAK_TREE]);
-    for h in public_key.end_hashes.iter() {
-        hasher.update(h.as_ref());
+    TweakHasher::leaf(param).hash_public_key(public_key)
+}
+
+/// The public key hash of trees built before leaves got their own tweak.
+///
+/// It shares the tweak with [`tweak_hash_tree_node`], so it is only kept to recompute old roots.
+pub fn tweak_public_key_hash_legacy(param: &Param, public_key: &Pk) -> Hash {
+    TweakHasher::tree(param).finish(TWEAK_TREE, |hasher| absorb_public_key(hasher, public_key))
+}
+
+#[cfg(test)]
+mod tests {
+    use hex_literal::hex;
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    use super::*;
+    use crate::RAND_LEN;
+
+    /// Absorbs `param || tweak || parts` from scratch, as the tweaked hashes were originally
+    /// defined.
+    fn reference_hash(param: &Param, tweak: u8, parts: &[&[u8]]) -> Hash {
+        let mut hasher = Keccak::v256();
+        hasher.update(param.as_ref());
+        hasher.update(&[tweak]);
+        for part in parts {
+            hasher.update(part);
+        }
+        let mut result = [0u8; HASH_LEN];
+        hasher.finalize(&mut result);
+        Hash(result)
+    }
+
+    #[test]
+    fn test_tweak_hasher_matches_reference() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..32 {
+            let param = Param::random(rng.random_range(0..64), &mut rng);
+            let (a, b) = (Hash::random(&mut rng), Hash::random(&mut rng));
+
+            let mut message = Message([0; 32]);
+            rng.fill_bytes(&mut message.0);
+            let mut nonce = Nonce([0; RAND_LEN]);
+            rng.fill_bytes(&mut nonce.0);
+            assert_eq!(
+                TweakHasher::message(&param).hash_message(&message, &nonce),
+                reference_hash(&param, TWEAK_MESSAGE, &[&nonce.0, &message.0])
+            );
+
+            let (chain_index, pos) = (rng.random::<u32>(), rng.random::<u32>());
+            assert_eq!(
+                TweakHasher::chain(&param).hash_chain(chain_index, pos, a),
+                reference_hash(
+                    &param,
+                    TWEAK_CHAIN,
+                    &[
+                        &a.0,
+                        &u64::from(chain_index).to_be_bytes(),
+                        &(pos as u64).to_be_bytes()
+                    ]
+                )
+            );
+
+            let (level, index) = (rng.random::<u32>(), rng.random::<u32>());
+            assert_eq!(
+                TweakHasher::tree(&param).hash_tree_node(&a, &b, level, index),
+                reference_hash(
+                    &param,
+                    TWEAK_TREE,
+                    &[&level.to_be_bytes(), &index.to_be_bytes(), &a.0, &b.0]
+                )
+            );
+
+            let pk = Pk {
+                param: param.clone(),
+                end_hashes: vec![a, b],
+            };
+            assert_eq!(
+                TweakHasher::leaf(&param).hash_public_key(&pk),
+                reference_hash(&param, TWEAK_LEAF, &[&a.0, &b.0])
+            );
+
+            // Span several 136-byte rate blocks.
+            let end_hashes: Vec<_> = (0..rng.random_range(5..40))
+                .map(|_| Hash::random(&mut rng))
+                .collect();
+            let parts: Vec<&[u8]> = end_hashes.iter().map(|h| h.as_ref()).collect();
+            let expected = reference_hash(&param, TWEAK_LEAF, &parts);
+            let pk = Pk {
+                param: param.clone(),
+                end_hashes,
+            };
+            assert_eq!(TweakHasher::leaf(&param).hash_public_key(&pk), expected);
+        }
+    }
+
+    #[cfg(all(feature = "multi_buffer", not(target_os = "zkvm")))]
+    #[test]
+    fn test_tweak_hash_message_x4_matches_scalar() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..64 {
+            let param = Param::random(rng.random_range(0..200), &mut rng);
+            let mut message = Message([0; 32]);
+            rng.fill_bytes(&mut message.0);
+            let nonces: [Nonce; 4] = core::array::from_fn(|_| Nonce::random(&mut rng));
+
+            let hashes = tweak_hash_message_x4(&param, &message, &nonces);
+            for (nonce, hash) in nonces.iter().zip(hashes) {
+                assert_eq!(hash, tweak_hash_message(&param, &message, nonce));
+            }
+        }
+    }
+
+    #[test]
+    fn test_sha3_backend_matches_reference() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let param = Param::random(18, &mut rng);
+        let hash = Hash::random(&mut rng);
+
+        let mut expected = Sha3::v256();
+        expected.update(param.as_ref());
+        expected.update(&[TWEAK_CHAIN]);
+        expected.update(hash.as_ref());
+        expected.update(&3u64.to_be_bytes());
+        expected.update(&5u64.to_be_bytes());
+        let mut result = [0u8; HASH_LEN];
+        expected.finalize(&mut result);
+
+        let sha3 = Sha3Backend::new(&param).hash_chain_step(0, 3, 5, hash);
+        assert_eq!(sha3, Hash(result));
+        assert_ne!(
+            sha3,
+            KeccakBackend::new(&param).hash_chain_step(0, 3, 5, hash)
+        );
+    }
+
+    /// Assembles every input of [`HashSigCompat`] byte by byte, as laid out in the module docs.
+    #[cfg(feature = "compat-hashsig")]
+    #[test]
+    fn test_hashsig_compat_layout() {
+        let sha3 = |parts: &[&[u8]]| {
+            let mut hasher = Sha3::v256();
+            for part in parts {
+                hasher.update(part);
+            }
+            let mut result = [0u8; HASH_LEN];
+            hasher.finalize(&mut result);
+            Hash(result)
+        };
+        let mut rng = StdRng::seed_from_u64(0);
+        let param = Param::random(18, &mut rng);
+        let (left, right) = (Hash::random(&mut rng), Hash::random(&mut rng));
+        let nonce = Nonce::random(&mut rng);
+        let message = Message([7; 32]);
+        let public_key = Pk {
+            param: param.clone(),
+            end_hashes: vec![left, right],
+        };
+        let backend = HashSigCompat::new(&param);
+        let p = param.as_ref();
+
+        assert_eq!(
+            backend.hash_chain_step(0x0102_0304, 5, 6, left),
+            sha3(&[p, &[0x00, 0x01, 0x02, 0x03, 0x04, 5, 6], &left.0])
+        );
+        assert_eq!(
+            backend.hash_tree_node(&left, &right, 2, 0x0a0b_0c0d),
+            sha3(&[p, &[0x01, 3, 0x0a, 0x0b, 0x0c, 0x0d], &left.0, &right.0])
+        );
+        assert_eq!(
+            backend.hash_public_key(9, &public_key),
+            sha3(&[p, &[0x01, 0, 0, 0, 0, 9], &left.0, &right.0])
+        );
+        assert_eq!(
+            backend.hash_message(9, &message, &nonce),
+            sha3(&[&nonce.0, p, &[0x02, 0, 0, 0, 9], &message.0])
+        );
+    }
+
+    /// Pins the chain tweak bytes, so that a host and a 32-bit guest hashing the same chain step
+    /// cannot diverge without this failing on one of them.
+    #[test]
+    fn test_chain_hash_vectors() {
+        let param = Param {
+            data: (0..18).collect(),
+        };
+        let hash = Hash([7; HASH_LEN]);
+        for (chain_index, pos_in_chain) in [(3, 5), (u32::MAX, u32::MAX)] {
+            assert_eq!(
+                tweak_hash_chain(&param, chain_index, pos_in_chain, hash),
+                reference_hash(
+                    &param,
+                    TWEAK_CHAIN,
+                    &[
+                        &hash.0,
+                        &[0, 0, 0, 0],
+                        &chain_index.to_be_bytes(),
+                        &[0, 0, 0, 0],
+                        &pos_in_chain.to_be_bytes()
+                    ]
+                )
+            );
+        }
+        assert_eq!(
+            tweak_hash_chain(&param, 3, 5, hash),
+            Hash(hex!(
+                "24c7b8ad3c1412293a117cce36f045dab80bd7b244b44cb78d273a2b96007d2d"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_public_key_hash_vectors() {
+        let param = Param {
+            data: (0..18).collect(),
+        };
+        let pk = Pk {
+            param: param.clone(),
+            end_hashes: vec![Hash([1; HASH_LEN]), Hash([2; HASH_LEN])],
+        };
+        assert_eq!(
+            tweak_public_key_hash(&param, &pk),
+            Hash(hex!(
+                "32a7651ee3fea75234f69fcf944a3efba56b9d5ac89023871da3642065a0d91a"
+            ))
+        );
+        assert_eq!(
+            tweak_public_key_hash_legacy(&param, &pk),
+            Hash(hex!(
+                "74dd75e2d4a83ae2adb2d7d1c24999d0175b9621c09d5868f085c0e2ebece33a"
+            ))
+        );
     }
-    let mut result = [0u8; 32];
-    hasher.finalize(&mut result);
-    Hash(result)
 }
@@ -1,9 +1,17 @@
 // Copyright 2025 Irreducible Inc.
+use alloc::vec::Vec;
+use core::{fmt, iter};
+
 use crate::{
     Param,
-    hash::{Hash, tweak_hash_chain},
+    hash::{HASH_LEN, Hash, KECCAK_EPOCH, KeccakBackend, TweakableHash},
 };
 
+// Chain indices and positions are `u32` so that they have the same range on 64-bit hosts and
+// 32-bit zkVM guests. A valid spec has at most one chain per bit of the message hash, each of at
+// most 256 positions, so callers can cast both from `usize` without truncation.
+const _: () = assert!(HASH_LEN * 8 <= u32::MAX as usize);
+
 /// Returns the last hash in the hash chain.
 ///
 /// A hash chain is a sequence of values where each value is computed by hashing the previous one:
@@ -16,18 +24,291 @@ use crate::{
 /// and returns the last hash in the chain.
 ///
 /// Because we use a tweak hash function, we have to specifically keep track where in the chain
-/// we are to correctly form the input to the hash function.
+/// we are to correctly form the input to the hash function. The Keccak tweaks do not bind the
+/// epoch of the key, so unlike [`hash_chain_with`] this takes none.
 pub fn hash_chain(
     param: &Param,
-    chain_index: usize,
+    chain_index: u32,
     start_hash: Hash,
-    start_pos: usize,
-    steps: usize,
+    start_pos: u32,
+    steps: u32,
 ) -> Hash {
-    let mut current = start_hash;
-    for j in 0..steps {
-        let pos_in_chain = start_pos + j + 1;
-        current = tweak_hash_chain(param, chain_index, pos_in_chain, current);
+    hash_chain_with(
+        &KeccakBackend::new(param),
+        KECCAK_EPOCH,
+        chain_index,
+        start_hash,
+        start_pos,
+        steps,
+    )
+}
+
+/// Same as [`hash_chain`], but with any hash backend, reusing the state it has already absorbed
+/// for the parameter, for a chain of the key of `epoch`.
+pub fn hash_chain_with<H: TweakableHash>(
+    hasher: &H,
+    epoch: u32,
+    chain_index: u32,
+    start_hash: Hash,
+    start_pos: u32,
+    steps: u32,
+) -> Hash {
+    let (_, end) = hash_chain_iter_with(hasher, epoch, chain_index, start_hash, start_pos)
+        .nth(steps as usize)
+        .expect("chain walks do not end");
+    end
+}
+
+/// The reasons a checked chain walk can be rejected.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ChainWalkError {
+    /// The walk would go past the last position of the chain, `chain_len - 1`.
+    PastEnd {
+        start_pos: u32,
+        steps: u32,
+        chain_len: u32,
+    },
+}
+
+impl fmt::Display for ChainWalkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChainWalkError::PastEnd {
+                start_pos,
+                steps,
+                chain_len,
+            } => write!(
+                f,
+                "walking {steps} steps from position {start_pos} goes past the end of a chain of length {chain_len}"
+            ),
+        }
+    }
+}
+
+impl core::error::Error for ChainWalkError {}
+
+/// Same as [`hash_chain`], but fails instead of walking past the end of a chain of `chain_len`
+/// positions.
+///
+/// Positions past the end were never committed to by the signer, so a walk reaching them must
+/// come from a malformed coordinate.
+pub fn hash_chain_checked(
+    param: &Param,
+    chain_index: u32,
+    start_hash: Hash,
+    start_pos: u32,
+    steps: u32,
+    chain_len: u32,
+) -> Result<Hash, ChainWalkError> {
+    hash_chain_checked_with(
+        &KeccakBackend::new(param),
+        KECCAK_EPOCH,
+        chain_index,
+        start_hash,
+        start_pos,
+        steps,
+        chain_len,
+    )
+}
+
+/// Same as [`hash_chain_checked`], but with any hash backend.
+pub fn hash_chain_checked_with<H: TweakableHash>(
+    hasher: &H,
+    epoch: u32,
+    chain_index: u32,
+    start_hash: Hash,
+    start_pos: u32,
+    steps: u32,
+    chain_len: u32,
+) -> Result<Hash, ChainWalkError> {
+    match start_pos.checked_add(steps) {
+        Some(end_pos) if end_pos < chain_len => Ok(hash_chain_with(
+            hasher,
+            epoch,
+            chain_index,
+            start_hash,
+            start_pos,
+            steps,
+        )),
+        _ => Err(ChainWalkError::PastEnd {
+            start_pos,
+            steps,
+            chain_len,
+        }),
+    }
+}
+
+/// Walks a hash chain, yielding `(pos_in_chain, hash)` for every position from `start_pos` on.
+///
+/// The first item is `(start_pos, start_hash)` itself. The walk never ends, so callers take as
+/// many steps as they need; [`hash_chain`] is the hash after `steps` of them.
+pub fn hash_chain_iter(
+    param: &Param,
+    chain_index: u32,
+    start_hash: Hash,
+    start_pos: u32,
+) -> impl Iterator<Item = (u32, Hash)> {
+    let hasher = KeccakBackend::new(param);
+    walk(start_hash, start_pos, move |pos_in_chain, hash| {
+        hasher.hash_chain_step(KECCAK_EPOCH, chain_index, pos_in_chain, hash)
+    })
+}
+
+/// Same as [`hash_chain_iter`], but with any hash backend.
+pub fn hash_chain_iter_with<H: TweakableHash>(
+    hasher: &H,
+    epoch: u32,
+    chain_index: u32,
+    start_hash: Hash,
+    start_pos: u32,
+) -> impl Iterator<Item = (u32, Hash)> + '_ {
+    walk(start_hash, start_pos, move |pos_in_chain, hash| {
+        hasher.hash_chain_step(epoch, chain_index, pos_in_chain, hash)
+    })
+}
+
+fn walk(
+    start_hash: Hash,
+    start_pos: u32,
+    step: impl Fn(u32, Hash) -> Hash,
+) -> impl Iterator<Item = (u32, Hash)> {
+    let steps = (start_pos + 1..).scan(start_hash, move |hash, pos_in_chain| {
+        *hash = step(pos_in_chain, *hash);
+        Some((pos_in_chain, *hash))
+    });
+    iter::once((start_pos, start_hash)).chain(steps)
+}
+
+/// Every hash along a set of chains, so that partial walks become lookups.
+///
+/// This takes `chain_len * dimension` hashes per key, about 18 KB for SPEC_2.
+#[derive(Clone, Debug)]
+pub struct ChainCache {
+    /// `chains[i][pos]` is the hash at position `pos` of chain `i`.
+    chains: Vec<Vec<Hash>>,
+}
+
+impl ChainCache {
+    /// Walks every chain of the key of `epoch` from its start hash to the end, keeping all the
+    /// hashes.
+    pub fn new_with_backend<H: TweakableHash>(
+        hasher: &H,
+        epoch: u32,
+        start_hashes: &[Hash],
+        chain_len: usize,
+    ) -> Self {
+        let chains = start_hashes
+            .iter()
+            .enumerate()
+            .map(|(chain_index, &start_hash)| {
+                hash_chain_iter_with(hasher, epoch, chain_index as u32, start_hash, 0)
+                    .take(chain_len)
+                    .map(|(_, hash)| hash)
+                    .collect()
+            })
+            .collect();
+        Self { chains }
+    }
+
+    /// Returns the hash at `pos_in_chain` of chain `chain_index`.
+    pub fn get(&self, chain_index: usize, pos_in_chain: usize) -> Hash {
+        self.chains[chain_index][pos_in_chain]
+    }
+
+    /// Returns the last hash of every chain.
+    pub fn end_hashes(&self) -> impl Iterator<Item = Hash> + '_ {
+        self.chains
+            .iter()
+            .map(|chain| *chain.last().expect("chains are not empty"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    use super::*;
+    use crate::{
+        hash::tweak_hash_chain,
+        spec::{SPEC_1, SPEC_2},
+    };
+
+    #[test]
+    fn test_partial_walks_compose() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..64 {
+            let param = Param::random(18, &mut rng);
+            let chain_index = rng.random_range(0..64);
+            let start_hash = Hash::random(&mut rng);
+            let n = rng.random_range(0..32);
+            let k = rng.random_range(0..=n);
+
+            let middle = hash_chain(&param, chain_index, start_hash, 0, k);
+            assert_eq!(
+                hash_chain(&param, chain_index, middle, k, n - k),
+                hash_chain(&param, chain_index, start_hash, 0, n)
+            );
+        }
+    }
+
+    #[test]
+    fn test_hash_chain_checked() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let param = Param::random(18, &mut rng);
+        let start_hash = Hash::random(&mut rng);
+
+        assert_eq!(
+            hash_chain_checked(&param, 1, start_hash, 3, 12, 16),
+            Ok(hash_chain(&param, 1, start_hash, 3, 12))
+        );
+        for (start_pos, steps) in [(3, 13), (16, 0), (0, u32::MAX), (u32::MAX, 1)] {
+            assert_eq!(
+                hash_chain_checked(&param, 1, start_hash, start_pos, steps, 16),
+                Err(ChainWalkError::PastEnd {
+                    start_pos,
+                    steps,
+                    chain_len: 16
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn test_hash_chain_iter() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let param = Param::random(18, &mut rng);
+        let start_hash = Hash::random(&mut rng);
+
+        let walk: Vec<_> = hash_chain_iter(&param, 3, start_hash, 2).take(6).collect();
+        assert_eq!(walk[0], (2, start_hash));
+        for pair in walk.windows(2) {
+            let ((pos, previous), (next_pos, hash)) = (pair[0], pair[1]);
+            assert_eq!(next_pos, pos + 1);
+            assert_eq!(hash, tweak_hash_chain(&param, 3, next_pos, previous));
+        }
+    }
+
+    #[test]
+    fn test_hash_chains_matches_scalar() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for spec in [SPEC_1, SPEC_2] {
+            let hasher = KeccakBackend::new(&Param::random(spec.param_len, &mut rng));
+            // Cover a partial group of chains on top of the full groups.
+            let start_hashes: Vec<_> = (0..spec.dimension() + 3)
+                .map(|_| Hash::random(&mut rng))
+                .collect();
+            let chain_len = spec.chain_len() as u32;
+            for start_pos in 0..chain_len {
+                let steps = chain_len - 1 - start_pos;
+                let mut hashes = start_hashes.clone();
+                hasher.hash_chains(7, 5, &mut hashes, start_pos, steps);
+                for (i, (hash, start_hash)) in (0..).zip(hashes.iter().zip(&start_hashes)) {
+                    assert_eq!(
+                        *hash,
+                        hash_chain_with(&hasher, 7, 5 + i, *start_hash, start_pos, steps)
+                    );
+                }
+            }
+        }
     }
-    current
 }
@@ -1,8 +1,38 @@
 // Copyright 2025 Irreducible Inc.
-use crate::{Hash, Param, hash::tweak_hash_tree_node};
+use alloc::collections::{BTreeMap, btree_map::Entry};
+use alloc::{vec, vec::Vec};
+use core::{fmt, marker::PhantomData};
+
+#[cfg(feature = "persistence")]
+use crate::wire::{self, PayloadKind};
+use crate::{
+    DecodeError, Hash, Param,
+    footprint::MemoryFootprint,
+    hash::{HASH_LEN, KeccakBackend, TweakableHash},
+};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 
-pub struct HashTree {
+/// The leaf that fills a tree up to a power of two of leaves.
+///
+/// A padding position could only be proven for a public key that hashes to all zeroes, which
+/// takes a preimage of the leaf hash.
+pub const PADDING_LEAF: Hash = Hash([0; HASH_LEN]);
+
+/// The format version of the files written by [`HashTree::write_to`], bumped whenever the layout
+/// of the tree changes.
+#[cfg(feature = "persistence")]
+pub const HASH_TREE_FORMAT_VERSION: u16 = 1;
+
+/// The smallest level that [`parent_level`] splits across threads; below it the thread
+/// hand-off costs more than hashing the level.
+#[cfg(feature = "parallel")]
+const PARALLEL_MIN_LEVEL_LEN: usize = 256;
+
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "", try_from = "HashTreeRepr")]
+pub struct HashTree<H: TweakableHash = KeccakBackend> {
     /// The hash nodes in each level of the tree.
     ///
     /// - `levels[0]` contains all leaf nodes (bottom level)
@@ -10,12 +40,69 @@ pub struct HashTree {
     ///
     /// Within each level, nodes are ordered left-to-right. For example,
     /// `levels[l][2i]` and `levels[l][2i + 1]` are hashed together to produce `levels[l + 1][i]`.
+    ///
+    /// A compact tree (see [`HashTree::new_compact`]) leaves the levels it does not retain empty.
     pub levels: Vec<Vec<Hash>>,
 
     /// The root hash of the Hash tree.
     ///
     /// This is equal to `levels[levels.len() - 1][0]`.
     pub root: Hash,
+
+    /// The number of leaves before padding, see [`PADDING_LEAF`].
+    num_leaves: usize,
+
+    /// The parameter the nodes are hashed with, needed to recompute the levels that are not
+    /// retained.
+    param: Param,
+
+    #[serde(skip)]
+    backend: PhantomData<H>,
+}
+
+/// The serialized fields of a [`HashTree`], checked for the shape of a tree before use.
+#[derive(Deserialize)]
+struct HashTreeRepr {
+    levels: Vec<Vec<Hash>>,
+    root: Hash,
+    num_leaves: usize,
+    param: Param,
+}
+
+impl<H: TweakableHash> TryFrom<HashTreeRepr> for HashTree<H> {
+    type Error = HashTreeError;
+
+    fn try_from(repr: HashTreeRepr) -> Result<Self, HashTreeError> {
+        let HashTreeRepr {
+            levels,
+            root,
+            num_leaves,
+            param,
+        } = repr;
+        let padded_len = levels.first().map_or(0, Vec::len);
+        let well_formed = padded_len.is_power_of_two()
+            && num_leaves != 0
+            && num_leaves.checked_next_power_of_two() == Some(padded_len)
+            && levels[0][num_leaves..]
+                .iter()
+                .all(|&leaf| leaf == PADDING_LEAF)
+            && levels.len() == padded_len.ilog2() as usize + 1
+            && (levels.iter().enumerate())
+                .all(|(level, nodes)| nodes.is_empty() || nodes.len() == padded_len >> level)
+            && levels[levels.len() - 1]
+                .first()
+                .is_none_or(|&top| top == root);
+        if !well_formed {
+            return Err(HashTreeError::MalformedLevels);
+        }
+        Ok(Self {
+            levels,
+            root,
+            num_leaves,
+            param,
+            backend: PhantomData,
+        })
+    }
 }
 
 impl HashTree {
@@ -26,7 +113,7 @@ impl HashTree {
     /// # Arguments
     ///
     /// * `param` - Cryptographic parameters for the hash function
-    /// * `leaves` - Vector of leaf hashes (must be a power of 2 in length)
+    /// * `leaves` - Vector of leaf hashes, padded with [`PADDING_LEAF`] up to the next power of 2
     ///
     /// # Returns
     ///
@@ -35,37 +122,159 @@ impl HashTree {
     ///
     /// # Panics
     ///
-    /// Panics if the number of leaves is not a power of 2.
+    /// Panics if there are no leaves.
     pub fn new(param: &Param, leaves: Vec<Hash>) -> Self {
+        Self::new_with_backend(param, leaves)
+    }
+
+    /// Constructs a Hash tree that retains only the leaves and the top `cached_levels` levels.
+    ///
+    /// The other nodes on an authentication path are recomputed by [`HashTree::get_proof`], which
+    /// then costs up to `2^(height - cached_levels)` hashes instead of none. With `cached_levels`
+    /// of zero only the leaves are kept, and with at least the height of the tree every level is.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are no leaves.
+    pub fn new_compact(param: &Param, leaves: Vec<Hash>, cached_levels: usize) -> Self {
+        Self::new_compact_with_backend(param, leaves, cached_levels)
+    }
+}
+
+/// The retained levels and the param, so a compact tree only counts the levels it keeps.
+impl<H: TweakableHash> MemoryFootprint for HashTree<H> {
+    fn heap_bytes(&self) -> usize {
+        self.levels.heap_bytes() + self.param.heap_bytes()
+    }
+}
+
+impl<H: TweakableHash> HashTree<H> {
+    /// Same as [`HashTree::new`], but with the hash backend `H`.
+    pub fn new_with_backend(param: &Param, leaves: Vec<Hash>) -> Self {
+        Self::new_compact_with_backend(param, leaves, usize::MAX)
+    }
+
+    /// Same as [`HashTree::new_compact`], but with the hash backend `H`.
+    pub fn new_compact_with_backend(
+        param: &Param,
+        mut leaves: Vec<Hash>,
+        cached_levels: usize,
+    ) -> Self {
+        let hasher = H::new(param);
         let num_leaves = leaves.len();
-        assert!(
-            num_leaves.is_power_of_two(),
-            "Number of leaves must be a power of 2"
-        );
+        assert!(num_leaves != 0, "A tree needs at least one leaf");
+        leaves.resize(num_leaves.next_power_of_two(), PADDING_LEAF);
 
-        let height = num_leaves.ilog2() as usize;
+        let height = leaves.len().ilog2() as usize;
         let mut levels = vec![leaves];
 
         for current_level_idx in 0..height {
-            let parent_nodes = levels[current_level_idx]
-                .chunks_exact(2)
-                .enumerate()
-                .map(|(i, pair)| {
-                    tweak_hash_tree_node(
-                        param,
-                        &pair[0],
-                        &pair[1],
-                        current_level_idx as u32,
-                        i as u32,
-                    )
-                })
-                .collect();
+            let parent_nodes = parent_level(&hasher, current_level_idx, &levels[current_level_idx]);
             levels.push(parent_nodes);
+            // Drop the level as soon as its parents are known, so that building a compact tree
+            // never holds more than the leaves and two levels above them.
+            if !is_retained(current_level_idx, height, cached_levels) {
+                levels[current_level_idx] = Vec::new();
+            }
         }
 
         let root = levels[height][0];
+        if !is_retained(height, height, cached_levels) {
+            levels[height] = Vec::new();
+        }
 
-        Self { levels, root }
+        Self {
+            levels,
+            root,
+            num_leaves,
+            param: param.clone(),
+            backend: PhantomData,
+        }
+    }
+
+    /// Drops every level except the leaves and the top `cached_levels`, see
+    /// [`HashTree::new_compact`].
+    pub fn compact(&mut self, cached_levels: usize) {
+        let height = self.height();
+        for (level_idx, level) in self.levels.iter_mut().enumerate() {
+            if !is_retained(level_idx, height, cached_levels) {
+                *level = Vec::new();
+            }
+        }
+    }
+
+    /// The number of leaves, not counting the padding.
+    pub fn num_leaves(&self) -> usize {
+        self.num_leaves
+    }
+
+    /// The number of levels above the leaves.
+    pub fn height(&self) -> usize {
+        self.levels.len() - 1
+    }
+
+    /// The parameter the nodes are hashed with.
+    pub fn param(&self) -> &Param {
+        &self.param
+    }
+
+    /// Returns the node at `index` within `level`, recomputing it from the level below if that
+    /// level is not retained.
+    fn node(&self, hasher: &H, level: usize, index: usize) -> Hash {
+        match self.levels[level].get(index) {
+            Some(&node) => node,
+            None => {
+                let left = self.node(hasher, level - 1, 2 * index);
+                let right = self.node(hasher, level - 1, 2 * index + 1);
+                hasher.hash_tree_node(&left, &right, (level - 1) as u32, index as u32)
+            }
+        }
+    }
+
+    /// Recomputes every node from the leaves and checks it against the retained levels and the
+    /// root.
+    ///
+    /// This detects a tree that was corrupted at rest or that belongs to another parameter or
+    /// backend, at the cost of hashing the whole tree once.
+    pub fn validate(&self, param: &Param) -> bool {
+        let padding = &self.levels[0][self.num_leaves..];
+        if param.as_ref() != self.param.as_ref() || padding.iter().any(|&leaf| leaf != PADDING_LEAF)
+        {
+            return false;
+        }
+        let hasher = H::new(param);
+        let mut nodes = self.levels[0].clone();
+        for (level, retained) in self.levels.iter().enumerate().skip(1) {
+            nodes = parent_level(&hasher, level - 1, &nodes);
+            if !retained.is_empty() && *retained != nodes {
+                return false;
+            }
+        }
+        nodes == [self.root]
+    }
+
+    /// Writes the tree to `path` with bincode, after the envelope of a
+    /// [`PayloadKind::HashTree`] in [`HASH_TREE_FORMAT_VERSION`].
+    #[cfg(feature = "persistence")]
+    pub fn write_to(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        wire::write_envelope(&mut file, PayloadKind::HashTree, HASH_TREE_FORMAT_VERSION)?;
+        bincode::serialize_into(file, self).map_err(std::io::Error::other)
+    }
+
+    /// Reads a tree written by [`HashTree::write_to`].
+    ///
+    /// Only the shape of the tree is checked, see [`HashTree::validate`] for the nodes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`std::io::ErrorKind::InvalidData`] if the file holds another
+    /// kind of payload or another format version, see [`wire::read_envelope`].
+    #[cfg(feature = "persistence")]
+    pub fn read_from(path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        let mut file = std::io::BufReader::new(std::fs::File::open(path)?);
+        wire::read_envelope(&mut file, PayloadKind::HashTree, HASH_TREE_FORMAT_VERSION)?;
+        bincode::deserialize_from(file).map_err(std::io::Error::other)
     }
 
     /// Generates a Hash proof for a leaf at the given index.
@@ -83,36 +292,384 @@ impl HashTree {
     /// A `HashTreeProof` containing:
     /// - The original leaf index
     /// - Authentication path: sibling hashes from leaf level to just below root
-    pub fn get_proof(&self, leaf_index: usize) -> HashTreeProof {
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `leaf_index` is not less than the number of leaves.
+    pub fn get_proof(&self, leaf_index: usize) -> Result<HashTreeProof, HashTreeError> {
+        self.get_proof_to_level(leaf_index, self.height())
+    }
+
+    /// Returns the node at `index` within `level`, i.e. the root of the subtree over the leaves
+    /// `index * 2^level..(index + 1) * 2^level`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `level` is above the root or `index` is outside the level.
+    pub fn subtree_root(&self, level: usize, index: usize) -> Hash {
+        let height = self.height();
+        assert!(level <= height, "level {level} is above the root");
+        assert!(
+            index < 1 << (height - level),
+            "index {index} is outside level {level}"
+        );
+        self.node(&H::new(&self.param), level, index)
+    }
+
+    /// Generates a proof that only reaches the ancestor of the leaf on `stop_level`, see
+    /// [`HashTreeProof::verify_against_subtree`].
+    ///
+    /// A proof to the height of the tree is the same as [`HashTree::get_proof`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `leaf_index` is not less than the number of leaves or `stop_level` is
+    /// above the root.
+    pub fn get_proof_to_level(
+        &self,
+        leaf_index: usize,
+        stop_level: usize,
+    ) -> Result<HashTreeProof, HashTreeError> {
+        let num_leaves = self.num_leaves();
+        if leaf_index >= num_leaves {
+            return Err(HashTreeError::LeafIndexOutOfRange {
+                leaf_index,
+                num_leaves,
+            });
+        }
+        let height = self.height();
+        if stop_level > height {
+            return Err(HashTreeError::LevelOutOfRange {
+                level: stop_level,
+                height,
+            });
+        }
+
+        let hasher = H::new(&self.param);
         let mut path = Vec::new();
         let mut index = leaf_index;
 
-        for level in &self.levels[..self.levels.len() - 1] {
+        for level in 0..stop_level {
             // Siblings appear in pairs at indices (2i, 2i + 1)
             // so we can find the index of a sibling by flipping
             // the least-significant bit.
             let sibling_index = index ^ 1;
-            path.push(level[sibling_index]);
+            path.push(self.node(&hasher, level, sibling_index));
             // The parent index for siblings (2i, 2i + 1) is i
             index /= 2;
         }
 
-        HashTreeProof { leaf_index, path }
+        Ok(HashTreeProof::new(leaf_index, path))
+    }
+
+    /// Generates a single proof for several leaves at once.
+    ///
+    /// Siblings shared by the authentication paths, or that are themselves on one of the paths,
+    /// are included only once, so the proof is never larger than the individual proofs together.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `leaf_indices` is empty, not strictly increasing or contains an index past the
+    /// last leaf.
+    pub fn get_multi_proof(&self, leaf_indices: &[usize]) -> MultiProof {
+        assert!(
+            indices_are_valid(leaf_indices.iter().copied(), self.num_leaves()),
+            "leaf indices must be non-empty, strictly increasing and in range"
+        );
+
+        let hasher = H::new(&self.param);
+        let mut siblings = Vec::new();
+        let mut indices = leaf_indices.to_vec();
+        for level in 0..self.height() {
+            let mut i = 0;
+            while i < indices.len() {
+                let index = indices[i];
+                if indices.get(i + 1) == Some(&(index ^ 1)) {
+                    // Both children are known to the verifier.
+                    i += 2;
+                } else {
+                    siblings.push(self.node(&hasher, level, index ^ 1));
+                    i += 1;
+                }
+            }
+            indices = parent_indices(&indices);
+        }
+
+        MultiProof {
+            leaf_indices: leaf_indices.to_vec(),
+            height: self.height(),
+            siblings,
+        }
     }
 }
 
+/// Returns `true` if `indices` is non-empty, strictly increasing and below `num_leaves`.
+fn indices_are_valid(mut indices: impl Iterator<Item = usize>, num_leaves: usize) -> bool {
+    let Some(first) = indices.next() else {
+        return false;
+    };
+    let mut previous = first;
+    for index in indices {
+        if index <= previous {
+            return false;
+        }
+        previous = index;
+    }
+    previous < num_leaves
+}
+
+/// The sorted, deduplicated parents of the sorted `indices`.
+fn parent_indices(indices: &[usize]) -> Vec<usize> {
+    let mut parents: Vec<usize> = indices.iter().map(|index| index / 2).collect();
+    parents.dedup();
+    parents
+}
+
+/// The reasons a [`HashTree`] or [`HashTreeBuilder`] operation can fail.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HashTreeError {
+    /// The leaves do not fill a tree, because their number is not a power of two.
+    LeafCountNotPowerOfTwo { num_leaves: usize },
+    /// A proof was requested for a leaf that does not exist.
+    LeafIndexOutOfRange {
+        leaf_index: usize,
+        num_leaves: usize,
+    },
+    /// A deserialized tree does not have the shape of a complete binary tree.
+    MalformedLevels,
+    /// A level above the root of the tree was requested.
+    LevelOutOfRange { level: usize, height: usize },
+}
+
+impl fmt::Display for HashTreeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HashTreeError::LeafCountNotPowerOfTwo { num_leaves } => {
+                write!(f, "number of leaves {num_leaves} is not a power of two")
+            }
+            HashTreeError::LeafIndexOutOfRange {
+                leaf_index,
+                num_leaves,
+            } => write!(
+                f,
+                "leaf index {leaf_index} is out of range for a tree of {num_leaves} leaves"
+            ),
+            HashTreeError::MalformedLevels => {
+                write!(f, "levels do not have the shape of a complete binary tree")
+            }
+            HashTreeError::LevelOutOfRange { level, height } => {
+                write!(
+                    f,
+                    "level {level} is above the root of a tree of height {height}"
+                )
+            }
+        }
+    }
+}
+
+impl core::error::Error for HashTreeError {}
+
+/// Builds a [`HashTree`] from leaves pushed one at a time.
+///
+/// Every pair of siblings is hashed as soon as its second node arrives, so the leaves never have
+/// to be materialized up front. Besides the nodes of the finished tree, the builder only holds
+/// the roots of the complete subtrees that are still waiting for a sibling, at most one per
+/// level.
+pub struct HashTreeBuilder<H: TweakableHash = KeccakBackend> {
+    /// `levels[l]` holds the nodes of level `l` completed so far, as in [`HashTree::levels`].
+    levels: Vec<Vec<Hash>>,
+    param: Param,
+    hasher: H,
+}
+
+impl HashTreeBuilder {
+    /// Starts an empty tree hashed with Keccak-256.
+    pub fn new(param: &Param) -> Self {
+        Self::new_with_backend(param)
+    }
+}
+
+impl<H: TweakableHash> HashTreeBuilder<H> {
+    /// Same as [`HashTreeBuilder::new`], but with the hash backend `H`.
+    pub fn new_with_backend(param: &Param) -> Self {
+        Self {
+            levels: vec![Vec::new()],
+            param: param.clone(),
+            hasher: H::new(param),
+        }
+    }
+
+    /// Appends the next leaf, hashing every subtree it completes.
+    pub fn push_leaf(&mut self, leaf: Hash) {
+        self.levels[0].push(leaf);
+        let mut level = 0;
+        while self.levels[level].len().is_multiple_of(2) {
+            let nodes = &self.levels[level];
+            let index = nodes.len() / 2 - 1;
+            let parent = self.hasher.hash_tree_node(
+                &nodes[2 * index],
+                &nodes[2 * index + 1],
+                level as u32,
+                index as u32,
+            );
+            if self.levels.len() == level + 1 {
+                self.levels.push(Vec::new());
+            }
+            self.levels[level + 1].push(parent);
+            level += 1;
+        }
+    }
+
+    /// The number of leaves pushed so far.
+    pub fn num_leaves(&self) -> usize {
+        self.levels[0].len()
+    }
+
+    /// Returns the tree, identical to [`HashTree::new_with_backend`] over the same leaves.
+    ///
+    /// Unlike [`HashTree::new`], the builder does not pad: the leaves are hashed as they arrive,
+    /// before their final number is known.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the number of leaves is not a power of two.
+    pub fn finalize(self) -> Result<HashTree<H>, HashTreeError> {
+        self.finalize_compact(usize::MAX)
+    }
+
+    /// Returns the tree, identical to [`HashTree::new_compact_with_backend`] over the same leaves.
+    ///
+    /// The height is only known once all leaves are in, so the levels that are not retained are
+    /// dropped here rather than during construction.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the number of leaves is not a power of two.
+    pub fn finalize_compact(self, cached_levels: usize) -> Result<HashTree<H>, HashTreeError> {
+        let num_leaves = self.num_leaves();
+        if !num_leaves.is_power_of_two() {
+            return Err(HashTreeError::LeafCountNotPowerOfTwo { num_leaves });
+        }
+        // With a power of two of leaves every subtree is complete, so the top level is the root.
+        let root = self.levels[self.levels.len() - 1][0];
+        let mut tree = HashTree {
+            levels: self.levels,
+            root,
+            num_leaves,
+            param: self.param,
+            backend: PhantomData,
+        };
+        tree.compact(cached_levels);
+        Ok(tree)
+    }
+}
+
+/// Hashes the pairs of `nodes` on `level` into the level above.
+///
+/// With the `parallel` feature, large levels are split across threads. Every node only depends
+/// on its own pair, so the result is the same either way.
+fn parent_level<H: TweakableHash>(hasher: &H, level: usize, nodes: &[Hash]) -> Vec<Hash> {
+    let hash_pair = |(i, pair): (usize, &[Hash])| {
+        hasher.hash_tree_node(&pair[0], &pair[1], level as u32, i as u32)
+    };
+    #[cfg(feature = "parallel")]
+    if nodes.len() >= PARALLEL_MIN_LEVEL_LEN {
+        return nodes
+            .par_chunks_exact(2)
+            .enumerate()
+            .map(hash_pair)
+            .collect();
+    }
+    (nodes.as_chunks::<2>().0.iter())
+        .map(|pair| pair.as_slice())
+        .enumerate()
+        .map(hash_pair)
+        .collect()
+}
+
+/// Whether `level` of a tree of `height` is kept when retaining the top `cached_levels` levels.
+///
+/// The leaves are always kept, since they cannot be recomputed.
+fn is_retained(level: usize, height: usize, cached_levels: usize) -> bool {
+    level == 0 || height - level < cached_levels
+}
+
+/// A proof that a leaf is in a [`HashTree`].
+///
+/// The leaf index is a `u32`, as in the tweaks of the tree nodes, so it serializes to the same
+/// width on the 32-bit zkVM guests as on the hosts.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct HashTreeProof {
-    leaf_index: usize,
+    leaf_index: u32,
     pub path: Vec<Hash>,
 }
 
 impl HashTreeProof {
+    /// A proof for `leaf_index` with the siblings in `path`, bottom up, e.g. from a
+    /// [`crate::traversal::TraversalState`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the leaf index does not fit in a `u32`.
+    pub fn new(leaf_index: usize, path: Vec<Hash>) -> Self {
+        let leaf_index = u32::try_from(leaf_index).expect("leaf index fits in a u32");
+        Self { leaf_index, path }
+    }
+
+    /// The index of the leaf this proof is for.
+    pub fn leaf_index(&self) -> usize {
+        self.leaf_index as usize
+    }
+
+    /// The height of the tree this proof is for, which is the length of the path.
+    pub fn height(&self) -> usize {
+        self.path.len()
+    }
+
+    /// The length of [`HashTreeProof::to_bytes`] for a tree of `height`.
+    pub fn encoded_len(height: usize) -> usize {
+        4 + height * HASH_LEN
+    }
+
+    /// Encodes the leaf index as a big-endian `u32` followed by the path, bottom up.
+    ///
+    /// There is no length prefix, so the height must be known to decode the proof.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(Self::encoded_len(self.height()));
+        bytes.extend_from_slice(&self.leaf_index.to_be_bytes());
+        for node in &self.path {
+            bytes.extend_from_slice(&node.0);
+        }
+        bytes
+    }
+
+    /// Decodes a proof written by [`HashTreeProof::to_bytes`] for a tree of `expected_height`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error unless `bytes` has exactly the length of such a proof.
+    pub fn from_bytes(bytes: &[u8], expected_height: usize) -> Result<Self, DecodeError> {
+        let expected = Self::encoded_len(expected_height);
+        if bytes.len() != expected {
+            return Err(DecodeError::InvalidLength {
+                expected,
+                actual: bytes.len(),
+            });
+        }
+        let (leaf_index, path) = bytes.split_at(4);
+        let leaf_index = u32::from_be_bytes(leaf_index.try_into().expect("split at 4 bytes"));
+        Ok(Self {
+            leaf_index,
+            path: crate::decode_hashes(path),
+        })
+    }
+
     /// Verifies that a leaf value belongs to a Hash tree with the given root.
     ///
     /// Reconstructs the path from leaf to root by iteratively hashing the
     /// current value with siblings from the path. The proof is valid if the
-    /// computed root matches the expected root.
+    /// leaf index fits in a tree of the proof's height and the computed root
+    /// matches the expected root.
     ///
     /// # Arguments
     ///
@@ -124,8 +681,133 @@ impl HashTreeProof {
     ///
     /// `true` if the proof is valid (computed root matches expected root), `false` otherwise
     pub fn verify(&self, param: &Param, leaf: &Hash, root: &Hash) -> bool {
+        self.verify_with_backend(&KeccakBackend::new(param), leaf, root)
+    }
+
+    /// Same as [`HashTreeProof::verify`], but with any hash backend.
+    pub fn verify_with_backend<H: TweakableHash>(
+        &self,
+        hasher: &H,
+        leaf: &Hash,
+        root: &Hash,
+    ) -> bool {
+        self.index_fits() && self.compute_root_with_backend(hasher, leaf) == *root
+    }
+
+    /// Verifies several proofs of leaves of the same tree at once, as if each was checked with
+    /// [`HashTreeProof::verify`].
+    ///
+    /// Every node is hashed at most once, so proofs of nearby leaves, e.g. of consecutive epochs,
+    /// share the hashes of their common ancestors. The proofs must also agree on every node they
+    /// both contain. Returns `false` if `items` is empty.
+    pub fn verify_batch(param: &Param, items: &[(&HashTreeProof, Hash)], root: &Hash) -> bool {
+        Self::verify_batch_with_backend(&KeccakBackend::new(param), items, root)
+    }
+
+    /// Same as [`HashTreeProof::verify_batch`], but with any hash backend.
+    pub fn verify_batch_with_backend<H: TweakableHash>(
+        hasher: &H,
+        items: &[(&HashTreeProof, Hash)],
+        root: &Hash,
+    ) -> bool {
+        let Some((first, _)) = items.first() else {
+            return false;
+        };
+        let height = first.height();
+        // Every node seen so far by `(level, index)`, with whether it was computed from its
+        // children rather than taken from a path.
+        let mut nodes = BTreeMap::new();
+        for (proof, leaf) in items {
+            if proof.height() != height || !proof.index_fits() {
+                return false;
+            }
+            let mut index = proof.leaf_index();
+            let mut current = *leaf;
+            if !record_node(&mut nodes, (0, index), current, true) {
+                return false;
+            }
+            for (level, &sibling) in proof.path.iter().enumerate() {
+                if !record_node(&mut nodes, (level, index ^ 1), sibling, false) {
+                    return false;
+                }
+                let parent_index = index / 2;
+                current = match nodes.get(&(level + 1, parent_index)) {
+                    // Both children were just checked against the ones it was computed from.
+                    Some(&(parent, true)) => parent,
+                    _ => {
+                        let (left, right) = if index & 1 == 0 {
+                            (current, sibling)
+                        } else {
+                            (sibling, current)
+                        };
+                        let parent =
+                            hasher.hash_tree_node(&left, &right, level as u32, parent_index as u32);
+                        if !record_node(&mut nodes, (level + 1, parent_index), parent, true) {
+                            return false;
+                        }
+                        parent
+                    }
+                };
+                index = parent_index;
+            }
+        }
+        nodes
+            .get(&(height, 0))
+            .is_some_and(|(node, _)| node == root)
+    }
+
+    /// Whether the leaf index fits in a tree of the proof's height.
+    fn index_fits(&self) -> bool {
+        u32::try_from(self.height())
+            .ok()
+            .and_then(|height| 1usize.checked_shl(height))
+            .is_none_or(|num_leaves| self.leaf_index() < num_leaves)
+    }
+
+    /// Verifies that a leaf value belongs to the subtree with root `subtree_root` on
+    /// `stop_level`, for a proof from [`HashTree::get_proof_to_level`].
+    ///
+    /// The subtree is the one at index `leaf_index() >> stop_level` within its level, which the
+    /// caller must have looked `subtree_root` up by. The nodes are hashed with the same tweaks as
+    /// in the full tree, so a proof to the height of the tree is checked like
+    /// [`HashTreeProof::verify`] checks it, except for the bound on the leaf index.
+    pub fn verify_against_subtree(
+        &self,
+        param: &Param,
+        leaf: &Hash,
+        subtree_root: &Hash,
+        stop_level: usize,
+    ) -> bool {
+        self.verify_against_subtree_with_backend(
+            &KeccakBackend::new(param),
+            leaf,
+            subtree_root,
+            stop_level,
+        )
+    }
+
+    /// Same as [`HashTreeProof::verify_against_subtree`], but with any hash backend.
+    pub fn verify_against_subtree_with_backend<H: TweakableHash>(
+        &self,
+        hasher: &H,
+        leaf: &Hash,
+        subtree_root: &Hash,
+        stop_level: usize,
+    ) -> bool {
+        self.height() == stop_level && self.compute_root_with_backend(hasher, leaf) == *subtree_root
+    }
+
+    /// Returns the root that the path reconstructs from `leaf`.
+    ///
+    /// Unlike [`HashTreeProof::verify`], this does not check the leaf index against the height.
+    pub fn compute_root(&self, param: &Param, leaf: &Hash) -> Hash {
+        self.compute_root_with_backend(&KeccakBackend::new(param), leaf)
+    }
+
+    /// Same as [`HashTreeProof::compute_root`], but with any hash backend.
+    pub fn compute_root_with_backend<H: TweakableHash>(&self, hasher: &H, leaf: &Hash) -> Hash {
         let mut current_hash = *leaf;
-        let mut index = self.leaf_index;
+        let mut index = self.leaf_index();
 
         for (level, &sibling_hash) in self.path.iter().enumerate() {
             // Siblings appear in pairs at indices (2i, 2i + 1)
@@ -140,10 +822,558 @@ impl HashTreeProof {
             // The parent index for siblings (2i, 2i + 1) is i
             let parent_index = index / 2;
 
-            current_hash =
-                tweak_hash_tree_node(param, &left, &right, level as u32, parent_index as u32);
+            current_hash = hasher.hash_tree_node(&left, &right, level as u32, parent_index as u32);
             index = parent_index;
         }
-        current_hash == *root
+        current_hash
+    }
+}
+
+/// Records the node at `(level, index)` for [`HashTreeProof::verify_batch`] and returns whether it
+/// agrees with the one recorded before, if any.
+fn record_node(
+    nodes: &mut BTreeMap<(usize, usize), (Hash, bool)>,
+    position: (usize, usize),
+    hash: Hash,
+    computed: bool,
+) -> bool {
+    match nodes.entry(position) {
+        Entry::Vacant(entry) => {
+            entry.insert((hash, computed));
+            true
+        }
+        Entry::Occupied(mut entry) => {
+            let (known, known_computed) = entry.get_mut();
+            *known_computed |= computed;
+            *known == hash
+        }
+    }
+}
+
+/// A proof that several leaves belong to a Hash tree, see [`HashTree::get_multi_proof`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MultiProof {
+    /// The proven leaf indices, strictly increasing.
+    leaf_indices: Vec<usize>,
+    /// The number of levels above the leaves.
+    height: usize,
+    /// The siblings that are not on any of the authentication paths, level by level from the
+    /// leaves up and left to right within a level.
+    pub siblings: Vec<Hash>,
+}
+
+impl MultiProof {
+    /// The indices of the leaves this proof is for.
+    pub fn leaf_indices(&self) -> &[usize] {
+        &self.leaf_indices
+    }
+
+    /// Verifies that every `(leaf_index, leaf)` pair belongs to a Hash tree with the given root.
+    ///
+    /// The leaves must be given for exactly the indices of the proof, in increasing order.
+    /// Malformed proofs, e.g. with duplicate indices or too few or too many siblings, are
+    /// rejected.
+    pub fn verify(&self, param: &Param, leaves: &[(usize, Hash)], root: &Hash) -> bool {
+        self.verify_with_backend(&KeccakBackend::new(param), leaves, root)
+    }
+
+    /// Same as [`MultiProof::verify`], but with any hash backend.
+    pub fn verify_with_backend<H: TweakableHash>(
+        &self,
+        hasher: &H,
+        leaves: &[(usize, Hash)],
+        root: &Hash,
+    ) -> bool {
+        let Some(num_leaves) = u32::try_from(self.height)
+            .ok()
+            .and_then(|height| 1usize.checked_shl(height))
+        else {
+            return false;
+        };
+        if !indices_are_valid(self.leaf_indices.iter().copied(), num_leaves)
+            || !leaves
+                .iter()
+                .map(|&(index, _)| index)
+                .eq(self.leaf_indices.iter().copied())
+        {
+            return false;
+        }
+
+        let mut siblings = self.siblings.iter();
+        let mut nodes = leaves.to_vec();
+        for level in 0..self.height {
+            let mut parents = Vec::with_capacity(nodes.len());
+            let mut i = 0;
+            while i < nodes.len() {
+                let (index, node) = nodes[i];
+                let (left, right) = match nodes.get(i + 1) {
+                    // Sorted indices put the left sibling first.
+                    Some(&(next_index, next_node)) if next_index == index ^ 1 => {
+                        i += 2;
+                        (node, next_node)
+                    }
+                    _ => {
+                        let Some(&sibling) = siblings.next() else {
+                            return false;
+                        };
+                        i += 1;
+                        if index & 1 == 0 {
+                            (node, sibling)
+                        } else {
+                            (sibling, node)
+                        }
+                    }
+                };
+                let parent_index = index / 2;
+                let parent =
+                    hasher.hash_tree_node(&left, &right, level as u32, parent_index as u32);
+                parents.push((parent_index, parent));
+            }
+            nodes = parents;
+        }
+        siblings.next().is_none() && nodes == [(0, *root)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    use super::*;
+
+    #[test]
+    fn test_compact_tree_proofs_match_full_tree() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let param = Param::random(18, &mut rng);
+        let leaves: Vec<_> = (0..1 << 6).map(|_| Hash::random(&mut rng)).collect();
+        let full = HashTree::new(&param, leaves.clone());
+
+        for cached_levels in 0..=7 {
+            let compact = HashTree::new_compact(&param, leaves.clone(), cached_levels);
+            assert_eq!(compact.root, full.root);
+            let retained = compact
+                .levels
+                .iter()
+                .filter(|level| !level.is_empty())
+                .count();
+            assert_eq!(retained, 1 + cached_levels.min(6));
+
+            let mut compacted = HashTree::new(&param, leaves.clone());
+            compacted.compact(cached_levels);
+            assert_eq!(compacted.levels, compact.levels);
+
+            for (leaf_index, leaf) in leaves.iter().enumerate() {
+                let proof = compact.get_proof(leaf_index).unwrap();
+                assert_eq!(
+                    bincode::serialize(&proof).unwrap(),
+                    bincode::serialize(&full.get_proof(leaf_index).unwrap()).unwrap()
+                );
+                assert!(proof.verify(&param, leaf, &full.root));
+            }
+        }
+    }
+
+    #[test]
+    fn test_builder_matches_new() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..16 {
+            let param = Param::random(18, &mut rng);
+            let height = rng.random_range(0..8);
+            let leaves: Vec<_> = (0..1 << height).map(|_| Hash::random(&mut rng)).collect();
+            let tree = HashTree::new(&param, leaves.clone());
+
+            let mut builder = HashTreeBuilder::new(&param);
+            for &leaf in &leaves {
+                builder.push_leaf(leaf);
+            }
+            let built = builder.finalize().unwrap();
+            assert_eq!(built.levels, tree.levels);
+            assert_eq!(built.root, tree.root);
+
+            let cached_levels = rng.random_range(0..=height);
+            let mut builder = HashTreeBuilder::new(&param);
+            for &leaf in &leaves {
+                builder.push_leaf(leaf);
+            }
+            let compact = HashTree::new_compact(&param, leaves, cached_levels);
+            let built = builder.finalize_compact(cached_levels).unwrap();
+            assert_eq!(built.levels, compact.levels);
+            assert_eq!(built.root, compact.root);
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_matches_serial() {
+        // The builder hashes one pair at a time, and levels of 1024 leaves are split across
+        // threads by `HashTree::new`.
+        let mut rng = StdRng::seed_from_u64(0);
+        let param = Param::random(18, &mut rng);
+        let leaves: Vec<_> = (0..1 << 10).map(|_| Hash::random(&mut rng)).collect();
+        let tree = HashTree::new(&param, leaves.clone());
+
+        let mut builder = HashTreeBuilder::new(&param);
+        for leaf in leaves {
+            builder.push_leaf(leaf);
+        }
+        let serial = builder.finalize().unwrap();
+        assert_eq!(tree.levels, serial.levels);
+        assert_eq!(tree.root, serial.root);
+        assert!(tree.validate(&param));
+    }
+
+    #[test]
+    fn test_builder_rejects_incomplete_tree() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let param = Param::random(18, &mut rng);
+        for num_leaves in [0, 3, 6, 7] {
+            let mut builder = HashTreeBuilder::new(&param);
+            for _ in 0..num_leaves {
+                builder.push_leaf(Hash::random(&mut rng));
+            }
+            assert_eq!(
+                builder.finalize().err(),
+                Some(HashTreeError::LeafCountNotPowerOfTwo { num_leaves })
+            );
+        }
+    }
+
+    #[test]
+    fn test_multi_proof() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let param = Param::random(18, &mut rng);
+        let leaves: Vec<_> = (0..8).map(|_| Hash::random(&mut rng)).collect();
+        let tree = HashTree::new(&param, leaves.clone());
+
+        let indices = [0, 1, 5, 7];
+        let proof = tree.get_multi_proof(&indices);
+        let proven: Vec<_> = indices.iter().map(|&i| (i, leaves[i])).collect();
+        assert!(proof.verify(&param, &proven, &tree.root));
+
+        // Leaves 0 and 1 are siblings, and so are their parent and the parent of 5 and 7, so
+        // only leaves 4 and 6 and the node above leaves 2 and 3 are needed.
+        assert_eq!(proof.siblings, [leaves[4], leaves[6], tree.levels[1][1]]);
+        let individual: usize = indices
+            .iter()
+            .map(|&i| {
+                bincode::serialize(&tree.get_proof(i).unwrap())
+                    .unwrap()
+                    .len()
+            })
+            .sum();
+        let proof_bytes = bincode::serialize(&proof).unwrap();
+        assert!(proof_bytes.len() < individual);
+        let decoded: MultiProof = bincode::deserialize(&proof_bytes).unwrap();
+        assert!(decoded.verify(&param, &proven, &tree.root));
+
+        for i in 0..proven.len() {
+            let mut tampered = proven.clone();
+            tampered[i].1 = Hash::random(&mut rng);
+            assert!(!proof.verify(&param, &tampered, &tree.root));
+        }
+        assert!(!proof.verify(&param, &proven[1..], &tree.root));
+        let mut reordered = proven.clone();
+        reordered.swap(0, 1);
+        assert!(!proof.verify(&param, &reordered, &tree.root));
+
+        let mut extra_sibling = proof.clone();
+        extra_sibling.siblings.push(leaves[2]);
+        assert!(!extra_sibling.verify(&param, &proven, &tree.root));
+        let mut duplicate = proof.clone();
+        duplicate.leaf_indices = vec![0, 0, 5, 7];
+        let duplicated: Vec<_> = [0, 0, 5, 7].iter().map(|&i| (i, leaves[i])).collect();
+        assert!(!duplicate.verify(&param, &duplicated, &tree.root));
+
+        // A single index gives back the individual authentication path.
+        let single = tree.get_multi_proof(&[3]);
+        assert_eq!(single.siblings, tree.get_proof(3).unwrap().path);
+        assert!(single.verify(&param, &[(3, leaves[3])], &tree.root));
+    }
+
+    #[test]
+    #[should_panic(expected = "strictly increasing")]
+    fn test_multi_proof_rejects_unsorted_indices() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let param = Param::random(18, &mut rng);
+        let leaves: Vec<_> = (0..8).map(|_| Hash::random(&mut rng)).collect();
+        HashTree::new(&param, leaves).get_multi_proof(&[5, 1]);
+    }
+
+    #[test]
+    fn test_get_proof_out_of_range() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let param = Param::random(18, &mut rng);
+        let leaves: Vec<_> = (0..8).map(|_| Hash::random(&mut rng)).collect();
+        let tree = HashTree::new(&param, leaves);
+        assert_eq!((tree.num_leaves(), tree.height()), (8, 3));
+
+        assert!(tree.get_proof(7).is_ok());
+        for leaf_index in [8, usize::MAX] {
+            assert_eq!(
+                tree.get_proof(leaf_index).unwrap_err(),
+                HashTreeError::LeafIndexOutOfRange {
+                    leaf_index,
+                    num_leaves: 8
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let param = Param::random(18, &mut rng);
+        let leaves: Vec<_> = (0..1 << 6).map(|_| Hash::random(&mut rng)).collect();
+
+        for tree in [
+            HashTree::new(&param, leaves.clone()),
+            HashTree::new_compact(&param, leaves.clone(), 2),
+        ] {
+            let decoded: HashTree =
+                bincode::deserialize(&bincode::serialize(&tree).unwrap()).unwrap();
+            assert_eq!(decoded.levels, tree.levels);
+            assert_eq!(decoded.root, tree.root);
+            assert!(decoded.validate(&param));
+            assert!(!decoded.validate(&Param::random(18, &mut rng)));
+        }
+
+        let mut malformed = HashTree::new(&param, leaves.clone());
+        malformed.levels[2].pop();
+        let bytes = bincode::serialize(&malformed).unwrap();
+        let err = bincode::deserialize::<HashTree>(&bytes)
+            .map(drop)
+            .unwrap_err();
+        assert!(err.to_string().contains("complete binary tree"));
+
+        // A leaf count whose next power of two does not fit in a usize
+        for num_leaves in [usize::MAX / 2 + 2, usize::MAX] {
+            let mut malformed = HashTree::new(&param, leaves.clone());
+            malformed.num_leaves = num_leaves;
+            let bytes = bincode::serialize(&malformed).unwrap();
+            let err = bincode::deserialize::<HashTree>(&bytes)
+                .map(drop)
+                .unwrap_err();
+            assert!(err.to_string().contains("complete binary tree"));
+        }
+    }
+
+    #[cfg(feature = "persistence")]
+    #[test]
+    fn test_persistence() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let param = Param::random(18, &mut rng);
+        let leaves: Vec<_> = (0..1 << 6).map(|_| Hash::random(&mut rng)).collect();
+        let tree = HashTree::new(&param, leaves);
+
+        let path = std::env::temp_dir().join(format!("leansig-hash-tree-{}", std::process::id()));
+        tree.write_to(&path).unwrap();
+        let read: HashTree = HashTree::read_from(&path).unwrap();
+        assert!(read.validate(&param));
+        for leaf_index in 0..tree.num_leaves() {
+            assert_eq!(
+                read.get_proof(leaf_index).unwrap().path,
+                tree.get_proof(leaf_index).unwrap().path
+            );
+        }
+
+        // Any flipped byte either fails to parse or fails validation.
+        let bytes = std::fs::read(&path).unwrap();
+        for position in (wire::ENVELOPE_LEN..bytes.len()).step_by(7) {
+            let mut corrupted = bytes.clone();
+            corrupted[position] ^= 1;
+            std::fs::write(&path, &corrupted).unwrap();
+            if let Ok(read) = HashTree::<KeccakBackend>::read_from(&path) {
+                assert!(!read.validate(&param), "flipped byte {position}");
+            }
+        }
+
+        // A file of another kind or format version is rejected before decoding the tree
+        let mut envelope = Vec::new();
+        wire::write_envelope(&mut envelope, PayloadKind::Signature, 1).unwrap();
+        std::fs::write(&path, [&envelope, &bytes[wire::ENVELOPE_LEN..]].concat()).unwrap();
+        let err = HashTree::<KeccakBackend>::read_from(&path)
+            .map(drop)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "payload kind signature instead of hash tree"
+        );
+        let mut envelope = Vec::new();
+        wire::write_envelope(
+            &mut envelope,
+            PayloadKind::HashTree,
+            HASH_TREE_FORMAT_VERSION + 1,
+        )
+        .unwrap();
+        std::fs::write(&path, [&envelope, &bytes[wire::ENVELOPE_LEN..]].concat()).unwrap();
+        let err = HashTree::<KeccakBackend>::read_from(&path)
+            .map(drop)
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), "hash tree format version 2 instead of 1");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_padded_tree() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let param = Param::random(18, &mut rng);
+        for num_leaves in [1, 3, 5, 6] {
+            let leaves: Vec<_> = (0..num_leaves).map(|_| Hash::random(&mut rng)).collect();
+            let tree = HashTree::new(&param, leaves.clone());
+            assert_eq!(tree.num_leaves(), num_leaves);
+            assert_eq!(tree.levels[0].len(), num_leaves.next_power_of_two());
+            assert!(tree.validate(&param));
+
+            let mut padded = leaves.clone();
+            padded.resize(num_leaves.next_power_of_two(), PADDING_LEAF);
+            assert_eq!(HashTree::new(&param, padded).root, tree.root);
+
+            for (leaf_index, leaf) in leaves.iter().enumerate() {
+                let proof = tree.get_proof(leaf_index).unwrap();
+                assert!(proof.verify(&param, leaf, &tree.root));
+            }
+            for leaf_index in num_leaves..tree.levels[0].len() {
+                assert_eq!(
+                    tree.get_proof(leaf_index).unwrap_err(),
+                    HashTreeError::LeafIndexOutOfRange {
+                        leaf_index,
+                        num_leaves
+                    }
+                );
+            }
+
+            let decoded: HashTree =
+                bincode::deserialize(&bincode::serialize(&tree).unwrap()).unwrap();
+            assert_eq!(decoded.num_leaves(), num_leaves);
+            assert_eq!(decoded.root, tree.root);
+        }
+    }
+
+    #[test]
+    fn test_subtree_proof() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let param = Param::random(18, &mut rng);
+        let leaves: Vec<_> = (0..16).map(|_| Hash::random(&mut rng)).collect();
+        let tree = HashTree::new_compact(&param, leaves.clone(), 1);
+
+        // Leaf 9 lies below node 2 of level 2.
+        let proof = tree.get_proof_to_level(9, 2).unwrap();
+        assert_eq!((proof.leaf_index(), proof.height()), (9, 2));
+        assert_eq!(proof.path, tree.get_proof(9).unwrap().path[..2]);
+        assert!(proof.verify_against_subtree(&param, &leaves[9], &tree.subtree_root(2, 2), 2));
+        assert!(!proof.verify_against_subtree(&param, &leaves[9], &tree.subtree_root(2, 1), 2));
+        assert!(!proof.verify_against_subtree(&param, &leaves[8], &tree.subtree_root(2, 2), 2));
+
+        // The level is bound into the tweaks, so a proof cannot stand in for one of another
+        // level, even when the path is cut to match.
+        assert!(!proof.verify_against_subtree(&param, &leaves[9], &tree.subtree_root(3, 1), 3));
+        let shortened = HashTreeProof::new(9, proof.path[..1].to_vec());
+        assert!(!shortened.verify_against_subtree(&param, &leaves[9], &tree.subtree_root(2, 2), 2));
+        assert!(!shortened.verify_against_subtree(&param, &leaves[9], &tree.subtree_root(2, 2), 1));
+
+        // A proof to the top is a full proof.
+        let full = tree.get_proof_to_level(9, 4).unwrap();
+        assert_eq!(full.path, tree.get_proof(9).unwrap().path);
+        assert_eq!(tree.subtree_root(4, 0), tree.root);
+        assert!(full.verify_against_subtree(&param, &leaves[9], &tree.root, 4));
+        assert!(full.verify(&param, &leaves[9], &tree.root));
+
+        assert_eq!(
+            tree.get_proof_to_level(9, 5).map(drop),
+            Err(HashTreeError::LevelOutOfRange {
+                level: 5,
+                height: 4
+            })
+        );
+    }
+
+    #[test]
+    fn test_verify_batch() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let param = Param::random(18, &mut rng);
+        let leaves: Vec<_> = (0..16).map(|_| Hash::random(&mut rng)).collect();
+        let tree = HashTree::new(&param, leaves.clone());
+        let proofs: Vec<_> = (0..16).map(|i| tree.get_proof(i).unwrap()).collect();
+
+        let items: Vec<_> = [3, 4, 5, 12]
+            .iter()
+            .map(|&i| (&proofs[i], leaves[i]))
+            .collect();
+        assert!(HashTreeProof::verify_batch(&param, &items, &tree.root));
+        assert!(!HashTreeProof::verify_batch(&param, &items, &leaves[0]));
+        assert!(!HashTreeProof::verify_batch(&param, &[], &tree.root));
+        let wrong_leaf = [(&proofs[3], leaves[3]), (&proofs[4], leaves[5])];
+        assert!(!HashTreeProof::verify_batch(
+            &param,
+            &wrong_leaf,
+            &tree.root
+        ));
+
+        // Leaves 4 and 5 share every node above level 0, so the parent of the second one is
+        // reused, but its path must still agree with the first one further up.
+        let mut disagreeing = proofs[5].clone();
+        disagreeing.path[2] = Hash::random(&mut rng);
+        let items = [(&proofs[4], leaves[4]), (&disagreeing, leaves[5])];
+        assert!(!HashTreeProof::verify_batch(&param, &items, &tree.root));
+
+        // A node taken from the path of one proof is checked once another proof computes it.
+        let mut claimed = proofs[0].clone();
+        claimed.path[1] = Hash::random(&mut rng);
+        let items = [(&claimed, leaves[0]), (&proofs[2], leaves[2])];
+        assert!(!HashTreeProof::verify_batch(&param, &items, &tree.root));
+
+        let short = HashTreeProof::new(4, proofs[4].path[..3].to_vec());
+        let items = [(&proofs[5], leaves[5]), (&short, leaves[4])];
+        assert!(!HashTreeProof::verify_batch(&param, &items, &tree.root));
+    }
+
+    #[test]
+    fn test_proof_bytes() {
+        let proof = HashTreeProof::new(5, vec![Hash([1; HASH_LEN]), Hash([2; HASH_LEN])]);
+        let bytes = proof.to_bytes();
+        assert_eq!(bytes, [&[0, 0, 0, 5][..], &[1; 32], &[2; 32]].concat());
+        assert_eq!(bytes.len(), HashTreeProof::encoded_len(2));
+
+        let decoded = HashTreeProof::from_bytes(&bytes, 2).unwrap();
+        assert_eq!((decoded.leaf_index(), decoded.path), (5, proof.path));
+
+        let mut extended = bytes.clone();
+        extended.push(0);
+        assert_eq!(
+            HashTreeProof::from_bytes(&extended, 2).map(drop),
+            Err(DecodeError::InvalidLength {
+                expected: 68,
+                actual: 69
+            })
+        );
+        assert!(HashTreeProof::from_bytes(&bytes, 3).is_err());
+        assert!(HashTreeProof::from_bytes(&bytes[..67], 2).is_err());
+    }
+
+    #[test]
+    fn test_proof_height() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let param = Param::random(18, &mut rng);
+        let leaves: Vec<_> = (0..32).map(|_| Hash::random(&mut rng)).collect();
+        let small = HashTree::new(&param, leaves[..16].to_vec());
+        let large = HashTree::new(&param, leaves.clone());
+
+        let proof = small.get_proof(3).unwrap();
+        assert_eq!((proof.leaf_index(), proof.height()), (3, 4));
+        assert_eq!(proof.compute_root(&param, &leaves[3]), small.root);
+        assert!(proof.verify(&param, &leaves[3], &small.root));
+
+        // The tree the verifier expects has height 5.
+        assert_ne!(proof.height(), large.get_proof(3).unwrap().height());
+        assert_ne!(proof.compute_root(&param, &leaves[3]), large.root);
+        assert!(!proof.verify(&param, &leaves[3], &large.root));
+
+        // An index past the leaves of the tree is rejected, and one wider than the `u32` of the
+        // tweaks does not even deserialize.
+        let shifted = HashTreeProof::new(3 + (1 << 20), proof.path.clone());
+        assert!(!shifted.verify(&param, &leaves[3], &small.root));
+        let json = format!(r#"{{"leaf_index":{},"path":[]}}"#, 3 + (1u64 << 40));
+        assert!(serde_json::from_str::<HashTreeProof>(&json).is_err());
     }
 }
@@ -0,0 +1,149 @@
+// Copyright 2025 Irreducible Inc.
+//! Hex encoding of the byte-string types.
+//!
+//! [`crate::hash::Hash`], [`crate::Param`], [`crate::Nonce`] and [`crate::Message`] print as
+//! lowercase hex and parse from hex with an optional `0x` prefix. They serialize as `0x`-prefixed
+//! hex strings in human-readable formats such as JSON, while binary formats such as bincode, and
+//! so the I/O of the zkVM guests, keep the raw bytes.
+
+use alloc::{string::String, vec::Vec};
+use core::{fmt, str::FromStr};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
+
+/// The reasons a hex string can be rejected.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseHexError {
+    /// The string has an odd number of hex digits.
+    OddLength { len: usize },
+    /// The string contains a character that is not a hex digit.
+    InvalidCharacter { index: usize, character: char },
+    /// The decoded bytes do not have the length of the type.
+    InvalidLength { expected: usize, actual: usize },
+}
+
+impl fmt::Display for ParseHexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseHexError::OddLength { len } => {
+                write!(f, "hex string has an odd number of digits ({len})")
+            }
+            ParseHexError::InvalidCharacter { index, character } => {
+                write!(f, "invalid hex character {character:?} at position {index}")
+            }
+            ParseHexError::InvalidLength { expected, actual } => {
+                write!(f, "expected {expected} bytes, got {actual}")
+            }
+        }
+    }
+}
+
+impl core::error::Error for ParseHexError {}
+
+/// Writes `bytes` as lowercase hex.
+pub(crate) fn write(f: &mut fmt::Formatter<'_>, bytes: &[u8]) -> fmt::Result {
+    for byte in bytes {
+        write!(f, "{byte:02x}")?;
+    }
+    Ok(())
+}
+
+/// Decodes a hex string of any even length, with or without a `0x` prefix.
+pub(crate) fn decode(s: &str) -> Result<Vec<u8>, ParseHexError> {
+    let digits = s.strip_prefix("0x").unwrap_or(s);
+    let offset = s.len() - digits.len();
+    if let Some((index, character)) = digits.char_indices().find(|(_, c)| !c.is_ascii_hexdigit()) {
+        return Err(ParseHexError::InvalidCharacter {
+            index: offset + index,
+            character,
+        });
+    }
+    if !digits.len().is_multiple_of(2) {
+        return Err(ParseHexError::OddLength { len: digits.len() });
+    }
+    Ok(digits
+        .as_bytes()
+        .as_chunks::<2>()
+        .0
+        .iter()
+        .map(|pair| {
+            let pair = core::str::from_utf8(pair).expect("hex digits are ASCII");
+            u8::from_str_radix(pair, 16).expect("checked to be hex digits")
+        })
+        .collect())
+}
+
+/// Decodes a hex string of exactly `N` bytes.
+pub(crate) fn decode_array<const N: usize>(s: &str) -> Result<[u8; N], ParseHexError> {
+    let bytes = decode(s)?;
+    bytes
+        .try_into()
+        .map_err(|bytes: Vec<u8>| ParseHexError::InvalidLength {
+            expected: N,
+            actual: bytes.len(),
+        })
+}
+
+/// Serializes `value` through its `Display` impl with a `0x` prefix in human-readable formats,
+/// and as `repr` otherwise.
+pub(crate) fn serialize<T, R, S>(
+    value: &T,
+    repr: impl FnOnce() -> R,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    T: fmt::Display,
+    R: Serialize,
+    S: Serializer,
+{
+    if serializer.is_human_readable() {
+        serializer.collect_str(&format_args!("0x{value}"))
+    } else {
+        repr().serialize(serializer)
+    }
+}
+
+/// The inverse of [`serialize`].
+pub(crate) fn deserialize<'de, T, R, D>(
+    deserializer: D,
+    from_repr: impl FnOnce(R) -> T,
+) -> Result<T, D::Error>
+where
+    T: FromStr<Err = ParseHexError>,
+    R: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    if deserializer.is_human_readable() {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    } else {
+        R::deserialize(deserializer).map(from_repr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(decode(""), Ok(vec![]));
+        assert_eq!(decode("00ff7A"), Ok(vec![0x00, 0xff, 0x7a]));
+        assert_eq!(decode("0x0102"), Ok(vec![1, 2]));
+        assert_eq!(decode("abc"), Err(ParseHexError::OddLength { len: 3 }));
+        assert_eq!(
+            decode("0x01g2"),
+            Err(ParseHexError::InvalidCharacter {
+                index: 4,
+                character: 'g'
+            })
+        );
+        assert_eq!(
+            decode_array::<2>("010203"),
+            Err(ParseHexError::InvalidLength {
+                expected: 2,
+                actual: 3
+            })
+        );
+    }
+}
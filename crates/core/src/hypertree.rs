@@ -0,0 +1,362 @@
+// Copyright 2025 Irreducible Inc.
+//! Two-layer hypertree signatures for lifetimes too large for a single tree.
+//!
+//! A top [`Signer`] of `2^top_height` one-time keys signs the roots of `2^top_height` bottom
+//! signers of `2^bottom_height` keys each, so the lifetime is `2^(top_height + bottom_height)`.
+//! The top key `i` signs the root of bottom tree `i` as its message. Bottom trees are derived
+//! from a seed and only generated once an epoch in them is signed, so key generation costs
+//! `2^top_height + 2^bottom_height` key pairs instead of one per epoch.
+
+#[cfg(feature = "std")]
+use alloc::collections::BTreeMap;
+
+#[cfg(feature = "std")]
+use rand::{RngCore, SeedableRng, rngs::StdRng};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "std")]
+use tiny_keccak::{Hasher, Keccak};
+
+use crate::{
+    Message, Param, Signature, VerifyError,
+    hash::{Hash, KeccakBackend, TweakableHash},
+    spec::Spec,
+    verify_signature_checked_with_backend,
+};
+#[cfg(feature = "std")]
+use crate::{Signer, SignerError};
+
+/// A signature from a [`HyperSigner`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HyperSignature {
+    /// The signature of the message by the bottom tree holding the epoch
+    pub bottom: Signature,
+    /// The root of the bottom tree
+    pub bottom_root: Hash,
+    /// The parameter of the bottom tree
+    pub bottom_param: Param,
+    /// The signature of the bottom root by the top tree
+    pub top: Signature,
+}
+
+/// The bottom tree that signed most recently, kept until an epoch of another one is signed.
+#[cfg(feature = "std")]
+struct BottomTree<H: TweakableHash> {
+    index: usize,
+    signer: Signer<H>,
+    top_signature: Signature,
+}
+
+/// A signer over a two-layer hypertree, generic over the tweakable hash backend.
+#[cfg(feature = "std")]
+pub struct HyperSigner<H: TweakableHash = KeccakBackend> {
+    max_retries: usize,
+    bottom_height: usize,
+    /// Derives the rng of every bottom tree, see [`bottom_seed`].
+    seed: [u8; 32],
+    top: Signer<H>,
+    /// The signature of every bottom root signed so far, by the index of its bottom tree.
+    ///
+    /// A bottom tree that is generated again reuses it, so no top key ever signs twice.
+    top_signatures: BTreeMap<usize, Signature>,
+    bottom: Option<BottomTree<H>>,
+    /// The specification shared by both layers
+    pub spec: Spec,
+    /// The public parameter of the top tree
+    pub param: Param,
+    /// The root of the top tree, serving as the public commitment to all bottom trees
+    pub root: Hash,
+}
+
+#[cfg(feature = "std")]
+impl HyperSigner {
+    /// Create a new hypertree signer using Keccak-256
+    ///
+    /// See [`HyperSigner::new_with_backend`] for the arguments and errors.
+    pub fn new(
+        rng: StdRng,
+        max_retries: usize,
+        spec: Spec,
+        top_height: usize,
+        bottom_height: usize,
+    ) -> Result<Self, SignerError> {
+        Self::new_with_backend(rng, max_retries, spec, top_height, bottom_height)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<H: TweakableHash> HyperSigner<H> {
+    /// Create a new hypertree signer using the hash backend `H`
+    ///
+    /// Only the top tree is generated here.
+    ///
+    /// # Arguments
+    /// * `rng` - Random number generator for key generation
+    /// * `max_retries` - Maximum attempts to find a valid signature, in either layer
+    /// * `spec` - The specification defining the signature scheme parameters
+    /// * `top_height` - The height of the top tree, which has a leaf per bottom tree
+    /// * `bottom_height` - The height of every bottom tree, which has a leaf per epoch
+    ///
+    /// # Errors
+    /// Returns an error if the spec is invalid.
+    pub fn new_with_backend(
+        mut rng: StdRng,
+        max_retries: usize,
+        spec: Spec,
+        top_height: usize,
+        bottom_height: usize,
+    ) -> Result<Self, SignerError> {
+        let mut seed = [0; 32];
+        rng.fill_bytes(&mut seed);
+        let top = Signer::new_with_backend(rng, max_retries, spec.clone(), 1 << top_height)?;
+        Ok(Self {
+            max_retries,
+            bottom_height,
+            seed,
+            param: top.param.clone(),
+            root: top.root,
+            top,
+            top_signatures: BTreeMap::new(),
+            bottom: None,
+            spec,
+        })
+    }
+
+    /// The number of epochs this signer can sign.
+    pub fn lifetime(&self) -> usize {
+        self.top.lifetime() << self.bottom_height
+    }
+
+    /// Sign a message at the given epoch, generating its bottom tree if needed
+    ///
+    /// Returns None if the epoch is past the lifetime of the signer or if either layer could not
+    /// produce a signature
+    pub fn sign(&mut self, epoch: usize, message: &Message) -> Option<HyperSignature> {
+        if epoch >= self.lifetime() {
+            return None;
+        }
+        let index = epoch >> self.bottom_height;
+        if self
+            .bottom
+            .as_ref()
+            .is_none_or(|bottom| bottom.index != index)
+        {
+            self.bottom = Some(self.generate_bottom(index)?);
+        }
+        let bottom = self.bottom.as_mut().expect("generated above");
+
+        let signature = bottom
+            .signer
+            .sign(epoch & ((1 << self.bottom_height) - 1), message)
+            .ok()?;
+        Some(HyperSignature {
+            bottom: signature,
+            bottom_root: bottom.signer.root,
+            bottom_param: bottom.signer.param.clone(),
+            top: bottom.top_signature.clone(),
+        })
+    }
+
+    /// Generates bottom tree `index` from the seed and signs its root with top key `index`, or
+    /// reuses the signature of that root if the tree was generated before.
+    fn generate_bottom(&mut self, index: usize) -> Option<BottomTree<H>> {
+        let signer = Signer::new_with_backend(
+            StdRng::from_seed(bottom_seed(&self.seed, index)),
+            self.max_retries,
+            self.spec.clone(),
+            1 << self.bottom_height,
+        )
+        .expect("the spec was validated by the top signer");
+        let top_signature = match self.top_signatures.get(&index) {
+            Some(top_signature) => top_signature.clone(),
+            None => {
+                let top_signature = self.top.sign(index, &Message(signer.root.0)).ok()?;
+                self.top_signatures.insert(index, top_signature.clone());
+                top_signature
+            }
+        };
+        Some(BottomTree {
+            index,
+            signer,
+            top_signature,
+        })
+    }
+}
+
+/// The seed of bottom tree `index`, so that it is the same every time the tree is generated.
+#[cfg(feature = "std")]
+fn bottom_seed(seed: &[u8; 32], index: usize) -> [u8; 32] {
+    let mut hasher = Keccak::v256();
+    hasher.update(b"leansig-hypertree-bottom");
+    hasher.update(seed);
+    hasher.update(&(index as u64).to_be_bytes());
+    let mut bottom_seed = [0; 32];
+    hasher.finalize(&mut bottom_seed);
+    bottom_seed
+}
+
+/// Verify a hypertree signature
+///
+/// The bottom signature is checked against the bottom root it carries, and that root is then
+/// checked to be signed by the top tree with the given `param` and `root`.
+pub fn verify_hyper_signature(
+    spec: &Spec,
+    param: &Param,
+    message: &Message,
+    signature: &HyperSignature,
+    root: &Hash,
+) -> bool {
+    verify_hyper_signature_with_backend::<KeccakBackend>(spec, param, message, signature, root)
+}
+
+/// Same as [`verify_hyper_signature`], but with the hash backend `H`
+pub fn verify_hyper_signature_with_backend<H: TweakableHash>(
+    spec: &Spec,
+    param: &Param,
+    message: &Message,
+    signature: &HyperSignature,
+    root: &Hash,
+) -> bool {
+    verify_hyper_signature_checked_with_backend::<H>(spec, param, message, signature, root).is_ok()
+}
+
+/// Same as [`verify_hyper_signature`], but returns the reason a signature is rejected
+///
+/// The bottom signature is checked first, and the error is that of the first layer that fails.
+pub fn verify_hyper_signature_checked(
+    spec: &Spec,
+    param: &Param,
+    message: &Message,
+    signature: &HyperSignature,
+    root: &Hash,
+) -> Result<(), VerifyError> {
+    verify_hyper_signature_checked_with_backend::<KeccakBackend>(
+        spec, param, message, signature, root,
+    )
+}
+
+/// Same as [`verify_hyper_signature_checked`], but with the hash backend `H`
+pub fn verify_hyper_signature_checked_with_backend<H: TweakableHash>(
+    spec: &Spec,
+    param: &Param,
+    message: &Message,
+    signature: &HyperSignature,
+    root: &Hash,
+) -> Result<(), VerifyError> {
+    verify_signature_checked_with_backend::<H>(
+        spec,
+        &signature.bottom_param,
+        message,
+        &signature.bottom,
+        &signature.bottom_root,
+    )?;
+    verify_signature_checked_with_backend::<H>(
+        spec,
+        param,
+        &Message(signature.bottom_root.0),
+        &signature.top,
+        root,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        AggregatedSignature, AggregatedVerifier, ValidatorSignature, registry::ValidatorRegistry,
+        spec::SPEC_2,
+    };
+
+    #[test]
+    fn test_hyper_signature() {
+        let spec = SPEC_2;
+        let mut signer =
+            HyperSigner::new(StdRng::seed_from_u64(0), 1000000, spec.clone(), 3, 4).unwrap();
+        assert_eq!(signer.lifetime(), 128);
+        let (param, root) = (signer.param.clone(), signer.root);
+        let message = Message([10; 32]);
+
+        // Epoch 21 is the sixth epoch of the second bottom tree.
+        let sig = signer.sign(21, &message).unwrap();
+        assert!(verify_hyper_signature(&spec, &param, &message, &sig, &root));
+        assert!(!verify_hyper_signature(
+            &spec,
+            &param,
+            &Message([11; 32]),
+            &sig,
+            &root
+        ));
+
+        // Switching to another bottom tree and back regenerates the same tree, whose root top
+        // key 1 does not sign again.
+        let first_tree_sig = signer.sign(3, &message).unwrap();
+        assert!(verify_hyper_signature(
+            &spec,
+            &param,
+            &message,
+            &first_tree_sig,
+            &root
+        ));
+        let again = signer.sign(22, &message).unwrap();
+        assert_eq!(again.bottom_root, sig.bottom_root);
+        assert_eq!(again.top.to_bytes(), sig.top.to_bytes());
+        assert!(verify_hyper_signature(
+            &spec, &param, &message, &again, &root
+        ));
+        assert_ne!(first_tree_sig.bottom_root, sig.bottom_root);
+
+        // A bottom root that the top tree did not sign is rejected.
+        let mut forged = sig.clone();
+        forged.bottom = first_tree_sig.bottom.clone();
+        forged.bottom_root = first_tree_sig.bottom_root;
+        forged.bottom_param = first_tree_sig.bottom_param.clone();
+        assert!(!verify_hyper_signature(
+            &spec, &param, &message, &forged, &root
+        ));
+
+        assert!(signer.sign(128, &message).is_none());
+
+        // The aggregate verifier accepts both flavors side by side.
+        let mut xmss_signer =
+            Signer::new(StdRng::seed_from_u64(1), 1000000, spec.clone(), 4).unwrap();
+        let xmss_sig = xmss_signer.sign(2, &message).unwrap();
+        let aggregated = AggregatedSignature::new(vec![
+            ValidatorSignature {
+                epoch: 21,
+                signature: sig.into(),
+                xmss_root: root,
+                param: param.clone(),
+                validator_index: None,
+            },
+            ValidatorSignature {
+                epoch: 2,
+                signature: xmss_sig.into(),
+                xmss_root: xmss_signer.root,
+                param: xmss_signer.param.clone(),
+                validator_index: None,
+            },
+        ]);
+        let registry = ValidatorRegistry::new(vec![
+            (root, param),
+            (xmss_signer.root, xmss_signer.param.clone()),
+        ]);
+        let verifier = AggregatedVerifier::new(registry, spec);
+        assert!(verifier.verify(&message, &aggregated));
+        assert!(!verifier.verify(&Message([11; 32]), &aggregated));
+    }
+
+    #[test]
+    fn test_top_key_signs_once() {
+        let mut signer = HyperSigner::new(StdRng::seed_from_u64(2), 1000000, SPEC_2, 2, 2).unwrap();
+        let message = Message([10; 32]);
+
+        // Leave every bottom tree and come back to it, so that each is generated twice.
+        let first: Vec<_> = (0..4)
+            .map(|index| signer.sign(index << 2, &message).unwrap())
+            .collect();
+        for (index, first) in first.iter().enumerate() {
+            let again = signer.sign((index << 2) + 1, &message).unwrap();
+            assert_eq!(again.bottom_root, first.bottom_root);
+            assert_eq!(again.top.to_bytes(), first.top.to_bytes());
+        }
+    }
+}
@@ -0,0 +1,276 @@
+// Copyright 2025 Irreducible Inc.
+//! Known-answer test vectors, pinning every value from the secret key to the signature bytes.
+//!
+//! A [`KatFile`] is generated deterministically from a spec and a seed, so any change to the
+//! tweak encoding, the codeword chopping or the byte layouts shows up as a diff against the
+//! files checked in under `tests/fixtures`, which would otherwise silently invalidate roots that
+//! are already published. When the format changes on purpose, regenerate them with
+//!
+//! ```text
+//! cargo run -p leansig-core --features compat-hashsig --example kat_vectors
+//! ```
+//!
+//! The `hashsig_compat_*.json` files are the same vectors over [`crate::hash::HashSigCompat`],
+//! pinning its layout of the tweaks of the `b-wagn/hash-sig` reference implementation. They are
+//! generated by this crate like the others, not by the reference crate, so they catch regressions
+//! of the layout but not a misreading of the reference.
+
+use alloc::{string::String, vec::Vec};
+
+use rand::{SeedableRng, rngs::StdRng};
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
+
+use crate::{
+    Message, Nonce, Param, Signer,
+    hash::{Hash, KeccakBackend, TweakableHash},
+    spec::Spec,
+    verify_signature_with_backend,
+};
+
+/// The number of epochs of the signer of the vectors
+pub const KAT_LIFETIME: usize = 4;
+
+/// The epoch the vectors are signed at
+pub const KAT_EPOCH: usize = 1;
+
+/// The message the vectors sign
+pub const KAT_MESSAGE: Message = Message([42; 32]);
+
+const KAT_MAX_RETRIES: usize = 100_000;
+
+/// The values a signer seeded with `seed` derives for `spec`, and the signature of
+/// [`KAT_MESSAGE`] at [`KAT_EPOCH`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KatFile {
+    /// The spec of the signer
+    pub spec: Spec,
+    /// The seed of the signer's rng
+    pub seed: u64,
+    /// The lifetime of the signer, see [`KAT_LIFETIME`]
+    pub lifetime: u64,
+    /// The epoch of the signature, see [`KAT_EPOCH`]
+    pub epoch: u64,
+    /// The param of the signer
+    pub param: Param,
+    /// The start hashes of the chains of the secret key at the epoch
+    pub sk_start_hashes: Vec<Hash>,
+    /// The end hashes of the chains of the public key at the epoch
+    pub pk_end_hashes: Vec<Hash>,
+    /// The root of the signer's tree
+    pub root: Hash,
+    /// The signed message
+    pub message: Message,
+    /// The nonce the signer ground for the message
+    pub nonce: Nonce,
+    /// The [`crate::Signature::to_bytes`] encoding of the signature
+    #[serde(with = "hex_bytes")]
+    pub signature: Vec<u8>,
+    /// Whether the signature verifies for the message against the root
+    pub verifies: bool,
+}
+
+/// Generates the vectors of a signer over `spec` seeded with `seed`.
+///
+/// # Panics
+/// Panics if `spec` is invalid or if grinding for the nonce fails.
+pub fn generate_vectors(spec: &Spec, seed: u64) -> KatFile {
+    generate_vectors_with_backend::<KeccakBackend>(spec, seed)
+}
+
+/// Same as [`generate_vectors`], but with the hash backend `H`
+pub fn generate_vectors_with_backend<H: TweakableHash>(spec: &Spec, seed: u64) -> KatFile {
+    let mut signer = Signer::<H>::new_with_backend(
+        StdRng::seed_from_u64(seed),
+        KAT_MAX_RETRIES,
+        spec.clone(),
+        KAT_LIFETIME,
+    )
+    .expect("a valid spec");
+    let signature = (signer.sign(KAT_EPOCH, &KAT_MESSAGE)).expect("grinding succeeds");
+    let (sk, pk) = &signer.key_pairs[KAT_EPOCH];
+    KatFile {
+        spec: spec.clone(),
+        seed,
+        lifetime: KAT_LIFETIME as u64,
+        epoch: KAT_EPOCH as u64,
+        param: signer.param.clone(),
+        sk_start_hashes: sk.start_hashes.clone(),
+        pk_end_hashes: pk.end_hashes.clone(),
+        root: signer.root,
+        message: KAT_MESSAGE,
+        nonce: signature.signature.nonce.clone(),
+        signature: signature.to_bytes(),
+        verifies: verify_signature_with_backend::<H>(
+            spec,
+            &signer.param,
+            &KAT_MESSAGE,
+            &signature,
+            &signer.root,
+        ),
+    }
+}
+
+/// The serde layout of byte strings of any length, as `0x`-prefixed hex like the byte-string
+/// types of [`crate::hex`]
+mod hex_bytes {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        let digits: String = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+        serializer.collect_str(&format_args!("0x{digits}"))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        crate::hex::decode(&s).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Pk, Signature, Sk,
+        spec::{SPEC_1, SPEC_2},
+    };
+
+    const SPEC_1_VECTORS: &str = include_str!("../tests/fixtures/kat_spec_1.json");
+    const SPEC_2_VECTORS: &str = include_str!("../tests/fixtures/kat_spec_2.json");
+
+    /// Checks every field of the checked-in `json` against values computed afresh with the hash
+    /// backend `H`
+    fn check_vectors<H: TweakableHash>(json: &str, spec: &Spec) {
+        let file: KatFile = serde_json::from_str(json).unwrap();
+        assert_eq!(&file.spec, spec);
+        let fresh = generate_vectors_with_backend::<H>(spec, file.seed);
+        assert_eq!(file.lifetime, fresh.lifetime, "lifetime");
+        assert_eq!(file.epoch, fresh.epoch, "epoch");
+        assert_eq!(file.param, fresh.param, "param");
+        assert_eq!(
+            file.sk_start_hashes, fresh.sk_start_hashes,
+            "sk start hashes"
+        );
+        assert_eq!(file.pk_end_hashes, fresh.pk_end_hashes, "pk end hashes");
+        assert_eq!(file.root, fresh.root, "root");
+        assert_eq!(file.message, fresh.message, "message");
+        assert_eq!(file.nonce, fresh.nonce, "nonce");
+        assert_eq!(file.signature, fresh.signature, "signature bytes");
+        assert_eq!(file.verifies, fresh.verifies, "verification result");
+        assert_eq!(
+            serde_json::to_string_pretty(&fresh).unwrap(),
+            json.trim_end()
+        );
+
+        // The values are also consistent with each other, independently of the signer.
+        let sk = Sk {
+            param: file.param.clone(),
+            start_hashes: file.sk_start_hashes.clone(),
+        };
+        let hasher = H::new(&file.param);
+        let pk = Pk::derive_with_backend(&sk, spec, &hasher, file.epoch as u32);
+        assert_eq!(pk.end_hashes, file.pk_end_hashes);
+        let height = KAT_LIFETIME.ilog2() as usize;
+        let signature = Signature::from_bytes(&file.signature, spec, height).unwrap();
+        assert_eq!(signature.signature.nonce, file.nonce);
+        assert_eq!(signature.public_key.end_hashes, file.pk_end_hashes);
+        assert_eq!(signature.hash_tree_proof.leaf_index() as u64, file.epoch);
+        assert!(file.verifies);
+        assert!(verify_signature_with_backend::<H>(
+            spec,
+            &file.param,
+            &file.message,
+            &signature,
+            &file.root
+        ));
+        let mut other_message = file.message;
+        other_message.0[0] ^= 1;
+        assert!(!verify_signature_with_backend::<H>(
+            spec,
+            &file.param,
+            &other_message,
+            &signature,
+            &file.root
+        ));
+    }
+
+    #[test]
+    fn test_spec_1_vectors() {
+        check_vectors::<KeccakBackend>(SPEC_1_VECTORS, &SPEC_1);
+    }
+
+    #[test]
+    fn test_spec_2_vectors() {
+        check_vectors::<KeccakBackend>(SPEC_2_VECTORS, &SPEC_2);
+    }
+
+    #[test]
+    fn test_vectors_depend_on_the_seed() {
+        let (a, b) = (generate_vectors(&SPEC_2, 0), generate_vectors(&SPEC_2, 1));
+        assert_ne!(a.sk_start_hashes, b.sk_start_hashes);
+        assert_ne!(a.root, b.root);
+        assert_eq!(generate_vectors(&SPEC_2, 0), a);
+    }
+
+    #[cfg(feature = "compat-hashsig")]
+    mod hashsig_compat {
+        use super::*;
+        use crate::{code::Codeword, hash::HashSigCompat, verify_ots_with_backend};
+
+        const SPEC_1_VECTORS: &str = include_str!("../tests/fixtures/hashsig_compat_spec_1.json");
+        const SPEC_2_VECTORS: &str = include_str!("../tests/fixtures/hashsig_compat_spec_2.json");
+
+        /// Checks the one-time signature and the tree proof of the vectors separately, as the
+        /// reference verifies them
+        fn check_parts(json: &str, spec: &Spec) {
+            let file: KatFile = serde_json::from_str(json).unwrap();
+            let height = KAT_LIFETIME.ilog2() as usize;
+            let signature = Signature::from_bytes(&file.signature, spec, height).unwrap();
+            let hasher = HashSigCompat::new(&file.param);
+            let epoch = file.epoch as u32;
+
+            let codeword =
+                Codeword::new_with_backend(spec, &hasher, epoch, &file.message, &file.nonce);
+            let pk = &signature.public_key;
+            let hashes = &signature.signature.hashes;
+            assert_eq!(
+                verify_ots_with_backend(&hasher, spec, epoch, &codeword, hashes, pk),
+                Ok(())
+            );
+            let leaf = hasher.hash_public_key(epoch, pk);
+            let proof = &signature.hash_tree_proof;
+            assert!(proof.verify_with_backend(&hasher, &leaf, &file.root));
+
+            // The epoch is bound into the chains, the leaf and the message hash.
+            assert!(
+                verify_ots_with_backend(&hasher, spec, epoch + 1, &codeword, hashes, pk).is_err()
+            );
+            let other_leaf = hasher.hash_public_key(epoch + 1, pk);
+            assert!(!proof.verify_with_backend(&hasher, &other_leaf, &file.root));
+            assert_ne!(
+                hasher.hash_message(epoch, &file.message, &file.nonce),
+                hasher.hash_message(epoch + 1, &file.message, &file.nonce)
+            );
+
+            // Nor do the vectors verify with the default backend.
+            assert!(!crate::verify_signature(
+                spec,
+                &file.param,
+                &file.message,
+                &signature,
+                &file.root
+            ));
+        }
+
+        #[test]
+        fn test_spec_1_vectors() {
+            check_vectors::<HashSigCompat>(SPEC_1_VECTORS, &SPEC_1);
+            check_parts(SPEC_1_VECTORS, &SPEC_1);
+        }
+
+        #[test]
+        fn test_spec_2_vectors() {
+            check_vectors::<HashSigCompat>(SPEC_2_VECTORS, &SPEC_2);
+            check_parts(SPEC_2_VECTORS, &SPEC_2);
+        }
+    }
+}
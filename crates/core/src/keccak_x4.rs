@@ -0,0 +1,237 @@
+// Copyright 2025 Irreducible Inc.
+//! Multi-way interleaved Keccak-256.
+//!
+//! The state stores lane `i` of all instances next to each other, so every step of the
+//! permutation is a loop over independent words that the compiler turns into SIMD instructions
+//! (e.g. AVX2 or AVX-512 on x86-64). The output is bit-identical to `tiny_keccak::Keccak::v256`.
+
+/// The rate of Keccak-256 in bytes.
+const RATE: usize = 136;
+
+const ROUND_CONSTANTS: [u64; 24] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808a,
+    0x8000000080008000,
+    0x000000000000808b,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008a,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000a,
+    0x000000008000808b,
+    0x800000000000008b,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800a,
+    0x800000008000000a,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+const RHO: [u32; 24] = [
+    1, 3, 6, 10, 15, 21, 28, 36, 45, 55, 2, 14, 27, 41, 56, 8, 25, 43, 62, 18, 39, 61, 20, 44,
+];
+
+const PI: [usize; 24] = [
+    10, 7, 11, 17, 18, 3, 5, 16, 8, 21, 24, 4, 15, 23, 19, 13, 12, 2, 20, 14, 22, 9, 6, 1,
+];
+
+fn keccak_f<const LANES: usize>(a: &mut [[u64; LANES]; 25]) {
+    for round_constant in ROUND_CONSTANTS {
+        // θ
+        let mut c = [[0u64; LANES]; 5];
+        for (x, c) in c.iter_mut().enumerate() {
+            for l in 0..LANES {
+                c[l] = a[x][l] ^ a[x + 5][l] ^ a[x + 10][l] ^ a[x + 15][l] ^ a[x + 20][l];
+            }
+        }
+        for x in 0..5 {
+            for l in 0..LANES {
+                let d = c[(x + 4) % 5][l] ^ c[(x + 1) % 5][l].rotate_left(1);
+                for y in 0..5 {
+                    a[x + 5 * y][l] ^= d;
+                }
+            }
+        }
+
+        // ρ and π
+        let mut last = a[1];
+        for (&pi, &rho) in PI.iter().zip(RHO.iter()) {
+            let tmp = a[pi];
+            for l in 0..LANES {
+                a[pi][l] = last[l].rotate_left(rho);
+            }
+            last = tmp;
+        }
+
+        // χ
+        for y in 0..5 {
+            let row = [
+                a[5 * y],
+                a[5 * y + 1],
+                a[5 * y + 2],
+                a[5 * y + 3],
+                a[5 * y + 4],
+            ];
+            for x in 0..5 {
+                for l in 0..LANES {
+                    a[5 * y + x][l] = row[x][l] ^ (!row[(x + 1) % 5][l] & row[(x + 2) % 5][l]);
+                }
+            }
+        }
+
+        // ι
+        for lane in a[0].iter_mut() {
+            *lane ^= round_constant;
+        }
+    }
+}
+
+/// `LANES` Keccak-256 sponges absorbing inputs of the same length in lockstep.
+#[derive(Clone)]
+pub struct KeccakMulti<const LANES: usize> {
+    state: [[u64; LANES]; 25],
+    offset: usize,
+}
+
+/// Four sponges, used for grinding.
+#[cfg(feature = "multi_buffer")]
+pub type KeccakX4 = KeccakMulti<4>;
+
+/// Eight sponges, used for walking hash chains.
+#[cfg(feature = "simd")]
+pub type KeccakX8 = KeccakMulti<8>;
+
+impl<const LANES: usize> Default for KeccakMulti<LANES> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const LANES: usize> KeccakMulti<LANES> {
+    pub fn new() -> Self {
+        Self {
+            state: [[0; LANES]; 25],
+            offset: 0,
+        }
+    }
+
+    fn xor_byte(&mut self, lane: usize, byte: u8) {
+        self.state[self.offset / 8][lane] ^= (byte as u64) << (8 * (self.offset % 8));
+    }
+
+    /// Absorbs the same bytes into all instances.
+    pub fn update_all(&mut self, input: &[u8]) {
+        self.update([input; LANES]);
+    }
+
+    /// Absorbs one input per instance.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the inputs differ in length.
+    pub fn update(&mut self, inputs: [&[u8]; LANES]) {
+        let len = inputs[0].len();
+        assert!(inputs.iter().all(|input| input.len() == len));
+        for i in 0..len {
+            for (lane, input) in inputs.iter().enumerate() {
+                self.xor_byte(lane, input[i]);
+            }
+            self.offset += 1;
+            if self.offset == RATE {
+                keccak_f(&mut self.state);
+                self.offset = 0;
+            }
+        }
+    }
+
+    /// Pads, permutes and squeezes the 32-byte digest of every instance.
+    pub fn finalize(mut self) -> [[u8; 32]; LANES] {
+        for lane in 0..LANES {
+            self.xor_byte(lane, 0x01);
+        }
+        self.offset = RATE - 1;
+        for lane in 0..LANES {
+            self.xor_byte(lane, 0x80);
+        }
+        keccak_f(&mut self.state);
+
+        let mut out = [[0u8; 32]; LANES];
+        for (lane, out) in out.iter_mut().enumerate() {
+            for (word, chunk) in out.as_chunks_mut::<8>().0.iter_mut().enumerate() {
+                chunk.copy_from_slice(&self.state[word][lane].to_le_bytes());
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, RngCore, SeedableRng, rngs::StdRng};
+    use tiny_keccak::{Hasher, Keccak};
+
+    use super::*;
+
+    fn keccak(input: &[u8]) -> [u8; 32] {
+        let mut hasher = Keccak::v256();
+        hasher.update(input);
+        let mut output = [0u8; 32];
+        hasher.finalize(&mut output);
+        output
+    }
+
+    fn check_matches_tiny_keccak<const LANES: usize>() {
+        let mut rng = StdRng::seed_from_u64(0);
+        // Cover lengths around the rate so that multi-block absorption and padding that spills
+        // into the last byte of a block are exercised.
+        for len in (0..300).chain([RATE - 1, RATE, RATE + 1, 2 * RATE]) {
+            let inputs: [Vec<u8>; LANES] = core::array::from_fn(|_| {
+                let mut input = vec![0; len];
+                rng.fill_bytes(&mut input);
+                input
+            });
+            let split = rng.random_range(0..=len);
+
+            let mut hasher = KeccakMulti::<LANES>::new();
+            hasher.update(core::array::from_fn(|lane| &inputs[lane][..split]));
+            hasher.update(core::array::from_fn(|lane| &inputs[lane][split..]));
+            let digests = hasher.finalize();
+
+            for (input, digest) in inputs.iter().zip(digests) {
+                assert_eq!(digest, keccak(input), "length {len}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_matches_tiny_keccak() {
+        check_matches_tiny_keccak::<4>();
+        check_matches_tiny_keccak::<8>();
+    }
+
+    #[test]
+    fn test_update_patterns() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut input = vec![0; 3 * RATE + 7];
+        rng.fill_bytes(&mut input);
+        let expected = keccak(&input);
+
+        // Absorb in chunks of a fixed size, so that updates end before, on and after the block
+        // boundaries.
+        for chunk_len in [1, RATE - 1, RATE, RATE + 1] {
+            let mut hasher = KeccakMulti::<4>::new();
+            for chunk in input.chunks(chunk_len) {
+                hasher.update_all(chunk);
+            }
+            assert_eq!(hasher.finalize(), [expected; 4], "chunk length {chunk_len}");
+        }
+    }
+}
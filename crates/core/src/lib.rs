@@ -1,26 +1,87 @@
 // Copyright 2025 Irreducible Inc.
-use hash_chain::hash_chain;
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
+use alloc::boxed::Box;
+use alloc::{sync::Arc, vec, vec::Vec};
+use core::{fmt, marker::PhantomData, str::FromStr};
+
+#[cfg(feature = "std")]
+use hash_chain::hash_chain_with;
+use hash_chain::{ChainCache, ChainWalkError, hash_chain_checked_with};
+#[cfg(feature = "std")]
 use rand::{RngCore, rngs::StdRng};
-use serde::{Deserialize, Serialize};
-use spec::Spec;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use spec::{Spec, SpecError};
 
-use crate::hash::Hash;
-use crate::hash::tweak_public_key_hash;
-use crate::hash_tree::{HashTree, HashTreeProof};
+use crate::code::Codeword;
+#[cfg(feature = "std")]
+use crate::code::{GrindStrategy, SumHistogram};
+#[cfg(feature = "std")]
+use crate::footprint::{MemoryFootprint, SignerFootprint};
+use crate::hash::{HASH_LEN, Hash, KECCAK_EPOCH, KeccakBackend, TweakableHash};
+#[cfg(feature = "std")]
+use crate::hash_tree::HashTree;
+use crate::hash_tree::HashTreeProof;
+use crate::hex::ParseHexError;
+use crate::hypertree::{HyperSignature, verify_hyper_signature_checked_with_backend};
+use crate::registry::ValidatorRegistry;
+#[cfg(feature = "std")]
+use crate::traversal::TraversalState;
 
+#[cfg(feature = "cbor")]
+pub mod cbor;
 pub mod code;
+pub mod compressed;
+pub mod cycle_tracker;
+pub mod footprint;
 pub mod hash;
 pub mod hash_chain;
 pub mod hash_tree;
-
+pub mod hex;
+pub mod hypertree;
+#[cfg(feature = "std")]
+pub mod kat;
+#[cfg(all(
+    any(feature = "multi_buffer", feature = "simd"),
+    not(target_os = "zkvm")
+))]
+mod keccak_x4;
+pub mod multi_epoch;
+#[cfg(feature = "poseidon2")]
+pub mod poseidon2;
+pub mod registry;
 pub mod spec;
+#[cfg(feature = "ssz")]
+pub mod ssz;
+pub mod traversal;
+#[cfg(feature = "std")]
+pub mod wire;
+
+#[cfg(all(feature = "parallel", target_os = "zkvm"))]
+compile_error!("the `parallel` feature is host-only and must not be enabled for zkVM guests");
+
+/// The number of chains each parallel task walks in [`Pk::derive`], a multiple of the lanes of
+/// the eight-way keccak.
+#[cfg(feature = "parallel")]
+const PARALLEL_CHAIN_GROUP_LEN: usize = 8;
 
 const MESSAGE_LEN: usize = 32;
 const RAND_LEN: usize = 23;
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Nonce(pub [u8; RAND_LEN]);
 
+/// The serde layout of [`Nonce`] in binary formats.
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Nonce")]
+struct NonceRepr([u8; RAND_LEN]);
+
+#[cfg(feature = "std")]
 impl Nonce {
     /// Generate a random nonce.
     pub fn random(rng: &mut StdRng) -> Nonce {
@@ -36,22 +97,99 @@ impl AsRef<[u8]> for Nonce {
     }
 }
 
-#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+impl fmt::Display for Nonce {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        hex::write(f, &self.0)
+    }
+}
+
+impl fmt::LowerHex for Nonce {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        hex::write(f, &self.0)
+    }
+}
+
+impl FromStr for Nonce {
+    type Err = ParseHexError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        hex::decode_array(s).map(Nonce)
+    }
+}
+
+impl Serialize for Nonce {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        hex::serialize(self, || NonceRepr(self.0), serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Nonce {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        hex::deserialize(deserializer, |NonceRepr(bytes)| Nonce(bytes))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Message(pub [u8; MESSAGE_LEN]);
 
+/// The serde layout of [`Message`] in binary formats.
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Message")]
+struct MessageRepr([u8; MESSAGE_LEN]);
+
 impl AsRef<[u8]> for Message {
     fn as_ref(&self) -> &[u8] {
         &self.0
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        hex::write(f, &self.0)
+    }
+}
+
+impl fmt::LowerHex for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        hex::write(f, &self.0)
+    }
+}
+
+impl FromStr for Message {
+    type Err = ParseHexError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        hex::decode_array(s).map(Message)
+    }
+}
+
+impl Serialize for Message {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        hex::serialize(self, || MessageRepr(self.0), serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Message {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        hex::deserialize(deserializer, |MessageRepr(bytes)| Message(bytes))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Param {
     data: Vec<u8>,
 }
 
+/// The serde layout of [`Param`] in binary formats.
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Param")]
+struct ParamRepr {
+    data: Vec<u8>,
+}
+
+#[cfg(feature = "std")]
 impl Param {
-    pub fn random(param_len: usize, rng: &mut StdRng) -> Self {
+    pub fn random(param_len: usize, rng: &mut (impl RngCore + ?Sized)) -> Self {
         let mut data = vec![0; param_len];
         rng.fill_bytes(&mut data);
         Self { data }
@@ -64,6 +202,52 @@ impl AsRef<[u8]> for Param {
     }
 }
 
+impl From<&[u8]> for Param {
+    fn from(data: &[u8]) -> Self {
+        Self {
+            data: data.to_vec(),
+        }
+    }
+}
+
+impl fmt::Display for Param {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        hex::write(f, &self.data)
+    }
+}
+
+impl fmt::LowerHex for Param {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        hex::write(f, &self.data)
+    }
+}
+
+impl FromStr for Param {
+    type Err = ParseHexError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        hex::decode(s).map(|data| Self { data })
+    }
+}
+
+impl Serialize for Param {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        hex::serialize(
+            self,
+            || ParamRepr {
+                data: self.data.clone(),
+            },
+            serializer,
+        )
+    }
+}
+
+impl<'de> Deserialize<'de> for Param {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        hex::deserialize(deserializer, |ParamRepr { data }| Self { data })
+    }
+}
+
 /// A public key.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Pk {
@@ -73,22 +257,55 @@ pub struct Pk {
 
 impl Pk {
     pub fn derive(sk: &Sk, spec: &Spec) -> Self {
+        Self::derive_with_backend(sk, spec, &KeccakBackend::new(&sk.param), KECCAK_EPOCH)
+    }
+
+    /// Same as [`Pk::derive`], but also keeps every intermediate chain hash for
+    /// [`Signer::sign_with_cache`].
+    pub fn derive_with_cache(sk: &Sk, spec: &Spec) -> (Self, ChainCache) {
+        let hasher = KeccakBackend::new(&sk.param);
+        let cache =
+            ChainCache::new_with_backend(&hasher, KECCAK_EPOCH, &sk.start_hashes, spec.chain_len());
+        let pk = Self {
+            param: sk.param.clone(),
+            end_hashes: cache.end_hashes().collect(),
+        };
+        (pk, cache)
+    }
+
+    /// Same as [`Pk::derive`], but with any hash backend keyed by the secret key's param, for the
+    /// key of `epoch`.
+    pub fn derive_with_backend<H: TweakableHash>(
+        sk: &Sk,
+        spec: &Spec,
+        hasher: &H,
+        epoch: u32,
+    ) -> Self {
         let param = sk.param.clone();
-        let chain_len = spec.chain_len();
-        let end_hashes = sk
-            .start_hashes
-            .iter()
+        let chain_len = spec.chain_len() as u32;
+        let mut end_hashes = sk.start_hashes.clone();
+        #[cfg(feature = "parallel")]
+        end_hashes
+            .par_chunks_mut(PARALLEL_CHAIN_GROUP_LEN)
             .enumerate()
-            .map(|(chain_index, start_hash)| {
-                hash_chain(
-                    &param,
-                    chain_index,
-                    *start_hash,
+            .for_each(|(group_index, group)| {
+                let first_chain_index = (group_index * PARALLEL_CHAIN_GROUP_LEN) as u32;
+                hasher.hash_chains(
+                    epoch,
+                    first_chain_index,
+                    group,
                     /* start pos */ 0,
                     chain_len - 1,
-                )
-            })
-            .collect();
+                );
+            });
+        #[cfg(not(feature = "parallel"))]
+        hasher.hash_chains(
+            epoch,
+            0,
+            &mut end_hashes,
+            /* start pos */ 0,
+            chain_len - 1,
+        );
         Self { param, end_hashes }
     }
 }
@@ -100,8 +317,9 @@ pub struct Sk {
     start_hashes: Vec<Hash>,
 }
 
+#[cfg(feature = "std")]
 impl Sk {
-    pub fn random(rng: &mut StdRng, param: Param, spec: &Spec) -> Self {
+    pub fn random(rng: &mut (impl RngCore + ?Sized), param: Param, spec: &Spec) -> Self {
         let start_hashes = (0..spec.dimension()).map(|_| Hash::random(rng)).collect();
         Self {
             param,
@@ -127,104 +345,949 @@ pub struct Signature {
     pub public_key: Pk,
 }
 
-pub struct Signer {
+impl Signature {
+    /// The length of [`Signature::to_bytes`] for `spec` and a tree of `height`.
+    pub fn encoded_len(spec: &Spec, height: usize) -> usize {
+        RAND_LEN
+            + spec.dimension() * HASH_LEN
+            + HashTreeProof::encoded_len(height)
+            + spec.param_len
+            + spec.dimension() * HASH_LEN
+    }
+
+    /// The height of the tree of a signature that [`Signature::to_bytes`] encodes in `len`
+    /// bytes for `spec`, if any
+    ///
+    /// The encoding has no length prefixes, but only the proof depends on the height, so callers
+    /// that don't know the height of the signer's tree can recover it from the length.
+    pub fn height_for_encoded_len(spec: &Spec, len: usize) -> Option<usize> {
+        let proof_len = len.checked_sub(Self::encoded_len(spec, 0))?;
+        proof_len
+            .is_multiple_of(HASH_LEN)
+            .then_some(proof_len / HASH_LEN)
+    }
+
+    /// Encodes the signature without any length prefixes, as the nonce, the one-time signature
+    /// hashes, the [`HashTreeProof::to_bytes`] encoding of the proof, the public key parameter
+    /// and the public key end hashes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&self.signature.nonce.0);
+        for hash in &self.signature.hashes {
+            bytes.extend_from_slice(&hash.0);
+        }
+        bytes.extend_from_slice(&self.hash_tree_proof.to_bytes());
+        bytes.extend_from_slice(self.public_key.param.as_ref());
+        for hash in &self.public_key.end_hashes {
+            bytes.extend_from_slice(&hash.0);
+        }
+        bytes
+    }
+
+    /// Decodes a signature written by [`Signature::to_bytes`] for `spec` and a tree of `height`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error unless `bytes` has exactly the length of such a signature.
+    pub fn from_bytes(bytes: &[u8], spec: &Spec, height: usize) -> Result<Self, DecodeError> {
+        let expected = Self::encoded_len(spec, height);
+        if bytes.len() != expected {
+            return Err(DecodeError::InvalidLength {
+                expected,
+                actual: bytes.len(),
+            });
+        }
+        let hashes_len = spec.dimension() * HASH_LEN;
+        let (nonce, rest) = bytes.split_at(RAND_LEN);
+        let (hashes, rest) = rest.split_at(hashes_len);
+        let (proof, rest) = rest.split_at(HashTreeProof::encoded_len(height));
+        let (param, end_hashes) = rest.split_at(spec.param_len);
+        Ok(Self {
+            signature: OtsSignature {
+                nonce: Nonce(nonce.try_into().expect("split at the nonce length")),
+                hashes: decode_hashes(hashes),
+            },
+            hash_tree_proof: HashTreeProof::from_bytes(proof, height)?,
+            public_key: Pk {
+                param: Param {
+                    data: param.to_vec(),
+                },
+                end_hashes: decode_hashes(end_hashes),
+            },
+        })
+    }
+}
+
+/// The format version of the files written by [`Signature::write_to_file`], bumped whenever
+/// [`Signature::to_bytes`] changes.
+#[cfg(feature = "persistence")]
+pub const SIGNATURE_FORMAT_VERSION: u16 = 1;
+
+#[cfg(feature = "persistence")]
+impl Signature {
+    /// Writes [`Signature::to_bytes`] to `path`, after the envelope of a
+    /// [`wire::PayloadKind::Signature`] in [`SIGNATURE_FORMAT_VERSION`].
+    pub fn write_to_file(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        let mut bytes = Vec::new();
+        wire::write_envelope(
+            &mut bytes,
+            wire::PayloadKind::Signature,
+            SIGNATURE_FORMAT_VERSION,
+        )?;
+        bytes.extend(self.to_bytes());
+        std::fs::write(path, bytes)
+    }
+
+    /// Reads a signature for `spec` written by [`Signature::write_to_file`], of whatever tree
+    /// height its length implies.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`std::io::ErrorKind::InvalidData`] if the file holds another
+    /// kind of payload or another format version, or does not hold a signature for `spec`.
+    pub fn read_from_file(path: impl AsRef<std::path::Path>, spec: &Spec) -> std::io::Result<Self> {
+        let bytes = std::fs::read(path)?;
+        let mut payload = bytes.as_slice();
+        wire::read_envelope(
+            &mut payload,
+            wire::PayloadKind::Signature,
+            SIGNATURE_FORMAT_VERSION,
+        )?;
+        let invalid = |err| std::io::Error::new(std::io::ErrorKind::InvalidData, err);
+        let height = Self::height_for_encoded_len(spec, payload.len()).ok_or_else(|| {
+            invalid(DecodeError::InvalidLength {
+                expected: Self::encoded_len(spec, 0),
+                actual: payload.len(),
+            })
+        })?;
+        Self::from_bytes(payload, spec, height).map_err(invalid)
+    }
+}
+
+/// The format version of the files written by [`SignerState::write_to_file`], bumped whenever
+/// the layout of [`SignerState`] changes.
+#[cfg(feature = "persistence")]
+pub const SIGNER_STATE_FORMAT_VERSION: u16 = 2;
+
+/// The generator a [`SignerState`] derives the keys from its seed with.
+///
+/// Recreating a signer must derive the same keys in every build, so the generator is recorded
+/// with the seed rather than left to whatever [`StdRng`] is at the time.
+#[cfg(feature = "persistence")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum KeyRng {
+    /// ChaCha20 from `rand_chacha`, drawing the keys from stream 0 and the nonces from stream 1
+    #[default]
+    ChaCha20,
+}
+
+/// What it takes to recreate a [`Signer`] in a later run: the seed its keys are derived from,
+/// and the first epoch it has not signed at, so that no epoch is signed at twice.
+///
+/// The seed determines the secret keys, so the state must be kept as secret as they are.
+#[cfg(feature = "persistence")]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignerState {
+    /// The spec of the signer
+    pub spec: Spec,
+    /// The number of epochs of the signer
+    pub lifetime: u64,
+    /// The generator the keys and nonces are drawn from
+    pub key_rng: KeyRng,
+    /// The seed of `key_rng`
+    pub seed: [u8; 32],
+    /// The first epoch the signer has not signed at
+    pub next_epoch: u64,
+}
+
+#[cfg(feature = "persistence")]
+impl SignerState {
+    /// The state of a signer that has not signed yet
+    pub fn new(spec: Spec, lifetime: usize, seed: [u8; 32]) -> Self {
+        Self {
+            spec,
+            lifetime: lifetime as u64,
+            key_rng: KeyRng::default(),
+            seed,
+            next_epoch: 0,
+        }
+    }
+
+    /// Recreates the signer, deriving all its keys again, with the suggested number of retries
+    /// of the spec
+    ///
+    /// See [`Signer::new`] for the errors.
+    pub fn signer(&self) -> Result<Signer, SignerError> {
+        use rand::SeedableRng as _;
+        use rand_chacha::ChaCha20Rng;
+
+        let (mut key_rng, mut nonce_rng) = match self.key_rng {
+            KeyRng::ChaCha20 => (
+                ChaCha20Rng::from_seed(self.seed),
+                ChaCha20Rng::from_seed(self.seed),
+            ),
+        };
+        nonce_rng.set_stream(1);
+        Signer::new_with_key_rng(
+            &mut key_rng,
+            StdRng::from_rng(&mut nonce_rng),
+            self.spec.suggested_max_retries(),
+            self.spec.clone(),
+            self.lifetime as usize,
+        )
+    }
+
+    /// Records that the signer is about to sign at `epoch`, so that it never signs at `epoch`
+    /// or an earlier epoch again
+    ///
+    /// Returns `false`, leaving the state as is, if `epoch` is before [`SignerState::next_epoch`]
+    /// or past the lifetime.
+    pub fn consume(&mut self, epoch: u64) -> bool {
+        if epoch < self.next_epoch || epoch >= self.lifetime {
+            return false;
+        }
+        self.next_epoch = epoch + 1;
+        true
+    }
+
+    /// Writes the state to `path` with bincode, after the envelope of a
+    /// [`wire::PayloadKind::SignerState`] in [`SIGNER_STATE_FORMAT_VERSION`].
+    pub fn write_to_file(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        wire::write_envelope(
+            &mut file,
+            wire::PayloadKind::SignerState,
+            SIGNER_STATE_FORMAT_VERSION,
+        )?;
+        bincode::serialize_into(file, self).map_err(std::io::Error::other)
+    }
+
+    /// Reads a state written by [`SignerState::write_to_file`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`std::io::ErrorKind::InvalidData`] if the file holds another
+    /// kind of payload or another format version, see [`wire::read_envelope`].
+    pub fn read_from_file(path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        let mut file = std::io::BufReader::new(std::fs::File::open(path)?);
+        wire::read_envelope(
+            &mut file,
+            wire::PayloadKind::SignerState,
+            SIGNER_STATE_FORMAT_VERSION,
+        )?;
+        bincode::deserialize_from(file).map_err(std::io::Error::other)
+    }
+}
+
+/// Splits `bytes` into hashes, ignoring a trailing partial hash.
+fn decode_hashes(bytes: &[u8]) -> Vec<Hash> {
+    (bytes.as_chunks::<HASH_LEN>().0.iter())
+        .map(|chunk| Hash(*chunk))
+        .collect()
+}
+
+/// The reasons a fixed-layout byte encoding such as [`Signature::to_bytes`], or a
+/// [`compressed::CompressedAggregate`], can be rejected.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The bytes do not have the length the layout requires.
+    InvalidLength { expected: usize, actual: usize },
+    /// A compressed aggregate refers to a param past the end of its table.
+    ParamIndexOutOfRange { index: u32, num_params: usize },
+    /// A compressed aggregate has a different number of param indices than params it left out.
+    ParamCountMismatch { expected: usize, actual: usize },
+    /// A byte that encodes a boolean is neither 0 nor 1.
+    InvalidBool(u8),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::InvalidLength { expected, actual } => {
+                write!(f, "expected {expected} bytes, got {actual}")
+            }
+            DecodeError::ParamIndexOutOfRange { index, num_params } => {
+                write!(
+                    f,
+                    "param index {index} is out of range for {num_params} params"
+                )
+            }
+            DecodeError::ParamCountMismatch { expected, actual } => {
+                write!(f, "expected {expected} param indices, got {actual}")
+            }
+            DecodeError::InvalidBool(byte) => write!(f, "expected a boolean byte, got {byte}"),
+        }
+    }
+}
+
+impl core::error::Error for DecodeError {}
+
+/// The reasons a [`Signer`] can fail to be constructed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SignerError {
+    /// The spec violates one of its invariants.
+    InvalidSpec(SpecError),
+    /// The lifetime is not one a tree can be built for.
+    InvalidLifetime {
+        got: usize,
+        reason: InvalidLifetimeReason,
+    },
+    /// The tree height exceeds [`MAX_TREE_HEIGHT`].
+    InvalidTreeHeight { height: u32 },
+    /// The given param is not `spec.param_len` bytes long.
+    ParamLength { expected: usize, actual: usize },
+}
+
+/// The largest height of the tree of a [`Signer`], at which every leaf index still fits in the
+/// `u32` of a [`HashTreeProof`].
+pub const MAX_TREE_HEIGHT: u32 = 32;
+
+/// Why a lifetime is rejected, see [`SignerError::InvalidLifetime`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InvalidLifetimeReason {
+    /// A signer must hold at least one key pair.
+    Zero,
+    /// The tree, padded to a power of two of leaves, would be higher than [`MAX_TREE_HEIGHT`].
+    TooLarge,
+}
+
+impl fmt::Display for InvalidLifetimeReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvalidLifetimeReason::Zero => write!(f, "must be non-zero"),
+            InvalidLifetimeReason::TooLarge => {
+                write!(f, "must be at most 2^{MAX_TREE_HEIGHT} epochs")
+            }
+        }
+    }
+}
+
+impl fmt::Display for SignerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignerError::InvalidSpec(err) => write!(f, "invalid spec: {err}"),
+            SignerError::InvalidLifetime { got, reason } => {
+                write!(f, "lifetime {got} {reason}")
+            }
+            SignerError::InvalidTreeHeight { height } => {
+                write!(f, "tree height {height} exceeds {MAX_TREE_HEIGHT}")
+            }
+            SignerError::ParamLength { expected, actual } => {
+                write!(f, "param of {actual} bytes instead of {expected}")
+            }
+        }
+    }
+}
+
+impl core::error::Error for SignerError {}
+
+impl From<SpecError> for SignerError {
+    fn from(err: SpecError) -> Self {
+        SignerError::InvalidSpec(err)
+    }
+}
+
+/// The reasons a [`Signer`] can fail to sign.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SignError {
+    /// The epoch is past the lifetime of the signer, which has no key for it.
+    EpochOutOfRange { epoch: usize, lifetime: usize },
+    /// The epoch was already signed at, or skipped, by a signer that signs its epochs in order,
+    /// see [`Signer::with_traversal`].
+    EpochConsumed { epoch: usize, next_epoch: usize },
+    /// The epoch is after the next one of a signer that signs its epochs in order, see
+    /// [`Signer::with_traversal`].
+    EpochNotNext { epoch: usize, next_epoch: usize },
+    /// No nonce within the maximum number of retries gives an acceptable codeword.
+    GrindingFailed { max_retries: usize },
+}
+
+impl fmt::Display for SignError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignError::EpochOutOfRange { epoch, lifetime } => {
+                write!(f, "epoch {epoch} is past the lifetime of {lifetime} epochs")
+            }
+            SignError::EpochConsumed { epoch, next_epoch } => {
+                write!(
+                    f,
+                    "epoch {epoch} was already consumed, the next epoch is {next_epoch}"
+                )
+            }
+            SignError::EpochNotNext { epoch, next_epoch } => {
+                write!(f, "epoch {epoch} is not the next epoch {next_epoch}")
+            }
+            SignError::GrindingFailed { max_retries } => {
+                write!(f, "no acceptable nonce within {max_retries} retries")
+            }
+        }
+    }
+}
+
+impl core::error::Error for SignError {}
+
+/// Where a [`Signer`] takes the authentication paths of its signatures from.
+#[cfg(feature = "std")]
+enum AuthPaths<H: TweakableHash> {
+    /// Any epoch, from the whole (possibly compacted) tree.
+    Tree(HashTree<H>),
+    /// Only the next epoch, see [`Signer::with_traversal`].
+    Traversal(TraversalState<H>),
+}
+
+/// The number of key pairs [`Signer::new_with_progress`] derives between two progress reports.
+#[cfg(feature = "std")]
+pub const KEYGEN_PROGRESS_INTERVAL: usize = 256;
+
+/// What key generation is busy with, see [`KeygenProgress`].
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeygenPhase {
+    /// Deriving the one-time key pair of every epoch, which takes most of the time.
+    DeriveKeys,
+    /// Hashing the public keys and building the hash tree over them.
+    BuildTree,
+}
+
+/// A progress report of [`Signer::new_with_progress`].
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeygenProgress {
+    /// The number of epochs whose key pair is derived.
+    pub epochs_done: usize,
+    /// The lifetime of the signer.
+    pub epochs_total: usize,
+    /// What key generation is busy with.
+    pub phase: KeygenPhase,
+}
+
+/// An XMSS signer, generic over the tweakable hash backend.
+#[cfg(feature = "std")]
+pub struct Signer<H: TweakableHash = KeccakBackend> {
     rng: StdRng,
     max_retries: usize,
+    grind_strategy: GrindStrategy,
     /// The specification defining the signature scheme parameters (chain length, dimensions, etc.)
     pub spec: Spec,
     /// The public parameter shared across all signatures from this signer
     pub param: Param,
-    hash_tree: HashTree,
-    key_pairs: Vec<(Sk, Pk)>,
+    hasher: H,
+    auth_paths: AuthPaths<H>,
+    key_pairs: Arc<[(Sk, Pk)]>,
+    last_grind_attempts: Option<usize>,
+    record_sum_histogram: bool,
+    last_sum_histogram: Option<SumHistogram>,
     /// The root hash of the XMSS Merkle tree, serving as the public commitment to all one-time keys
     pub root: Hash,
 }
 
+#[cfg(feature = "std")]
 impl Signer {
-    /// Create a new XMSS signer with multiple one-time key pairs
+    /// Create a new XMSS signer with multiple one-time key pairs, using Keccak-256
+    ///
+    /// See [`Signer::new_with_backend`] for the arguments and errors.
+    pub fn new(
+        rng: StdRng,
+        max_retries: usize,
+        spec: Spec,
+        lifetime: usize,
+    ) -> Result<Self, SignerError> {
+        Self::new_with_backend(rng, max_retries, spec, lifetime)
+    }
+
+    /// Same as [`Signer::new`], but reports the progress of key generation to `progress`
+    ///
+    /// See [`Signer::new_with_progress_and_backend`] for when it is reported.
+    pub fn new_with_progress(
+        rng: StdRng,
+        max_retries: usize,
+        spec: Spec,
+        lifetime: usize,
+        progress: impl FnMut(KeygenProgress),
+    ) -> Result<Self, SignerError> {
+        Self::new_with_progress_and_backend(rng, max_retries, spec, lifetime, progress)
+    }
+
+    /// Create a new XMSS signer with the given public parameter, using Keccak-256
+    ///
+    /// See [`Signer::new_with_param_and_backend`] for the arguments and errors.
+    pub fn new_with_param(
+        rng: StdRng,
+        max_retries: usize,
+        spec: Spec,
+        lifetime: usize,
+        param: Param,
+    ) -> Result<Self, SignerError> {
+        Self::new_with_param_and_backend(rng, max_retries, spec, lifetime, param)
+    }
+
+    /// Create a new XMSS signer with its param and keys drawn from `key_rng`, using Keccak-256
+    ///
+    /// See [`Signer::new_with_key_rng_and_backend`] for the arguments and errors.
+    pub fn new_with_key_rng(
+        key_rng: &mut impl RngCore,
+        rng: StdRng,
+        max_retries: usize,
+        spec: Spec,
+        lifetime: usize,
+    ) -> Result<Self, SignerError> {
+        Self::new_with_key_rng_and_backend(key_rng, rng, max_retries, spec, lifetime)
+    }
+
+    /// Create a new XMSS signer with a tree of `height`, so a lifetime of `2^height` epochs,
+    /// using Keccak-256
+    ///
+    /// See [`Signer::with_tree_height_and_backend`] for the arguments and errors.
+    pub fn with_tree_height(
+        rng: StdRng,
+        max_retries: usize,
+        spec: Spec,
+        height: u32,
+    ) -> Result<Self, SignerError> {
+        Self::with_tree_height_and_backend(rng, max_retries, spec, height)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<H: TweakableHash> Signer<H> {
+    /// Create a new XMSS signer with multiple one-time key pairs, using the hash backend `H`
     ///
     /// # Arguments
     /// * `rng` - Random number generator for key generation
     /// * `max_retries` - Maximum attempts to find a valid signature (for grinding the nonce)
     /// * `spec` - The specification defining the signature scheme parameters
-    /// * `lifetime` - Number of one-time signatures this signer can produce (number of epochs)
+    /// * `lifetime` - Number of one-time signatures this signer can produce (number of epochs),
+    ///   at most `2^MAX_TREE_HEIGHT`
     ///
     /// # Returns
     /// A new `Signer` with `lifetime` key pairs and a Merkle tree commitment
-    pub fn new(mut rng: StdRng, max_retries: usize, spec: Spec, lifetime: usize) -> Self {
+    ///
+    /// # Errors
+    /// Returns an error if the spec or `lifetime` is invalid.
+    pub fn new_with_backend(
+        mut rng: StdRng,
+        max_retries: usize,
+        spec: Spec,
+        lifetime: usize,
+    ) -> Result<Self, SignerError> {
+        spec.validate()?;
+        let param = Param::random(spec.param_len, &mut rng);
+        Self::new_with_param_and_backend(rng, max_retries, spec, lifetime, param)
+    }
+
+    /// Same as [`Signer::new_with_backend`], but with a tree of `height`, so a lifetime of
+    /// `2^height` epochs
+    ///
+    /// # Errors
+    /// Returns an error if the spec is invalid or `height` exceeds [`MAX_TREE_HEIGHT`].
+    pub fn with_tree_height_and_backend(
+        rng: StdRng,
+        max_retries: usize,
+        spec: Spec,
+        height: u32,
+    ) -> Result<Self, SignerError> {
+        let lifetime = (height <= MAX_TREE_HEIGHT)
+            .then(|| 1usize.checked_shl(height))
+            .flatten()
+            .ok_or(SignerError::InvalidTreeHeight { height })?;
+        Self::new_with_backend(rng, max_retries, spec, lifetime)
+    }
+
+    /// Same as [`Signer::new_with_backend`], but reports the progress of key generation to
+    /// `progress`
+    ///
+    /// Progress is reported every [`KEYGEN_PROGRESS_INTERVAL`] derived key pairs and after the
+    /// last one in [`KeygenPhase::DeriveKeys`], then once in [`KeygenPhase::BuildTree`]. The
+    /// number of epochs done never decreases, and reaches `lifetime`. For lifetimes of `2^16`
+    /// and more, this tells operators that key generation is still going.
+    pub fn new_with_progress_and_backend(
+        mut rng: StdRng,
+        max_retries: usize,
+        spec: Spec,
+        lifetime: usize,
+        progress: impl FnMut(KeygenProgress),
+    ) -> Result<Self, SignerError> {
+        spec.validate()?;
         let param = Param::random(spec.param_len, &mut rng);
+        Self::new_impl(rng, None, max_retries, spec, lifetime, param, progress)
+    }
+
+    /// Same as [`Signer::new_with_backend`], but with the given public parameter instead of one
+    /// drawn from `rng`
+    ///
+    /// This lets several signers share a parameter, e.g. to deduplicate it in an aggregate.
+    ///
+    /// # Errors
+    /// Returns an error if the spec or `lifetime` is invalid, or `param` is not
+    /// `spec.param_len` bytes long.
+    pub fn new_with_param_and_backend(
+        rng: StdRng,
+        max_retries: usize,
+        spec: Spec,
+        lifetime: usize,
+        param: Param,
+    ) -> Result<Self, SignerError> {
+        Self::new_impl(rng, None, max_retries, spec, lifetime, param, |_| {})
+    }
+
+    /// Same as [`Signer::new_with_backend`], but draws the param and the secret keys from
+    /// `key_rng`, leaving `rng` to the nonces
+    ///
+    /// The algorithm behind [`StdRng`] may change between releases of `rand`, so keys that must
+    /// be derived again from a seed, as by [`SignerState::signer`], should come from a generator
+    /// that pins its algorithm, such as `rand_chacha::ChaCha20Rng`.
+    ///
+    /// # Errors
+    /// Returns an error if the spec or `lifetime` is invalid.
+    pub fn new_with_key_rng_and_backend(
+        key_rng: &mut impl RngCore,
+        rng: StdRng,
+        max_retries: usize,
+        spec: Spec,
+        lifetime: usize,
+    ) -> Result<Self, SignerError> {
+        spec.validate()?;
+        let param = Param::random(spec.param_len, key_rng);
+        Self::new_impl(
+            rng,
+            Some(key_rng),
+            max_retries,
+            spec,
+            lifetime,
+            param,
+            |_| {},
+        )
+    }
+
+    /// Derives the keys from `key_rng`, or from `rng` if there is none.
+    fn new_impl(
+        mut rng: StdRng,
+        key_rng: Option<&mut dyn RngCore>,
+        max_retries: usize,
+        spec: Spec,
+        lifetime: usize,
+        param: Param,
+        mut progress: impl FnMut(KeygenProgress),
+    ) -> Result<Self, SignerError> {
+        spec.validate()?;
+        let invalid_lifetime = |reason| SignerError::InvalidLifetime {
+            got: lifetime,
+            reason,
+        };
+        if lifetime == 0 {
+            return Err(invalid_lifetime(InvalidLifetimeReason::Zero));
+        }
+        if lifetime as u64 > 1 << MAX_TREE_HEIGHT {
+            return Err(invalid_lifetime(InvalidLifetimeReason::TooLarge));
+        }
+        if param.as_ref().len() != spec.param_len {
+            return Err(SignerError::ParamLength {
+                expected: spec.param_len,
+                actual: param.as_ref().len(),
+            });
+        }
 
+        let hasher = H::new(&param);
+        let key_rng: &mut dyn RngCore = match key_rng {
+            Some(key_rng) => key_rng,
+            None => &mut rng,
+        };
+
+        let report = |epochs_done, phase| KeygenProgress {
+            epochs_done,
+            epochs_total: lifetime,
+            phase,
+        };
         let mut key_pairs = Vec::new();
-        for _ in 0..lifetime {
-            let sk = Sk::random(&mut rng, param.clone(), &spec);
-            let pk = Pk::derive(&sk, &spec);
+        for epoch in 0..lifetime {
+            let sk = Sk::random(key_rng, param.clone(), &spec);
+            let pk = Pk::derive_with_backend(&sk, &spec, &hasher, epoch as u32);
             key_pairs.push((sk, pk));
+            let epochs_done = epoch + 1;
+            if epochs_done % KEYGEN_PROGRESS_INTERVAL == 0 || epochs_done == lifetime {
+                progress(report(epochs_done, KeygenPhase::DeriveKeys));
+            }
         }
+        progress(report(lifetime, KeygenPhase::BuildTree));
 
+        #[cfg(feature = "parallel")]
+        let pub_key_hashes: Vec<_> = key_pairs
+            .par_iter()
+            .enumerate()
+            .map(|(epoch, (_, pk))| hasher.hash_public_key(epoch as u32, pk))
+            .collect();
+        #[cfg(not(feature = "parallel"))]
         let pub_key_hashes: Vec<_> = key_pairs
             .iter()
-            .map(|(_, pk)| tweak_public_key_hash(&param, pk))
+            .enumerate()
+            .map(|(epoch, (_, pk))| hasher.hash_public_key(epoch as u32, pk))
             .collect();
 
-        let hash_tree = HashTree::new(&param, pub_key_hashes);
+        let hash_tree = HashTree::new_with_backend(&param, pub_key_hashes);
         let root = hash_tree.root;
 
-        Self {
+        Ok(Self {
             rng,
             max_retries,
+            grind_strategy: GrindStrategy::default(),
             spec,
-            hash_tree,
-            key_pairs,
+            hasher,
+            auth_paths: AuthPaths::Tree(hash_tree),
+            key_pairs: key_pairs.into(),
+            last_grind_attempts: None,
+            record_sum_histogram: false,
+            last_sum_histogram: None,
             param,
             root,
+        })
+    }
+
+    /// Retains only the leaves and the top `cached_levels` levels of the hash tree.
+    ///
+    /// This trades memory for signing time, see [`HashTree::new_compact`]. For a lifetime of
+    /// `2^20` the full tree holds about 64 MB of hashes.
+    ///
+    /// Has no effect after [`Signer::with_traversal`], which keeps no tree at all.
+    pub fn with_cached_tree_levels(mut self, cached_levels: usize) -> Self {
+        if let AuthPaths::Tree(hash_tree) = &mut self.auth_paths {
+            hash_tree.compact(cached_levels);
         }
+        self
     }
 
-    /// Sign a message using the key at the given epoch
+    /// Drops the hash tree and computes the authentication path of each epoch on the fly instead,
+    /// see [`TraversalState`].
     ///
-    /// Returns None if the signer could not produce a Signature
-    pub fn sign(&mut self, epoch: usize, message: &Message) -> Option<Signature> {
-        assert!(
-            epoch < self.key_pairs.len(),
-            "epoch must be less than the total number of keys"
-        );
-        let (sk, pk) = &self.key_pairs[epoch];
+    /// This keeps `O(height^2)` hashes instead of `O(lifetime)`, at the cost of `height` public
+    /// key hashes per signature. Epochs must then be signed strictly in order, starting from 0:
+    /// [`Signer::sign`] returns [`SignError::EpochConsumed`] or [`SignError::EpochNotNext`] for
+    /// any other epoch.
+    pub fn with_traversal(mut self) -> Self
+    where
+        H: 'static,
+    {
+        let height = self.lifetime().next_power_of_two().trailing_zeros() as usize;
+        let (hasher, key_pairs) = (self.hasher.clone(), self.key_pairs.clone());
+        let leaf = Box::new(move |index: usize| match key_pairs.get(index) {
+            Some((_, pk)) => hasher.hash_public_key(index as u32, pk),
+            None => hash_tree::PADDING_LEAF,
+        });
+        let traversal = TraversalState::new_with_backend(&self.param, height, leaf);
+        debug_assert_eq!(traversal.root(), self.root);
+        self.auth_paths = AuthPaths::Traversal(traversal);
+        self
+    }
 
-        let (codeword, nonce) = code::grind(
-            &self.spec,
-            self.max_retries,
-            &sk.param,
-            message,
-            &mut self.rng,
-        )?;
-        assert_eq!(codeword.dimension(), self.spec.dimension());
+    /// Sets how nonces are searched for when signing.
+    pub fn with_grind_strategy(mut self, grind_strategy: GrindStrategy) -> Self {
+        self.grind_strategy = grind_strategy;
+        self
+    }
 
-        let start_hashes = sk.start_hashes.iter();
-        let coords = codeword.coords().iter().map(|&coords| coords as usize);
-        let hashes = start_hashes
-            .zip(coords)
-            .enumerate()
-            .map(|(chain_index, (start_hash, start_pos))| {
-                hash_chain(&sk.param, chain_index, *start_hash, 0, start_pos)
-            })
-            .collect();
+    /// Sets whether [`Signer::sign`] keeps a histogram of the codeword sums it tried, see
+    /// [`Signer::last_sum_histogram`].
+    ///
+    /// Histograms are only recorded with [`GrindStrategy::Serial`].
+    pub fn with_sum_histogram(mut self, record: bool) -> Self {
+        self.record_sum_histogram = record;
+        self
+    }
 
-        let signature = OtsSignature { nonce, hashes };
-        let hash_tree_proof = self.hash_tree.get_proof(epoch);
-        let public_key = pk.clone();
+    /// The memory held by the keys and the tree of this signer.
+    ///
+    /// The keys take `lifetime * 2 * dimension` hashes, i.e. about 19 MB for a lifetime of `2^13`
+    /// under [`spec::SPEC_2`], against less than 1 MB for the full tree.
+    pub fn memory_footprint(&self) -> SignerFootprint {
+        let (secret_bytes, public_key_bytes) = (self.key_pairs.iter())
+            .map(|(sk, pk)| (sk.memory_footprint(), pk.memory_footprint()))
+            .fold((0, 0), |(secret, public), (sk, pk)| {
+                (secret + sk, public + pk)
+            });
+        let tree_bytes = match &self.auth_paths {
+            AuthPaths::Tree(hash_tree) => hash_tree.memory_footprint(),
+            AuthPaths::Traversal(traversal) => traversal.memory_footprint(),
+        };
+        SignerFootprint {
+            secret_bytes,
+            public_key_bytes,
+            tree_bytes,
+        }
+    }
 
-        Some(Signature {
-            signature,
-            hash_tree_proof,
-            public_key,
-        })
+    /// The number of epochs this signer can sign.
+    pub fn lifetime(&self) -> usize {
+        self.key_pairs.len()
     }
-}
 
-/// Verify an XMSS signature with HashTree proof
-///
-/// The verification procedure consists of two main steps:
-///
+    /// Sign a message using the key at the given epoch
+    ///
+    /// # Errors
+    /// Returns an error if the epoch is past the lifetime of the signer, if it is not the next
+    /// epoch of a signer that signs in order, or if grinding gives up.
+    pub fn sign(&mut self, epoch: usize, message: &Message) -> Result<Signature, SignError> {
+        self.sign_impl(epoch, message, None)
+    }
+
+    /// Walks every chain of the key at `epoch` once, so that signing with it needs no hashing
+    /// beyond grinding, see [`Signer::sign_with_cache`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `epoch` is past the lifetime of the signer.
+    pub fn chain_cache(&self, epoch: usize) -> ChainCache {
+        let (sk, _) = &self.key_pairs[epoch];
+        ChainCache::new_with_backend(
+            &self.hasher,
+            epoch as u32,
+            &sk.start_hashes,
+            self.spec.chain_len(),
+        )
+    }
+
+    /// Same as [`Signer::sign`], but looks the signature hashes up in `cache` instead of walking
+    /// the chains.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cache` was not built for the key at `epoch`.
+    pub fn sign_with_cache(
+        &mut self,
+        epoch: usize,
+        message: &Message,
+        cache: &ChainCache,
+    ) -> Result<Signature, SignError> {
+        self.sign_impl(epoch, message, Some(cache))
+    }
+
+    fn sign_impl(
+        &mut self,
+        epoch: usize,
+        message: &Message,
+        cache: Option<&ChainCache>,
+    ) -> Result<Signature, SignError> {
+        let (sk, pk) = self
+            .key_pairs
+            .get(epoch)
+            .ok_or(SignError::EpochOutOfRange {
+                epoch,
+                lifetime: self.key_pairs.len(),
+            })?;
+        if let AuthPaths::Traversal(traversal) = &self.auth_paths {
+            let next_epoch = traversal.next_epoch();
+            if epoch < next_epoch {
+                return Err(SignError::EpochConsumed { epoch, next_epoch });
+            }
+            if epoch > next_epoch {
+                return Err(SignError::EpochNotNext { epoch, next_epoch });
+            }
+        }
+        if let Some(cache) = cache {
+            assert!(
+                cache.end_hashes().eq(pk.end_hashes.iter().copied()),
+                "chain cache does not belong to the key at this epoch"
+            );
+        }
+
+        // Lifetimes are at most `2^MAX_TREE_HEIGHT`, so every epoch fits.
+        let hash_epoch = epoch as u32;
+        let grind_result = match (self.record_sum_histogram, self.grind_strategy) {
+            (true, GrindStrategy::Serial) => {
+                let mut histogram = SumHistogram::new(&self.spec);
+                let grind_result = code::grind_observed(
+                    &self.spec,
+                    self.max_retries,
+                    &self.hasher,
+                    hash_epoch,
+                    message,
+                    &mut self.rng,
+                    |codeword| histogram.record(codeword.sum()),
+                );
+                self.last_sum_histogram = Some(histogram);
+                grind_result
+            }
+            _ => self.grind_strategy.grind(
+                &self.spec,
+                self.max_retries,
+                &self.hasher,
+                hash_epoch,
+                message,
+                &mut self.rng,
+            ),
+        };
+        self.last_grind_attempts = Some(match &grind_result {
+            Some(result) => result.attempts,
+            None => self.max_retries,
+        });
+        let code::GrindResult {
+            codeword, nonce, ..
+        } = grind_result.ok_or(SignError::GrindingFailed {
+            max_retries: self.max_retries,
+        })?;
+        assert_eq!(codeword.dimension(), self.spec.dimension());
+
+        let hasher = &self.hasher;
+        let walk = |(chain_index, (start_hash, &coord)): (usize, (&Hash, &u8))| match cache {
+            Some(cache) => cache.get(chain_index, coord as usize),
+            None => hash_chain_with(
+                hasher,
+                hash_epoch,
+                chain_index as u32,
+                *start_hash,
+                0,
+                coord as u32,
+            ),
+        };
+        #[cfg(feature = "parallel")]
+        let hashes = (sk.start_hashes.par_iter())
+            .zip(codeword.coords().par_iter())
+            .enumerate()
+            .map(walk)
+            .collect();
+        #[cfg(not(feature = "parallel"))]
+        let hashes = (sk.start_hashes.iter())
+            .zip(codeword.coords())
+            .enumerate()
+            .map(walk)
+            .collect();
+
+        let signature = OtsSignature { nonce, hashes };
+        let hash_tree_proof = match &mut self.auth_paths {
+            AuthPaths::Tree(hash_tree) => {
+                (hash_tree.get_proof(epoch)).expect("the hash tree has a leaf for every key pair")
+            }
+            AuthPaths::Traversal(traversal) => {
+                HashTreeProof::new(epoch, traversal.auth_path_for_next_epoch())
+            }
+        };
+        let public_key = pk.clone();
+
+        Ok(Signature {
+            signature,
+            hash_tree_proof,
+            public_key,
+        })
+    }
+
+    /// Returns the number of nonces tried by the most recent call to [`Signer::sign`].
+    ///
+    /// This is `max_retries` if that call gave up, and `None` if nothing has been signed yet.
+    pub fn last_grind_attempts(&self) -> Option<usize> {
+        self.last_grind_attempts
+    }
+
+    /// Returns the histogram of codeword sums tried by the most recent call to [`Signer::sign`].
+    ///
+    /// This is `None` unless enabled through [`Signer::with_sum_histogram`]. The histogram is
+    /// kept even if grinding gave up.
+    pub fn last_sum_histogram(&self) -> Option<&SumHistogram> {
+        self.last_sum_histogram.as_ref()
+    }
+}
+
+/// Verify an XMSS signature with HashTree proof
+///
+/// The verification procedure consists of two main steps:
+///
 /// 1. **One-Time Signature (OTS) Verification**:
 ///    - Reconstruct the codeword from the message and nonce
 ///    - Use the codeword coordinates to determine positions in hash chains
@@ -238,7 +1301,8 @@ impl Signer {
 ///
 /// # Arguments
 /// * `spec` - The specification for the signature scheme
-/// * `param` - The parameter used by the signer
+/// * `param` - The parameter used by the signer, under which the chains and the tree are hashed
+///   whatever the public key in the signature claims
 /// * `message` - The message that was signed
 /// * `signature` - The XMSS signature with hash tree proof and public key
 /// * `root` - The root hash of the XMSS tree to verify against
@@ -252,63 +1316,274 @@ pub fn verify_signature(
     signature: &Signature,
     root: &Hash,
 ) -> bool {
-    // Use the public key from the signature for verification
+    verify_signature_with_backend::<KeccakBackend>(spec, param, message, signature, root)
+}
+
+/// Same as [`verify_signature`], but with the hash backend `H`
+///
+/// A signature produced under one backend never verifies under another.
+pub fn verify_signature_with_backend<H: TweakableHash>(
+    spec: &Spec,
+    param: &Param,
+    message: &Message,
+    signature: &Signature,
+    root: &Hash,
+) -> bool {
+    verify_signature_checked_with_backend::<H>(spec, param, message, signature, root).is_ok()
+}
+
+/// Same as [`verify_signature`], but returns the reason a signature is rejected
+pub fn verify_signature_checked(
+    spec: &Spec,
+    param: &Param,
+    message: &Message,
+    signature: &Signature,
+    root: &Hash,
+) -> Result<(), VerifyError> {
+    verify_signature_checked_with_backend::<KeccakBackend>(spec, param, message, signature, root)
+}
+
+/// Same as [`verify_signature_checked`], but with the hash backend `H`
+pub fn verify_signature_checked_with_backend<H: TweakableHash>(
+    spec: &Spec,
+    param: &Param,
+    message: &Message,
+    signature: &Signature,
+    root: &Hash,
+) -> Result<(), VerifyError> {
+    // Everything is hashed under `param`, whatever param the public key of the signature claims
+    let hasher = H::new(param);
+    let leaf_hash = verify_ots_part(&hasher, spec, message, signature)?;
+
+    // Step 2: Verify the Merkle tree proof
+    // This proves that the public key used above is part of the XMSS tree
+    if !cycle_tracker::track(cycle_tracker::MERKLE_PROOF, || {
+        (signature.hash_tree_proof).verify_with_backend(&hasher, &leaf_hash, root)
+    }) {
+        return Err(VerifyError::InvalidTreeProof);
+    }
+    Ok(())
+}
+
+/// Verify several signatures of one validator, e.g. of consecutive epochs when catching up
+///
+/// Each `(message, signature)` pair is checked as by [`verify_signature`], but the tree proofs
+/// are checked together by [`HashTreeProof::verify_batch`], so the ancestors the leaves share are
+/// hashed once instead of once per signature.
+///
+/// # Returns
+/// `true` if every signature is valid and `items` is not empty, `false` otherwise
+pub fn verify_signatures_same_root(
+    spec: &Spec,
+    param: &Param,
+    root: &Hash,
+    items: &[(Message, Signature)],
+) -> bool {
+    verify_signatures_same_root_with_backend::<KeccakBackend>(spec, param, root, items)
+}
+
+/// Same as [`verify_signatures_same_root`], but with the hash backend `H`
+pub fn verify_signatures_same_root_with_backend<H: TweakableHash>(
+    spec: &Spec,
+    param: &Param,
+    root: &Hash,
+    items: &[(Message, Signature)],
+) -> bool {
+    let hasher = H::new(param);
+    let leaves: Result<Vec<_>, VerifyError> = (items.iter())
+        .map(|(message, signature)| {
+            let leaf_hash = verify_ots_part(&hasher, spec, message, signature)?;
+            Ok((&signature.hash_tree_proof, leaf_hash))
+        })
+        .collect();
+    leaves.is_ok_and(|leaves| HashTreeProof::verify_batch_with_backend(&hasher, &leaves, root))
+}
+
+/// Verifies the one-time signature of `signature` on `message` and returns the tree leaf of its
+/// public key.
+fn verify_ots_part<H: TweakableHash>(
+    hasher: &H,
+    spec: &Spec,
+    message: &Message,
+    signature: &Signature,
+) -> Result<Hash, VerifyError> {
     let pk = &signature.public_key;
+    // The epoch is the leaf index, at most `2^MAX_TREE_HEIGHT - 1` for any proof that can verify.
+    let epoch = signature.hash_tree_proof.leaf_index() as u32;
 
     // Step 1: Verify the one-time signature
     // First, reconstruct the codeword from the message and nonce
-    let Some(codeword) = code::new_valid(spec, &pk.param, message, &signature.signature.nonce)
-    else {
+    let Some(codeword) = cycle_tracker::track(cycle_tracker::CODEWORD, || {
+        code::new_valid_with_backend(spec, hasher, epoch, message, &signature.signature.nonce)
+    }) else {
         // The message + nonce combination doesn't produce a valid codeword
         // This means the signature is invalid
-        return false;
+        return Err(VerifyError::InvalidCodeword);
     };
     assert_eq!(codeword.dimension(), spec.dimension());
 
+    cycle_tracker::track(cycle_tracker::HASH_CHAINS, || {
+        verify_ots_with_backend(
+            hasher,
+            spec,
+            epoch,
+            &codeword,
+            &signature.signature.hashes,
+            pk,
+        )
+    })?;
+    Ok(hasher.hash_public_key(epoch, pk))
+}
+
+/// Checks that walking every chain of the key of `epoch` from the signed hash at its codeword
+/// coordinate to the end yields the end hashes of `pk`.
+///
+/// Malformed input, i.e. a coordinate past the end of its chain or a number of hashes other than
+/// the dimension, is rejected before any hashing.
+fn verify_ots_with_backend<H: TweakableHash>(
+    hasher: &H,
+    spec: &Spec,
+    epoch: u32,
+    codeword: &Codeword,
+    hashes: &[Hash],
+    pk: &Pk,
+) -> Result<(), VerifyError> {
     // The codeword tells us positions in each hash chain
     // We need to complete the hash chains from those positions to the end
-    let chain_len = spec.chain_len();
-    let hashes = signature.signature.hashes.iter();
-    let coords = codeword.coords().iter().map(|&coord| coord as usize);
+    let chain_len = spec.chain_len() as u32;
+    if hashes.len() != spec.dimension()
+        || codeword.dimension() != spec.dimension()
+        || (codeword.coords().iter()).any(|&coord| u32::from(coord) >= chain_len)
+    {
+        return Err(VerifyError::MalformedSignature);
+    }
 
     // For each chain, compute from the given hash at position `hash_pos`
     // to the end of the chain (position chain_len - 1)
-    let end_hashes = hashes
-        .zip(coords)
+    let walk = |(chain_index, (hash, &coord)): (usize, (&Hash, &u8))| {
+        let hash_pos = u32::from(coord);
+        hash_chain_checked_with(
+            hasher,
+            epoch,
+            chain_index as u32,
+            *hash,
+            hash_pos,                 // Current position in chain
+            chain_len - 1 - hash_pos, // Steps remaining to end
+            chain_len,
+        )
+    };
+    #[cfg(feature = "parallel")]
+    let end_hashes: Result<Vec<Hash>, ChainWalkError> = (hashes.par_iter())
+        .zip(codeword.coords().par_iter())
         .enumerate()
-        .map(|(chain_index, (hash, hash_pos))| {
-            hash_chain(
-                &pk.param,
-                chain_index,
-                *hash,
-                hash_pos,                 // Current position in chain
-                chain_len - 1 - hash_pos, // Steps remaining to end
-            )
-        });
+        .map(walk)
+        .collect();
+    #[cfg(not(feature = "parallel"))]
+    let end_hashes: Result<Vec<Hash>, ChainWalkError> = (hashes.iter())
+        .zip(codeword.coords())
+        .enumerate()
+        .map(walk)
+        .collect();
 
     // Compare computed end hashes with the public key's end hashes
     // If they don't match, the OTS signature is invalid
-    if !end_hashes.eq(pk.end_hashes.iter().cloned()) {
-        return false;
+    match end_hashes {
+        Ok(end_hashes) if end_hashes == pk.end_hashes => Ok(()),
+        Ok(_) => Err(VerifyError::PublicKeyMismatch),
+        Err(_) => Err(VerifyError::MalformedSignature),
+    }
+}
+
+/// The reasons a signature can be rejected, see [`verify_signature_checked`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VerifyError {
+    /// The message and nonce do not encode to a valid codeword.
+    InvalidCodeword,
+    /// The one-time signature does not fit the spec, e.g. it has the wrong number of hashes.
+    MalformedSignature,
+    /// Completing the chains does not yield the end hashes of the public key.
+    PublicKeyMismatch,
+    /// The public key is not a leaf of the tree with the given root.
+    InvalidTreeProof,
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyError::InvalidCodeword => {
+                write!(f, "message and nonce do not encode to a valid codeword")
+            }
+            VerifyError::MalformedSignature => {
+                write!(f, "one-time signature does not fit the spec")
+            }
+            VerifyError::PublicKeyMismatch => {
+                write!(f, "chain ends do not match the public key")
+            }
+            VerifyError::InvalidTreeProof => {
+                write!(f, "public key is not in the tree with the given root")
+            }
+        }
     }
+}
 
-    // Step 2: Verify the Merkle tree proof
-    // This proves that the public key used above is part of the XMSS tree
-    let leaf_hash = tweak_public_key_hash(param, pk);
-    signature.hash_tree_proof.verify(param, &leaf_hash, root)
+impl core::error::Error for VerifyError {}
+
+/// A signature from either a single-tree [`Signer`] or a [`hypertree::HyperSigner`]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum AnySignature {
+    /// A signature from a single XMSS tree
+    Xmss(Signature),
+    /// A signature from a two-layer hypertree
+    Hyper(HyperSignature),
+}
+
+impl AnySignature {
+    /// The epoch the signature is for, which is the index of the leaf it authenticates
+    ///
+    /// For a hypertree signature this combines the leaf of the top tree, which selects the bottom
+    /// tree, with the leaf of the bottom tree.
+    pub fn leaf_index(&self) -> usize {
+        match self {
+            AnySignature::Xmss(signature) => signature.hash_tree_proof.leaf_index(),
+            AnySignature::Hyper(signature) => {
+                let bottom = &signature.bottom.hash_tree_proof;
+                (signature.top.hash_tree_proof.leaf_index() << bottom.height())
+                    | bottom.leaf_index()
+            }
+        }
+    }
+}
+
+impl From<Signature> for AnySignature {
+    fn from(signature: Signature) -> Self {
+        AnySignature::Xmss(signature)
+    }
+}
+
+impl From<HyperSignature> for AnySignature {
+    fn from(signature: HyperSignature) -> Self {
+        AnySignature::Hyper(signature)
+    }
 }
 
 /// A signature from a single validator
+///
+/// The epoch is a `u64` and the validator index a `u32`, so the signature serializes to the same
+/// bytes on the 32-bit zkVM guests as on the hosts.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ValidatorSignature {
     /// The epoch used for signing
-    pub epoch: usize,
-    /// The XMSS signature
-    pub signature: Signature,
+    pub epoch: u64,
+    /// The XMSS or hypertree signature
+    pub signature: AnySignature,
     /// The root hash this signature should verify against
     pub xmss_root: Hash,
     /// The parameter used by this validator
     pub param: Param,
+    /// The position of the validator in the roots the verifier was constructed with, if the
+    /// signature claims one
+    #[serde(default)]
+    pub validator_index: Option<u32>,
 }
 
 /// Aggregated signatures from multiple validators
@@ -323,158 +1598,2189 @@ impl AggregatedSignature {
     pub fn new(signatures: Vec<ValidatorSignature>) -> Self {
         Self { signatures }
     }
+
+    /// The roots of the signatures, sorted and without duplicates
+    pub fn distinct_roots(&self) -> Vec<Hash> {
+        let mut roots: Vec<_> = self.signatures.iter().map(|sig| sig.xmss_root).collect();
+        roots.sort_unstable();
+        roots.dedup();
+        roots
+    }
+
+    /// Keeps only the first signature under every root
+    pub fn dedup_roots(&mut self) {
+        let mut first_occurrences = first_occurrences(&self.signatures).into_iter();
+        self.signatures
+            .retain(|_| first_occurrences.next().expect("one flag per signature"));
+    }
+
+    /// Whether every signature claims `epoch` and authenticates the leaf of that epoch
+    ///
+    /// This does not verify the signatures, see [`AggregatedVerifier::verify_at_epoch`].
+    pub fn is_at_epoch(&self, epoch: u64) -> bool {
+        (self.signatures.iter())
+            .all(|sig| sig.epoch == epoch && sig.signature.leaf_index() as u64 == epoch)
+    }
+
+    /// Marks the validators of `0..num_validators` that a signature claims the index of
+    ///
+    /// This only reads the claimed indices, so it describes who signed once the aggregate has
+    /// been verified. Signatures without an index, or with one past `num_validators`, are not
+    /// marked.
+    pub fn participation_bitmap(&self, num_validators: usize) -> Vec<bool> {
+        let mut bitmap = vec![false; num_validators];
+        for index in self.signatures.iter().filter_map(|sig| sig.validator_index) {
+            if let Some(bit) = bitmap.get_mut(index as usize) {
+                *bit = true;
+            }
+        }
+        bitmap
+    }
 }
 
 /// A collection of validator root hashes for verification
-#[derive(Clone, Debug)]
-pub struct AggregatedVerifier {
-    /// List of registered validator roots
-    roots: Vec<Hash>,
+///
+/// The set can change between epochs through [`AggregatedVerifier::add_root`] and
+/// [`AggregatedVerifier::remove_root`], and [`AggregatedVerifier::snapshot`] freezes it for
+/// threads that keep verifying meanwhile.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct AggregatedVerifier<H: TweakableHash = KeccakBackend> {
+    /// The registered validators, whose params the signatures are verified under
+    registry: ValidatorRegistry,
     /// The specification for the signature scheme
     spec: Spec,
+    /// What an aggregate must satisfy beyond valid signatures
+    policy: VerifierPolicy,
+    #[serde(skip)]
+    _backend: PhantomData<fn() -> H>,
 }
 
 impl AggregatedVerifier {
-    /// Create a new validator roots collection with specification
-    pub fn new(roots: Vec<Hash>, spec: Spec) -> Self {
-        Self { roots, spec }
+    /// Create a new verifier for the validators of `registry` with specification
+    pub fn new(registry: ValidatorRegistry, spec: Spec) -> Self {
+        Self::new_with_backend(registry, spec)
     }
+}
 
-    /// Verify an aggregated signature from multiple validators
+impl<H: TweakableHash> AggregatedVerifier<H> {
+    /// Same as [`AggregatedVerifier::new`], but verifying with the hash backend `H`
+    pub fn new_with_backend(registry: ValidatorRegistry, spec: Spec) -> Self {
+        Self {
+            registry,
+            spec,
+            policy: VerifierPolicy::default(),
+            _backend: PhantomData,
+        }
+    }
+
+    /// Sets whether an aggregate holding several signatures under the same root is rejected
     ///
-    /// Returns `true` if all signatures are valid and from registered validators,
-    /// `false` otherwise
-    pub fn verify(&self, message: &Message, aggregated: &AggregatedSignature) -> bool {
-        aggregated.signatures.iter().all(|sig| {
-            // Check if this signature's root is in our validator set
-            self.roots.contains(&sig.xmss_root) &&
-                // Verify using the param from the ValidatorSignature
-                verify_signature(
-                    &self.spec,
-                    &sig.param,
-                    message,
-                    &sig.signature,
-                    &sig.xmss_root,
-                )
-        })
+    /// Without this, a valid signature repeated five times verifies like a single one, which
+    /// fools anything that counts the signatures of an aggregate. In strict mode every
+    /// occurrence of a root after the first is reported as
+    /// [`ValidatorVerification::DuplicateRoot`].
+    ///
+    /// This is a shorthand for [`VerifierPolicy::require_distinct_roots`].
+    pub fn with_strict_roots(mut self, strict: bool) -> Self {
+        self.policy.require_distinct_roots = strict;
+        self
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use rand::SeedableRng;
+    /// Sets what an aggregate must satisfy beyond valid signatures
+    pub fn with_policy(mut self, policy: VerifierPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
 
-    #[test]
-    fn test_xmss_verify() {
-        let spec = spec::SPEC_2;
-        let mut signer = Signer::new(StdRng::seed_from_u64(0), 1000000, spec.clone(), 8);
+    /// What an aggregate must satisfy beyond valid signatures
+    pub fn policy(&self) -> VerifierPolicy {
+        self.policy
+    }
 
-        // Get public verification parameters
-        let root = signer.root;
-        let param = signer.param.clone();
+    /// The registered validators
+    pub fn registry(&self) -> &ValidatorRegistry {
+        &self.registry
+    }
 
-        let message1 = Message([10; 32]);
-        let message2 = Message([20; 32]);
-        let bad_message = Message([30; 32]);
+    /// The registered roots in index order, see [`ValidatorSignature::validator_index`]
+    pub fn roots(&self) -> &[Hash] {
+        self.registry.roots()
+    }
 
-        let sig1 = signer
-            .sign(0, &message1)
-            .expect("Failed to sign with epoch 0");
-        let sig3 = signer
-            .sign(3, &message2)
-            .expect("Failed to sign with epoch 3");
+    /// The number of registered roots
+    pub fn len(&self) -> usize {
+        self.registry.len()
+    }
 
-        assert!(verify_signature(&spec, &param, &message1, &sig1, &root));
-        assert!(verify_signature(&spec, &param, &message2, &sig3, &root));
+    /// Whether no root is registered
+    pub fn is_empty(&self) -> bool {
+        self.registry.is_empty()
+    }
 
-        assert!(!verify_signature(&spec, &param, &bad_message, &sig1, &root));
-        assert!(!verify_signature(&spec, &param, &message2, &sig1, &root));
-        assert!(!verify_signature(&spec, &param, &message1, &sig3, &root));
+    /// Registers `root` with `param` and a weight of 1 at the next index, unless it is already
+    /// registered
+    ///
+    /// Returns whether the root was added.
+    pub fn add_root(&mut self, root: Hash, param: Param) -> bool {
+        self.add_weighted_root(root, param, 1)
     }
 
-    #[test]
-    fn test_aggregated_signatures() {
-        let spec = spec::SPEC_2;
+    /// Registers `root` with `param` and `weight` at the next index, unless it is already
+    /// registered
+    ///
+    /// Returns whether the root was added.
+    pub fn add_weighted_root(&mut self, root: Hash, param: Param, weight: u64) -> bool {
+        self.registry.insert(root, param, weight)
+    }
 
-        // Create multiple validators (each with their own param)
-        let mut validator1 = Signer::new(StdRng::seed_from_u64(1), 10000, spec.clone(), 4);
-        let mut validator2 = Signer::new(StdRng::seed_from_u64(2), 10000, spec.clone(), 4);
-        let mut validator3 = Signer::new(StdRng::seed_from_u64(3), 10000, spec.clone(), 4);
+    /// Unregisters `root`, so that its signatures no longer verify
+    ///
+    /// The indices of the roots registered after it move down by one. Returns whether the root
+    /// was registered.
+    pub fn remove_root(&mut self, root: &Hash) -> bool {
+        self.registry.remove(root)
+    }
 
-        // Register validator roots
-        let roots = vec![
-            validator1.root,
-            validator2.root,
-            validator3.root,
-        ];
+    /// Freezes the current set into a view that is cheap to clone and share between threads
+    ///
+    /// Later changes to this verifier do not affect the snapshot.
+    pub fn snapshot(&self) -> Arc<Self> {
+        Arc::new(self.clone())
+    }
 
-        // Create the validator roots collection for verification
-        let verifier = AggregatedVerifier::new(roots.clone(), spec.clone());
+    /// Whether `root` is a registered validator root
+    pub fn contains_root(&self, root: &Hash) -> bool {
+        self.registry.contains(root)
+    }
 
-        // Message to be signed by all validators
-        let message = Message([42; 32]);
+    /// The weight of `root`, or None if it is not registered
+    pub fn weight(&self, root: &Hash) -> Option<u64> {
+        self.registry.weight(root)
+    }
 
-        // Each validator signs the message
-        let sig1 = validator1.sign(0, &message).expect("Failed to sign");
-        let sig2 = validator2.sign(0, &message).expect("Failed to sign");
-        let sig3 = validator3.sign(0, &message).expect("Failed to sign");
+    /// Verify an aggregated signature from multiple validators
+    ///
+    /// Returns `true` if all signatures are valid and from registered validators, and there are
+    /// at least [`VerifierPolicy::min_signatures`] of them, `false` otherwise
+    ///
+    /// With the `parallel` feature the signatures are verified across threads, which stop soon
+    /// after one of them finds an invalid signature.
+    pub fn verify(&self, message: &Message, aggregated: &AggregatedSignature) -> bool {
+        if aggregated.signatures.len() < self.policy.min_signatures {
+            return false;
+        }
+        #[cfg(feature = "parallel")]
+        let all_valid = {
+            let first_occurrences = first_occurrences(&aggregated.signatures);
+            (aggregated.signatures.par_iter())
+                .zip(first_occurrences.par_iter())
+                .all(|(sig, &first)| {
+                    self.verify_occurrence(message, sig, first) == ValidatorVerification::Ok
+                })
+        };
+        #[cfg(not(feature = "parallel"))]
+        let all_valid = (self.verify_iter(message, &aggregated.signatures))
+            .all(|(_, result)| result == ValidatorVerification::Ok);
+        all_valid
+    }
 
-        // Create aggregated signature
-        let aggregated = AggregatedSignature::new(vec![
-            ValidatorSignature {
-                epoch: 0,
-                signature: sig1,
-                xmss_root: validator1.root,
-                param: validator1.param.clone(),
-            },
-            ValidatorSignature {
-                epoch: 0,
-                signature: sig2,
-                xmss_root: validator2.root,
-                param: validator2.param.clone(),
-            },
-            ValidatorSignature {
-                epoch: 0,
-                signature: sig3,
-                xmss_root: validator3.root,
-                param: validator3.param.clone(),
-            },
-        ]);
+    /// Verify the signatures one at a time as the iterator yields them, with the index of each
+    ///
+    /// Nothing is verified until the outcome of a signature is requested, so the signatures can
+    /// arrive incrementally, e.g. over the network, and the outcomes can be consumed past a
+    /// failure. Repeated roots are tracked as in [`AggregatedVerifier::verify`], but
+    /// [`VerifierPolicy::min_signatures`] is left to the caller.
+    pub fn verify_iter<'a>(
+        &'a self,
+        message: &'a Message,
+        signatures: impl IntoIterator<Item = &'a ValidatorSignature> + 'a,
+    ) -> impl Iterator<Item = (usize, ValidatorVerification)> + 'a {
+        let mut seen = Vec::new();
+        (signatures.into_iter().enumerate()).map(move |(index, sig)| {
+            let first = insert_root(&mut seen, sig.xmss_root);
+            (index, self.verify_occurrence(message, sig, first))
+        })
+    }
 
-        // Verify the aggregated signature (all should be valid)
-        assert!(verifier.verify(&message, &aggregated));
+    /// The first signature that does not verify, with its index and outcome, or None if all do
+    ///
+    /// See [`AggregatedVerifier::verify_iter`].
+    pub fn first_failure<'a>(
+        &'a self,
+        message: &'a Message,
+        signatures: impl IntoIterator<Item = &'a ValidatorSignature> + 'a,
+    ) -> Option<(usize, ValidatorVerification)> {
+        (self.verify_iter(message, signatures))
+            .find(|(_, result)| *result != ValidatorVerification::Ok)
+    }
 
-        // Test with only 2 signatures
-        let partial_aggregated = AggregatedSignature::new(vec![
-            ValidatorSignature {
-                epoch: 0,
-                signature: validator1.sign(1, &message).expect("Failed to sign"),
-                xmss_root: validator1.root,
-                param: validator1.param.clone(),
-            },
-            ValidatorSignature {
-                epoch: 0,
-                signature: validator2.sign(1, &message).expect("Failed to sign"),
-                xmss_root: validator2.root,
-                param: validator2.param.clone(),
-            },
-        ]);
+    /// Same as [`AggregatedVerifier::verify`], but also requires every signature to be for
+    /// `expected_epoch`, see [`AggregatedSignature::is_at_epoch`]
+    ///
+    /// [`AggregatedVerifier::verify`] does not look at the epochs, so validators that sign the
+    /// message at different epochs pass it.
+    pub fn verify_at_epoch(
+        &self,
+        message: &Message,
+        aggregated: &AggregatedSignature,
+        expected_epoch: u64,
+    ) -> bool {
+        aggregated.is_at_epoch(expected_epoch) && self.verify(message, aggregated)
+    }
 
-        // Both signatures should be valid
-        assert!(verifier.verify(&message, &partial_aggregated));
+    /// Verify every signature of an aggregate and report the outcome for each validator
+    ///
+    /// Unlike [`AggregatedVerifier::verify`], this does not stop at the first invalid signature,
+    /// and it leaves [`VerifierPolicy::min_signatures`] to the caller.
+    pub fn verify_detailed(
+        &self,
+        message: &Message,
+        aggregated: &AggregatedSignature,
+    ) -> VerificationReport {
+        let first_occurrences = first_occurrences(&aggregated.signatures);
+        let check = |(sig, &first): (&ValidatorSignature, &bool)| {
+            self.verify_occurrence(message, sig, first)
+        };
+        #[cfg(feature = "parallel")]
+        let results = (aggregated.signatures.par_iter())
+            .zip(first_occurrences.par_iter())
+            .map(check)
+            .collect();
+        #[cfg(not(feature = "parallel"))]
+        let results = (aggregated.signatures.iter())
+            .zip(&first_occurrences)
+            .map(check)
+            .collect();
+        VerificationReport { results }
+    }
+
+    /// Verify an aggregate in which every validator signs its own message
+    ///
+    /// `messages` pairs each validator root with the message signed under it. Returns `true` if
+    /// every signature is valid for the message of its root and from a registered validator,
+    /// `false` otherwise, including when a root has no message or several or when there are fewer
+    /// than [`VerifierPolicy::min_signatures`] signatures.
+    pub fn verify_multi(
+        &self,
+        messages: &[(Hash, Message)],
+        aggregated: &AggregatedSignature,
+    ) -> bool {
+        let mut by_root = messages.to_vec();
+        by_root.sort_unstable_by_key(|(root, _)| *root);
+        if by_root.windows(2).any(|pair| pair[0].0 == pair[1].0)
+            || aggregated.signatures.len() < self.policy.min_signatures
+            || (self.policy.require_distinct_roots && check_distinct_roots(aggregated).is_err())
+        {
+            return false;
+        }
+        aggregated.signatures.iter().all(|sig| {
+            by_root
+                .binary_search_by_key(&sig.xmss_root, |(root, _)| *root)
+                .is_ok_and(|i| {
+                    self.verify_validator(&by_root[i].1, sig) == ValidatorVerification::Ok
+                })
+        })
+    }
+
+    /// Verify that at least `threshold` distinct registered validators signed the message
+    ///
+    /// Signatures that do not verify or whose root is not registered are not counted, but do not
+    /// fail the check on their own.
+    ///
+    /// # Returns
+    /// The number of registered validators with a valid signature
+    ///
+    /// # Errors
+    /// Returns an error if a root appears more than once in the aggregate, or if fewer than
+    /// `threshold` signatures are valid.
+    pub fn verify_threshold(
+        &self,
+        message: &Message,
+        aggregated: &AggregatedSignature,
+        threshold: usize,
+    ) -> Result<usize, ThresholdError> {
+        check_distinct_roots(aggregated)?;
+        let num_valid = (aggregated.signatures.iter())
+            .filter(|sig| self.verify_validator(message, sig) == ValidatorVerification::Ok)
+            .count();
+        if num_valid < threshold {
+            return Err(ThresholdError::BelowThreshold {
+                num_valid,
+                threshold,
+            });
+        }
+        Ok(num_valid)
+    }
+
+    /// Verify that the distinct registered validators that signed the message weigh at least
+    /// `min_weight` together
+    ///
+    /// Signatures are counted as in [`AggregatedVerifier::verify_threshold`], each with the
+    /// weight of its root.
+    ///
+    /// # Returns
+    /// The total weight of the registered validators with a valid signature
+    ///
+    /// # Errors
+    /// Returns an error if a root appears more than once in the aggregate, if the total weight
+    /// overflows a `u64`, or if it is less than `min_weight`.
+    pub fn verify_weighted(
+        &self,
+        message: &Message,
+        aggregated: &AggregatedSignature,
+        min_weight: u64,
+    ) -> Result<u64, ThresholdError> {
+        check_distinct_roots(aggregated)?;
+        let mut weight = 0u64;
+        for sig in &aggregated.signatures {
+            if self.verify_validator(message, sig) == ValidatorVerification::Ok {
+                let sig_weight = self
+                    .weight(&sig.xmss_root)
+                    .expect("verified roots are registered");
+                weight = (weight.checked_add(sig_weight)).ok_or(ThresholdError::WeightOverflow)?;
+            }
+        }
+        if weight < min_weight {
+            return Err(ThresholdError::InsufficientWeight { weight, min_weight });
+        }
+        Ok(weight)
+    }
+
+    /// Verifies a signature, which is the first one under its root if `first` is set.
+    fn verify_occurrence(
+        &self,
+        message: &Message,
+        sig: &ValidatorSignature,
+        first: bool,
+    ) -> ValidatorVerification {
+        if self.policy.require_distinct_roots && !first {
+            return ValidatorVerification::DuplicateRoot;
+        }
+        self.verify_validator(message, sig)
+    }
+
+    fn verify_validator(
+        &self,
+        message: &Message,
+        sig: &ValidatorSignature,
+    ) -> ValidatorVerification {
+        // Check if this signature's root is in our validator set
+        let Some(index) = self.registry.index_of(&sig.xmss_root) else {
+            return ValidatorVerification::UnknownRoot;
+        };
+        // A claimed index must point at the root the signature verifies against
+        if sig
+            .validator_index
+            .is_some_and(|claimed| claimed as usize != index)
+        {
+            return ValidatorVerification::IndexMismatch;
+        }
+        // The param of the aggregate is only cross-checked, the signature is verified under the
+        // registered one
+        let param = self
+            .registry
+            .param(&sig.xmss_root)
+            .expect("the root is registered");
+        if sig.param != *param {
+            return ValidatorVerification::ParamMismatch;
+        }
+        let result = match &sig.signature {
+            AnySignature::Xmss(signature) => verify_signature_checked_with_backend::<H>(
+                &self.spec,
+                param,
+                message,
+                signature,
+                &sig.xmss_root,
+            ),
+            AnySignature::Hyper(signature) => verify_hyper_signature_checked_with_backend::<H>(
+                &self.spec,
+                param,
+                message,
+                signature,
+                &sig.xmss_root,
+            ),
+        };
+        match result {
+            Ok(()) => ValidatorVerification::Ok,
+            Err(err) => ValidatorVerification::InvalidSignature(err),
+        }
+    }
+}
+
+/// What [`AggregatedVerifier::verify`] requires of an aggregate beyond valid signatures from
+/// registered validators
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerifierPolicy {
+    /// The fewest signatures an aggregate may hold
+    ///
+    /// This is 1 by default, so that an empty aggregate does not verify vacuously.
+    pub min_signatures: usize,
+    /// Whether an aggregate holding several signatures under the same root is rejected, see
+    /// [`AggregatedVerifier::with_strict_roots`]
+    pub require_distinct_roots: bool,
+}
+
+impl Default for VerifierPolicy {
+    fn default() -> Self {
+        Self {
+            min_signatures: 1,
+            require_distinct_roots: false,
+        }
+    }
+}
+
+/// Whether each signature is the first one under its root.
+fn first_occurrences(signatures: &[ValidatorSignature]) -> Vec<bool> {
+    let mut seen = Vec::with_capacity(signatures.len());
+    (signatures.iter())
+        .map(|sig| insert_root(&mut seen, sig.xmss_root))
+        .collect()
+}
+
+/// Adds `root` to the sorted `seen` and returns whether it was not there yet.
+fn insert_root(seen: &mut Vec<Hash>, root: Hash) -> bool {
+    match seen.binary_search(&root) {
+        Ok(_) => false,
+        Err(position) => {
+            seen.insert(position, root);
+            true
+        }
+    }
+}
+
+/// Rejects an aggregate that holds more than one signature under the same root, which would
+/// otherwise be counted twice towards a threshold.
+fn check_distinct_roots(aggregated: &AggregatedSignature) -> Result<(), ThresholdError> {
+    let mut roots: Vec<_> = aggregated
+        .signatures
+        .iter()
+        .map(|sig| sig.xmss_root)
+        .collect();
+    roots.sort_unstable();
+    match roots.windows(2).find(|pair| pair[0] == pair[1]) {
+        Some(pair) => Err(ThresholdError::DuplicateRoot { root: pair[0] }),
+        None => Ok(()),
+    }
+}
+
+/// The reasons a threshold check of an aggregate can fail, see
+/// [`AggregatedVerifier::verify_threshold`] and [`AggregatedVerifier::verify_weighted`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ThresholdError {
+    /// Several signatures of the aggregate claim the same root.
+    DuplicateRoot { root: Hash },
+    /// Fewer registered validators than required have a valid signature.
+    BelowThreshold { num_valid: usize, threshold: usize },
+    /// The validators with a valid signature weigh less than required.
+    InsufficientWeight { weight: u64, min_weight: u64 },
+    /// The total weight of the valid signatures does not fit in a `u64`.
+    WeightOverflow,
+}
+
+impl fmt::Display for ThresholdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThresholdError::DuplicateRoot { root } => {
+                write!(f, "root {root} signs more than once")
+            }
+            ThresholdError::BelowThreshold {
+                num_valid,
+                threshold,
+            } => write!(
+                f,
+                "{num_valid} valid signatures, {} short of the threshold of {threshold}",
+                threshold.saturating_sub(*num_valid)
+            ),
+            ThresholdError::InsufficientWeight { weight, min_weight } => write!(
+                f,
+                "valid signatures weigh {weight}, {} short of the minimum of {min_weight}",
+                min_weight.saturating_sub(*weight)
+            ),
+            ThresholdError::WeightOverflow => write!(f, "total weight overflows a u64"),
+        }
+    }
+}
+
+impl core::error::Error for ThresholdError {}
+
+/// The outcome of verifying one [`ValidatorSignature`] of an aggregate
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ValidatorVerification {
+    /// The signature is valid and its root is registered.
+    Ok,
+    /// The root of the signature is not registered with the verifier.
+    UnknownRoot,
+    /// The signature claims a validator index whose registered root is not its root.
+    IndexMismatch,
+    /// The signature claims another param than the one its root is registered with.
+    ParamMismatch,
+    /// An earlier signature of the aggregate has the same root, see
+    /// [`AggregatedVerifier::with_strict_roots`].
+    DuplicateRoot,
+    /// The root is registered but the signature does not verify against it.
+    InvalidSignature(VerifyError),
+}
+
+/// The outcome of [`AggregatedVerifier::verify_detailed`], in the order of the signatures
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerificationReport {
+    /// The outcome for each validator signature
+    pub results: Vec<ValidatorVerification>,
+}
+
+impl VerificationReport {
+    /// Whether every signature is valid and from a registered validator.
+    pub fn is_valid(&self) -> bool {
+        self.num_valid() == self.results.len()
+    }
+
+    /// The number of valid signatures from registered validators.
+    pub fn num_valid(&self) -> usize {
+        self.count(|result| *result == ValidatorVerification::Ok)
+    }
+
+    /// The number of signatures that repeat the root of an earlier one in strict mode.
+    pub fn num_duplicate_root(&self) -> usize {
+        self.count(|result| *result == ValidatorVerification::DuplicateRoot)
+    }
+
+    /// The number of signatures whose root is not registered.
+    pub fn num_unknown_root(&self) -> usize {
+        self.count(|result| *result == ValidatorVerification::UnknownRoot)
+    }
+
+    /// The number of signatures that do not verify against their registered root.
+    pub fn num_invalid(&self) -> usize {
+        self.count(|result| matches!(result, ValidatorVerification::InvalidSignature(_)))
+    }
+
+    /// The indices of the signatures that are not valid, with their outcome.
+    pub fn failures(&self) -> impl Iterator<Item = (usize, &ValidatorVerification)> {
+        (self.results.iter().enumerate())
+            .filter(|(_, result)| **result != ValidatorVerification::Ok)
+    }
+
+    fn count(&self, predicate: impl Fn(&ValidatorVerification) -> bool) -> usize {
+        self.results
+            .iter()
+            .filter(|result| predicate(result))
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::Sha3Backend;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_xmss_verify() {
+        let spec = spec::SPEC_2;
+        let mut signer = Signer::new(StdRng::seed_from_u64(0), 1000000, spec.clone(), 8).unwrap();
+
+        // Get public verification parameters
+        let root = signer.root;
+        let param = signer.param.clone();
+
+        let message1 = Message([10; 32]);
+        let message2 = Message([20; 32]);
+        let bad_message = Message([30; 32]);
+
+        let sig1 = signer
+            .sign(0, &message1)
+            .expect("Failed to sign with epoch 0");
+        let sig3 = signer
+            .sign(3, &message2)
+            .expect("Failed to sign with epoch 3");
+
+        assert!(verify_signature(&spec, &param, &message1, &sig1, &root));
+        assert!(verify_signature(&spec, &param, &message2, &sig3, &root));
+        assert!(signer.last_grind_attempts().unwrap() >= 1);
+        assert!(signer.last_sum_histogram().is_none());
+
+        assert!(!verify_signature(&spec, &param, &bad_message, &sig1, &root));
+        assert!(!verify_signature(&spec, &param, &message2, &sig1, &root));
+        assert!(!verify_signature(&spec, &param, &message1, &sig3, &root));
+
+        assert_eq!(
+            signer.sign(8, &message1).map(drop),
+            Err(SignError::EpochOutOfRange {
+                epoch: 8,
+                lifetime: 8
+            })
+        );
+        assert_eq!(
+            signer.sign(usize::MAX, &message1).map(drop),
+            Err(SignError::EpochOutOfRange {
+                epoch: usize::MAX,
+                lifetime: 8
+            })
+        );
+        assert_eq!(
+            signer.sign(usize::MAX, &message1).unwrap_err().to_string(),
+            format!("epoch {} is past the lifetime of 8 epochs", usize::MAX)
+        );
+    }
+
+    #[test]
+    fn test_cached_tree_levels() {
+        let spec = spec::SPEC_2;
+        let new_signer =
+            || Signer::new(StdRng::seed_from_u64(0), 1000000, spec.clone(), 16).unwrap();
+        let mut signer = new_signer();
+        let mut compact_signer = new_signer().with_cached_tree_levels(1);
+        let message = Message([10; 32]);
+
+        let sig = signer.sign(5, &message).unwrap();
+        let compact_sig = compact_signer.sign(5, &message).unwrap();
+        assert_eq!(
+            bincode::serialize(&sig).unwrap(),
+            bincode::serialize(&compact_sig).unwrap()
+        );
+    }
+
+    #[cfg(feature = "persistence")]
+    #[test]
+    fn test_signature_file() {
+        let spec = spec::SPEC_2;
+        let mut signer = Signer::new(StdRng::seed_from_u64(0), 1000000, spec.clone(), 4).unwrap();
+        let message = Message([10; 32]);
+        let signature = signer.sign(1, &message).unwrap();
+        let path = std::env::temp_dir().join(format!("leansig-signature-{}", std::process::id()));
+        signature.write_to_file(&path).unwrap();
+        let read = Signature::read_from_file(&path, &spec).unwrap();
+        assert_eq!(read.to_bytes(), signature.to_bytes());
+
+        // A signature file of a later format version, or a truncated one, is rejected
+        let mut bytes = std::fs::read(&path).unwrap();
+        bytes[4] += 1;
+        std::fs::write(&path, &bytes).unwrap();
+        let err = Signature::read_from_file(&path, &spec).unwrap_err();
+        assert_eq!(err.to_string(), "signature format version 2 instead of 1");
+        bytes[4] -= 1;
+        std::fs::write(&path, &bytes[..bytes.len() - 1]).unwrap();
+        let err = Signature::read_from_file(&path, &spec).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(feature = "persistence")]
+    #[test]
+    fn test_signer_state_file() {
+        let mut state = SignerState::new(spec::SPEC_2, 4, [7; 32]);
+        assert_eq!(state.key_rng, KeyRng::ChaCha20);
+        let root = state.signer().unwrap().root;
+        // Pins the keys a seed derives, whatever the version of `rand`.
+        assert_eq!(
+            root.to_string(),
+            "907f54b617ee29d65fc86994a3d94e4e23432d77ef72182616e405e9ac80ec21"
+        );
+        assert!(state.consume(2));
+        assert!(!state.consume(2));
+        assert!(!state.consume(1));
+        assert_eq!(state.next_epoch, 3);
+
+        let path = std::env::temp_dir().join(format!("leansig-state-{}", std::process::id()));
+        state.write_to_file(&path).unwrap();
+        let read = SignerState::read_from_file(&path).unwrap();
+        assert_eq!(read, state);
+        // The keys are derived from the seed alone.
+        assert_eq!(read.signer().unwrap().root, root);
+
+        let err = Signature::read_from_file(&path, &spec::SPEC_2).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "payload kind signer state instead of signature"
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(feature = "persistence")]
+    #[test]
+    fn test_signer_state_consume() {
+        let mut state = SignerState::new(spec::SPEC_2, 4, [7; 32]);
+        // Epochs past the lifetime are never consumed, so the next epoch cannot overflow.
+        for epoch in [4, u64::MAX] {
+            assert!(!state.consume(epoch));
+            assert_eq!(state.next_epoch, 0);
+        }
+        assert!(state.consume(3));
+        assert_eq!(state.next_epoch, 4);
+        assert!(!state.consume(4));
+        assert!(!state.consume(u64::MAX));
+        assert_eq!(state.next_epoch, 4);
+    }
+
+    #[test]
+    fn test_signature_bytes() {
+        let spec = spec::SPEC_2;
+        let dimension = spec.dimension();
+        let signature = Signature {
+            signature: OtsSignature {
+                nonce: Nonce([1; RAND_LEN]),
+                hashes: vec![Hash([2; HASH_LEN]); dimension],
+            },
+            hash_tree_proof: HashTreeProof::new(3, vec![Hash([4; HASH_LEN]); 2]),
+            public_key: Pk {
+                param: Param {
+                    data: vec![5; spec.param_len],
+                },
+                end_hashes: vec![Hash([6; HASH_LEN]); dimension],
+            },
+        };
+        let bytes = signature.to_bytes();
+        assert_eq!(
+            bytes,
+            [
+                vec![1; RAND_LEN],
+                vec![2; dimension * HASH_LEN],
+                vec![0, 0, 0, 3],
+                vec![4; 2 * HASH_LEN],
+                vec![5; spec.param_len],
+                vec![6; dimension * HASH_LEN],
+            ]
+            .concat()
+        );
+        assert_eq!(bytes.len(), Signature::encoded_len(&spec, 2));
+
+        let mut signer = Signer::new(StdRng::seed_from_u64(0), 1000000, spec.clone(), 4).unwrap();
+        let message = Message([10; 32]);
+        let sig = signer.sign(1, &message).unwrap();
+        let bytes = sig.to_bytes();
+        let decoded = Signature::from_bytes(&bytes, &spec, 2).unwrap();
+        assert_eq!(
+            bincode::serialize(&decoded).unwrap(),
+            bincode::serialize(&sig).unwrap()
+        );
+        assert!(verify_signature(
+            &spec,
+            &signer.param,
+            &message,
+            &decoded,
+            &signer.root
+        ));
+
+        let mut extended = bytes.clone();
+        extended.push(0);
+        assert_eq!(
+            Signature::from_bytes(&extended, &spec, 2).map(drop),
+            Err(DecodeError::InvalidLength {
+                expected: bytes.len(),
+                actual: bytes.len() + 1
+            })
+        );
+        assert!(Signature::from_bytes(&bytes, &spec, 3).is_err());
+
+        assert_eq!(
+            Signature::height_for_encoded_len(&spec, bytes.len()),
+            Some(2)
+        );
+        assert_eq!(
+            Signature::height_for_encoded_len(&spec, bytes.len() + 1),
+            None
+        );
+        assert_eq!(Signature::height_for_encoded_len(&spec, 3), None);
+    }
+
+    #[test]
+    fn test_traversal() {
+        let spec = spec::SPEC_2;
+        let new_signer =
+            || Signer::new(StdRng::seed_from_u64(0), 1000000, spec.clone(), 6).unwrap();
+        let mut signer = new_signer();
+        let mut traversal_signer = new_signer().with_traversal();
+        let message = Message([10; 32]);
+
+        assert_eq!(
+            traversal_signer.sign(1, &message).map(drop),
+            Err(SignError::EpochNotNext {
+                epoch: 1,
+                next_epoch: 0
+            })
+        );
+        for epoch in 0..6 {
+            let sig = signer.sign(epoch, &message).unwrap();
+            let traversal_sig = traversal_signer.sign(epoch, &message).unwrap();
+            assert_eq!(traversal_sig.hash_tree_proof.path, sig.hash_tree_proof.path);
+            assert!(verify_signature(
+                &spec,
+                &traversal_signer.param,
+                &message,
+                &traversal_sig,
+                &traversal_signer.root
+            ));
+            // An epoch that was already used cannot be signed again.
+            assert_eq!(
+                traversal_signer.sign(epoch, &message).map(drop),
+                Err(SignError::EpochConsumed {
+                    epoch,
+                    next_epoch: epoch + 1
+                })
+            );
+        }
+        assert_eq!(
+            traversal_signer.sign(6, &message).map(drop),
+            Err(SignError::EpochOutOfRange {
+                epoch: 6,
+                lifetime: 6
+            })
+        );
+    }
+
+    #[test]
+    fn test_aggregated_signatures() {
+        let spec = spec::SPEC_2;
+
+        // Create multiple validators (each with their own param)
+        let mut validator1 = Signer::new(StdRng::seed_from_u64(1), 10000, spec.clone(), 4).unwrap();
+        let mut validator2 = Signer::new(StdRng::seed_from_u64(2), 10000, spec.clone(), 4).unwrap();
+        let mut validator3 = Signer::new(StdRng::seed_from_u64(3), 10000, spec.clone(), 4).unwrap();
+
+        // Register validator roots with their params
+        let registry = [&validator1, &validator2, &validator3]
+            .into_iter()
+            .map(|validator| (validator.root, validator.param.clone()))
+            .collect();
+
+        // Create the validator roots collection for verification
+        let verifier = AggregatedVerifier::new(registry, spec.clone());
+
+        // Message to be signed by all validators
+        let message = Message([42; 32]);
+
+        // Each validator signs the message
+        let sig1 = validator1.sign(0, &message).expect("Failed to sign");
+        let sig2 = validator2.sign(0, &message).expect("Failed to sign");
+        let sig3 = validator3.sign(0, &message).expect("Failed to sign");
+
+        // Create aggregated signature
+        let aggregated = AggregatedSignature::new(vec![
+            ValidatorSignature {
+                epoch: 0,
+                signature: sig1.into(),
+                xmss_root: validator1.root,
+                param: validator1.param.clone(),
+                validator_index: None,
+            },
+            ValidatorSignature {
+                epoch: 0,
+                signature: sig2.into(),
+                xmss_root: validator2.root,
+                param: validator2.param.clone(),
+                validator_index: None,
+            },
+            ValidatorSignature {
+                epoch: 0,
+                signature: sig3.into(),
+                xmss_root: validator3.root,
+                param: validator3.param.clone(),
+                validator_index: None,
+            },
+        ]);
+
+        // Verify the aggregated signature (all should be valid)
+        assert!(verifier.verify(&message, &aggregated));
+
+        // Test with only 2 signatures
+        let partial_aggregated = AggregatedSignature::new(vec![
+            ValidatorSignature {
+                epoch: 0,
+                signature: validator1.sign(1, &message).expect("Failed to sign").into(),
+                xmss_root: validator1.root,
+                param: validator1.param.clone(),
+                validator_index: None,
+            },
+            ValidatorSignature {
+                epoch: 0,
+                signature: validator2.sign(1, &message).expect("Failed to sign").into(),
+                xmss_root: validator2.root,
+                param: validator2.param.clone(),
+                validator_index: None,
+            },
+        ]);
+
+        // Both signatures should be valid
+        assert!(verifier.verify(&message, &partial_aggregated));
 
         // Test with invalid signature
         let bad_message = Message([99; 32]);
         let bad_sig = validator1.sign(2, &bad_message).expect("Failed to sign");
         let invalid_aggregated = AggregatedSignature::new(vec![ValidatorSignature {
             epoch: 2,
-            signature: bad_sig,
+            signature: bad_sig.into(),
             xmss_root: validator1.root,
             param: validator1.param.clone(),
+            validator_index: None,
         }]);
 
         // Should fail because signature is for wrong message
         assert!(!verifier.verify(&message, &invalid_aggregated));
     }
+
+    #[test]
+    fn test_verify_multi() {
+        let spec = spec::SPEC_2;
+        let mut validators: Vec<_> = (1..=3)
+            .map(|seed| Signer::new(StdRng::seed_from_u64(seed), 10000, spec.clone(), 4).unwrap())
+            .collect();
+        let messages: Vec<_> = (validators.iter_mut())
+            .zip(1..)
+            .map(|(validator, i)| (validator.root, Message([i; 32])))
+            .collect();
+        let signatures = (validators.iter_mut())
+            .zip(&messages)
+            .map(|(validator, (_, message))| ValidatorSignature {
+                epoch: 0,
+                signature: validator.sign(0, message).unwrap().into(),
+                xmss_root: validator.root,
+                param: validator.param.clone(),
+                validator_index: None,
+            })
+            .collect();
+        let aggregated = AggregatedSignature::new(signatures);
+        let registry = (validators.iter())
+            .map(|validator| (validator.root, validator.param.clone()))
+            .collect();
+        let verifier = AggregatedVerifier::new(registry, spec);
+
+        assert!(verifier.verify_multi(&messages, &aggregated));
+        // The order of the pairs does not matter.
+        let reversed: Vec<_> = messages.iter().rev().copied().collect();
+        assert!(verifier.verify_multi(&reversed, &aggregated));
+        assert!(!verifier.verify(&messages[0].1, &aggregated));
+
+        let mut swapped = messages.clone();
+        (swapped[0].1, swapped[1].1) = (messages[1].1, messages[0].1);
+        assert!(!verifier.verify_multi(&swapped, &aggregated));
+
+        // Every root needs exactly one message.
+        assert!(!verifier.verify_multi(&messages[..2], &aggregated));
+        let mut duplicated = messages.clone();
+        duplicated.push(messages[2]);
+        assert!(!verifier.verify_multi(&duplicated, &aggregated));
+    }
+
+    #[test]
+    fn test_verify_threshold() {
+        let spec = spec::SPEC_2;
+        let message = Message([42; 32]);
+        let mut validators: Vec<_> = (1..=4)
+            .map(|seed| Signer::new(StdRng::seed_from_u64(seed), 10000, spec.clone(), 4).unwrap())
+            .collect();
+        let registry = (validators.iter())
+            .map(|validator| (validator.root, validator.param.clone()))
+            .collect();
+        let verifier = AggregatedVerifier::new(registry, spec);
+        // Only the first three of the four registered validators sign.
+        let signatures: Vec<_> = (validators.iter_mut().take(3))
+            .map(|validator| ValidatorSignature {
+                epoch: 0,
+                signature: validator.sign(0, &message).unwrap().into(),
+                xmss_root: validator.root,
+                param: validator.param.clone(),
+                validator_index: None,
+            })
+            .collect();
+        let aggregated = AggregatedSignature::new(signatures.clone());
+
+        assert_eq!(verifier.verify_threshold(&message, &aggregated, 3), Ok(3));
+        assert_eq!(
+            verifier.verify_threshold(&message, &aggregated, 4),
+            Err(ThresholdError::BelowThreshold {
+                num_valid: 3,
+                threshold: 4
+            })
+        );
+
+        // Signatures of another message are not counted.
+        let two = AggregatedSignature::new(signatures[..2].to_vec());
+        assert_eq!(verifier.verify_threshold(&message, &two, 2), Ok(2));
+        assert_eq!(
+            verifier.verify_threshold(&Message([43; 32]), &two, 1),
+            Err(ThresholdError::BelowThreshold {
+                num_valid: 0,
+                threshold: 1
+            })
+        );
+
+        // Padding the aggregate with a copy of a valid signature does not count it twice.
+        let mut padded = signatures[..2].to_vec();
+        padded.push(signatures[0].clone());
+        assert_eq!(
+            verifier.verify_threshold(&message, &AggregatedSignature::new(padded), 3),
+            Err(ThresholdError::DuplicateRoot {
+                root: validators[0].root
+            })
+        );
+    }
+
+    #[test]
+    fn test_threshold_error_display() {
+        let below = ThresholdError::BelowThreshold {
+            num_valid: 3,
+            threshold: 4,
+        };
+        assert_eq!(
+            below.to_string(),
+            "3 valid signatures, 1 short of the threshold of 4"
+        );
+        // Errors built by hand need not be consistent, and must still display.
+        let above = ThresholdError::BelowThreshold {
+            num_valid: 5,
+            threshold: 4,
+        };
+        assert_eq!(
+            above.to_string(),
+            "5 valid signatures, 0 short of the threshold of 4"
+        );
+        let heavy = ThresholdError::InsufficientWeight {
+            weight: u64::MAX,
+            min_weight: 1,
+        };
+        assert_eq!(
+            heavy.to_string(),
+            format!(
+                "valid signatures weigh {}, 0 short of the minimum of 1",
+                u64::MAX
+            )
+        );
+    }
+
+    #[test]
+    fn test_verify_weighted() {
+        let spec = spec::SPEC_2;
+        let message = Message([42; 32]);
+        let mut validators: Vec<_> = (1..=3)
+            .map(|seed| Signer::new(StdRng::seed_from_u64(seed), 10000, spec.clone(), 4).unwrap())
+            .collect();
+        let signatures: Vec<_> = (validators.iter_mut())
+            .map(|validator| ValidatorSignature {
+                epoch: 0,
+                signature: validator.sign(0, &message).unwrap().into(),
+                xmss_root: validator.root,
+                param: validator.param.clone(),
+                validator_index: None,
+            })
+            .collect();
+        let weighted = |weights: [u64; 3]| {
+            let entries = (validators.iter().zip(weights))
+                .map(|(validator, weight)| (validator.root, validator.param.clone(), weight))
+                .collect();
+            AggregatedVerifier::new(ValidatorRegistry::with_weights(entries), spec.clone())
+        };
+
+        // Any two validators weigh less than 10.
+        let verifier = weighted([5, 3, 2]);
+        assert_eq!(verifier.weight(&validators[1].root), Some(3));
+        let all = AggregatedSignature::new(signatures.clone());
+        assert_eq!(verifier.verify_weighted(&message, &all, 10), Ok(10));
+        for missing in 0..3 {
+            let mut partial = signatures.clone();
+            partial.remove(missing);
+            let partial = AggregatedSignature::new(partial);
+            assert!(matches!(
+                verifier.verify_weighted(&message, &partial, 10),
+                Err(ThresholdError::InsufficientWeight { min_weight: 10, .. })
+            ));
+        }
+
+        // A duplicated signature weighs nothing extra.
+        let mut padded = signatures[..2].to_vec();
+        padded.push(signatures[0].clone());
+        assert_eq!(
+            verifier.verify_weighted(&message, &AggregatedSignature::new(padded), 10),
+            Err(ThresholdError::DuplicateRoot {
+                root: validators[0].root
+            })
+        );
+
+        let verifier = weighted([u64::MAX, 1, 0]);
+        assert_eq!(
+            verifier.verify_weighted(&message, &all, 0),
+            Err(ThresholdError::WeightOverflow)
+        );
+    }
+
+    #[test]
+    fn test_validator_index() {
+        let spec = spec::SPEC_2;
+        let message = Message([42; 32]);
+        let mut validators: Vec<_> = (1..=4)
+            .map(|seed| Signer::new(StdRng::seed_from_u64(seed), 10000, spec.clone(), 4).unwrap())
+            .collect();
+        let registry = (validators.iter())
+            .map(|validator| (validator.root, validator.param.clone()))
+            .collect();
+        let verifier = AggregatedVerifier::new(registry, spec);
+        let mut signatures: Vec<_> = (validators.iter_mut().enumerate())
+            .filter(|(i, _)| *i != 1)
+            .map(|(i, validator)| ValidatorSignature {
+                epoch: 0,
+                signature: validator.sign(0, &message).unwrap().into(),
+                xmss_root: validator.root,
+                param: validator.param.clone(),
+                validator_index: Some(i as u32),
+            })
+            .collect();
+        // The index is optional.
+        signatures[1].validator_index = None;
+
+        let aggregated = AggregatedSignature::new(signatures.clone());
+        assert!(verifier.verify(&message, &aggregated));
+        assert_eq!(
+            aggregated.participation_bitmap(4),
+            [true, false, false, true]
+        );
+        assert_eq!(aggregated.participation_bitmap(2), [true, false]);
+
+        // Validator 3 claiming to be validator 1, or a validator that does not exist.
+        for wrong_index in [1, 4] {
+            signatures[2].validator_index = Some(wrong_index);
+            let aggregated = AggregatedSignature::new(signatures.clone());
+            assert!(!verifier.verify(&message, &aggregated));
+            assert_eq!(
+                verifier.verify_detailed(&message, &aggregated).results[2],
+                ValidatorVerification::IndexMismatch
+            );
+        }
+    }
+
+    #[test]
+    fn test_verify_at_epoch() {
+        let spec = spec::SPEC_2;
+        let message = Message([42; 32]);
+        let mut validators: Vec<_> = (1..=3)
+            .map(|seed| Signer::new(StdRng::seed_from_u64(seed), 10000, spec.clone(), 4).unwrap())
+            .collect();
+        let registry = (validators.iter())
+            .map(|validator| (validator.root, validator.param.clone()))
+            .collect();
+        let verifier = AggregatedVerifier::new(registry, spec.clone());
+        // The last validator signs at epoch 3 instead of 2.
+        let mut signatures: Vec<_> = (validators.iter_mut())
+            .zip([2, 2, 3])
+            .map(|(validator, epoch)| ValidatorSignature {
+                epoch: epoch as u64,
+                signature: validator.sign(epoch, &message).unwrap().into(),
+                xmss_root: validator.root,
+                param: validator.param.clone(),
+                validator_index: None,
+            })
+            .collect();
+
+        let aggregated = AggregatedSignature::new(signatures.clone());
+        assert!(verifier.verify(&message, &aggregated));
+        assert!(!verifier.verify_at_epoch(&message, &aggregated, 2));
+
+        // Claiming epoch 2 does not help, since the signature is for the leaf of epoch 3.
+        signatures[2].epoch = 2;
+        let aggregated = AggregatedSignature::new(signatures.clone());
+        assert!(verifier.verify(&message, &aggregated));
+        assert!(!verifier.verify_at_epoch(&message, &aggregated, 2));
+
+        signatures.pop();
+        let aggregated = AggregatedSignature::new(signatures);
+        assert!(verifier.verify_at_epoch(&message, &aggregated, 2));
+        assert!(!verifier.verify_at_epoch(&message, &aggregated, 3));
+
+        // A hypertree epoch combines the leaves of both layers.
+        let mut hyper_signer =
+            hypertree::HyperSigner::new(StdRng::seed_from_u64(4), 10000, spec.clone(), 1, 2)
+                .unwrap();
+        let signature: AnySignature = hyper_signer.sign(6, &message).unwrap().into();
+        assert_eq!(signature.leaf_index(), 6);
+    }
+
+    #[test]
+    fn test_empty_aggregate() {
+        let spec = spec::SPEC_2;
+        let message = Message([42; 32]);
+        let signer = Signer::new(StdRng::seed_from_u64(1), 10000, spec.clone(), 4).unwrap();
+        let registry = ValidatorRegistry::new(vec![(signer.root, signer.param.clone())]);
+        let verifier = AggregatedVerifier::new(registry, spec);
+        let empty = AggregatedSignature::new(Vec::new());
+        assert!(!verifier.verify(&message, &empty));
+        assert!(!verifier.verify_at_epoch(&message, &empty, 0));
+        assert!(!verifier.verify_multi(&[(signer.root, message)], &empty));
+
+        // Only an explicit policy lets an empty aggregate through.
+        let verifier = verifier.with_policy(VerifierPolicy {
+            min_signatures: 0,
+            ..VerifierPolicy::default()
+        });
+        assert!(verifier.verify(&message, &empty));
+    }
+
+    #[test]
+    fn test_min_signatures() {
+        let spec = spec::SPEC_2;
+        let message = Message([42; 32]);
+        let mut validators: Vec<_> = (1..=2)
+            .map(|seed| Signer::new(StdRng::seed_from_u64(seed), 10000, spec.clone(), 4).unwrap())
+            .collect();
+        let registry = (validators.iter())
+            .map(|validator| (validator.root, validator.param.clone()))
+            .collect();
+        let policy = VerifierPolicy {
+            min_signatures: 2,
+            require_distinct_roots: true,
+        };
+        let verifier = AggregatedVerifier::new(registry, spec).with_policy(policy);
+        assert_eq!(verifier.policy(), policy);
+        let signatures: Vec<_> = (validators.iter_mut())
+            .map(|validator| ValidatorSignature {
+                epoch: 0,
+                signature: validator.sign(0, &message).unwrap().into(),
+                xmss_root: validator.root,
+                param: validator.param.clone(),
+                validator_index: None,
+            })
+            .collect();
+
+        assert!(verifier.verify(&message, &AggregatedSignature::new(signatures.clone())));
+        let single = AggregatedSignature::new(signatures[..1].to_vec());
+        assert!(!verifier.verify(&message, &single));
+        // Repeating a signature does not make up the count.
+        let repeated = AggregatedSignature::new(vec![signatures[0].clone(); 2]);
+        assert!(!verifier.verify(&message, &repeated));
+    }
+
+    #[test]
+    fn test_registered_param() {
+        let spec = spec::SPEC_2;
+        let message = Message([42; 32]);
+        let mut signer = Signer::new(StdRng::seed_from_u64(1), 10000, spec.clone(), 4).unwrap();
+        let signature = signer.sign(0, &message).unwrap();
+        let registered_param = Param::random(spec.param_len, &mut StdRng::seed_from_u64(2));
+        let registry = ValidatorRegistry::new(vec![(signer.root, registered_param.clone())]);
+        let verifier = AggregatedVerifier::new(registry, spec.clone());
+
+        // The tree was built under another param than the registered one, which the aggregate
+        // states truthfully.
+        let mut sig = ValidatorSignature {
+            epoch: 0,
+            signature: signature.clone().into(),
+            xmss_root: signer.root,
+            param: signer.param.clone(),
+            validator_index: None,
+        };
+        let aggregated = AggregatedSignature::new(vec![sig.clone()]);
+        assert!(!verifier.verify(&message, &aggregated));
+        assert_eq!(
+            verifier.verify_detailed(&message, &aggregated).results,
+            [ValidatorVerification::ParamMismatch]
+        );
+
+        // Claiming the registered param does not help, since the chains and the tree are then
+        // hashed under it even though the public key still names the param of the tree.
+        sig.param = registered_param;
+        let aggregated = AggregatedSignature::new(vec![sig]);
+        assert!(!verifier.verify(&message, &aggregated));
+        assert!(matches!(
+            verifier.verify_detailed(&message, &aggregated).results[0],
+            ValidatorVerification::InvalidSignature(_)
+        ));
+
+        // The param of the public key is ignored rather than trusted.
+        let mut relabeled = signature;
+        relabeled.public_key.param = Param::random(spec.param_len, &mut StdRng::seed_from_u64(3));
+        assert!(verify_signature(
+            &spec,
+            &signer.param,
+            &message,
+            &relabeled,
+            &signer.root
+        ));
+    }
+
+    #[test]
+    fn test_verify_iter() {
+        let spec = spec::SPEC_2;
+        let message = Message([42; 32]);
+        let mut validators: Vec<_> = (1..=5)
+            .map(|seed| Signer::new(StdRng::seed_from_u64(seed), 10000, spec.clone(), 4).unwrap())
+            .collect();
+        let registry = (validators.iter())
+            .map(|validator| (validator.root, validator.param.clone()))
+            .collect();
+        let verifier = AggregatedVerifier::new(registry, spec);
+        let mut signatures: Vec<_> = (validators.iter_mut())
+            .map(|validator| ValidatorSignature {
+                epoch: 0,
+                signature: validator.sign(0, &message).unwrap().into(),
+                xmss_root: validator.root,
+                param: validator.param.clone(),
+                validator_index: None,
+            })
+            .collect();
+        assert_eq!(verifier.first_failure(&message, &signatures), None);
+
+        // Corrupt entry 2 by swapping in the signature of another validator.
+        signatures[2].signature = signatures[3].signature.clone();
+        let failure = verifier.first_failure(&message, &signatures);
+        assert!(matches!(
+            failure,
+            Some((2, ValidatorVerification::InvalidSignature(_)))
+        ));
+
+        // The iterator goes on past the failure.
+        let mut outcomes = verifier.verify_iter(&message, &signatures);
+        assert_eq!(
+            outcomes
+                .by_ref()
+                .find(|(_, result)| *result != ValidatorVerification::Ok),
+            failure
+        );
+        assert_eq!(
+            outcomes.collect::<Vec<_>>(),
+            [
+                (3, ValidatorVerification::Ok),
+                (4, ValidatorVerification::Ok)
+            ]
+        );
+        assert!(!verifier.verify(&message, &AggregatedSignature::new(signatures)));
+    }
+
+    #[test]
+    fn test_verify_signatures_same_root() {
+        let spec = spec::SPEC_2;
+        let mut signer = Signer::new(StdRng::seed_from_u64(0), 10000, spec.clone(), 8).unwrap();
+        let (param, root) = (signer.param.clone(), signer.root);
+        let mut items: Vec<_> = (0..4)
+            .map(|epoch| {
+                let message = Message([epoch as u8; 32]);
+                (message, signer.sign(epoch, &message).unwrap())
+            })
+            .collect();
+        assert!(verify_signatures_same_root(&spec, &param, &root, &items));
+        assert!(!verify_signatures_same_root(&spec, &param, &root, &[]));
+        assert!(!verify_signatures_same_root(
+            &spec,
+            &param,
+            &Hash([0; HASH_LEN]),
+            &items
+        ));
+
+        // Epochs 0 and 1 share every node above the leaves, and their proofs must agree on them.
+        let mut disagreeing = items.clone();
+        disagreeing[1].1.hash_tree_proof.path[2] = Hash([7; HASH_LEN]);
+        assert!(!verify_signatures_same_root(
+            &spec,
+            &param,
+            &root,
+            &disagreeing
+        ));
+
+        // A signature on another message fails as it would on its own.
+        items[3].0 = Message([9; 32]);
+        assert!(!verify_signatures_same_root(&spec, &param, &root, &items));
+    }
+
+    #[test]
+    fn test_strict_roots() {
+        let spec = spec::SPEC_2;
+        let message = Message([42; 32]);
+        let mut validators: Vec<_> = (1..=2)
+            .map(|seed| Signer::new(StdRng::seed_from_u64(seed), 10000, spec.clone(), 4).unwrap())
+            .collect();
+        let registry = (validators.iter())
+            .map(|validator| (validator.root, validator.param.clone()))
+            .collect();
+        let verifier = AggregatedVerifier::new(registry, spec);
+        let strict_verifier = verifier.clone().with_strict_roots(true);
+        let signatures: Vec<_> = (validators.iter_mut())
+            .map(|validator| ValidatorSignature {
+                epoch: 0,
+                signature: validator.sign(0, &message).unwrap().into(),
+                xmss_root: validator.root,
+                param: validator.param.clone(),
+                validator_index: None,
+            })
+            .collect();
+
+        // The first signature five times over, and the second one once.
+        let mut padded = vec![signatures[0].clone(); 5];
+        padded.push(signatures[1].clone());
+        let mut aggregated = AggregatedSignature::new(padded);
+        let mut distinct_roots = vec![validators[0].root, validators[1].root];
+        distinct_roots.sort();
+        assert_eq!(aggregated.distinct_roots(), distinct_roots);
+
+        assert!(verifier.verify(&message, &aggregated));
+        assert!(!strict_verifier.verify(&message, &aggregated));
+        let report = strict_verifier.verify_detailed(&message, &aggregated);
+        assert_eq!((report.num_valid(), report.num_duplicate_root()), (2, 4));
+        assert_eq!(
+            report.failures().map(|(i, _)| i).collect::<Vec<_>>(),
+            [1, 2, 3, 4]
+        );
+
+        aggregated.dedup_roots();
+        assert_eq!(aggregated.signatures.len(), 2);
+        assert_eq!(aggregated.distinct_roots(), distinct_roots);
+        assert!(strict_verifier.verify(&message, &aggregated));
+    }
+
+    #[test]
+    fn test_mutable_validator_set() {
+        let spec = spec::SPEC_2;
+        let message = Message([42; 32]);
+        let mut validators: Vec<_> = (1..=3)
+            .map(|seed| Signer::new(StdRng::seed_from_u64(seed), 10000, spec.clone(), 4).unwrap())
+            .collect();
+        let signatures: Vec<_> = (validators.iter_mut())
+            .map(|validator| ValidatorSignature {
+                epoch: 0,
+                signature: validator.sign(0, &message).unwrap().into(),
+                xmss_root: validator.root,
+                param: validator.param.clone(),
+                validator_index: None,
+            })
+            .collect();
+        let aggregated = AggregatedSignature::new(signatures);
+
+        let registry =
+            ValidatorRegistry::new(vec![(validators[0].root, validators[0].param.clone())]);
+        let mut verifier = AggregatedVerifier::new(registry, spec);
+        assert!(!verifier.verify(&message, &aggregated));
+        assert!(verifier.add_root(validators[1].root, validators[1].param.clone()));
+        assert!(verifier.add_root(validators[2].root, validators[2].param.clone()));
+        assert!(!verifier.add_root(validators[2].root, validators[2].param.clone()));
+        assert_eq!(verifier.len(), 3);
+        assert_eq!(
+            verifier.roots(),
+            validators.iter().map(|v| v.root).collect::<Vec<_>>()
+        );
+        assert!(verifier.verify(&message, &aggregated));
+
+        let snapshot = verifier.snapshot();
+        let shared = Arc::clone(&snapshot);
+        assert!(verifier.remove_root(&validators[1].root));
+        assert!(!verifier.remove_root(&validators[1].root));
+        assert_eq!(verifier.roots(), [validators[0].root, validators[2].root]);
+        assert!(!verifier.verify(&message, &aggregated));
+        assert_eq!(
+            verifier.verify_detailed(&message, &aggregated).results[1],
+            ValidatorVerification::UnknownRoot
+        );
+
+        // The snapshot still holds the set from before the removal.
+        assert_eq!(shared.len(), 3);
+        assert!(
+            std::thread::spawn(move || shared.verify(&message, &aggregated))
+                .join()
+                .unwrap()
+        );
+
+        // The set, weights and strictness survive serialization.
+        let verifier = verifier.with_strict_roots(true);
+        let json: AggregatedVerifier =
+            serde_json::from_str(&serde_json::to_string(&verifier).unwrap()).unwrap();
+        let bytes: AggregatedVerifier =
+            bincode::deserialize(&bincode::serialize(&verifier).unwrap()).unwrap();
+        for decoded in [json, bytes] {
+            assert_eq!(decoded.roots(), verifier.roots());
+            assert_eq!(decoded.weight(&validators[2].root), Some(1));
+            assert!(decoded.policy().require_distinct_roots);
+        }
+    }
+
+    #[test]
+    fn test_contains_root() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let roots: Vec<_> = (0..100).map(|_| Hash::random(&mut rng)).collect();
+        let mut registered = roots[..50].to_vec();
+        registered.extend_from_slice(&roots[..10]);
+        let param = Param::random(18, &mut rng);
+        let registry = (registered.into_iter())
+            .map(|root| (root, param.clone()))
+            .collect();
+        let verifier = AggregatedVerifier::new(registry, spec::SPEC_2);
+        for (i, root) in roots.iter().enumerate() {
+            assert_eq!(verifier.contains_root(root), i < 50);
+        }
+    }
+
+    #[test]
+    fn test_verification_report() {
+        let spec = spec::SPEC_2;
+        let message = Message([42; 32]);
+        let mut validators: Vec<_> = (1..=3)
+            .map(|seed| Signer::new(StdRng::seed_from_u64(seed), 10000, spec.clone(), 4).unwrap())
+            .collect();
+        let mut signatures: Vec<_> = (validators.iter_mut())
+            .map(|validator| ValidatorSignature {
+                epoch: 0,
+                signature: validator.sign(0, &message).unwrap().into(),
+                xmss_root: validator.root,
+                param: validator.param.clone(),
+                validator_index: None,
+            })
+            .collect();
+        let registry = (validators.iter())
+            .map(|validator| (validator.root, validator.param.clone()))
+            .collect();
+        let verifier = AggregatedVerifier::new(registry, spec.clone());
+
+        let report =
+            verifier.verify_detailed(&message, &AggregatedSignature::new(signatures.clone()));
+        assert!(report.is_valid());
+        assert_eq!(report.num_valid(), 3);
+
+        // Corrupt one chain hash of the second signature.
+        let AnySignature::Xmss(signature) = &mut signatures[1].signature else {
+            unreachable!()
+        };
+        signature.signature.hashes[0].0[0] ^= 1;
+        let aggregated = AggregatedSignature::new(signatures.clone());
+        let report = verifier.verify_detailed(&message, &aggregated);
+        assert!(!report.is_valid());
+        assert!(!verifier.verify(&message, &aggregated));
+        assert_eq!(
+            (
+                report.num_valid(),
+                report.num_invalid(),
+                report.num_unknown_root()
+            ),
+            (2, 1, 0)
+        );
+        assert_eq!(
+            report.failures().collect::<Vec<_>>(),
+            [(
+                1,
+                &ValidatorVerification::InvalidSignature(VerifyError::PublicKeyMismatch)
+            )]
+        );
+
+        // A validator the verifier does not know is told apart from a bad signature.
+        let registry = (validators[..2].iter())
+            .map(|validator| (validator.root, validator.param.clone()))
+            .collect();
+        let verifier = AggregatedVerifier::new(registry, spec);
+        let report = verifier.verify_detailed(&message, &aggregated);
+        assert_eq!(
+            report.results,
+            [
+                ValidatorVerification::Ok,
+                ValidatorVerification::InvalidSignature(VerifyError::PublicKeyMismatch),
+                ValidatorVerification::UnknownRoot,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_message_hash_lengths() {
+        // Target sums are chosen near the mean coordinate sum, `dimension * (chain_len - 1) / 2`.
+        let spec_16 = Spec::new(16, 4, 18, 240).unwrap();
+        let spec_32 = Spec::new(32, 4, 18, 480).unwrap();
+
+        for spec in [spec_16, spec_32] {
+            let mut signer =
+                Signer::new(StdRng::seed_from_u64(0), 100000, spec.clone(), 2).unwrap();
+            let message = Message([7; 32]);
+            let sig = signer.sign(1, &message).expect("Failed to sign");
+
+            assert_eq!(sig.signature.hashes.len(), spec.dimension());
+            assert!(verify_signature(
+                &spec,
+                &signer.param,
+                &message,
+                &sig,
+                &signer.root
+            ));
+            assert!(!verify_signature(
+                &spec,
+                &signer.param,
+                &Message([8; 32]),
+                &sig,
+                &signer.root
+            ));
+        }
+    }
+
+    #[test]
+    fn test_signer_with_tree_height() {
+        let spec = spec::SPEC_2;
+        let signer =
+            Signer::with_tree_height(StdRng::seed_from_u64(0), 10, spec.clone(), 13).unwrap();
+        assert_eq!(signer.lifetime(), 1 << 13);
+        let by_height =
+            Signer::with_tree_height(StdRng::seed_from_u64(1), 10, spec.clone(), 3).unwrap();
+        let by_lifetime = Signer::new(StdRng::seed_from_u64(1), 10, spec.clone(), 8).unwrap();
+        assert_eq!(by_height.root, by_lifetime.root);
+
+        // Rejected before any key is derived
+        #[cfg(target_pointer_width = "64")]
+        for lifetime in [(1 << 32) + 1, usize::MAX] {
+            assert_eq!(
+                Signer::new(StdRng::seed_from_u64(0), 10, spec.clone(), lifetime).map(|_| ()),
+                Err(SignerError::InvalidLifetime {
+                    got: lifetime,
+                    reason: InvalidLifetimeReason::TooLarge
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn test_signer_rejects_invalid_configuration() {
+        let new_signer = |spec: Spec, lifetime| {
+            Signer::new(StdRng::seed_from_u64(0), 10, spec, lifetime).map(|_| ())
+        };
+
+        let unreachable = Spec {
+            target_sum: spec::SPEC_2.max_sum() + 1,
+            ..spec::SPEC_2
+        };
+        assert_eq!(
+            new_signer(unreachable, 4),
+            Err(SignerError::InvalidSpec(SpecError::TargetSumUnreachable {
+                target_sum: 541,
+                max_sum: 540
+            }))
+        );
+        assert_eq!(
+            new_signer(spec::SPEC_2, 0),
+            Err(SignerError::InvalidLifetime {
+                got: 0,
+                reason: InvalidLifetimeReason::Zero
+            })
+        );
+        assert_eq!(
+            new_signer(spec::SPEC_2, 0).unwrap_err().to_string(),
+            "lifetime 0 must be non-zero"
+        );
+        // The tree is padded to a power of two of leaves, see test_lifetime_not_power_of_two.
+        assert_eq!(new_signer(spec::SPEC_2, 3), Ok(()));
+        assert_eq!(
+            Signer::with_tree_height(StdRng::seed_from_u64(0), 10, spec::SPEC_2, 33).map(|_| ()),
+            Err(SignerError::InvalidTreeHeight { height: 33 })
+        );
+        assert_eq!(
+            Signer::with_tree_height(StdRng::seed_from_u64(0), 10, spec::SPEC_2, u32::MAX)
+                .map(|_| ()),
+            Err(SignerError::InvalidTreeHeight { height: u32::MAX })
+        );
+
+        let short_param = Param::random(spec::SPEC_2.param_len - 1, &mut StdRng::seed_from_u64(0));
+        assert_eq!(
+            Signer::new_with_param(StdRng::seed_from_u64(0), 10, spec::SPEC_2, 4, short_param)
+                .map(|_| ()),
+            Err(SignerError::ParamLength {
+                expected: spec::SPEC_2.param_len,
+                actual: spec::SPEC_2.param_len - 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_signer_memory_footprint() {
+        use core::mem::size_of;
+
+        let spec = spec::SPEC_2;
+        let signer = Signer::new(StdRng::seed_from_u64(0), 10, spec.clone(), 4).unwrap();
+        let key_hashes = spec.dimension() * size_of::<Hash>();
+        let footprint = signer.memory_footprint();
+        assert_eq!(
+            footprint.secret_bytes,
+            4 * (size_of::<Sk>() + spec.param_len + key_hashes)
+        );
+        assert_eq!(
+            footprint.public_key_bytes,
+            4 * (size_of::<Pk>() + spec.param_len + key_hashes)
+        );
+        // Levels of 4, 2 and 1 nodes
+        assert_eq!(
+            footprint.tree_bytes,
+            size_of::<HashTree>()
+                + 3 * size_of::<Vec<Hash>>()
+                + 7 * size_of::<Hash>()
+                + spec.param_len
+        );
+        assert_eq!(
+            footprint.total(),
+            footprint.secret_bytes + footprint.public_key_bytes + footprint.tree_bytes
+        );
+
+        // Only the tree depends on how authentication paths are computed
+        let traversal = signer.with_traversal().memory_footprint();
+        assert_eq!(traversal.secret_bytes, footprint.secret_bytes);
+        assert_ne!(traversal.tree_bytes, footprint.tree_bytes);
+    }
+
+    #[test]
+    fn test_keygen_progress() {
+        let lifetime = 2 * KEYGEN_PROGRESS_INTERVAL + 3;
+        let mut reports = Vec::new();
+        let signer = Signer::new_with_progress(
+            StdRng::seed_from_u64(0),
+            10,
+            spec::SPEC_1,
+            lifetime,
+            |progress| reports.push(progress),
+        )
+        .unwrap();
+
+        let epochs_done: Vec<_> = reports.iter().map(|p| p.epochs_done).collect();
+        assert_eq!(
+            epochs_done,
+            [
+                KEYGEN_PROGRESS_INTERVAL,
+                2 * KEYGEN_PROGRESS_INTERVAL,
+                lifetime,
+                lifetime
+            ]
+        );
+        assert!(reports.iter().all(|p| p.epochs_total == lifetime));
+        assert_eq!(reports.last().unwrap().phase, KeygenPhase::BuildTree);
+
+        // Reporting progress doesn't change the keys
+        let expected = Signer::new(StdRng::seed_from_u64(0), 10, spec::SPEC_1, lifetime).unwrap();
+        assert_eq!(signer.root, expected.root);
+    }
+
+    #[test]
+    fn test_lifetime_not_power_of_two() {
+        let spec = spec::SPEC_2;
+        let mut signer = Signer::new(StdRng::seed_from_u64(0), 1000000, spec.clone(), 5).unwrap();
+        let message = Message([10; 32]);
+
+        let sig = signer.sign(4, &message).unwrap();
+        assert!(verify_signature(
+            &spec,
+            &signer.param,
+            &message,
+            &sig,
+            &signer.root
+        ));
+        // Epochs 5 to 7 are padding in the hash tree and have no keys.
+        assert_eq!(
+            signer.sign(5, &message).map(drop),
+            Err(SignError::EpochOutOfRange {
+                epoch: 5,
+                lifetime: 5
+            })
+        );
+    }
+
+    #[test]
+    fn test_signer_sum_histogram() {
+        let spec = spec::SPEC_2;
+        let mut signer = Signer::new(StdRng::seed_from_u64(0), 10000, spec.clone(), 4)
+            .unwrap()
+            .with_sum_histogram(true);
+
+        signer.sign(0, &Message([1; 32])).expect("Failed to sign");
+        let histogram = signer.last_sum_histogram().unwrap();
+        assert_eq!(histogram.total(), signer.last_grind_attempts().unwrap());
+        assert_eq!(histogram.count(spec.target_sum), 1);
+
+        // A signer that gives up still reports what it saw.
+        let mut signer = Signer::new(StdRng::seed_from_u64(0), 3, spec, 4)
+            .unwrap()
+            .with_sum_histogram(true);
+        assert_eq!(
+            signer.sign(0, &Message([1; 32])).map(drop),
+            Err(SignError::GrindingFailed { max_retries: 3 })
+        );
+        assert_eq!(signer.last_sum_histogram().unwrap().total(), 3);
+    }
+
+    /// Keccak with every output perturbed, standing in for a second hash function.
+    #[derive(Clone)]
+    struct FlippedKeccak(KeccakBackend);
+
+    fn flip(mut hash: Hash) -> Hash {
+        hash.0[0] ^= 1;
+        hash
+    }
+
+    impl TweakableHash for FlippedKeccak {
+        const NAME: &'static str = "flipped-keccak256";
+
+        fn new(param: &Param) -> Self {
+            Self(KeccakBackend::new(param))
+        }
+
+        fn hash_message(&self, epoch: u32, message: &Message, nonce: &Nonce) -> Hash {
+            flip(self.0.hash_message(epoch, message, nonce))
+        }
+
+        fn hash_chain_step(
+            &self,
+            epoch: u32,
+            chain_index: u32,
+            pos_in_chain: u32,
+            hash: Hash,
+        ) -> Hash {
+            flip(
+                self.0
+                    .hash_chain_step(epoch, chain_index, pos_in_chain, hash),
+            )
+        }
+
+        fn hash_tree_node(&self, left: &Hash, right: &Hash, level: u32, index: u32) -> Hash {
+            flip(self.0.hash_tree_node(left, right, level, index))
+        }
+
+        fn hash_public_key(&self, epoch: u32, public_key: &Pk) -> Hash {
+            flip(self.0.hash_public_key(epoch, public_key))
+        }
+    }
+
+    #[test]
+    fn test_custom_backend() {
+        let spec = spec::SPEC_2;
+        let mut signer = Signer::<FlippedKeccak>::new_with_backend(
+            StdRng::seed_from_u64(0),
+            1000000,
+            spec.clone(),
+            4,
+        )
+        .unwrap();
+        let root = signer.root;
+        let param = signer.param.clone();
+        let message = Message([10; 32]);
+
+        let sig = signer.sign(1, &message).expect("Failed to sign");
+        assert!(verify_signature_with_backend::<FlippedKeccak>(
+            &spec, &param, &message, &sig, &root
+        ));
+        assert!(!verify_signature(&spec, &param, &message, &sig, &root));
+
+        let registry = ValidatorRegistry::new(vec![(root, param.clone())]);
+        let verifier =
+            AggregatedVerifier::<FlippedKeccak>::new_with_backend(registry.clone(), spec.clone());
+        let aggregated = AggregatedSignature::new(vec![ValidatorSignature {
+            epoch: 1,
+            signature: sig.into(),
+            xmss_root: root,
+            param,
+            validator_index: None,
+        }]);
+        assert!(verifier.verify(&message, &aggregated));
+        assert!(!AggregatedVerifier::new(registry, spec.clone()).verify(&message, &aggregated));
+
+        // A Keccak signer's signatures do not verify under the other backend either.
+        let mut signer = Signer::new(StdRng::seed_from_u64(0), 1000000, spec.clone(), 4).unwrap();
+        let sig = signer.sign(1, &message).expect("Failed to sign");
+        assert!(verify_signature(
+            &spec,
+            &signer.param,
+            &message,
+            &sig,
+            &signer.root
+        ));
+        assert!(!verify_signature_with_backend::<FlippedKeccak>(
+            &spec,
+            &signer.param,
+            &message,
+            &sig,
+            &signer.root
+        ));
+
+        assert_ne!(spec.id(), spec.id_with_backend::<FlippedKeccak>());
+        assert_eq!(spec.id(), spec.id_with_backend::<KeccakBackend>());
+    }
+
+    #[test]
+    fn test_sha3_backend() {
+        let spec = spec::SPEC_2;
+        let mut signer = Signer::<Sha3Backend>::new_with_backend(
+            StdRng::seed_from_u64(0),
+            1000000,
+            spec.clone(),
+            4,
+        )
+        .unwrap();
+        let root = signer.root;
+        let param = signer.param.clone();
+        let message = Message([10; 32]);
+
+        let sig = signer.sign(2, &message).expect("Failed to sign");
+        let bytes = bincode::serialize(&sig).unwrap();
+        let sig: Signature = bincode::deserialize(&bytes).unwrap();
+        assert!(verify_signature_with_backend::<Sha3Backend>(
+            &spec, &param, &message, &sig, &root
+        ));
+        assert!(!verify_signature_with_backend::<Sha3Backend>(
+            &spec,
+            &param,
+            &Message([11; 32]),
+            &sig,
+            &root
+        ));
+        assert!(!verify_signature(&spec, &param, &message, &sig, &root));
+
+        let mut keccak_signer =
+            Signer::new(StdRng::seed_from_u64(0), 1000000, spec.clone(), 4).unwrap();
+        let keccak_sig = keccak_signer.sign(2, &message).expect("Failed to sign");
+        assert!(!verify_signature_with_backend::<Sha3Backend>(
+            &spec,
+            &keccak_signer.param,
+            &message,
+            &keccak_sig,
+            &keccak_signer.root
+        ));
+
+        assert_ne!(spec.id(), spec.id_with_backend::<Sha3Backend>());
+    }
+
+    #[cfg(feature = "poseidon2")]
+    #[test]
+    fn test_poseidon2_backend() {
+        use crate::poseidon2::Poseidon2Backend;
+
+        let spec = spec::SPEC_2;
+        let mut signer = Signer::<Poseidon2Backend>::new_with_backend(
+            StdRng::seed_from_u64(0),
+            1000000,
+            spec.clone(),
+            4,
+        )
+        .unwrap();
+        let root = signer.root;
+        let param = signer.param.clone();
+        let message = Message([10; 32]);
+
+        let sig = signer.sign(3, &message).expect("Failed to sign");
+        assert!(verify_signature_with_backend::<Poseidon2Backend>(
+            &spec, &param, &message, &sig, &root
+        ));
+        assert!(!verify_signature_with_backend::<Poseidon2Backend>(
+            &spec,
+            &param,
+            &Message([11; 32]),
+            &sig,
+            &root
+        ));
+        assert!(!verify_signature(&spec, &param, &message, &sig, &root));
+        assert_ne!(spec.id(), spec.id_with_backend::<Poseidon2Backend>());
+    }
+
+    #[cfg(feature = "blake3")]
+    #[test]
+    fn test_blake3_backend() {
+        use crate::hash::Blake3Backend;
+
+        let spec = spec::SPEC_2;
+        let mut signer = Signer::<Blake3Backend>::new_with_backend(
+            StdRng::seed_from_u64(0),
+            1000000,
+            spec.clone(),
+            4,
+        )
+        .unwrap();
+        let message = Message([10; 32]);
+        let sig = signer.sign(0, &message).expect("Failed to sign");
+        assert!(verify_signature_with_backend::<Blake3Backend>(
+            &spec,
+            &signer.param,
+            &message,
+            &sig,
+            &signer.root
+        ));
+        assert!(!verify_signature(
+            &spec,
+            &signer.param,
+            &message,
+            &sig,
+            &signer.root
+        ));
+
+        let mut keccak_signer =
+            Signer::new(StdRng::seed_from_u64(0), 1000000, spec.clone(), 4).unwrap();
+        let keccak_sig = keccak_signer.sign(0, &message).expect("Failed to sign");
+        assert!(!verify_signature_with_backend::<Blake3Backend>(
+            &spec,
+            &keccak_signer.param,
+            &message,
+            &keccak_sig,
+            &keccak_signer.root
+        ));
+        assert_ne!(spec.id(), spec.id_with_backend::<Blake3Backend>());
+    }
+
+    #[test]
+    fn test_hex_round_trip() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let hash = Hash::random(&mut rng);
+        let param = Param::random(18, &mut rng);
+        let nonce = Nonce::random(&mut rng);
+        let message = Message([42; 32]);
+
+        assert_eq!(hash.to_string().len(), 64);
+        assert_eq!(format!("{hash:x}"), hash.to_string());
+        assert_eq!(hash.to_string().parse::<Hash>().unwrap(), hash);
+        assert_eq!(param.to_string().parse::<Param>().unwrap().data, param.data);
+        assert_eq!(nonce.to_string().parse::<Nonce>().unwrap().0, nonce.0);
+        assert_eq!(message.to_string(), "2a".repeat(32));
+        assert_eq!(
+            format!("0x{message:x}").parse::<Message>().unwrap(),
+            message
+        );
+
+        let json = serde_json::to_string(&(hash, &param, &nonce, message)).unwrap();
+        assert_eq!(
+            json,
+            format!("[\"0x{hash}\",\"0x{param}\",\"0x{nonce}\",\"0x{message}\"]")
+        );
+        let (h, p, n, m): (Hash, Param, Nonce, Message) = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            (h, p.data, n.0, m),
+            (hash, param.data.clone(), nonce.0, message)
+        );
+        // The prefix is optional when deserializing
+        let m: Message = serde_json::from_str(&format!("\"{message}\"")).unwrap();
+        assert_eq!(m, message);
+
+        // Binary formats keep the raw bytes.
+        let bytes = bincode::serialize(&(hash, &param, &nonce, message)).unwrap();
+        assert_eq!(bytes.len(), 32 + 8 + 18 + RAND_LEN + 32);
+        assert_eq!(&bytes[bytes.len() - 32..], &message.0);
+        let (h, p, n, m): (Hash, Param, Nonce, Message) = bincode::deserialize(&bytes).unwrap();
+        assert_eq!((h, p.data, n.0, m), (hash, param.data, nonce.0, message));
+
+        assert_eq!(
+            "00".parse::<Hash>().unwrap_err(),
+            ParseHexError::InvalidLength {
+                expected: 32,
+                actual: 1
+            }
+        );
+        let err = serde_json::from_str::<Hash>("\"zz\"").unwrap_err();
+        assert!(err.to_string().contains("invalid hex character"));
+    }
+
+    /// An aggregate of hand-built signatures, one of each kind, small enough to read as a fixture
+    fn fixture_aggregated_signature() -> AggregatedSignature {
+        let signature = |byte: u8| Signature {
+            signature: OtsSignature {
+                nonce: Nonce([byte; RAND_LEN]),
+                hashes: vec![Hash([byte + 1; 32]), Hash([byte + 2; 32])],
+            },
+            hash_tree_proof: hash_tree::HashTreeProof::new(1, vec![Hash([byte + 3; 32])]),
+            public_key: Pk {
+                param: Param::from(&[byte + 4; 18][..]),
+                end_hashes: vec![Hash([byte + 5; 32]), Hash([byte + 6; 32])],
+            },
+        };
+        let hyper_signature = hypertree::HyperSignature {
+            bottom: signature(0x20),
+            bottom_root: Hash([0x27; 32]),
+            bottom_param: Param::from(&[0x24; 18][..]),
+            top: signature(0x30),
+        };
+        AggregatedSignature::new(vec![
+            ValidatorSignature {
+                epoch: 1,
+                signature: signature(0x10).into(),
+                xmss_root: Hash([0x17; 32]),
+                param: Param::from(&[0x14; 18][..]),
+                validator_index: Some(0),
+            },
+            ValidatorSignature {
+                epoch: 3,
+                signature: hyper_signature.into(),
+                xmss_root: Hash([0x37; 32]),
+                param: Param::from(&[0x34; 18][..]),
+                validator_index: None,
+            },
+        ])
+    }
+
+    #[test]
+    fn test_aggregated_signature_json_fixture() {
+        let fixture = include_str!("../tests/fixtures/aggregated_signature.json");
+        let aggregated = fixture_aggregated_signature();
+        let json = serde_json::to_string_pretty(&aggregated).unwrap();
+        assert_eq!(json, fixture.trim_end());
+
+        let decoded: AggregatedSignature = serde_json::from_str(fixture).unwrap();
+        assert_eq!(serde_json::to_string_pretty(&decoded).unwrap(), json);
+        let bytes = bincode::serialize(&aggregated).unwrap();
+        let decoded: AggregatedSignature = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(bincode::serialize(&decoded).unwrap(), bytes);
+        // The bincode encoding of a hash is its bytes, not its hex.
+        assert!(bytes.windows(32).any(|window| window == [0x17; 32]));
+    }
+
+    #[test]
+    fn test_sign_with_cache() {
+        let spec = spec::SPEC_2;
+        let new_signer =
+            || Signer::new(StdRng::seed_from_u64(0), 1000000, spec.clone(), 4).unwrap();
+        let (mut signer, mut cached_signer) = (new_signer(), new_signer());
+        let message = Message([10; 32]);
+
+        let cache = cached_signer.chain_cache(2);
+        let (sk, pk) = &cached_signer.key_pairs[2];
+        let (derived_pk, derived_cache) = Pk::derive_with_cache(sk, &spec);
+        assert_eq!(derived_pk.end_hashes, pk.end_hashes);
+        assert!(derived_cache.end_hashes().eq(cache.end_hashes()));
+
+        let sig = signer.sign(2, &message).unwrap();
+        let cached_sig = cached_signer.sign_with_cache(2, &message, &cache).unwrap();
+        assert_eq!(
+            bincode::serialize(&sig).unwrap(),
+            bincode::serialize(&cached_sig).unwrap()
+        );
+        assert!(verify_signature(
+            &spec,
+            &cached_signer.param,
+            &message,
+            &cached_sig,
+            &cached_signer.root
+        ));
+    }
+
+    #[test]
+    fn test_verify_rejects_malformed_ots() {
+        let spec = spec::SPEC_2;
+        let mut signer = Signer::new(StdRng::seed_from_u64(0), 1000000, spec.clone(), 4).unwrap();
+        let message = Message([10; 32]);
+        let sig = signer.sign(1, &message).unwrap();
+        let pk = &sig.public_key;
+        let hasher = KeccakBackend::new(&pk.param);
+        let codeword = code::new_valid(&spec, &pk.param, &message, &sig.signature.nonce).unwrap();
+        let hashes = &sig.signature.hashes;
+        assert_eq!(
+            verify_ots_with_backend(&hasher, &spec, 1, &codeword, hashes, pk),
+            Ok(())
+        );
+
+        // A coordinate at or past the end of its chain is rejected rather than walked.
+        for bad_coord in [spec.chain_len() as u8, u8::MAX] {
+            let mut coords = codeword.coords().to_vec();
+            coords[3] = bad_coord;
+            let bad_codeword = Codeword::from_coords_unchecked(coords);
+            assert_eq!(
+                verify_ots_with_backend(&hasher, &spec, 1, &bad_codeword, hashes, pk),
+                Err(VerifyError::MalformedSignature)
+            );
+        }
+
+        // So are codewords and signatures of the wrong dimension.
+        let short_codeword = Codeword::from_coords_unchecked(codeword.coords()[1..].to_vec());
+        assert_eq!(
+            verify_ots_with_backend(&hasher, &spec, 1, &short_codeword, &hashes[1..], pk),
+            Err(VerifyError::MalformedSignature)
+        );
+        assert_eq!(
+            verify_ots_with_backend(&hasher, &spec, 1, &codeword, &hashes[1..], pk),
+            Err(VerifyError::MalformedSignature)
+        );
+
+        let mut truncated = sig.clone();
+        truncated.signature.hashes.pop();
+        assert!(!verify_signature(
+            &spec,
+            &signer.param,
+            &message,
+            &truncated,
+            &signer.root
+        ));
+    }
 }
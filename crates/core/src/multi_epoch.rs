@@ -0,0 +1,125 @@
+// Copyright 2025 Irreducible Inc.
+//! Signatures of one validator at several epochs, verified together.
+//!
+//! This proves in one go that a validator signed a message at each of a set of epochs, e.g. at
+//! epochs 5, 6 and 7. The tree proofs of the entries are checked jointly, so the ancestors of
+//! nearby epochs are hashed once.
+
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Message, Param, Signature,
+    hash::{Hash, KeccakBackend, TweakableHash},
+    spec::Spec,
+    verify_signatures_same_root_with_backend,
+};
+
+/// The signatures of one validator, each on its own message at its own epoch
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MultiEpochSignature {
+    /// The root of the validator's tree
+    pub root: Hash,
+    /// The parameter of the validator's tree
+    pub param: Param,
+    /// Every epoch with the message signed at it and the signature
+    pub entries: Vec<(u64, Message, Signature)>,
+}
+
+impl MultiEpochSignature {
+    /// The epochs of the entries with the messages signed at them
+    pub fn messages(&self) -> Vec<(u64, Message)> {
+        (self.entries.iter())
+            .map(|(epoch, message, _)| (*epoch, *message))
+            .collect()
+    }
+
+    /// Verify every entry against the shared root using Keccak-256
+    ///
+    /// See [`MultiEpochSignature::verify_with_backend`].
+    pub fn verify(&self, spec: &Spec) -> bool {
+        self.verify_with_backend::<KeccakBackend>(spec)
+    }
+
+    /// Verify every entry against the shared root using the hash backend `H`
+    ///
+    /// Returns `true` if there is at least one entry, the epochs are strictly increasing, every
+    /// signature is for the leaf of its epoch and every signature is valid for its message,
+    /// `false` otherwise.
+    pub fn verify_with_backend<H: TweakableHash>(&self, spec: &Spec) -> bool {
+        let epochs_increase = (self.entries.windows(2)).all(|pair| pair[0].0 < pair[1].0);
+        let leaves_match = (self.entries.iter())
+            .all(|(epoch, _, signature)| signature.hash_tree_proof.leaf_index() as u64 == *epoch);
+        let items: Vec<_> = (self.entries.iter())
+            .map(|(_, message, signature)| (*message, signature.clone()))
+            .collect();
+        epochs_increase
+            && leaves_match
+            && verify_signatures_same_root_with_backend::<H>(spec, &self.param, &self.root, &items)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::*;
+    use crate::{Signer, spec::SPEC_2};
+
+    fn sign_epochs(signer: &mut Signer, epochs: &[usize]) -> MultiEpochSignature {
+        let entries = (epochs.iter())
+            .map(|&epoch| {
+                let message = Message([epoch as u8; 32]);
+                let signature = signer.sign(epoch, &message).unwrap();
+                (epoch as u64, message, signature)
+            })
+            .collect();
+        MultiEpochSignature {
+            root: signer.root,
+            param: signer.param.clone(),
+            entries,
+        }
+    }
+
+    #[test]
+    fn test_multi_epoch_signature() {
+        let spec = SPEC_2;
+        let mut signer = Signer::new(StdRng::seed_from_u64(0), 10000, spec.clone(), 8).unwrap();
+        let signature = sign_epochs(&mut signer, &[5, 6, 7]);
+        assert!(signature.verify(&spec));
+        assert_eq!(
+            signature.messages(),
+            [5, 6, 7].map(|epoch| (epoch as u64, Message([epoch as u8; 32])))
+        );
+
+        // One invalid entry fails the whole signature.
+        let mut invalid = signature.clone();
+        invalid.entries[1].1 = Message([0; 32]);
+        assert!(!invalid.verify(&spec));
+
+        // An entry must be at the epoch of its leaf.
+        let mut relabeled = signature.clone();
+        relabeled.entries[0].0 = 4;
+        assert!(!relabeled.verify(&spec));
+
+        let empty = MultiEpochSignature {
+            entries: Vec::new(),
+            ..signature
+        };
+        assert!(!empty.verify(&spec));
+    }
+
+    #[test]
+    fn test_rejects_repeated_epochs() {
+        let spec = SPEC_2;
+        let mut signer = Signer::new(StdRng::seed_from_u64(0), 10000, spec.clone(), 8).unwrap();
+        let mut signature = sign_epochs(&mut signer, &[2, 3]);
+
+        let mut duplicated = signature.clone();
+        duplicated.entries.push(duplicated.entries[1].clone());
+        assert!(!duplicated.verify(&spec));
+
+        signature.entries.reverse();
+        assert!(!signature.verify(&spec));
+    }
+}
@@ -0,0 +1,329 @@
+// Copyright 2025 Irreducible Inc.
+//! The tweaked hash functions instantiated with Poseidon2 over BabyBear.
+//!
+//! Poseidon2 is an arithmetization-friendly permutation, so verifying inside a zkVM or a circuit
+//! is far cheaper than with Keccak. The permutation is the width-24 BabyBear instance of the
+//! Poseidon2 paper (x^7 S-box, 8 full and 21 partial rounds); the constants are those of the
+//! authors' reference implementation.
+//!
+//! Bytes are packed three to an element, which is injective since 2^24 < p, and the low three
+//! bytes of each output element form the digest. Each tweaked hash function is domain separated
+//! by a constant in the capacity, next to the input length in bytes.
+
+use alloc::vec;
+use core::fmt;
+
+use crate::{
+    Message, Nonce, Param, Pk,
+    hash::{HASH_LEN, Hash, TweakableHash},
+};
+
+/// The BabyBear prime `15 * 2^27 + 1`.
+const P: u32 = 0x7800_0001;
+
+/// The number of field elements in the state.
+const WIDTH: usize = 24;
+
+/// The number of state elements the input is absorbed into and the output squeezed from.
+const RATE: usize = 16;
+
+const FULL_ROUNDS: usize = 8;
+const PARTIAL_ROUNDS: usize = 21;
+
+/// The number of input bytes per element.
+const BYTES_PER_ELEMENT: usize = 3;
+
+/// The number of output elements needed for a digest.
+const DIGEST_ELEMENTS: usize = HASH_LEN.div_ceil(BYTES_PER_ELEMENT);
+
+const DOMAIN_CHAIN: u32 = 1;
+const DOMAIN_TREE_NODE: u32 = 2;
+const DOMAIN_MESSAGE: u32 = 3;
+const DOMAIN_PUBLIC_KEY: u32 = 4;
+
+/// The diagonal of the internal matrix minus the identity.
+const INTERNAL_DIAG_M_1: [u32; WIDTH] = [
+    0x409133f0, 0x1667a8a1, 0x06a6c7b6, 0x6f53160e, 0x273b11d1, 0x03176c5d, 0x72f9bbf9, 0x73ceba91,
+    0x5cdef81d, 0x01393285, 0x46daee06, 0x065d7ba6, 0x52d72d6f, 0x05dd05e0, 0x3bab4b63, 0x6ada3842,
+    0x2fc5fbec, 0x770d61b0, 0x5715aae9, 0x03ef0e90, 0x75b6c770, 0x242adf5f, 0x00d0ca4c, 0x36c0e388,
+];
+
+/// Round constants of the full rounds, the first half before and the second after the partial
+/// rounds.
+const FULL_ROUND_CONSTANTS: [[u32; WIDTH]; FULL_ROUNDS] = [
+    [
+        0x0fa20c37, 0x0795bb97, 0x12c60b9c, 0x0eabd88e, 0x096485ca, 0x07093527, 0x1b1d4e50,
+        0x30a01ace, 0x3bd86f5a, 0x69af7c28, 0x3f94775f, 0x731560e8, 0x465a0ecd, 0x574ef807,
+        0x62fd4870, 0x52ccfe44, 0x14772b14, 0x4dedf371, 0x260acd7c, 0x1f51dc58, 0x75125532,
+        0x686a4d7b, 0x54bac179, 0x31947706,
+    ],
+    [
+        0x29799d3b, 0x6e01ae90, 0x203a7a64, 0x4f7e25be, 0x72503f77, 0x45bd3b69, 0x769bd6b4,
+        0x5a867f08, 0x4fdba082, 0x251c4318, 0x28f06201, 0x6788c43a, 0x4c6d6a99, 0x357784a8,
+        0x2abaf051, 0x770f7de6, 0x1794b784, 0x4796c57a, 0x724b7a10, 0x449989a7, 0x64935cf1,
+        0x59e14aac, 0x0e620bb8, 0x3af5a33b,
+    ],
+    [
+        0x4465cc0e, 0x019df68f, 0x4af8d068, 0x08784f82, 0x0cefdeae, 0x6337a467, 0x32fa7a16,
+        0x486f62d6, 0x386a7480, 0x20f17c4a, 0x54e50da8, 0x2012cf03, 0x5fe52950, 0x09afb6cd,
+        0x2523044e, 0x5c54d0ef, 0x71c01f3c, 0x60b2c4fb, 0x4050b379, 0x5e6a70a5, 0x418543f5,
+        0x71debe56, 0x1aad2994, 0x3368a483,
+    ],
+    [
+        0x07a86f3a, 0x5ea43ff1, 0x2443780e, 0x4ce444f7, 0x146f9882, 0x3132b089, 0x197ea856,
+        0x667030c3, 0x2317d5dc, 0x0c2c48a7, 0x56b2df66, 0x67bd81e9, 0x4fcdfb19, 0x4baaef32,
+        0x0328d30a, 0x6235760d, 0x12432912, 0x0a49e258, 0x030e1b70, 0x48caeb03, 0x49e4d9e9,
+        0x1051b5c6, 0x6a36dbbe, 0x4cff27a5,
+    ],
+    [
+        0x032959ad, 0x2b18af6a, 0x55d3dc8c, 0x43bd26c8, 0x0c41595f, 0x7048d2e2, 0x00db8983,
+        0x2af563d7, 0x6e84758f, 0x611d64e1, 0x1f9977e2, 0x64163a0a, 0x5c5fc27b, 0x02e22561,
+        0x3a2d75db, 0x1ba7b71a, 0x34343f64, 0x7406b35d, 0x19df8299, 0x6ff4480a, 0x514a81c8,
+        0x57ab52ce, 0x6ad69f52, 0x3e0c0e0d,
+    ],
+    [
+        0x48126114, 0x2a9d62cc, 0x17441f23, 0x485762bb, 0x2f218674, 0x06fdc64a, 0x0861b7f2,
+        0x3b36eee6, 0x70a11040, 0x04b31737, 0x3722a872, 0x2a351c63, 0x623560dc, 0x62584ab2,
+        0x382c7c04, 0x3bf9edc7, 0x0e38fe51, 0x376f3b10, 0x5381e178, 0x3afc61c7, 0x5c1bcb4d,
+        0x6643ce1f, 0x2d0af1c1, 0x08f583cc,
+    ],
+    [
+        0x5d6ff60f, 0x6324c1e5, 0x74412fb7, 0x70c0192e, 0x0b72f141, 0x4067a111, 0x57388c4f,
+        0x351009ec, 0x0974c159, 0x539a58b3, 0x038c0cff, 0x476c0392, 0x3f7bc15f, 0x4491dd2c,
+        0x4d1fef55, 0x04936ae3, 0x58214dd4, 0x683c6aad, 0x1b42f16b, 0x6dc79135, 0x2d4e71ec,
+        0x3e2946ea, 0x59dce8db, 0x6cee892a,
+    ],
+    [
+        0x47f07350, 0x7106ce93, 0x3bd4a7a9, 0x2bfe636a, 0x430011e9, 0x001cd66a, 0x307faf5b,
+        0x0d9ef3fe, 0x6d40043a, 0x2e8f470c, 0x1b6865e8, 0x0c0e6c01, 0x4d41981f, 0x423b9d3d,
+        0x410408cc, 0x263f0884, 0x5311bbd0, 0x4dae58d8, 0x30401cea, 0x09afa575, 0x4b3d5b42,
+        0x63ac0b37, 0x5fe5bb14, 0x5244e9d4,
+    ],
+];
+
+/// Round constants of the partial rounds, added to the first element only.
+const PARTIAL_ROUND_CONSTANTS: [u32; PARTIAL_ROUNDS] = [
+    0x1da78ec2, 0x730b0924, 0x3eb56cf3, 0x5bd93073, 0x37204c97, 0x51642d89, 0x66e943e8, 0x1a3e72de,
+    0x70beb1e9, 0x30ff3b3f, 0x4240d1c4, 0x12647b8d, 0x65d86965, 0x49ef4d7c, 0x47785697, 0x46b3969f,
+    0x5c7b7a0e, 0x7078fc60, 0x4f22d482, 0x482a9aee, 0x6beb839d,
+];
+
+fn add(a: u32, b: u32) -> u32 {
+    let sum = a + b;
+    if sum >= P { sum - P } else { sum }
+}
+
+fn mul(a: u32, b: u32) -> u32 {
+    ((a as u64 * b as u64) % P as u64) as u32
+}
+
+fn sbox(x: u32) -> u32 {
+    let x2 = mul(x, x);
+    let x4 = mul(x2, x2);
+    mul(mul(x4, x2), x)
+}
+
+/// Multiplies four elements by the 4x4 MDS matrix of the paper.
+fn mat_m4(chunk: &mut [u32]) {
+    let t0 = add(chunk[0], chunk[1]);
+    let t1 = add(chunk[2], chunk[3]);
+    let t2 = add(add(chunk[1], chunk[1]), t1);
+    let t3 = add(add(chunk[3], chunk[3]), t0);
+    let t4 = add(mul(t1, 4), t3);
+    let t5 = add(mul(t0, 4), t2);
+    chunk[0] = add(t3, t5);
+    chunk[1] = t5;
+    chunk[2] = add(t2, t4);
+    chunk[3] = t4;
+}
+
+fn external_layer(state: &mut [u32; WIDTH]) {
+    for chunk in state.as_chunks_mut::<4>().0.iter_mut() {
+        mat_m4(chunk);
+    }
+    let mut sums = [0u32; 4];
+    for chunk in state.as_chunks::<4>().0 {
+        for (sum, &x) in sums.iter_mut().zip(chunk) {
+            *sum = add(*sum, x);
+        }
+    }
+    for (i, x) in state.iter_mut().enumerate() {
+        *x = add(*x, sums[i % 4]);
+    }
+}
+
+fn internal_layer(state: &mut [u32; WIDTH]) {
+    let sum = state.iter().fold(0, |acc, &x| add(acc, x));
+    for (x, &diag) in state.iter_mut().zip(INTERNAL_DIAG_M_1.iter()) {
+        *x = add(mul(*x, diag), sum);
+    }
+}
+
+fn full_round(state: &mut [u32; WIDTH], round_constants: &[u32; WIDTH]) {
+    for (x, &rc) in state.iter_mut().zip(round_constants) {
+        *x = sbox(add(*x, rc));
+    }
+    external_layer(state);
+}
+
+/// The Poseidon2 permutation.
+fn permute(state: &mut [u32; WIDTH]) {
+    external_layer(state);
+    let (first, last) = FULL_ROUND_CONSTANTS.split_at(FULL_ROUNDS / 2);
+    for round_constants in first {
+        full_round(state, round_constants);
+    }
+    for &rc in PARTIAL_ROUND_CONSTANTS.iter() {
+        state[0] = sbox(add(state[0], rc));
+        internal_layer(state);
+    }
+    for round_constants in last {
+        full_round(state, round_constants);
+    }
+}
+
+/// Hashes the concatenation of `parts` under `domain`.
+fn sponge(domain: u32, parts: &[&[u8]]) -> Hash {
+    let len: usize = parts.iter().map(|part| part.len()).sum();
+    let mut state = [0u32; WIDTH];
+    state[RATE] = domain;
+    state[RATE + 1] = u32::try_from(len).expect("input is shorter than 4 GiB") % P;
+
+    let mut bytes = parts
+        .iter()
+        .flat_map(|part| part.iter().copied())
+        .peekable();
+    loop {
+        for x in &mut state[..RATE] {
+            let mut element = [0u8; 4];
+            for byte in &mut element[..BYTES_PER_ELEMENT] {
+                *byte = bytes.next().unwrap_or(0);
+            }
+            *x = add(*x, u32::from_le_bytes(element));
+        }
+        permute(&mut state);
+        if bytes.peek().is_none() {
+            break;
+        }
+    }
+
+    let mut digest = [0u8; DIGEST_ELEMENTS * BYTES_PER_ELEMENT];
+    for (chunk, x) in (digest.as_chunks_mut::<BYTES_PER_ELEMENT>().0.iter_mut()).zip(state) {
+        chunk.copy_from_slice(&x.to_le_bytes()[..BYTES_PER_ELEMENT]);
+    }
+    let mut hash = [0u8; HASH_LEN];
+    hash.copy_from_slice(&digest[..HASH_LEN]);
+    Hash(hash)
+}
+
+/// The tweaked hash functions instantiated with Poseidon2 over BabyBear.
+#[derive(Clone)]
+pub struct Poseidon2Backend {
+    param: Param,
+}
+
+impl fmt::Debug for Poseidon2Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Poseidon2Backend").finish_non_exhaustive()
+    }
+}
+
+impl TweakableHash for Poseidon2Backend {
+    const NAME: &'static str = "poseidon2-babybear-24";
+
+    fn new(param: &Param) -> Self {
+        Self {
+            param: param.clone(),
+        }
+    }
+
+    fn hash_message(&self, _epoch: u32, message: &Message, nonce: &Nonce) -> Hash {
+        sponge(
+            DOMAIN_MESSAGE,
+            &[self.param.as_ref(), nonce.as_ref(), message.as_ref()],
+        )
+    }
+
+    fn hash_chain_step(
+        &self,
+        _epoch: u32,
+        chain_index: u32,
+        pos_in_chain: u32,
+        hash: Hash,
+    ) -> Hash {
+        sponge(
+            DOMAIN_CHAIN,
+            &[
+                self.param.as_ref(),
+                hash.as_ref(),
+                &u64::from(chain_index).to_be_bytes(),
+                &u64::from(pos_in_chain).to_be_bytes(),
+            ],
+        )
+    }
+
+    fn hash_tree_node(&self, left: &Hash, right: &Hash, level: u32, index: u32) -> Hash {
+        sponge(
+            DOMAIN_TREE_NODE,
+            &[
+                self.param.as_ref(),
+                &level.to_be_bytes(),
+                &index.to_be_bytes(),
+                left.as_ref(),
+                right.as_ref(),
+            ],
+        )
+    }
+
+    fn hash_public_key(&self, _epoch: u32, public_key: &Pk) -> Hash {
+        let mut parts = vec![self.param.as_ref()];
+        parts.extend(public_key.end_hashes.iter().map(|hash| hash.as_ref()));
+        sponge(DOMAIN_PUBLIC_KEY, &parts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::*;
+
+    #[test]
+    fn test_permutation_known_answer() {
+        // From the test vectors of the reference implementation.
+        let mut state: [u32; WIDTH] = core::array::from_fn(|i| i as u32);
+        permute(&mut state);
+        assert_eq!(
+            state,
+            [
+                0x2ed3e23d, 0x12921fb0, 0x0e659e79, 0x61d81dc9, 0x32bae33b, 0x62486ae3, 0x1e681b60,
+                0x24b91325, 0x2a2ef5b9, 0x50e8593e, 0x5bc818ec, 0x10691997, 0x35a14520, 0x2ba6a3c5,
+                0x279d47ec, 0x55014e81, 0x5953a67f, 0x2f403111, 0x6b8828ff, 0x1801301f, 0x2749207a,
+                0x3dc9cf21, 0x3c985ba2, 0x57a99864,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_domain_and_length_separation() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let param = Param::random(18, &mut rng);
+        let hash = Hash::random(&mut rng);
+        let backend = Poseidon2Backend::new(&param);
+
+        assert_ne!(
+            backend.hash_chain_step(0, 0, 1, hash),
+            backend.hash_chain_step(0, 0, 2, hash)
+        );
+        assert_ne!(
+            sponge(DOMAIN_CHAIN, &[&hash.0]),
+            sponge(DOMAIN_TREE_NODE, &[&hash.0])
+        );
+        // Trailing zero bytes are absorbed as zero elements, only the length tells them apart.
+        assert_ne!(
+            sponge(DOMAIN_CHAIN, &[&[1]]),
+            sponge(DOMAIN_CHAIN, &[&[1, 0]])
+        );
+        assert_ne!(sponge(DOMAIN_CHAIN, &[]), sponge(DOMAIN_CHAIN, &[&[0; 48]]));
+    }
+}
@@ -0,0 +1,187 @@
+// Copyright 2025 Irreducible Inc.
+//! The validators an [`AggregatedVerifier`](crate::AggregatedVerifier) accepts signatures from.
+//!
+//! A validator is registered with the param of its tree, so that signatures are verified under
+//! the param the validator committed to rather than the one an aggregate claims for it.
+
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+use crate::{Param, hash::Hash};
+
+/// The registered validators, each with its root, param and weight
+///
+/// The order of registration defines the validator indices, see
+/// [`crate::ValidatorSignature::validator_index`]. Lookups by root use a sorted table rather than
+/// a hash map, which keeps them free of std's randomized hashing that zkVM guests would otherwise
+/// pay for.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(from = "Vec<(Hash, Param, u64)>", into = "Vec<(Hash, Param, u64)>")]
+pub struct ValidatorRegistry {
+    /// The roots in index order
+    roots: Vec<Hash>,
+    /// The param of every root, in index order
+    params: Vec<Param>,
+    /// The weight of every root, in index order
+    weights: Vec<u64>,
+    /// Every root with its index, sorted by root
+    lookup: Vec<(Hash, usize)>,
+}
+
+impl ValidatorRegistry {
+    /// Registers the validators in order, every one with a weight of 1
+    ///
+    /// A root listed more than once keeps its first param.
+    pub fn new(validators: Vec<(Hash, Param)>) -> Self {
+        validators.into_iter().collect()
+    }
+
+    /// Registers the validators in order, every one with a weight, e.g. its stake, for
+    /// [`crate::AggregatedVerifier::verify_weighted`]
+    ///
+    /// A root listed more than once keeps its first param and weight.
+    pub fn with_weights(validators: Vec<(Hash, Param, u64)>) -> Self {
+        let mut registry = Self::default();
+        for (root, param, weight) in validators {
+            registry.insert(root, param, weight);
+        }
+        registry
+    }
+
+    /// The number of registered validators
+    pub fn len(&self) -> usize {
+        self.roots.len()
+    }
+
+    /// Whether no validator is registered
+    pub fn is_empty(&self) -> bool {
+        self.roots.is_empty()
+    }
+
+    /// The registered roots in index order
+    pub fn roots(&self) -> &[Hash] {
+        &self.roots
+    }
+
+    /// The index of `root`, or None if it is not registered
+    pub fn index_of(&self, root: &Hash) -> Option<usize> {
+        let position = (self.lookup)
+            .binary_search_by_key(root, |(root, _)| *root)
+            .ok()?;
+        Some(self.lookup[position].1)
+    }
+
+    /// Whether `root` is registered
+    pub fn contains(&self, root: &Hash) -> bool {
+        self.index_of(root).is_some()
+    }
+
+    /// The param `root` was registered with, or None if it is not registered
+    pub fn param(&self, root: &Hash) -> Option<&Param> {
+        Some(&self.params[self.index_of(root)?])
+    }
+
+    /// The weight of `root`, or None if it is not registered
+    pub fn weight(&self, root: &Hash) -> Option<u64> {
+        Some(self.weights[self.index_of(root)?])
+    }
+
+    /// Registers `root` with `param` and `weight` at the next index, unless it is already
+    /// registered
+    ///
+    /// Returns whether the validator was added.
+    pub fn insert(&mut self, root: Hash, param: Param, weight: u64) -> bool {
+        match (self.lookup).binary_search_by_key(&root, |(root, _)| *root) {
+            Ok(_) => false,
+            Err(position) => {
+                self.lookup.insert(position, (root, self.roots.len()));
+                self.roots.push(root);
+                self.params.push(param);
+                self.weights.push(weight);
+                true
+            }
+        }
+    }
+
+    /// Unregisters `root`
+    ///
+    /// The indices of the validators registered after it move down by one. Returns whether the
+    /// root was registered.
+    pub fn remove(&mut self, root: &Hash) -> bool {
+        let Ok(position) = (self.lookup).binary_search_by_key(root, |(root, _)| *root) else {
+            return false;
+        };
+        let (_, index) = self.lookup.remove(position);
+        self.roots.remove(index);
+        self.params.remove(index);
+        self.weights.remove(index);
+        for (_, later) in self.lookup.iter_mut().filter(|(_, later)| *later > index) {
+            *later -= 1;
+        }
+        true
+    }
+}
+
+impl FromIterator<(Hash, Param)> for ValidatorRegistry {
+    fn from_iter<I: IntoIterator<Item = (Hash, Param)>>(validators: I) -> Self {
+        let mut registry = Self::default();
+        for (root, param) in validators {
+            registry.insert(root, param, 1);
+        }
+        registry
+    }
+}
+
+impl From<Vec<(Hash, Param, u64)>> for ValidatorRegistry {
+    fn from(validators: Vec<(Hash, Param, u64)>) -> Self {
+        Self::with_weights(validators)
+    }
+}
+
+impl From<ValidatorRegistry> for Vec<(Hash, Param, u64)> {
+    fn from(registry: ValidatorRegistry) -> Self {
+        (registry.roots.into_iter())
+            .zip(registry.params)
+            .zip(registry.weights)
+            .map(|((root, param), weight)| (root, param, weight))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::*;
+
+    #[test]
+    fn test_registry() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let validators: Vec<_> = (0..4)
+            .map(|weight| (Hash::random(&mut rng), Param::random(18, &mut rng), weight))
+            .collect();
+        let mut registry = ValidatorRegistry::with_weights(validators.clone());
+        assert_eq!(registry.len(), 4);
+        for (index, (root, param, weight)) in validators.iter().enumerate() {
+            assert_eq!(registry.index_of(root), Some(index));
+            assert_eq!(registry.param(root), Some(param));
+            assert_eq!(registry.weight(root), Some(*weight));
+        }
+
+        // A repeated root keeps its first param.
+        let (root, _, _) = &validators[1];
+        assert!(!registry.insert(*root, Param::random(18, &mut rng), 7));
+        assert_eq!(registry.param(root), Some(&validators[1].1));
+
+        // Removing a validator moves the later ones down.
+        assert!(registry.remove(root));
+        assert!(!registry.remove(root));
+        assert!(!registry.contains(root));
+        assert_eq!(registry.index_of(&validators[3].0), Some(2));
+        assert_eq!(registry.param(&validators[3].0), Some(&validators[3].1));
+
+        let entries: Vec<(Hash, Param, u64)> = registry.clone().into();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(ValidatorRegistry::from(entries).roots(), registry.roots());
+    }
+}
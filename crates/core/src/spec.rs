@@ -1,9 +1,50 @@
 // Copyright 2025 Irreducible Inc.
+use alloc::vec;
+use core::fmt;
+
 use serde::{Deserialize, Serialize};
+use tiny_keccak::{Hasher, Keccak};
+
+use crate::hash::{HASH_LEN, Hash, KeccakBackend, TweakableHash};
+
+/// Domain separator for [`Spec::id`].
+///
+/// The version is bumped whenever the tweaked hashes change, so that ids of incompatible
+/// instantiations differ. Version 2 gave public-key leaves their own tweak.
+const SPEC_ID_DOMAIN: &[u8] = b"leansig-spec-v2";
+
+/// The log2 of the probability that grinding gives up after [`Spec::suggested_max_retries`]
+/// attempts, i.e. that a signer fails to sign a message.
+///
+/// At `2^-40`, a validator signing every few seconds would not expect to fail once in its
+/// lifetime.
+pub const SUGGESTED_GIVE_UP_PROBABILITY_LOG2: u32 = 40;
+
+/// Which codeword sums are accepted, relative to [`Spec::target_sum`].
+///
+/// Target-sum Winternitz is only secure if every valid codeword has the same sum: then no valid
+/// codeword is at or past another one in every coordinate. Were larger sums accepted, a forger
+/// holding a signature could grind for a codeword dominating it and walk the chains forward, so
+/// `sum == target_sum` is the only rule. It is still carried in the serialized specs and bound
+/// into [`Spec::id`], so that their layouts stay as they are.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Acceptance {
+    /// Only `sum == target_sum` is accepted.
+    #[default]
+    Exact,
+}
 
 /// Specification for the signature scheme instantiation.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+///
+/// The fields are `usize` lengths and sums, which serde serializes as `u64` whatever the pointer
+/// width, so a spec has the same bytes on the 32-bit zkVM guests as on the hosts. A field past
+/// `u32::MAX` fails to deserialize on a guest rather than wrapping, and [`Spec::validate`] keeps
+/// every field but the param length far below that.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Spec {
+    /// The number of bytes of the message hash that are used to derive the codeword.
+    ///
+    /// Must not exceed the hash output length.
     pub message_hash_len: usize,
     /// The number of bits per each coordinate in a codeword.
     ///
@@ -13,9 +54,172 @@ pub struct Spec {
     pub param_len: usize,
     /// The sum of all coordinates of a vertex of a signature that we accept.
     pub target_sum: usize,
+    /// Which sums are accepted, see [`Acceptance`].
+    ///
+    /// The signer and the verifier both go through [`Spec::accepts`], so they cannot disagree.
+    #[serde(default)]
+    pub acceptance: Acceptance,
+}
+
+/// The reasons a [`Spec`] can be rejected.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SpecError {
+    /// The message hash is longer than the hash output.
+    MessageHashTooLong { message_hash_len: usize },
+    /// The message hash is empty.
+    EmptyMessageHash,
+    /// The coordinate resolution is not a power of two between 1 and 8 bits.
+    InvalidCoordinateResolution { coordinate_resolution_bits: usize },
+    /// The target sum exceeds the largest possible coordinate sum, so no codeword can hit it.
+    TargetSumUnreachable { target_sum: usize, max_sum: usize },
+    /// A codeword does not have one coordinate per chain.
+    DimensionMismatch { expected: usize, actual: usize },
+    /// A codeword coordinate is past the end of its chain.
+    CoordinateOutOfRange {
+        index: usize,
+        coordinate: u8,
+        chain_len: usize,
+    },
 }
 
+impl fmt::Display for SpecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpecError::MessageHashTooLong { message_hash_len } => write!(
+                f,
+                "message hash length {message_hash_len} exceeds the hash output length {HASH_LEN}"
+            ),
+            SpecError::EmptyMessageHash => write!(f, "message hash length must be non-zero"),
+            SpecError::InvalidCoordinateResolution {
+                coordinate_resolution_bits,
+            } => write!(
+                f,
+                "coordinate resolution of {coordinate_resolution_bits} bits is not one of 1, 2, 4 or 8"
+            ),
+            SpecError::TargetSumUnreachable {
+                target_sum,
+                max_sum,
+            } => write!(
+                f,
+                "target sum {target_sum} is unreachable, the largest coordinate sum is {max_sum}"
+            ),
+            SpecError::DimensionMismatch { expected, actual } => {
+                write!(f, "expected {expected} coordinates, got {actual}")
+            }
+            SpecError::CoordinateOutOfRange {
+                index,
+                coordinate,
+                chain_len,
+            } => write!(
+                f,
+                "coordinate {index} is {coordinate}, which is past the end of a chain of length {chain_len}"
+            ),
+        }
+    }
+}
+
+impl core::error::Error for SpecError {}
+
 impl Spec {
+    /// Creates a new spec, checking that the parameters describe a valid instantiation.
+    pub fn new(
+        message_hash_len: usize,
+        coordinate_resolution_bits: usize,
+        param_len: usize,
+        target_sum: usize,
+    ) -> Result<Self, SpecError> {
+        let spec = Self {
+            message_hash_len,
+            coordinate_resolution_bits,
+            param_len,
+            target_sum,
+            acceptance: Acceptance::Exact,
+        };
+        spec.validate()?;
+        Ok(spec)
+    }
+
+    /// Returns the spec with the given acceptance rule.
+    pub fn with_acceptance(self, acceptance: Acceptance) -> Self {
+        Self { acceptance, ..self }
+    }
+
+    /// Returns `true` if a codeword with the given coordinate sum is acceptable.
+    pub fn accepts(&self, sum: usize) -> bool {
+        match self.acceptance {
+            Acceptance::Exact => sum == self.target_sum,
+        }
+    }
+
+    /// A digest identifying this instantiation, including its acceptance rule.
+    ///
+    /// Proofs that commit to the id are bound to the exact rule the verifier applied.
+    pub fn id(&self) -> Hash {
+        self.id_with_backend::<KeccakBackend>()
+    }
+
+    /// Same as [`Spec::id`], but for an instantiation over the hash backend `H`.
+    ///
+    /// The backend name is part of the digest, so the same parameters over different backends
+    /// have different ids.
+    pub fn id_with_backend<H: TweakableHash>(&self) -> Hash {
+        let mut hasher = Keccak::v256();
+        hasher.update(SPEC_ID_DOMAIN);
+        hasher.update(&(H::NAME.len() as u64).to_be_bytes());
+        hasher.update(H::NAME.as_bytes());
+        for value in [
+            self.message_hash_len,
+            self.coordinate_resolution_bits,
+            self.param_len,
+            self.target_sum,
+        ] {
+            hasher.update(&(value as u64).to_be_bytes());
+        }
+        // A tag and a width, as the id was laid out when it could bind other rules
+        let (tag, width) = match self.acceptance {
+            Acceptance::Exact => (0u8, 0u64),
+        };
+        hasher.update(&[tag]);
+        hasher.update(&width.to_be_bytes());
+        let mut id = [0u8; HASH_LEN];
+        hasher.finalize(&mut id);
+        Hash(id)
+    }
+
+    /// Checks the invariants the rest of the scheme relies upon.
+    pub fn validate(&self) -> Result<(), SpecError> {
+        if self.message_hash_len == 0 {
+            return Err(SpecError::EmptyMessageHash);
+        }
+        if self.message_hash_len > HASH_LEN {
+            return Err(SpecError::MessageHashTooLong {
+                message_hash_len: self.message_hash_len,
+            });
+        }
+        // A power of two no larger than 8 always divides the bit length of the message hash,
+        // so the dimension is exact.
+        if self.coordinate_resolution_bits > 8 || !self.coordinate_resolution_bits.is_power_of_two()
+        {
+            return Err(SpecError::InvalidCoordinateResolution {
+                coordinate_resolution_bits: self.coordinate_resolution_bits,
+            });
+        }
+        let max_sum = self.max_sum();
+        if self.target_sum > max_sum {
+            return Err(SpecError::TargetSumUnreachable {
+                target_sum: self.target_sum,
+                max_sum,
+            });
+        }
+        Ok(())
+    }
+
+    /// The largest possible sum of codeword coordinates, reached when every coordinate is at the
+    /// end of its chain.
+    pub fn max_sum(&self) -> usize {
+        self.dimension() * (self.chain_len() - 1)
+    }
+
     /// The dimension of the hypercube.
     ///
     /// This is the same as the number of chains.
@@ -27,6 +231,51 @@ impl Spec {
     pub fn chain_len(&self) -> usize {
         1 << self.coordinate_resolution_bits
     }
+
+    /// The probability that a single grinding attempt produces an acceptable codeword.
+    ///
+    /// The coordinates of a codeword are independent and uniform over the chain positions, so
+    /// this is exact up to floating point rounding.
+    pub fn acceptance_probability(&self) -> f64 {
+        self.sum_probability(true)
+    }
+
+    /// The probability that a codeword's sum is accepted, or rejected.
+    fn sum_probability(&self, accepted: bool) -> f64 {
+        // The distribution of the sum of the first coordinates, one coordinate at a time
+        let mut sums = vec![1.0];
+        let coordinate = 1.0 / self.chain_len() as f64;
+        for _ in 0..self.dimension() {
+            let mut next = vec![0.0; sums.len() + self.chain_len() - 1];
+            for (sum, probability) in sums.iter().enumerate() {
+                for value in &mut next[sum..sum + self.chain_len()] {
+                    *value += probability * coordinate;
+                }
+            }
+            sums = next;
+        }
+        (sums.iter().enumerate())
+            .filter(|(sum, _)| self.accepts(*sum) == accepted)
+            .map(|(_, probability)| probability)
+            .sum()
+    }
+
+    /// The number of grinding attempts after which a signer should give up, such that it gives
+    /// up with probability at most `2^-`[`SUGGESTED_GIVE_UP_PROBABILITY_LOG2`].
+    ///
+    /// This is 1275 for [`SPEC_1`] and 3074 for [`SPEC_2`], well below the 10000 attempts the
+    /// test data is signed with. `examples/grind_stats.rs` checks it against the attempts
+    /// actually needed, see [`crate::code::attempt_statistics`].
+    #[cfg(feature = "std")]
+    pub fn suggested_max_retries(&self) -> usize {
+        // The attempts fail independently, so n of them all fail with probability rejection^n
+        let rejection = self.sum_probability(false);
+        if rejection == 0.0 {
+            return 1;
+        }
+        let give_up = SUGGESTED_GIVE_UP_PROBABILITY_LOG2 as f64 * core::f64::consts::LN_2;
+        (give_up / -rejection.ln()).ceil().max(1.0) as usize
+    }
 }
 
 pub const SPEC_1: Spec = Spec {
@@ -34,6 +283,7 @@ pub const SPEC_1: Spec = Spec {
     coordinate_resolution_bits: 2,
     param_len: 18,
     target_sum: 119,
+    acceptance: Acceptance::Exact,
 };
 
 pub const SPEC_2: Spec = Spec {
@@ -41,4 +291,90 @@ pub const SPEC_2: Spec = Spec {
     coordinate_resolution_bits: 4,
     param_len: 18,
     target_sum: 297,
+    acceptance: Acceptance::Exact,
 };
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_specs_are_valid() {
+        assert_eq!(SPEC_1.validate(), Ok(()));
+        assert_eq!(SPEC_2.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_message_hash_len_bounds() {
+        assert!(Spec::new(16, 4, 18, 240).is_ok());
+        assert!(Spec::new(32, 4, 18, 480).is_ok());
+        assert_eq!(
+            Spec::new(33, 4, 18, 480).unwrap_err(),
+            SpecError::MessageHashTooLong {
+                message_hash_len: 33
+            }
+        );
+        assert_eq!(
+            Spec::new(0, 4, 18, 0).unwrap_err(),
+            SpecError::EmptyMessageHash
+        );
+    }
+
+    #[test]
+    fn test_coordinate_resolution() {
+        for bits in [1, 2, 4, 8] {
+            let spec = Spec::new(24, bits, 18, 0).unwrap();
+            assert_eq!(spec.dimension() * bits, 24 * 8);
+        }
+        for bits in [0, 3, 16] {
+            assert_eq!(
+                Spec::new(24, bits, 18, 0).unwrap_err(),
+                SpecError::InvalidCoordinateResolution {
+                    coordinate_resolution_bits: bits
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_target_sum_unreachable() {
+        // 16 bytes at 4 bits give 32 chains of length 16.
+        assert!(Spec::new(16, 4, 18, 480).is_ok());
+        assert_eq!(
+            Spec::new(16, 4, 18, 481).unwrap_err(),
+            SpecError::TargetSumUnreachable {
+                target_sum: 481,
+                max_sum: 480
+            }
+        );
+    }
+
+    #[test]
+    fn test_acceptance() {
+        let target = SPEC_2.target_sum;
+        for (sum, accepted) in [
+            (target - 1, false),
+            (target, true),
+            (target + 1, false),
+            (target + 2, false),
+        ] {
+            assert_eq!(SPEC_2.accepts(sum), accepted);
+        }
+    }
+
+    #[test]
+    fn test_suggested_max_retries() {
+        for spec in [SPEC_1, SPEC_2] {
+            let probability = spec.acceptance_probability();
+            assert!(probability > 0.0 && probability < 1.0);
+        }
+        assert_eq!(SPEC_1.suggested_max_retries(), 1275);
+        assert_eq!(SPEC_2.suggested_max_retries(), 3074);
+    }
+
+    #[test]
+    fn test_id() {
+        assert_ne!(SPEC_1.id(), SPEC_2.id());
+        assert_eq!(SPEC_2.id(), SPEC_2.clone().id());
+    }
+}
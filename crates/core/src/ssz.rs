@@ -0,0 +1,917 @@
+// Copyright 2025 Irreducible Inc.
+//! SimpleSerialize (SSZ) encodings of keys, signatures, aggregates and specs, with their hash
+//! tree roots, for integration with Ethereum consensus clients.
+//!
+//! Every list has an explicit maximum length, derived from the bounds [`Spec::validate`] puts on
+//! a spec where there is one:
+//!
+//! | Type                                      | SSZ type                                      |
+//! |-------------------------------------------|-----------------------------------------------|
+//! | [`Hash`], [`Message`]                     | `Vector[uint8, 32]`                           |
+//! | [`Nonce`]                                 | `Vector[uint8, 23]`                           |
+//! | [`Param`]                                 | `List[uint8, MAX_PARAM_LEN]`                  |
+//! | [`Pk`]                                    | `{param, end_hashes: List[Hash, MAX_CHAINS]}` |
+//! | [`OtsSignature`]                          | `{nonce, hashes: List[Hash, MAX_CHAINS]}`     |
+//! | [`HashTreeProof`]                         | `{leaf_index: uint64, path: List[Hash, MAX_TREE_HEIGHT]}` |
+//! | [`Signature`]                             | `{signature, hash_tree_proof, public_key}`    |
+//! | [`HyperSignature`]                        | `{bottom, bottom_root, bottom_param, top}`    |
+//! | [`AnySignature`]                          | `Union[Signature, HyperSignature]`            |
+//! | [`ValidatorSignature`]                    | `{epoch: uint64, signature, xmss_root, param, validator_index: Union[None, uint32]}` |
+//! | [`AggregatedSignature`]                   | `{signatures: List[ValidatorSignature, MAX_VALIDATORS]}` |
+//! | [`Spec`]                                  | `{message_hash_len, coordinate_resolution_bits, param_len, target_sum: uint64, acceptance: {kind: uint8, width: uint64}}` |
+//!
+//! Decoding rejects lists longer than their maximum. The hash tree roots follow the
+//! merkleization of the consensus specs, over SHA-256 whatever the hash backend of the scheme.
+
+use sha2::{Digest, Sha256};
+use ssz::{BYTES_PER_LENGTH_OFFSET, SszDecoderBuilder, SszEncoder, split_union_bytes};
+pub use ssz::{Decode, DecodeError, Encode};
+
+use crate::{
+    AggregatedSignature, AnySignature, Message, Nonce, OtsSignature, Param, Pk, RAND_LEN,
+    Signature, ValidatorSignature,
+    hash::{HASH_LEN, Hash},
+    hash_tree::HashTreeProof,
+    hypertree::HyperSignature,
+    spec::{Acceptance, Spec},
+};
+
+/// The most bytes of a [`Param`], one hash worth, which the params of the specs stay below
+pub const MAX_PARAM_LEN: usize = HASH_LEN;
+/// The most chains of a spec, reached with the longest message hash at one bit per coordinate
+pub const MAX_CHAINS: usize = HASH_LEN * 8;
+/// The most levels of a hash tree, whose leaf indices are encoded as `u32` elsewhere
+pub const MAX_TREE_HEIGHT: usize = 32;
+/// The most signatures of an aggregate and validators of a validator set
+pub const MAX_VALIDATORS: usize = 1 << 20;
+
+/// The bytes of a chunk of the merkleization, and of a hash tree root
+const CHUNK_LEN: usize = 32;
+
+type Chunk = [u8; CHUNK_LEN];
+
+/// The SSZ hash tree root of a value.
+pub trait HashTreeRoot {
+    /// The root of the merkleization of the value, see the consensus specs.
+    fn hash_tree_root(&self) -> Hash;
+}
+
+fn hash_chunks(left: &Chunk, right: &Chunk) -> Chunk {
+    let mut hasher = Sha256::new();
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+/// The chunk of a basic value, i.e. its little-endian bytes padded with zeros.
+pub fn uint_chunk(value: u64) -> Chunk {
+    let mut chunk = [0; CHUNK_LEN];
+    chunk[..8].copy_from_slice(&value.to_le_bytes());
+    chunk
+}
+
+/// Packs `bytes` into chunks, padding the last one with zeros.
+pub fn pack(bytes: &[u8]) -> Vec<Chunk> {
+    (bytes.chunks(CHUNK_LEN))
+        .map(|bytes| {
+            let mut chunk = [0; CHUNK_LEN];
+            chunk[..bytes.len()].copy_from_slice(bytes);
+            chunk
+        })
+        .collect()
+}
+
+/// The root of the tree over `chunks` padded with zero chunks to `limit`, rounded up to a power
+/// of two.
+///
+/// # Panics
+///
+/// Panics if there are more chunks than `limit`.
+pub fn merkleize(mut chunks: Vec<Chunk>, limit: usize) -> Chunk {
+    assert!(chunks.len() <= limit.max(1), "more chunks than the limit");
+    let depth = limit.next_power_of_two().trailing_zeros();
+    let mut zero = [0; CHUNK_LEN];
+    for _ in 0..depth {
+        if chunks.len() % 2 == 1 {
+            chunks.push(zero);
+        }
+        chunks = (chunks.as_chunks::<2>().0.iter())
+            .map(|[left, right]| hash_chunks(left, right))
+            .collect();
+        zero = hash_chunks(&zero, &zero);
+    }
+    chunks.first().copied().unwrap_or(zero)
+}
+
+/// Mixes the length of a list into the root of its items.
+pub fn mix_in_length(root: Chunk, len: usize) -> Chunk {
+    hash_chunks(&root, &uint_chunk(len as u64))
+}
+
+/// Mixes the selector of a union into the root of its value.
+pub fn mix_in_selector(root: Chunk, selector: u8) -> Chunk {
+    hash_chunks(&root, &uint_chunk(selector.into()))
+}
+
+/// The root of a list of composite items with at most `max_len` items.
+pub fn list_root<T: HashTreeRoot>(items: &[T], max_len: usize) -> Chunk {
+    let roots = items.iter().map(|item| item.hash_tree_root().0).collect();
+    mix_in_length(merkleize(roots, max_len), items.len())
+}
+
+/// The root of a container with the roots of its fields.
+pub fn container_root(fields: &[Chunk]) -> Chunk {
+    merkleize(fields.to_vec(), fields.len())
+}
+
+/// Decodes a list of at most `max_len` items, without allocating for the items of a longer one.
+///
+/// # Errors
+///
+/// Returns an error if the bytes don't encode such a list.
+pub fn decode_list<T: Decode>(bytes: &[u8], max_len: usize) -> Result<Vec<T>, DecodeError> {
+    if T::is_ssz_fixed_len() {
+        let len = bytes.len() / T::ssz_fixed_len();
+        if len > max_len {
+            return Err(DecodeError::BytesInvalid(format!(
+                "list of {len} items is longer than {max_len}"
+            )));
+        }
+        Vec::from_ssz_bytes(bytes)
+    } else {
+        ssz::decode_list_of_variable_length_items(bytes, Some(max_len))
+    }
+}
+
+/// The bytes of `T` in the fixed part of a container.
+fn fixed_part<T: Encode>() -> usize {
+    if T::is_ssz_fixed_len() {
+        T::ssz_fixed_len()
+    } else {
+        BYTES_PER_LENGTH_OFFSET
+    }
+}
+
+/// The bytes of `value` in a container, in the fixed part and the variable part together.
+fn field_len<T: Encode>(value: &T) -> usize {
+    let len = value.ssz_bytes_len();
+    if T::is_ssz_fixed_len() {
+        len
+    } else {
+        BYTES_PER_LENGTH_OFFSET + len
+    }
+}
+
+fn decode_fixed<const N: usize>(bytes: &[u8]) -> Result<[u8; N], DecodeError> {
+    bytes
+        .try_into()
+        .map_err(|_| DecodeError::InvalidByteLength {
+            len: bytes.len(),
+            expected: N,
+        })
+}
+
+/// Implements `Encode`, `Decode` and `HashTreeRoot` for a byte array newtype.
+macro_rules! impl_byte_vector {
+    ($type:ident, $len:expr) => {
+        impl Encode for $type {
+            fn is_ssz_fixed_len() -> bool {
+                true
+            }
+
+            fn ssz_fixed_len() -> usize {
+                $len
+            }
+
+            fn ssz_append(&self, buf: &mut Vec<u8>) {
+                buf.extend_from_slice(&self.0);
+            }
+
+            fn ssz_bytes_len(&self) -> usize {
+                $len
+            }
+        }
+
+        impl Decode for $type {
+            fn is_ssz_fixed_len() -> bool {
+                true
+            }
+
+            fn ssz_fixed_len() -> usize {
+                $len
+            }
+
+            fn from_ssz_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+                decode_fixed(bytes).map($type)
+            }
+        }
+
+        impl HashTreeRoot for $type {
+            fn hash_tree_root(&self) -> Hash {
+                Hash(merkleize(pack(&self.0), $len.div_ceil(CHUNK_LEN)))
+            }
+        }
+    };
+}
+
+impl_byte_vector!(Hash, HASH_LEN);
+impl_byte_vector!(Message, crate::MESSAGE_LEN);
+impl_byte_vector!(Nonce, RAND_LEN);
+
+impl Encode for Param {
+    fn is_ssz_fixed_len() -> bool {
+        false
+    }
+
+    fn ssz_append(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(self.as_ref());
+    }
+
+    fn ssz_bytes_len(&self) -> usize {
+        self.as_ref().len()
+    }
+}
+
+impl Decode for Param {
+    fn is_ssz_fixed_len() -> bool {
+        false
+    }
+
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        Ok(Param::from(&decode_list::<u8>(bytes, MAX_PARAM_LEN)?[..]))
+    }
+}
+
+impl HashTreeRoot for Param {
+    fn hash_tree_root(&self) -> Hash {
+        let root = merkleize(pack(self.as_ref()), MAX_PARAM_LEN.div_ceil(CHUNK_LEN));
+        Hash(mix_in_length(root, self.as_ref().len()))
+    }
+}
+
+impl Encode for Pk {
+    fn is_ssz_fixed_len() -> bool {
+        false
+    }
+
+    fn ssz_append(&self, buf: &mut Vec<u8>) {
+        let mut encoder =
+            SszEncoder::container(buf, fixed_part::<Param>() + fixed_part::<Vec<Hash>>());
+        encoder.append(&self.param);
+        encoder.append(&self.end_hashes);
+        encoder.finalize();
+    }
+
+    fn ssz_bytes_len(&self) -> usize {
+        field_len(&self.param) + field_len(&self.end_hashes)
+    }
+}
+
+impl Decode for Pk {
+    fn is_ssz_fixed_len() -> bool {
+        false
+    }
+
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut builder = SszDecoderBuilder::new(bytes);
+        builder.register_type::<Param>()?;
+        builder.register_anonymous_variable_length_item()?;
+        let mut decoder = builder.build()?;
+        Ok(Pk {
+            param: decoder.decode_next()?,
+            end_hashes: decoder.decode_next_with(|bytes| decode_list(bytes, MAX_CHAINS))?,
+        })
+    }
+}
+
+impl HashTreeRoot for Pk {
+    fn hash_tree_root(&self) -> Hash {
+        Hash(container_root(&[
+            self.param.hash_tree_root().0,
+            list_root(&self.end_hashes, MAX_CHAINS),
+        ]))
+    }
+}
+
+impl Encode for OtsSignature {
+    fn is_ssz_fixed_len() -> bool {
+        false
+    }
+
+    fn ssz_append(&self, buf: &mut Vec<u8>) {
+        let mut encoder =
+            SszEncoder::container(buf, fixed_part::<Nonce>() + fixed_part::<Vec<Hash>>());
+        encoder.append(&self.nonce);
+        encoder.append(&self.hashes);
+        encoder.finalize();
+    }
+
+    fn ssz_bytes_len(&self) -> usize {
+        field_len(&self.nonce) + field_len(&self.hashes)
+    }
+}
+
+impl Decode for OtsSignature {
+    fn is_ssz_fixed_len() -> bool {
+        false
+    }
+
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut builder = SszDecoderBuilder::new(bytes);
+        builder.register_type::<Nonce>()?;
+        builder.register_anonymous_variable_length_item()?;
+        let mut decoder = builder.build()?;
+        Ok(OtsSignature {
+            nonce: decoder.decode_next()?,
+            hashes: decoder.decode_next_with(|bytes| decode_list(bytes, MAX_CHAINS))?,
+        })
+    }
+}
+
+impl HashTreeRoot for OtsSignature {
+    fn hash_tree_root(&self) -> Hash {
+        Hash(container_root(&[
+            self.nonce.hash_tree_root().0,
+            list_root(&self.hashes, MAX_CHAINS),
+        ]))
+    }
+}
+
+impl Encode for HashTreeProof {
+    fn is_ssz_fixed_len() -> bool {
+        false
+    }
+
+    fn ssz_append(&self, buf: &mut Vec<u8>) {
+        let mut encoder =
+            SszEncoder::container(buf, fixed_part::<u64>() + fixed_part::<Vec<Hash>>());
+        encoder.append(&(self.leaf_index() as u64));
+        encoder.append(&self.path);
+        encoder.finalize();
+    }
+
+    fn ssz_bytes_len(&self) -> usize {
+        field_len(&(self.leaf_index() as u64)) + field_len(&self.path)
+    }
+}
+
+impl Decode for HashTreeProof {
+    fn is_ssz_fixed_len() -> bool {
+        false
+    }
+
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut builder = SszDecoderBuilder::new(bytes);
+        builder.register_type::<u64>()?;
+        builder.register_anonymous_variable_length_item()?;
+        let mut decoder = builder.build()?;
+        let leaf_index: u64 = decoder.decode_next()?;
+        let path = decoder.decode_next_with(|bytes| decode_list(bytes, MAX_TREE_HEIGHT))?;
+        let leaf_index = u32::try_from(leaf_index)
+            .map_err(|_| DecodeError::BytesInvalid(format!("leaf index {leaf_index} overflows")))?;
+        Ok(HashTreeProof::new(leaf_index as usize, path))
+    }
+}
+
+impl HashTreeRoot for HashTreeProof {
+    fn hash_tree_root(&self) -> Hash {
+        Hash(container_root(&[
+            uint_chunk(self.leaf_index() as u64),
+            list_root(&self.path, MAX_TREE_HEIGHT),
+        ]))
+    }
+}
+
+impl Encode for Signature {
+    fn is_ssz_fixed_len() -> bool {
+        false
+    }
+
+    fn ssz_append(&self, buf: &mut Vec<u8>) {
+        let mut encoder = SszEncoder::container(buf, 3 * BYTES_PER_LENGTH_OFFSET);
+        encoder.append(&self.signature);
+        encoder.append(&self.hash_tree_proof);
+        encoder.append(&self.public_key);
+        encoder.finalize();
+    }
+
+    fn ssz_bytes_len(&self) -> usize {
+        field_len(&self.signature) + field_len(&self.hash_tree_proof) + field_len(&self.public_key)
+    }
+}
+
+impl Decode for Signature {
+    fn is_ssz_fixed_len() -> bool {
+        false
+    }
+
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut builder = SszDecoderBuilder::new(bytes);
+        builder.register_type::<OtsSignature>()?;
+        builder.register_type::<HashTreeProof>()?;
+        builder.register_type::<Pk>()?;
+        let mut decoder = builder.build()?;
+        Ok(Signature {
+            signature: decoder.decode_next()?,
+            hash_tree_proof: decoder.decode_next()?,
+            public_key: decoder.decode_next()?,
+        })
+    }
+}
+
+impl HashTreeRoot for Signature {
+    fn hash_tree_root(&self) -> Hash {
+        Hash(container_root(&[
+            self.signature.hash_tree_root().0,
+            self.hash_tree_proof.hash_tree_root().0,
+            self.public_key.hash_tree_root().0,
+        ]))
+    }
+}
+
+impl Encode for HyperSignature {
+    fn is_ssz_fixed_len() -> bool {
+        false
+    }
+
+    fn ssz_append(&self, buf: &mut Vec<u8>) {
+        let num_fixed_bytes = 3 * BYTES_PER_LENGTH_OFFSET + fixed_part::<Hash>();
+        let mut encoder = SszEncoder::container(buf, num_fixed_bytes);
+        encoder.append(&self.bottom);
+        encoder.append(&self.bottom_root);
+        encoder.append(&self.bottom_param);
+        encoder.append(&self.top);
+        encoder.finalize();
+    }
+
+    fn ssz_bytes_len(&self) -> usize {
+        field_len(&self.bottom)
+            + field_len(&self.bottom_root)
+            + field_len(&self.bottom_param)
+            + field_len(&self.top)
+    }
+}
+
+impl Decode for HyperSignature {
+    fn is_ssz_fixed_len() -> bool {
+        false
+    }
+
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut builder = SszDecoderBuilder::new(bytes);
+        builder.register_type::<Signature>()?;
+        builder.register_type::<Hash>()?;
+        builder.register_type::<Param>()?;
+        builder.register_type::<Signature>()?;
+        let mut decoder = builder.build()?;
+        Ok(HyperSignature {
+            bottom: decoder.decode_next()?,
+            bottom_root: decoder.decode_next()?,
+            bottom_param: decoder.decode_next()?,
+            top: decoder.decode_next()?,
+        })
+    }
+}
+
+impl HashTreeRoot for HyperSignature {
+    fn hash_tree_root(&self) -> Hash {
+        Hash(container_root(&[
+            self.bottom.hash_tree_root().0,
+            self.bottom_root.hash_tree_root().0,
+            self.bottom_param.hash_tree_root().0,
+            self.top.hash_tree_root().0,
+        ]))
+    }
+}
+
+impl AnySignature {
+    /// The selector of the variant in the SSZ union.
+    fn ssz_selector(&self) -> u8 {
+        match self {
+            AnySignature::Xmss(_) => 0,
+            AnySignature::Hyper(_) => 1,
+        }
+    }
+}
+
+impl Encode for AnySignature {
+    fn is_ssz_fixed_len() -> bool {
+        false
+    }
+
+    fn ssz_append(&self, buf: &mut Vec<u8>) {
+        buf.push(self.ssz_selector());
+        match self {
+            AnySignature::Xmss(signature) => signature.ssz_append(buf),
+            AnySignature::Hyper(signature) => signature.ssz_append(buf),
+        }
+    }
+
+    fn ssz_bytes_len(&self) -> usize {
+        1 + match self {
+            AnySignature::Xmss(signature) => signature.ssz_bytes_len(),
+            AnySignature::Hyper(signature) => signature.ssz_bytes_len(),
+        }
+    }
+}
+
+impl Decode for AnySignature {
+    fn is_ssz_fixed_len() -> bool {
+        false
+    }
+
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let (selector, body) = split_union_bytes(bytes)?;
+        match selector.into() {
+            0 => Signature::from_ssz_bytes(body).map(AnySignature::Xmss),
+            1 => HyperSignature::from_ssz_bytes(body).map(AnySignature::Hyper),
+            other => Err(DecodeError::UnionSelectorInvalid(other)),
+        }
+    }
+}
+
+impl HashTreeRoot for AnySignature {
+    fn hash_tree_root(&self) -> Hash {
+        let root = match self {
+            AnySignature::Xmss(signature) => signature.hash_tree_root(),
+            AnySignature::Hyper(signature) => signature.hash_tree_root(),
+        };
+        Hash(mix_in_selector(root.0, self.ssz_selector()))
+    }
+}
+
+/// The root of a `Union[None, uint32]`.
+fn optional_index_root(index: Option<u32>) -> Chunk {
+    match index {
+        None => mix_in_selector([0; CHUNK_LEN], 0),
+        Some(index) => mix_in_selector(uint_chunk(index.into()), 1),
+    }
+}
+
+impl Encode for ValidatorSignature {
+    fn is_ssz_fixed_len() -> bool {
+        false
+    }
+
+    fn ssz_append(&self, buf: &mut Vec<u8>) {
+        let num_fixed_bytes =
+            fixed_part::<u64>() + 3 * BYTES_PER_LENGTH_OFFSET + fixed_part::<Hash>();
+        let mut encoder = SszEncoder::container(buf, num_fixed_bytes);
+        encoder.append(&self.epoch);
+        encoder.append(&self.signature);
+        encoder.append(&self.xmss_root);
+        encoder.append(&self.param);
+        encoder.append(&self.validator_index);
+        encoder.finalize();
+    }
+
+    fn ssz_bytes_len(&self) -> usize {
+        field_len(&self.epoch)
+            + field_len(&self.signature)
+            + field_len(&self.xmss_root)
+            + field_len(&self.param)
+            + field_len(&self.validator_index)
+    }
+}
+
+impl Decode for ValidatorSignature {
+    fn is_ssz_fixed_len() -> bool {
+        false
+    }
+
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut builder = SszDecoderBuilder::new(bytes);
+        builder.register_type::<u64>()?;
+        builder.register_type::<AnySignature>()?;
+        builder.register_type::<Hash>()?;
+        builder.register_type::<Param>()?;
+        builder.register_type::<Option<u32>>()?;
+        let mut decoder = builder.build()?;
+        Ok(ValidatorSignature {
+            epoch: decoder.decode_next()?,
+            signature: decoder.decode_next()?,
+            xmss_root: decoder.decode_next()?,
+            param: decoder.decode_next()?,
+            validator_index: decoder.decode_next()?,
+        })
+    }
+}
+
+impl HashTreeRoot for ValidatorSignature {
+    fn hash_tree_root(&self) -> Hash {
+        Hash(container_root(&[
+            uint_chunk(self.epoch),
+            self.signature.hash_tree_root().0,
+            self.xmss_root.hash_tree_root().0,
+            self.param.hash_tree_root().0,
+            optional_index_root(self.validator_index),
+        ]))
+    }
+}
+
+impl Encode for AggregatedSignature {
+    fn is_ssz_fixed_len() -> bool {
+        false
+    }
+
+    fn ssz_append(&self, buf: &mut Vec<u8>) {
+        let mut encoder = SszEncoder::container(buf, BYTES_PER_LENGTH_OFFSET);
+        encoder.append(&self.signatures);
+        encoder.finalize();
+    }
+
+    fn ssz_bytes_len(&self) -> usize {
+        field_len(&self.signatures)
+    }
+}
+
+impl Decode for AggregatedSignature {
+    fn is_ssz_fixed_len() -> bool {
+        false
+    }
+
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut builder = SszDecoderBuilder::new(bytes);
+        builder.register_anonymous_variable_length_item()?;
+        let mut decoder = builder.build()?;
+        let signatures = decoder.decode_next_with(|bytes| decode_list(bytes, MAX_VALIDATORS))?;
+        Ok(AggregatedSignature::new(signatures))
+    }
+}
+
+impl HashTreeRoot for AggregatedSignature {
+    fn hash_tree_root(&self) -> Hash {
+        Hash(container_root(&[list_root(
+            &self.signatures,
+            MAX_VALIDATORS,
+        )]))
+    }
+}
+
+/// The SSZ bytes of a [`Spec`]: four `uint64` and the acceptance, a `uint8` kind and a `uint64`
+/// width.
+const SPEC_SSZ_LEN: usize = 4 * 8 + 1 + 8;
+
+impl Acceptance {
+    /// The kind and the width of the acceptance in its SSZ container.
+    fn ssz_fields(&self) -> (u8, u64) {
+        match *self {
+            Acceptance::Exact => (0, 0),
+        }
+    }
+}
+
+impl Spec {
+    fn ssz_values(&self) -> [u64; 4] {
+        [
+            self.message_hash_len,
+            self.coordinate_resolution_bits,
+            self.param_len,
+            self.target_sum,
+        ]
+        .map(|value| value as u64)
+    }
+}
+
+impl Encode for Spec {
+    fn is_ssz_fixed_len() -> bool {
+        true
+    }
+
+    fn ssz_fixed_len() -> usize {
+        SPEC_SSZ_LEN
+    }
+
+    fn ssz_append(&self, buf: &mut Vec<u8>) {
+        for value in self.ssz_values() {
+            buf.extend_from_slice(&value.to_le_bytes());
+        }
+        let (kind, width) = self.acceptance.ssz_fields();
+        buf.push(kind);
+        buf.extend_from_slice(&width.to_le_bytes());
+    }
+
+    fn ssz_bytes_len(&self) -> usize {
+        SPEC_SSZ_LEN
+    }
+}
+
+impl Decode for Spec {
+    fn is_ssz_fixed_len() -> bool {
+        true
+    }
+
+    fn ssz_fixed_len() -> usize {
+        SPEC_SSZ_LEN
+    }
+
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let bytes: [u8; SPEC_SSZ_LEN] = decode_fixed(bytes)?;
+        let value = |index: usize| {
+            let value = u64::from_le_bytes(bytes[8 * index..][..8].try_into().expect("8 bytes"));
+            usize::try_from(value)
+                .map_err(|_| DecodeError::BytesInvalid(format!("spec value {value} overflows")))
+        };
+        let width = u64::from_le_bytes(bytes[33..].try_into().expect("8 bytes"));
+        let acceptance = match (bytes[32], width) {
+            (0, 0) => Acceptance::Exact,
+            (kind, width) => {
+                return Err(DecodeError::BytesInvalid(format!(
+                    "invalid acceptance kind {kind} with width {width}"
+                )));
+            }
+        };
+        Ok(Spec {
+            message_hash_len: value(0)?,
+            coordinate_resolution_bits: value(1)?,
+            param_len: value(2)?,
+            target_sum: value(3)?,
+            acceptance,
+        })
+    }
+}
+
+impl HashTreeRoot for Spec {
+    fn hash_tree_root(&self) -> Hash {
+        let (kind, width) = self.acceptance.ssz_fields();
+        let acceptance = container_root(&[uint_chunk(kind.into()), uint_chunk(width)]);
+        let values = self.ssz_values().map(uint_chunk);
+        Hash(container_root(&[
+            values[0], values[1], values[2], values[3], acceptance,
+        ]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::*;
+    use crate::{
+        Signer,
+        hypertree::HyperSigner,
+        spec::{SPEC_1, SPEC_2},
+        verify_signature,
+    };
+
+    fn sha256(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+        Sha256::digest([&left[..], &right[..]].concat()).into()
+    }
+
+    /// Checks that `value` decodes from its encoding to a value with the same encoding and root.
+    fn round_trip<T: Encode + Decode + HashTreeRoot>(value: &T) -> T {
+        let bytes = value.as_ssz_bytes();
+        assert_eq!(bytes.len(), value.ssz_bytes_len());
+        let decoded = T::from_ssz_bytes(&bytes).unwrap();
+        assert_eq!(decoded.as_ssz_bytes(), bytes);
+        assert_eq!(decoded.hash_tree_root(), value.hash_tree_root());
+        decoded
+    }
+
+    #[test]
+    fn test_small_vectors() {
+        let hash = Hash([9; 32]);
+        assert_eq!(hash.as_ssz_bytes(), [9; 32]);
+        assert_eq!(hash.hash_tree_root(), hash);
+
+        // A list of bytes is one chunk padded with zeros, mixed with its length
+        let param = Param::from(&[1, 2, 3][..]);
+        assert_eq!(param.as_ssz_bytes(), [1, 2, 3]);
+        let mut chunk = [0; 32];
+        chunk[..3].copy_from_slice(&[1, 2, 3]);
+        let mut len = [0; 32];
+        len[0] = 3;
+        assert_eq!(param.hash_tree_root().0, sha256(&chunk, &len));
+
+        // The offsets of the two variable-size fields, then the fields
+        let pk = Pk {
+            param: Param::from(&[1, 2][..]),
+            end_hashes: vec![Hash([3; 32])],
+        };
+        assert_eq!(
+            pk.as_ssz_bytes(),
+            [vec![8, 0, 0, 0, 10, 0, 0, 0, 1, 2], vec![3; 32]].concat()
+        );
+
+        let proof = HashTreeProof::new(5, vec![Hash([7; 32])]);
+        assert_eq!(
+            proof.as_ssz_bytes(),
+            [vec![5, 0, 0, 0, 0, 0, 0, 0, 12, 0, 0, 0], vec![7; 32]].concat()
+        );
+        // The path is padded to the 32 levels of MAX_TREE_HEIGHT, five levels of hashing
+        let (mut node, mut zero) = ([7; 32], [0; 32]);
+        for _ in 0..5 {
+            node = sha256(&node, &zero);
+            zero = sha256(&zero, &zero);
+        }
+        let mut one = [0; 32];
+        one[0] = 1;
+        let mut five = [0; 32];
+        five[0] = 5;
+        assert_eq!(
+            proof.hash_tree_root().0,
+            sha256(&five, &sha256(&node, &one))
+        );
+
+        assert_eq!(
+            SPEC_2.as_ssz_bytes(),
+            [
+                &18u64.to_le_bytes()[..],
+                &4u64.to_le_bytes(),
+                &18u64.to_le_bytes(),
+                &297u64.to_le_bytes(),
+                &[0],
+                &0u64.to_le_bytes(),
+            ]
+            .concat()
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let message = Message([42; 32]);
+        let mut signer = Signer::new(StdRng::seed_from_u64(0), 100_000, SPEC_1, 4).unwrap();
+        let signature = signer.sign(1, &message).unwrap();
+        let decoded = round_trip(&signature);
+        assert!(verify_signature(
+            &SPEC_1,
+            &signer.param,
+            &message,
+            &decoded,
+            &signer.root
+        ));
+        round_trip(&signature.signature);
+        round_trip(&signature.hash_tree_proof);
+        round_trip(&signature.public_key);
+        round_trip(&signer.param);
+        round_trip(&signer.root);
+        round_trip(&message);
+
+        let mut hyper_signer =
+            HyperSigner::new(StdRng::seed_from_u64(1), 100_000, SPEC_1, 1, 1).unwrap();
+        let hyper_signature = hyper_signer.sign(2, &message).unwrap();
+        round_trip(&hyper_signature);
+
+        let signatures = vec![
+            ValidatorSignature {
+                epoch: 1,
+                signature: signature.into(),
+                xmss_root: signer.root,
+                param: signer.param.clone(),
+                validator_index: Some(3),
+            },
+            ValidatorSignature {
+                epoch: 2,
+                signature: hyper_signature.into(),
+                xmss_root: hyper_signer.root,
+                param: hyper_signer.param.clone(),
+                validator_index: None,
+            },
+        ];
+        for signature in &signatures {
+            let decoded = round_trip(signature);
+            assert_eq!(decoded.validator_index, signature.validator_index);
+        }
+        let decoded = round_trip(&AggregatedSignature::new(signatures));
+        assert_eq!(decoded.signatures.len(), 2);
+
+        assert_eq!(round_trip(&SPEC_2), SPEC_2);
+    }
+
+    #[test]
+    fn test_rejects_malformed() {
+        let pk = Pk {
+            param: Param::from(&[1; 18][..]),
+            end_hashes: vec![Hash([0; 32]); MAX_CHAINS + 1],
+        };
+        assert!(Pk::from_ssz_bytes(&pk.as_ssz_bytes()).is_err());
+        let param = Param::from(&[0; MAX_PARAM_LEN + 1][..]);
+        assert!(Param::from_ssz_bytes(&param.as_ssz_bytes()).is_err());
+
+        let mut signer = Signer::new(StdRng::seed_from_u64(0), 100_000, SPEC_1, 2).unwrap();
+        let signature = ValidatorSignature {
+            epoch: 0,
+            signature: signer.sign(0, &Message([1; 32])).unwrap().into(),
+            xmss_root: signer.root,
+            param: signer.param.clone(),
+            validator_index: Some(0),
+        };
+        let bytes = signature.as_ssz_bytes();
+        // Truncations are errors, not panics
+        for len in (0..bytes.len()).step_by(7) {
+            assert!(ValidatorSignature::from_ssz_bytes(&bytes[..len]).is_err());
+        }
+        assert_eq!(
+            AnySignature::from_ssz_bytes(&[2]).map(drop),
+            Err(DecodeError::UnionSelectorInvalid(2))
+        );
+
+        // Only the exact acceptance, with a zero width, decodes
+        for (kind, width) in [(1, 0), (1, 2), (2, 0), (3, 0), (0, 1)] {
+            let mut spec = SPEC_1.as_ssz_bytes();
+            spec[32] = kind;
+            spec[33..].copy_from_slice(&u64::to_le_bytes(width));
+            assert!(Spec::from_ssz_bytes(&spec).is_err());
+        }
+    }
+}
@@ -0,0 +1,247 @@
+// Copyright 2025 Irreducible Inc.
+//! Authentication paths for consecutive epochs without keeping the hash tree.
+//!
+//! This is the classic treehash traversal: the authentication node of every level only changes
+//! once every `2^level` epochs, and the node that replaces it is computed in the meantime from
+//! the leaves, one leaf per level and epoch. Each level keeps its current node, the next node and
+//! a treehash stack of at most `level` nodes, so the state is `O(height^2)` hashes however many
+//! leaves there are, at a cost of `height` leaf computations per epoch.
+
+use alloc::{boxed::Box, vec, vec::Vec};
+use core::fmt;
+
+use crate::{
+    Hash, Param,
+    footprint::MemoryFootprint,
+    hash::{HASH_LEN, KeccakBackend, TweakableHash},
+};
+
+/// Computes the leaf at an index on demand, e.g. by deriving a key pair from a seed.
+pub type LeafFn = Box<dyn Fn(usize) -> Hash + Send + Sync>;
+
+/// A node on a treehash stack.
+#[derive(Clone, Copy, Debug)]
+struct StackNode {
+    level: usize,
+    index: usize,
+    hash: Hash,
+}
+
+/// Computes the node at `(level, index)` one leaf at a time.
+#[derive(Clone, Debug)]
+struct TreeHash {
+    level: usize,
+    next_leaf: usize,
+    end_leaf: usize,
+    stack: Vec<StackNode>,
+}
+
+impl TreeHash {
+    fn new(level: usize, index: usize) -> Self {
+        Self {
+            level,
+            next_leaf: index << level,
+            end_leaf: (index + 1) << level,
+            stack: Vec::with_capacity(level + 1),
+        }
+    }
+
+    /// Absorbs the next leaf and returns the node once all of its leaves are absorbed.
+    fn step<H: TweakableHash>(&mut self, hasher: &H, leaf: &LeafFn) -> Option<Hash> {
+        self.step_visiting(hasher, leaf, |_| {})
+    }
+
+    /// Same as [`TreeHash::step`], but also passes every node it creates to `visit`.
+    fn step_visiting<H: TweakableHash>(
+        &mut self,
+        hasher: &H,
+        leaf: &LeafFn,
+        mut visit: impl FnMut(&StackNode),
+    ) -> Option<Hash> {
+        let mut node = StackNode {
+            level: 0,
+            index: self.next_leaf,
+            hash: leaf(self.next_leaf),
+        };
+        visit(&node);
+        self.next_leaf += 1;
+        while let Some(left) = self.stack.pop_if(|top| top.level == node.level) {
+            node = StackNode {
+                level: node.level + 1,
+                index: node.index / 2,
+                hash: hasher.hash_tree_node(
+                    &left.hash,
+                    &node.hash,
+                    node.level as u32,
+                    (node.index / 2) as u32,
+                ),
+            };
+            visit(&node);
+        }
+        if self.next_leaf == self.end_leaf {
+            debug_assert_eq!(node.level, self.level);
+            return Some(node.hash);
+        }
+        self.stack.push(node);
+        None
+    }
+}
+
+/// The authentication path state of a tree whose leaves are used strictly in order.
+pub struct TraversalState<H: TweakableHash = KeccakBackend> {
+    hasher: H,
+    leaf: LeafFn,
+    height: usize,
+    next_epoch: usize,
+    /// The authentication path of `next_epoch`.
+    auth: Vec<Hash>,
+    /// The node that replaces `auth[level]`, once computed.
+    next_auth: Vec<Option<Hash>>,
+    /// The computation of `next_auth[level]`, if it is in progress.
+    treehash: Vec<Option<TreeHash>>,
+    root: Hash,
+}
+
+impl<H: TweakableHash> fmt::Debug for TraversalState<H> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TraversalState")
+            .field("height", &self.height)
+            .field("next_epoch", &self.next_epoch)
+            .finish_non_exhaustive()
+    }
+}
+
+impl TraversalState {
+    /// Walks the tree of `2^height` leaves once to find the root and the first two
+    /// authentication nodes of every level.
+    ///
+    /// This takes `2^height` leaf computations but only `O(height)` hashes of memory.
+    pub fn new(param: &Param, height: usize, leaf: LeafFn) -> Self {
+        Self::new_with_backend(param, height, leaf)
+    }
+}
+
+/// The authentication path and the treehash stacks, but not what the leaf function captures.
+impl<H: TweakableHash> MemoryFootprint for TraversalState<H> {
+    fn heap_bytes(&self) -> usize {
+        let stacks: usize = (self.treehash.iter().flatten())
+            .map(|treehash| treehash.stack.len() * size_of::<StackNode>())
+            .sum();
+        self.auth.heap_bytes()
+            + self.next_auth.len() * size_of::<Option<Hash>>()
+            + self.treehash.len() * size_of::<Option<TreeHash>>()
+            + stacks
+    }
+}
+
+impl<H: TweakableHash> TraversalState<H> {
+    /// Same as [`TraversalState::new`], but with the hash backend `H`.
+    pub fn new_with_backend(param: &Param, height: usize, leaf: LeafFn) -> Self {
+        let hasher = H::new(param);
+        // Epoch 0 is authenticated by the right sibling of its path on every level, and the
+        // first replacement on every level is the left one.
+        let mut auth = vec![Hash([0; HASH_LEN]); height];
+        let mut next_auth = vec![None; height];
+        let mut whole = TreeHash::new(height, 0);
+        let root = loop {
+            let done = whole.step_visiting(&hasher, &leaf, |node| {
+                if node.level < height {
+                    match node.index {
+                        0 => next_auth[node.level] = Some(node.hash),
+                        1 => auth[node.level] = node.hash,
+                        _ => {}
+                    }
+                }
+            });
+            if let Some(root) = done {
+                break root;
+            }
+        };
+        Self {
+            hasher,
+            leaf,
+            height,
+            next_epoch: 0,
+            auth,
+            next_auth,
+            treehash: vec![None; height],
+            root,
+        }
+    }
+
+    /// The root of the tree.
+    pub fn root(&self) -> Hash {
+        self.root
+    }
+
+    /// The epoch whose authentication path is returned next.
+    pub fn next_epoch(&self) -> usize {
+        self.next_epoch
+    }
+
+    /// Returns the authentication path of [`TraversalState::next_epoch`] and advances to the
+    /// following epoch.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the paths of all `2^height` epochs have been returned.
+    pub fn auth_path_for_next_epoch(&mut self) -> Vec<Hash> {
+        assert!(
+            self.next_epoch >> self.height == 0,
+            "every epoch of the tree has been used"
+        );
+        let path = self.auth.clone();
+        let epoch = self.next_epoch;
+        self.next_epoch += 1;
+
+        // Every computation gets one leaf per epoch, which is exactly enough to finish the node
+        // of level `level` in the `2^level` epochs before it is installed.
+        for level in 0..self.height {
+            if let Some(treehash) = &mut self.treehash[level]
+                && let Some(node) = treehash.step(&self.hasher, &self.leaf)
+            {
+                self.next_auth[level] = Some(node);
+                self.treehash[level] = None;
+            }
+            if (epoch + 1).is_multiple_of(1 << level) {
+                if let Some(next) = self.next_auth[level].take() {
+                    self.auth[level] = next;
+                }
+                // The sibling on this level changes again after another `2^level` epochs.
+                let index = (((epoch + 1) >> level) + 1) ^ 1;
+                self.treehash[level] =
+                    (index < 1 << (self.height - level)).then(|| TreeHash::new(level, index));
+            }
+        }
+        path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::*;
+    use crate::hash_tree::HashTree;
+
+    #[test]
+    fn test_matches_hash_tree() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let param = Param::random(18, &mut rng);
+        for height in 0..=6 {
+            let leaves: Vec<_> = (0..1 << height).map(|_| Hash::random(&mut rng)).collect();
+            let tree = HashTree::new(&param, leaves.clone());
+
+            let mut state = TraversalState::new(&param, height, Box::new(move |i| leaves[i]));
+            assert_eq!(state.root(), tree.root);
+            for epoch in 0..1 << height {
+                assert_eq!(state.next_epoch(), epoch);
+                assert_eq!(
+                    state.auth_path_for_next_epoch(),
+                    tree.get_proof(epoch).unwrap().path,
+                    "height {height}, epoch {epoch}"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,247 @@
+// Copyright 2025 Irreducible Inc.
+//! The envelope of every artifact persisted to disk, so that a file written by another version of
+//! the code is rejected instead of decoding to garbage.
+//!
+//! An envelope is 8 bytes preceding the payload: the [`MAGIC`] bytes, then the format version of
+//! the payload and the [`PayloadKind`] id, both little-endian `u16`. Each kind has its own format
+//! version, owned by the code that writes the payload and bumped whenever its layout changes.
+
+use core::fmt;
+use std::io::{self, Read, Write};
+
+/// The bytes every file written through [`write_envelope`] starts with
+pub const MAGIC: [u8; 4] = *b"LSIG";
+
+/// The length of the envelope preceding the payload
+pub const ENVELOPE_LEN: usize = 8;
+
+/// What a persisted file holds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PayloadKind {
+    /// A [`crate::hash_tree::HashTree`] in bincode
+    HashTree,
+    /// A [`crate::Signature`] in its fixed layout
+    Signature,
+    /// The test data of `leansig-shared` in bincode
+    TestData,
+    /// The public inputs of `leansig-shared` in bincode
+    PublicInputs,
+    /// A zkVM receipt of a host crate
+    Receipt,
+    /// A [`crate::SignerState`] in bincode
+    SignerState,
+}
+
+impl PayloadKind {
+    const ALL: [PayloadKind; 6] = [
+        PayloadKind::HashTree,
+        PayloadKind::Signature,
+        PayloadKind::TestData,
+        PayloadKind::PublicInputs,
+        PayloadKind::Receipt,
+        PayloadKind::SignerState,
+    ];
+
+    /// The id of the kind in the envelope, which never changes once assigned
+    pub const fn id(self) -> u16 {
+        match self {
+            PayloadKind::HashTree => 1,
+            PayloadKind::Signature => 2,
+            PayloadKind::TestData => 3,
+            PayloadKind::PublicInputs => 4,
+            PayloadKind::Receipt => 5,
+            PayloadKind::SignerState => 6,
+        }
+    }
+
+    /// The kind with `id`, if any
+    pub fn from_id(id: u16) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.id() == id)
+    }
+}
+
+impl fmt::Display for PayloadKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            PayloadKind::HashTree => "hash tree",
+            PayloadKind::Signature => "signature",
+            PayloadKind::TestData => "test data",
+            PayloadKind::PublicInputs => "public inputs",
+            PayloadKind::Receipt => "receipt",
+            PayloadKind::SignerState => "signer state",
+        })
+    }
+}
+
+/// The reasons an envelope can be rejected by [`read_envelope`].
+#[derive(Debug)]
+pub enum WireError {
+    /// The envelope could not be read, e.g. because the file is shorter than it.
+    Io(io::Error),
+    /// The file does not start with [`MAGIC`], so was not written through an envelope.
+    BadMagic { found: [u8; 4] },
+    /// The kind id is not one of a [`PayloadKind`], e.g. one added by a later version.
+    UnknownKind { found: u16 },
+    /// The file holds another kind of payload than the one expected.
+    WrongKind {
+        found: PayloadKind,
+        expected: PayloadKind,
+    },
+    /// The payload is in another format version than the one this code reads.
+    UnsupportedVersion {
+        kind: PayloadKind,
+        found: u16,
+        expected: u16,
+    },
+}
+
+impl fmt::Display for WireError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WireError::Io(err) => write!(f, "failed to read the envelope: {err}"),
+            WireError::BadMagic { found } => {
+                write!(f, "magic bytes {found:02x?} instead of {MAGIC:02x?}")
+            }
+            WireError::UnknownKind { found } => write!(f, "unknown payload kind {found}"),
+            WireError::WrongKind { found, expected } => {
+                write!(f, "payload kind {found} instead of {expected}")
+            }
+            WireError::UnsupportedVersion {
+                kind,
+                found,
+                expected,
+            } => write!(f, "{kind} format version {found} instead of {expected}"),
+        }
+    }
+}
+
+impl std::error::Error for WireError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WireError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<WireError> for io::Error {
+    /// Keeps I/O errors as they are, and turns the others into errors of kind
+    /// [`io::ErrorKind::InvalidData`].
+    fn from(err: WireError) -> Self {
+        match err {
+            WireError::Io(err) => err,
+            err => io::Error::new(io::ErrorKind::InvalidData, err),
+        }
+    }
+}
+
+/// Writes the envelope of a payload of `kind` in format `version`, which the caller writes next.
+pub fn write_envelope(mut writer: impl Write, kind: PayloadKind, version: u16) -> io::Result<()> {
+    let mut envelope = [0; ENVELOPE_LEN];
+    envelope[..4].copy_from_slice(&MAGIC);
+    envelope[4..6].copy_from_slice(&version.to_le_bytes());
+    envelope[6..].copy_from_slice(&kind.id().to_le_bytes());
+    writer.write_all(&envelope)
+}
+
+/// Reads the envelope written by [`write_envelope`], leaving `reader` at the payload.
+///
+/// # Errors
+///
+/// Returns an error unless the envelope is of a payload of `kind` in format `version`.
+pub fn read_envelope(
+    mut reader: impl Read,
+    kind: PayloadKind,
+    version: u16,
+) -> Result<(), WireError> {
+    let mut envelope = [0; ENVELOPE_LEN];
+    reader.read_exact(&mut envelope).map_err(WireError::Io)?;
+    let (magic, rest) = envelope.split_at(4);
+    if magic != MAGIC {
+        return Err(WireError::BadMagic {
+            found: magic.try_into().expect("split at 4 bytes"),
+        });
+    }
+    let found_version = u16::from_le_bytes([rest[0], rest[1]]);
+    let found_id = u16::from_le_bytes([rest[2], rest[3]]);
+    let found = PayloadKind::from_id(found_id).ok_or(WireError::UnknownKind { found: found_id })?;
+    if found != kind {
+        return Err(WireError::WrongKind {
+            found,
+            expected: kind,
+        });
+    }
+    if found_version != version {
+        return Err(WireError::UnsupportedVersion {
+            kind,
+            found: found_version,
+            expected: version,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn envelope(kind: PayloadKind, version: u16) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        write_envelope(&mut bytes, kind, version).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for kind in PayloadKind::ALL {
+            assert_eq!(PayloadKind::from_id(kind.id()), Some(kind));
+            let mut bytes = envelope(kind, 3);
+            assert_eq!(bytes.len(), ENVELOPE_LEN);
+            bytes.extend_from_slice(b"payload");
+
+            let mut reader = bytes.as_slice();
+            read_envelope(&mut reader, kind, 3).unwrap();
+            assert_eq!(reader, b"payload");
+        }
+        assert_eq!(
+            envelope(PayloadKind::Signature, 1),
+            [b'L', b'S', b'I', b'G', 1, 0, 2, 0]
+        );
+    }
+
+    #[test]
+    fn test_rejects_other_versions_and_kinds() {
+        // A file written by a later version of the code
+        let bytes = envelope(PayloadKind::HashTree, 2);
+        let err = read_envelope(bytes.as_slice(), PayloadKind::HashTree, 1).unwrap_err();
+        assert!(matches!(
+            err,
+            WireError::UnsupportedVersion {
+                kind: PayloadKind::HashTree,
+                found: 2,
+                expected: 1
+            }
+        ));
+        assert_eq!(err.to_string(), "hash tree format version 2 instead of 1");
+
+        let bytes = envelope(PayloadKind::TestData, 1);
+        let err = read_envelope(bytes.as_slice(), PayloadKind::PublicInputs, 1).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "payload kind test data instead of public inputs"
+        );
+
+        // A kind added by a later version of the code
+        let mut bytes = envelope(PayloadKind::Receipt, 1);
+        bytes[6] = 0xff;
+        let err = read_envelope(bytes.as_slice(), PayloadKind::Receipt, 1).unwrap_err();
+        assert!(matches!(err, WireError::UnknownKind { found: 0xff }));
+
+        let err = read_envelope(&b"{\"not\": 1}"[..], PayloadKind::Receipt, 1).unwrap_err();
+        assert!(matches!(err, WireError::BadMagic { found } if &found == b"{\"no"));
+        let err = read_envelope(&MAGIC[..], PayloadKind::Receipt, 1).unwrap_err();
+        assert_eq!(io::Error::from(err).kind(), io::ErrorKind::UnexpectedEof);
+        let err = read_envelope(&[0; 8][..], PayloadKind::Receipt, 1).unwrap_err();
+        assert_eq!(io::Error::from(err).kind(), io::ErrorKind::InvalidData);
+    }
+}
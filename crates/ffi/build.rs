@@ -0,0 +1,11 @@
+// Copyright 2025 Irreducible Inc.
+//! Writes the C header of the crate to `include/leansig.h`.
+
+fn main() {
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    println!("cargo:rerun-if-changed=src/lib.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    cbindgen::generate(&crate_dir)
+        .expect("the header generates")
+        .write_to_file(format!("{crate_dir}/include/leansig.h"));
+}
@@ -0,0 +1,96 @@
+/* Copyright 2025 Irreducible Inc. */
+
+/*
+ * Verifies a signature through the C bindings, on its own and as an aggregate of one validator.
+ *
+ *     cargo build --release -p leansig-ffi
+ *     cc crates/ffi/examples/verify.c -Icrates/ffi/include -Ltarget/release -lleansig_ffi \
+ *         -o verify
+ *     LD_LIBRARY_PATH=target/release ./verify SPEC_ID PARAM MESSAGE SIGNATURE ROOT
+ *
+ * SPEC_ID is 1 or 2 and the other arguments are hex, e.g. those of the test vector printed by
+ * `cargo run -p leansig-core --example test_vector`. The exit status is 0 if both verify.
+ */
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "leansig.h"
+
+/* Decodes `hex` into a new buffer of `*len` bytes, or returns NULL if it is not hex. */
+static uint8_t *from_hex(const char *hex, size_t *len) {
+    size_t digits = strlen(hex);
+    if (digits % 2 != 0) {
+        return NULL;
+    }
+    *len = digits / 2;
+    uint8_t *bytes = malloc(*len ? *len : 1);
+    for (size_t i = 0; i < *len; i++) {
+        unsigned int byte;
+        if (sscanf(hex + 2 * i, "%2x", &byte) != 1) {
+            free(bytes);
+            return NULL;
+        }
+        bytes[i] = (uint8_t)byte;
+    }
+    return bytes;
+}
+
+static const char *describe(int32_t code) {
+    switch (code) {
+    case LEANSIG_VALID:
+        return "valid";
+    case LEANSIG_INVALID:
+        return "invalid";
+    case LEANSIG_ERR_NULL_POINTER:
+        return "null pointer";
+    case LEANSIG_ERR_UNKNOWN_SPEC:
+        return "unknown spec";
+    case LEANSIG_ERR_PARAM_LENGTH:
+        return "wrong param length";
+    case LEANSIG_ERR_SIGNATURE_LENGTH:
+        return "wrong signature length";
+    case LEANSIG_ERR_UNKNOWN_VALIDATOR:
+        return "unknown validator";
+    case LEANSIG_ERR_LENGTH_OVERFLOW:
+        return "validator set too large";
+    case LEANSIG_ERR_DUPLICATE_VALIDATOR:
+        return "repeated validator root";
+    default:
+        return "panic";
+    }
+}
+
+int main(int argc, char **argv) {
+    if (argc != 6) {
+        fprintf(stderr, "usage: %s SPEC_ID PARAM MESSAGE SIGNATURE ROOT\n", argv[0]);
+        return 2;
+    }
+    uint32_t spec_id = (uint32_t)strtoul(argv[1], NULL, 10);
+    size_t param_len, message_len, sig_len, root_len;
+    uint8_t *param = from_hex(argv[2], &param_len);
+    uint8_t *message = from_hex(argv[3], &message_len);
+    uint8_t *sig = from_hex(argv[4], &sig_len);
+    uint8_t *root = from_hex(argv[5], &root_len);
+    if (!param || !message || !sig || !root || message_len != 32 || root_len != 32) {
+        fprintf(stderr, "expected hex arguments, with 32-byte messages and roots\n");
+        return 2;
+    }
+
+    int32_t single = leansig_verify(spec_id, param, param_len, message, sig, sig_len, root);
+    printf("signature: %s\n", describe(single));
+
+    uint32_t validator_index = 0;
+    const uint8_t *sig_ptrs[1] = {sig};
+    size_t sig_lens[1] = {sig_len};
+    int32_t aggregate = leansig_verify_aggregate(spec_id, message, root, param, param_len, 1,
+                                                 &validator_index, sig_ptrs, sig_lens, 1);
+    printf("aggregate: %s\n", describe(aggregate));
+
+    free(param);
+    free(message);
+    free(sig);
+    free(root);
+    return single == LEANSIG_VALID && aggregate == LEANSIG_VALID ? 0 : 1;
+}
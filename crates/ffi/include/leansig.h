@@ -0,0 +1,104 @@
+/* Copyright 2025 Irreducible Inc. */
+
+#ifndef LEANSIG_H
+#define LEANSIG_H
+
+/* Generated by cbindgen from crates/ffi/src/lib.rs, do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// The id of [`SPEC_1`]
+#define LEANSIG_SPEC_1 1
+
+// The id of [`SPEC_2`]
+#define LEANSIG_SPEC_2 2
+
+// The signature or aggregate verifies
+#define LEANSIG_VALID 1
+
+// The inputs are well-formed, but the signature or aggregate does not verify
+#define LEANSIG_INVALID 0
+
+// A pointer is null, while its length is not zero
+#define LEANSIG_ERR_NULL_POINTER -1
+
+// The spec id is neither [`LEANSIG_SPEC_1`] nor [`LEANSIG_SPEC_2`]
+#define LEANSIG_ERR_UNKNOWN_SPEC -2
+
+// The param length is not the `param_len` of the spec
+#define LEANSIG_ERR_PARAM_LENGTH -3
+
+// No tree height gives a signature of the given length under the spec
+#define LEANSIG_ERR_SIGNATURE_LENGTH -4
+
+// A validator index is past the end of the validator set
+#define LEANSIG_ERR_UNKNOWN_VALIDATOR -5
+
+// Verification panicked, which is a bug
+#define LEANSIG_ERR_PANIC -6
+
+// The byte length of the roots or params of the validator set overflows a `size_t`
+#define LEANSIG_ERR_LENGTH_OVERFLOW -7
+
+// A root is listed more than once in the validator set
+#define LEANSIG_ERR_DUPLICATE_VALIDATOR -8
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Verifies a signature against the root and param of its signer, see
+// [`leansig_core::verify_signature`]
+//
+// Returns [`LEANSIG_VALID`], [`LEANSIG_INVALID`] or a negative error code.
+//
+// # Safety
+//
+// `param_ptr` must point to `param_len` readable bytes, `message_ptr` and `root_ptr` to 32
+// readable bytes each and `sig_ptr` to `sig_len` readable bytes, none of which are written
+// during the call.
+int32_t leansig_verify(uint32_t spec_id,
+                       const uint8_t *param_ptr,
+                       size_t param_len,
+                       const uint8_t *message_ptr,
+                       const uint8_t *sig_ptr,
+                       size_t sig_len,
+                       const uint8_t *root_ptr);
+
+// Verifies the signatures of the validators `validator_indices` of a validator set, see
+// [`AggregatedVerifier::verify`]
+//
+// The set has `num_validators` validators, whose roots are concatenated at `roots_ptr` and
+// whose params are concatenated at `params_ptr`, `param_len` bytes of the spec each. Signature
+// `i` is signed by validator `validator_indices[i]` at the epoch of the leaf its proof opens,
+// and takes `sig_lens[i]` bytes at `sig_ptrs[i]`. Every root of the set must be distinct, so
+// that the validator indices are the positions in the set.
+//
+// Returns [`LEANSIG_VALID`], [`LEANSIG_INVALID`] or a negative error code.
+//
+// # Safety
+//
+// `message_ptr` must point to 32 readable bytes, `roots_ptr` to `32 * num_validators` and
+// `params_ptr` to `param_len * num_validators` readable bytes. `validator_indices`, `sig_ptrs`
+// and `sig_lens` must point to `num_signatures` readable elements each, and every `sig_ptrs[i]`
+// to `sig_lens[i]` readable bytes. None of them are written during the call.
+int32_t leansig_verify_aggregate(uint32_t spec_id,
+                                 const uint8_t *message_ptr,
+                                 const uint8_t *roots_ptr,
+                                 const uint8_t *params_ptr,
+                                 size_t param_len,
+                                 size_t num_validators,
+                                 const uint32_t *validator_indices,
+                                 const uint8_t *const *sig_ptrs,
+                                 const size_t *sig_lens,
+                                 size_t num_signatures);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* LEANSIG_H */
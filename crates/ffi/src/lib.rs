@@ -0,0 +1,371 @@
+// Copyright 2025 Irreducible Inc.
+//! C bindings of signature verification.
+//!
+//! The build writes the header of the bindings to `include/leansig.h`, and
+//! `examples/verify.c` shows how to call them. Signatures are passed in the fixed-layout
+//! encoding of [`Signature::to_bytes`], roots and messages as 32 bytes and params as
+//! `param_len` bytes of the spec. The functions return [`LEANSIG_VALID`] or [`LEANSIG_INVALID`]
+//! for well-formed inputs and one of the negative `LEANSIG_ERR_*` codes otherwise. A panic is
+//! caught and reported as [`LEANSIG_ERR_PANIC`] rather than unwinding into the caller.
+
+use std::panic::{self, AssertUnwindSafe};
+use std::slice;
+
+use leansig_core::{
+    AggregatedSignature, AggregatedVerifier, Message, Param, Signature, ValidatorSignature,
+    hash::{HASH_LEN, Hash},
+    registry::ValidatorRegistry,
+    spec::{SPEC_1, SPEC_2, Spec},
+    verify_signature,
+};
+
+/// The id of [`SPEC_1`]
+pub const LEANSIG_SPEC_1: u32 = 1;
+/// The id of [`SPEC_2`]
+pub const LEANSIG_SPEC_2: u32 = 2;
+
+/// The signature or aggregate verifies
+pub const LEANSIG_VALID: i32 = 1;
+/// The inputs are well-formed, but the signature or aggregate does not verify
+pub const LEANSIG_INVALID: i32 = 0;
+/// A pointer is null, while its length is not zero
+pub const LEANSIG_ERR_NULL_POINTER: i32 = -1;
+/// The spec id is neither [`LEANSIG_SPEC_1`] nor [`LEANSIG_SPEC_2`]
+pub const LEANSIG_ERR_UNKNOWN_SPEC: i32 = -2;
+/// The param length is not the `param_len` of the spec
+pub const LEANSIG_ERR_PARAM_LENGTH: i32 = -3;
+/// No tree height gives a signature of the given length under the spec
+pub const LEANSIG_ERR_SIGNATURE_LENGTH: i32 = -4;
+/// A validator index is past the end of the validator set
+pub const LEANSIG_ERR_UNKNOWN_VALIDATOR: i32 = -5;
+/// Verification panicked, which is a bug
+pub const LEANSIG_ERR_PANIC: i32 = -6;
+/// The byte length of the roots or params of the validator set overflows a `size_t`
+pub const LEANSIG_ERR_LENGTH_OVERFLOW: i32 = -7;
+/// A root is listed more than once in the validator set
+pub const LEANSIG_ERR_DUPLICATE_VALIDATOR: i32 = -8;
+
+/// The spec of `spec_id`, see [`LEANSIG_SPEC_1`] and [`LEANSIG_SPEC_2`]
+fn spec(spec_id: u32) -> Result<Spec, i32> {
+    match spec_id {
+        LEANSIG_SPEC_1 => Ok(SPEC_1),
+        LEANSIG_SPEC_2 => Ok(SPEC_2),
+        _ => Err(LEANSIG_ERR_UNKNOWN_SPEC),
+    }
+}
+
+/// The `len` bytes at `ptr`, where `ptr` may only be null if `len` is zero
+///
+/// # Safety
+///
+/// Unless it is null, `ptr` must point to `len` readable elements that outlive `'a`.
+unsafe fn slice_from<'a, T>(ptr: *const T, len: usize) -> Result<&'a [T], i32> {
+    if len == 0 {
+        Ok(&[])
+    } else if ptr.is_null() {
+        Err(LEANSIG_ERR_NULL_POINTER)
+    } else {
+        // SAFETY: non-null and `len` elements long per the contract of the caller
+        Ok(unsafe { slice::from_raw_parts(ptr, len) })
+    }
+}
+
+/// # Safety
+///
+/// Unless it is null, `ptr` must point to `N` readable bytes.
+unsafe fn array_from<const N: usize>(ptr: *const u8) -> Result<[u8; N], i32> {
+    let bytes = unsafe { slice_from(ptr, N) }?;
+    Ok(bytes.try_into().expect("slice of N bytes"))
+}
+
+fn param_from(spec: &Spec, bytes: &[u8]) -> Result<Param, i32> {
+    if bytes.len() != spec.param_len {
+        return Err(LEANSIG_ERR_PARAM_LENGTH);
+    }
+    Ok(Param::from(bytes))
+}
+
+/// Decodes a signature of whatever tree height its length implies.
+fn signature_from(spec: &Spec, bytes: &[u8]) -> Result<Signature, i32> {
+    let height =
+        Signature::height_for_encoded_len(spec, bytes.len()).ok_or(LEANSIG_ERR_SIGNATURE_LENGTH)?;
+    Signature::from_bytes(bytes, spec, height).map_err(|_| LEANSIG_ERR_SIGNATURE_LENGTH)
+}
+
+fn to_code(valid: bool) -> i32 {
+    if valid {
+        LEANSIG_VALID
+    } else {
+        LEANSIG_INVALID
+    }
+}
+
+/// Runs `f`, turning a panic into [`LEANSIG_ERR_PANIC`] so that it never unwinds into C.
+fn catch(f: impl FnOnce() -> Result<i32, i32>) -> i32 {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(code) | Err(code)) => code,
+        Err(_) => LEANSIG_ERR_PANIC,
+    }
+}
+
+/// Verifies a signature against the root and param of its signer, see
+/// [`leansig_core::verify_signature`]
+///
+/// Returns [`LEANSIG_VALID`], [`LEANSIG_INVALID`] or a negative error code.
+///
+/// # Safety
+///
+/// `param_ptr` must point to `param_len` readable bytes, `message_ptr` and `root_ptr` to 32
+/// readable bytes each and `sig_ptr` to `sig_len` readable bytes, none of which are written
+/// during the call.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn leansig_verify(
+    spec_id: u32,
+    param_ptr: *const u8,
+    param_len: usize,
+    message_ptr: *const u8,
+    sig_ptr: *const u8,
+    sig_len: usize,
+    root_ptr: *const u8,
+) -> i32 {
+    catch(|| {
+        let spec = spec(spec_id)?;
+        let param = param_from(&spec, unsafe { slice_from(param_ptr, param_len) }?)?;
+        let message = Message(unsafe { array_from(message_ptr) }?);
+        let root = Hash(unsafe { array_from(root_ptr) }?);
+        let signature = signature_from(&spec, unsafe { slice_from(sig_ptr, sig_len) }?)?;
+        Ok(to_code(verify_signature(
+            &spec, &param, &message, &signature, &root,
+        )))
+    })
+}
+
+/// Verifies the signatures of the validators `validator_indices` of a validator set, see
+/// [`AggregatedVerifier::verify`]
+///
+/// The set has `num_validators` validators, whose roots are concatenated at `roots_ptr` and
+/// whose params are concatenated at `params_ptr`, `param_len` bytes of the spec each. Signature
+/// `i` is signed by validator `validator_indices[i]` at the epoch of the leaf its proof opens,
+/// and takes `sig_lens[i]` bytes at `sig_ptrs[i]`. Every root of the set must be distinct, so
+/// that the validator indices are the positions in the set.
+///
+/// Returns [`LEANSIG_VALID`], [`LEANSIG_INVALID`] or a negative error code.
+///
+/// # Safety
+///
+/// `message_ptr` must point to 32 readable bytes, `roots_ptr` to `32 * num_validators` and
+/// `params_ptr` to `param_len * num_validators` readable bytes. `validator_indices`, `sig_ptrs`
+/// and `sig_lens` must point to `num_signatures` readable elements each, and every `sig_ptrs[i]`
+/// to `sig_lens[i]` readable bytes. None of them are written during the call.
+#[unsafe(no_mangle)]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn leansig_verify_aggregate(
+    spec_id: u32,
+    message_ptr: *const u8,
+    roots_ptr: *const u8,
+    params_ptr: *const u8,
+    param_len: usize,
+    num_validators: usize,
+    validator_indices: *const u32,
+    sig_ptrs: *const *const u8,
+    sig_lens: *const usize,
+    num_signatures: usize,
+) -> i32 {
+    catch(|| {
+        let spec = spec(spec_id)?;
+        if param_len != spec.param_len {
+            return Err(LEANSIG_ERR_PARAM_LENGTH);
+        }
+        let message = Message(unsafe { array_from(message_ptr) }?);
+        let roots_len =
+            (HASH_LEN.checked_mul(num_validators)).ok_or(LEANSIG_ERR_LENGTH_OVERFLOW)?;
+        let params_len =
+            (param_len.checked_mul(num_validators)).ok_or(LEANSIG_ERR_LENGTH_OVERFLOW)?;
+        let roots = unsafe { slice_from(roots_ptr, roots_len) }?;
+        let params = unsafe { slice_from(params_ptr, params_len) }?;
+        let validators = (roots.as_chunks::<HASH_LEN>().0.iter())
+            .zip(params.chunks_exact(param_len))
+            .map(|(root, param)| Ok((Hash(*root), param_from(&spec, param)?)))
+            .collect::<Result<Vec<_>, i32>>()?;
+        // The registry keeps the first of repeated roots, which would shift the indices of the
+        // later validators away from their positions in the set
+        let registry = ValidatorRegistry::new(validators.clone());
+        if registry.len() != validators.len() {
+            return Err(LEANSIG_ERR_DUPLICATE_VALIDATOR);
+        }
+
+        let indices = unsafe { slice_from(validator_indices, num_signatures) }?;
+        let sig_ptrs = unsafe { slice_from(sig_ptrs, num_signatures) }?;
+        let sig_lens = unsafe { slice_from(sig_lens, num_signatures) }?;
+        let signatures = (indices.iter().zip(sig_ptrs).zip(sig_lens))
+            .map(|((&index, &sig_ptr), &sig_len)| {
+                let signature = signature_from(&spec, unsafe { slice_from(sig_ptr, sig_len) }?)?;
+                let (root, param) =
+                    (validators.get(index as usize)).ok_or(LEANSIG_ERR_UNKNOWN_VALIDATOR)?;
+                Ok(ValidatorSignature {
+                    epoch: signature.hash_tree_proof.leaf_index() as u64,
+                    signature: signature.into(),
+                    xmss_root: *root,
+                    param: param.clone(),
+                    validator_index: Some(index),
+                })
+            })
+            .collect::<Result<Vec<_>, i32>>()?;
+
+        let verifier = AggregatedVerifier::new(registry, spec);
+        Ok(to_code(
+            verifier.verify(&message, &AggregatedSignature::new(signatures)),
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ptr;
+
+    use leansig_core::Signer;
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::*;
+
+    const MESSAGE: Message = Message([42; 32]);
+
+    fn signers(count: u64) -> Vec<Signer> {
+        (0..count)
+            .map(|seed| Signer::new(StdRng::seed_from_u64(seed), 100_000, SPEC_2, 4).unwrap())
+            .collect()
+    }
+
+    fn verify(signer: &Signer, message: &Message, signature: &[u8]) -> i32 {
+        let param = signer.param.as_ref();
+        unsafe {
+            leansig_verify(
+                LEANSIG_SPEC_2,
+                param.as_ptr(),
+                param.len(),
+                message.0.as_ptr(),
+                signature.as_ptr(),
+                signature.len(),
+                signer.root.0.as_ptr(),
+            )
+        }
+    }
+
+    #[test]
+    fn test_verify() {
+        let mut signer = signers(1).remove(0);
+        let signature = signer.sign(1, &MESSAGE).unwrap().to_bytes();
+        assert_eq!(verify(&signer, &MESSAGE, &signature), LEANSIG_VALID);
+        assert_eq!(
+            verify(&signer, &Message([0; 32]), &signature),
+            LEANSIG_INVALID
+        );
+        assert_eq!(
+            verify(&signer, &MESSAGE, &signature[1..]),
+            LEANSIG_ERR_SIGNATURE_LENGTH
+        );
+
+        let param = signer.param.as_ref();
+        let call = |spec_id, param_len, message_ptr| unsafe {
+            leansig_verify(
+                spec_id,
+                param.as_ptr(),
+                param_len,
+                message_ptr,
+                signature.as_ptr(),
+                signature.len(),
+                signer.root.0.as_ptr(),
+            )
+        };
+        assert_eq!(
+            call(3, param.len(), MESSAGE.0.as_ptr()),
+            LEANSIG_ERR_UNKNOWN_SPEC
+        );
+        assert_eq!(
+            call(LEANSIG_SPEC_2, 1, MESSAGE.0.as_ptr()),
+            LEANSIG_ERR_PARAM_LENGTH
+        );
+        assert_eq!(
+            call(LEANSIG_SPEC_2, param.len(), ptr::null()),
+            LEANSIG_ERR_NULL_POINTER
+        );
+    }
+
+    #[test]
+    fn test_verify_aggregate() {
+        let mut validators = signers(3);
+        let roots: Vec<u8> = validators.iter().flat_map(|v| v.root.0).collect();
+        let params: Vec<u8> = (validators.iter())
+            .flat_map(|v| v.param.as_ref().to_vec())
+            .collect();
+        let signatures: Vec<Vec<u8>> = (validators.iter_mut())
+            .map(|v| v.sign(2, &MESSAGE).unwrap().to_bytes())
+            .collect();
+        let sig_ptrs: Vec<_> = signatures.iter().map(|s| s.as_ptr()).collect();
+        let sig_lens: Vec<_> = signatures.iter().map(Vec::len).collect();
+
+        let verify = |indices: &[u32], num_signatures: usize| unsafe {
+            leansig_verify_aggregate(
+                LEANSIG_SPEC_2,
+                MESSAGE.0.as_ptr(),
+                roots.as_ptr(),
+                params.as_ptr(),
+                SPEC_2.param_len,
+                validators.len(),
+                indices.as_ptr(),
+                sig_ptrs.as_ptr(),
+                sig_lens.as_ptr(),
+                num_signatures,
+            )
+        };
+        assert_eq!(verify(&[0, 1, 2], 3), LEANSIG_VALID);
+        assert_eq!(verify(&[0, 1], 2), LEANSIG_VALID);
+        // Empty aggregates don't verify vacuously
+        assert_eq!(verify(&[], 0), LEANSIG_INVALID);
+        assert_eq!(verify(&[1, 0, 2], 3), LEANSIG_INVALID);
+        assert_eq!(verify(&[0, 1, 3], 3), LEANSIG_ERR_UNKNOWN_VALIDATOR);
+
+        // A validator set whose byte length overflows fails before anything is read
+        for num_validators in [usize::MAX / HASH_LEN + 1, usize::MAX] {
+            let code = unsafe {
+                leansig_verify_aggregate(
+                    LEANSIG_SPEC_2,
+                    MESSAGE.0.as_ptr(),
+                    roots.as_ptr(),
+                    params.as_ptr(),
+                    SPEC_2.param_len,
+                    num_validators,
+                    [0].as_ptr(),
+                    sig_ptrs.as_ptr(),
+                    sig_lens.as_ptr(),
+                    1,
+                )
+            };
+            assert_eq!(code, LEANSIG_ERR_LENGTH_OVERFLOW);
+        }
+
+        // A repeated root is rejected rather than shifting the indices of the later validators
+        let mut repeated_roots = roots.clone();
+        repeated_roots.copy_within(..HASH_LEN, HASH_LEN);
+        let code = unsafe {
+            leansig_verify_aggregate(
+                LEANSIG_SPEC_2,
+                MESSAGE.0.as_ptr(),
+                repeated_roots.as_ptr(),
+                params.as_ptr(),
+                SPEC_2.param_len,
+                validators.len(),
+                [0, 2].as_ptr(),
+                [sig_ptrs[0], sig_ptrs[2]].as_ptr(),
+                [sig_lens[0], sig_lens[2]].as_ptr(),
+                2,
+            )
+        };
+        assert_eq!(code, LEANSIG_ERR_DUPLICATE_VALIDATOR);
+    }
+
+    #[test]
+    fn test_panic_does_not_unwind() {
+        assert_eq!(catch(|| panic!("boom")), LEANSIG_ERR_PANIC);
+    }
+}
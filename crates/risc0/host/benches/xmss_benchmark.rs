@@ -1,17 +1,31 @@
 // Copyright 2025 Irreducible Inc.
+use std::time::Instant;
+
 use criterion::{Criterion, black_box, criterion_group, criterion_main};
-use leansig_core::spec::{SPEC_1, SPEC_2, Spec};
-use leansig_shared::{XmssTestData, create_test_data};
-use methods::{XMSS_AGGREGATE_ELF, XMSS_AGGREGATE_ID};
-use risc0_zkvm::{
-    ExecutorEnv, ExecutorImpl, ProverOpts, Session, VerifierContext, get_prover_server,
+use leansig_core::{
+    hash::TweakableHash,
+    spec::{SPEC_1, SPEC_2, Spec},
+};
+use leansig_shared::{
+    Backend, BenchRecord, GuestInput, SweepGrid, TestDataBuilder, ValidatorSeeds, XmssTestData,
+    bench_results_dir, write_bench_results,
 };
+use methods::XMSS_AGGREGATE_ELF;
+use risc0_host::{ProofKind, ProveConfig, seal_size, verify_receipt};
+use risc0_zkvm::{ExecutorImpl, Session, VerifierContext, get_prover_server};
 
 /// Configuration parameters for benchmarking
+#[derive(Clone)]
 struct BenchmarkConfig {
     num_validators: usize,
     tree_height: usize,
     spec: Spec,
+    proof_kinds: Vec<ProofKind>,
+    segment_limit_po2: Option<u32>,
+    max_cycles: Option<u64>,
+    hashfn: Option<String>,
+    /// Run every point of a [`SweepGrid`] once instead of benchmarking a single point
+    sweep: bool,
 }
 
 impl Default for BenchmarkConfig {
@@ -20,6 +34,11 @@ impl Default for BenchmarkConfig {
             num_validators: 16,
             tree_height: 13,
             spec: SPEC_2,
+            proof_kinds: vec![ProofKind::Succinct],
+            segment_limit_po2: None,
+            max_cycles: None,
+            hashfn: None,
+            sweep: false,
         }
     }
 }
@@ -48,40 +67,107 @@ impl BenchmarkConfig {
             };
         }
 
+        // A comma-separated list of kinds, e.g. `composite,succinct,groth16`, or `all`
+        if let Ok(val) = std::env::var("BENCH_PROOF_KINDS") {
+            let kinds: Vec<_> = ProofKind::ALL
+                .into_iter()
+                .filter(|kind| val == "all" || val.split(',').any(|name| name == kind.name()))
+                .collect();
+            if !kinds.is_empty() {
+                config.proof_kinds = kinds;
+            }
+        }
+
+        if let Ok(val) = std::env::var("BENCH_SEGMENT_LIMIT_PO2") {
+            if let Ok(po2) = val.parse() {
+                config.segment_limit_po2 = Some(po2);
+            }
+        }
+
+        if let Ok(val) = std::env::var("BENCH_MAX_CYCLES") {
+            if let Ok(cycles) = val.parse() {
+                config.max_cycles = Some(cycles);
+            }
+        }
+
+        if let Ok(val) = std::env::var("BENCH_HASHFN") {
+            config.hashfn = Some(val);
+        }
+
+        if let Ok(val) = std::env::var("BENCH_SWEEP") {
+            config.sweep = val == "1" || val == "true";
+        }
+
         config
     }
+
+    /// The configuration to run the guest and prove receipts of `proof_kind` with
+    fn prove_config(&self, proof_kind: ProofKind) -> ProveConfig {
+        ProveConfig {
+            proof_kind,
+            segment_limit_po2: self.segment_limit_po2,
+            max_cycles: self.max_cycles,
+            hashfn: self.hashfn.clone(),
+            ..ProveConfig::default()
+        }
+    }
+}
+
+/// Where the test data is cached between runs, overridden by `BENCH_TEST_DATA_CACHE`
+///
+/// The file is regenerated whenever the configuration changes, so one path serves all of them.
+fn test_data_cache_path() -> std::path::PathBuf {
+    std::env::var_os("BENCH_TEST_DATA_CACHE")
+        .map(Into::into)
+        .unwrap_or_else(|| std::env::temp_dir().join("leansig-xmss-bench-test-data.bin"))
+}
+
+/// Where the test data of a point of the sweep is cached between runs, next to
+/// [`test_data_cache_path`]
+fn sweep_cache_path(num_validators: usize, tree_height: usize) -> std::path::PathBuf {
+    let path = test_data_cache_path();
+    path.with_file_name(format!(
+        "{}-{num_validators}-{tree_height}.bin",
+        path.file_stem().unwrap_or_default().to_string_lossy()
+    ))
 }
 
 /// Job structure for benchmarking XMSS signatures
 struct Job {
     elf: Vec<u8>,
     test_data: XmssTestData,
+    prove_config: ProveConfig,
 }
 
 impl Job {
-    fn new(config: BenchmarkConfig) -> Self {
-        // Create test data with specified parameters
-        let test_data = create_test_data(
-            config.num_validators,
-            config.spec.clone(),
-            config.tree_height,
-            10000, // max_retries for nonce grinding
-            None,  // use default message [42; 32]
-            None,  // use default epoch 0
-        );
+    fn new(config: BenchmarkConfig, cache_path: std::path::PathBuf) -> Self {
+        // Load the test data from the cache, or create it once for the specified parameters
+        // The message [42; 32], epoch 0 and 10000 nonce grinding retries are the defaults, and
+        // the legacy seeds keep the validators of earlier recorded runs
+        let test_data = TestDataBuilder::new()
+            .validators(config.num_validators)
+            .spec(config.spec.clone())
+            .tree_height(config.tree_height)
+            .seeds(ValidatorSeeds::Legacy)
+            .build_or_load(cache_path);
+        // Cached data in particular must still verify before it is proven
+        test_data
+            .verify()
+            .expect("failed to verify aggregated signature");
 
         Self {
             elf: XMSS_AGGREGATE_ELF.to_vec(),
             test_data,
+            // The kind of receipt does not matter for the execution
+            prove_config: config.prove_config(ProofKind::default()),
         }
     }
 
     /// Execute witness generation phase
     fn exec_compute(&self) -> Session {
-        let env = ExecutorEnv::builder()
-            .write(&self.test_data)
-            .unwrap()
-            .build()
+        let env = self
+            .prove_config
+            .executor_env(&GuestInput::SingleMessage(self.test_data.clone()))
             .unwrap();
 
         let mut exec = ExecutorImpl::from_elf(env, &self.elf).unwrap();
@@ -89,9 +175,62 @@ impl Job {
     }
 }
 
+/// Proves every point of the sweep grid once for each proof kind, and writes the results to
+/// `risc0.csv` and `risc0.json` in `target/bench-results`
+fn run_sweep(config: &BenchmarkConfig) {
+    let grid = SweepGrid::from_env();
+    let ctx = VerifierContext::default();
+    println!("{}", BenchRecord::CSV_HEADER);
+
+    let mut records = Vec::new();
+    for (num_validators, tree_height) in grid.points() {
+        let point = BenchmarkConfig {
+            num_validators,
+            tree_height,
+            ..config.clone()
+        };
+        let job = Job::new(point, sweep_cache_path(num_validators, tree_height));
+        let session = job.exec_compute();
+
+        for &kind in &config.proof_kinds {
+            let prover = get_prover_server(&config.prove_config(kind).prover_opts()).unwrap();
+            let start = Instant::now();
+            let receipt = prover.prove_session(&ctx, &session).unwrap().receipt;
+            let proving_time = start.elapsed();
+
+            let start = Instant::now();
+            verify_receipt(&receipt, &job.test_data.public_inputs, false).unwrap();
+            let verification_time = start.elapsed();
+
+            let record = BenchRecord::new(
+                "risc0",
+                kind.name(),
+                &config.spec,
+                num_validators,
+                tree_height,
+                session.total_cycles,
+                proving_time,
+                verification_time,
+                seal_size(&receipt),
+                receipt.journal.bytes.len(),
+            );
+            println!("{}", record.to_csv_row());
+            records.push(record);
+        }
+    }
+
+    let dir = bench_results_dir();
+    write_bench_results(&dir, "risc0", &records).expect("failed to write the results");
+    println!("\nResults written to {}", dir.join("risc0.csv").display());
+}
+
 /// Main benchmarking function
 fn xmss_benchmarks(c: &mut Criterion) {
     let config = BenchmarkConfig::from_env();
+    if config.sweep {
+        run_sweep(&config);
+        return;
+    }
 
     println!("\n════════════════════════════════════════════════");
     println!("XMSS Signature Benchmark Configuration:");
@@ -109,16 +248,26 @@ fn xmss_benchmarks(c: &mut Criterion) {
             "SPEC_2"
         }
     );
+    println!("  Hash Backend: {}", <Backend as TweakableHash>::NAME);
+    let kind_names: Vec<_> = config.proof_kinds.iter().map(|kind| kind.name()).collect();
+    println!("  Proof Kinds: {}", kind_names.join(", "));
+    if let Some(po2) = config.segment_limit_po2 {
+        println!("  Segment Limit: 2^{po2} cycles");
+    }
     println!("════════════════════════════════════════════════\n");
 
-    // Setup prover and verifier context once for all benchmarks
-    let prover = get_prover_server(&ProverOpts::succinct()).unwrap();
+    // Setup verifier context once for all benchmarks
     let ctx = VerifierContext::default();
+    let prove_configs: Vec<_> = config
+        .proof_kinds
+        .iter()
+        .map(|&kind| config.prove_config(kind))
+        .collect();
 
     let mut group = c.benchmark_group("xmss_signature");
     group.sample_size(100);
 
-    let job = Job::new(config);
+    let job = Job::new(config, test_data_cache_path());
 
     // Benchmark 1: Witness Generation
     group.bench_function("witness_generation", |b| {
@@ -134,48 +283,54 @@ fn xmss_benchmarks(c: &mut Criterion) {
     // Reset group configuration for proof generation
     group.finish();
 
-    // Create new group for proof generation benchmarks
-    let mut group = c.benchmark_group("xmss_signature_proving");
-    group.sample_size(10);
+    // Print additional metrics
+    println!("\nAdditional Metrics:");
+    println!("  Total Cycles: {}", session.total_cycles);
+    println!("  User Cycles: {}", session.user_cycles);
 
-    // Benchmark 2: Proof Generation (Succinct only)
-    group.bench_function("proof_generation", |b| {
-        b.iter(|| {
-            let receipt = prover.prove_session(&ctx, &session).unwrap().receipt;
-            black_box(receipt);
+    for prove_config in prove_configs {
+        let kind = prove_config.proof_kind;
+        let prover = get_prover_server(&prove_config.prover_opts()).unwrap();
+
+        // Create new group for proof generation benchmarks
+        let mut group = c.benchmark_group("xmss_signature_proving");
+        group.sample_size(10);
+
+        // Benchmark 2: Proof Generation
+        group.bench_function(format!("proof_generation_{}", kind.name()), |b| {
+            b.iter(|| {
+                let receipt = prover.prove_session(&ctx, &session).unwrap().receipt;
+                black_box(receipt);
+            });
         });
-    });
 
-    // Generate succinct receipt for verification benchmark
-    let receipt = prover.prove_session(&ctx, &session).unwrap().receipt;
+        // Generate a receipt of this kind for the verification benchmark
+        let receipt = prover.prove_session(&ctx, &session).unwrap().receipt;
 
-    group.finish();
+        group.finish();
 
-    // Create new group for verification benchmarks
-    let mut group = c.benchmark_group("xmss_signature_verification");
-    group.sample_size(100); // Many samples for quick operation
+        // Create new group for verification benchmarks
+        let mut group = c.benchmark_group("xmss_signature_verification");
+        group.sample_size(100); // Many samples for quick operation
 
-    group.bench_function("proof_verification", |b| {
-        b.iter(|| {
-            receipt.verify(XMSS_AGGREGATE_ID).unwrap();
+        group.bench_function(format!("proof_verification_{}", kind.name()), |b| {
+            // The digest of the expected public inputs is recomputed for every verification
+            b.iter(|| {
+                verify_receipt(&receipt, &job.test_data.public_inputs, false).unwrap();
+            });
         });
-    });
 
-    // Print additional metrics
-    println!("\nAdditional Metrics:");
-    println!("  Total Cycles: {}", session.total_cycles);
-    println!("  User Cycles: {}", session.user_cycles);
-    println!("  Journal Size: {} bytes", receipt.journal.bytes.len());
+        group.finish();
 
-    if let Ok(succinct) = receipt.inner.succinct() {
+        let seal_size = seal_size(&receipt);
         println!(
-            "  Succinct Proof Size: {:.2} KiB ({} bytes)",
-            succinct.seal_size() as f64 / 1024.0,
-            succinct.seal_size()
+            "  {} Seal Size: {:.2} KiB ({} bytes), Journal Size: {} bytes",
+            kind.name(),
+            seal_size as f64 / 1024.0,
+            seal_size,
+            receipt.journal.bytes.len()
         );
     }
-
-    group.finish();
 }
 
 criterion_group!(xmss_signature, xmss_benchmarks);
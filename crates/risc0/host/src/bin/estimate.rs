@@ -0,0 +1,31 @@
+// Copyright 2025 Irreducible Inc.
+//! Estimates the cost of proving an aggregate by running the guest without proving it.
+//!
+//! Usage: `estimate [validators] [tree height]`, by default 16 validators with trees of height 13.
+
+use leansig_core::spec;
+use leansig_shared::TestDataBuilder;
+use risc0_host::execute_xmss_aggregate;
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let num_validators = args.next().map_or(16, |arg| {
+        arg.parse().expect("the number of validators is an integer")
+    });
+    let tree_height = args.next().map_or(13, |arg| {
+        arg.parse().expect("the tree height is an integer")
+    });
+
+    let test_data = TestDataBuilder::new()
+        .validators(num_validators)
+        .spec(spec::SPEC_2)
+        .tree_height(tree_height)
+        .build();
+
+    let stats = execute_xmss_aggregate(&test_data).expect("failed to execute the guest");
+    println!("Validators: {num_validators}, tree height: {tree_height}");
+    println!(
+        "Total cycles: {}, user cycles: {}, segments: {}",
+        stats.total_cycles, stats.user_cycles, stats.segments
+    );
+}
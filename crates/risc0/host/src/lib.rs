@@ -0,0 +1,970 @@
+// Copyright 2025 Irreducible Inc.
+//! Proving XMSS signature aggregates with the RISC0 guests.
+
+use core::fmt;
+use std::{
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use leansig_core::{
+    DecodeError,
+    hash::Hash,
+    wire::{self, PayloadKind},
+};
+use leansig_shared::{
+    AggregateJournal, FailMode, GuestInput, MergeError, PublicInputs, XmssTestData,
+};
+use methods::{XMSS_AGGREGATE_ELF, XMSS_AGGREGATE_ID, XMSS_COMPOSE_ELF, XMSS_COMPOSE_ID};
+use risc0_zkvm::{
+    Digest, ExecutorEnv, ExecutorEnvBuilder, ExecutorImpl, InnerReceipt, Journal, ProverOpts,
+    Receipt, VerificationError, VerifierContext, default_prover, is_dev_mode, sha::Digestible,
+};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "remote")]
+mod remote;
+
+/// The kind of receipt to prove an aggregate with
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ProofKind {
+    /// A receipt per segment of the execution, the cheapest to prove and the largest
+    #[default]
+    Composite,
+    /// The segment receipts recursively compressed into a single STARK
+    Succinct,
+    /// The succinct receipt wrapped into a Groth16 proof, which can be verified on-chain
+    Groth16,
+}
+
+impl ProofKind {
+    /// Every kind, from the cheapest to prove to the smallest
+    pub const ALL: [ProofKind; 3] = [
+        ProofKind::Composite,
+        ProofKind::Succinct,
+        ProofKind::Groth16,
+    ];
+
+    /// The prover options that produce receipts of this kind
+    pub fn prover_opts(self) -> ProverOpts {
+        match self {
+            ProofKind::Composite => ProverOpts::composite(),
+            ProofKind::Succinct => ProverOpts::succinct(),
+            ProofKind::Groth16 => ProverOpts::groth16(),
+        }
+    }
+
+    /// The lowercase name of the kind
+    pub fn name(self) -> &'static str {
+        match self {
+            ProofKind::Composite => "composite",
+            ProofKind::Succinct => "succinct",
+            ProofKind::Groth16 => "groth16",
+        }
+    }
+}
+
+/// Where proofs are generated
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum ProverBackend {
+    /// The default prover of this machine, which may itself be selected by the environment
+    #[default]
+    Local,
+    /// Bonsai, the remote prover of RISC0, which requires the `remote` feature
+    Remote {
+        /// The environment variable holding the API key
+        api_key_env: String,
+        /// The URL of the API, or `BONSAI_API_URL` if `None`
+        url: Option<String>,
+    },
+}
+
+impl ProverBackend {
+    /// The lowercase name of the backend
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProverBackend::Local => "local",
+            ProverBackend::Remote { .. } => "remote",
+        }
+    }
+
+    /// The URL and the API key of a remote backend, read from the environment, or `None` for the
+    /// local one
+    ///
+    /// # Errors
+    /// Returns an error if the API key or the URL is not set.
+    pub fn remote_credentials(&self) -> Result<Option<(String, String)>, RemoteError> {
+        let ProverBackend::Remote { api_key_env, url } = self else {
+            return Ok(None);
+        };
+        let api_key = std::env::var(api_key_env).map_err(|_| RemoteError::MissingApiKey {
+            env: api_key_env.clone(),
+        })?;
+        let url = match url {
+            Some(url) => url.clone(),
+            None => std::env::var("BONSAI_API_URL").map_err(|_| RemoteError::MissingUrl)?,
+        };
+        Ok(Some((url, api_key)))
+    }
+}
+
+/// How to run the guest and prove its execution
+///
+/// The options left at `None` keep the defaults of RISC0.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProveConfig {
+    /// The kind of receipt to prove
+    pub proof_kind: ProofKind,
+    /// The log2 of the maximum number of cycles of a segment, trading prover memory for the
+    /// number of segment proofs
+    pub segment_limit_po2: Option<u32>,
+    /// The maximum number of cycles of the whole execution, beyond which the guest is aborted
+    pub max_cycles: Option<u64>,
+    /// The hash function of the proofs, e.g. `poseidon2` or `sha-256`
+    pub hashfn: Option<String>,
+    /// Environment variables visible to the guest
+    pub env_vars: Vec<(String, String)>,
+    /// Whether to skip proving and return fake receipts, as does `RISC0_DEV_MODE`
+    ///
+    /// Fake receipts prove nothing, and are only accepted by the verification functions that are
+    /// explicitly allowed to.
+    pub dev_mode: bool,
+    /// Where to prove, on this machine by default
+    ///
+    /// Dev mode proves locally whatever the backend, since fake receipts need no prover.
+    pub backend: ProverBackend,
+    /// How long to wait for a remote proof before giving up, or forever if `None`
+    pub remote_timeout: Option<Duration>,
+    /// What the guest does with an aggregate that fails to verify, see [`prove`]
+    pub fail_mode: FailMode,
+}
+
+impl ProveConfig {
+    /// The default configuration for receipts of `proof_kind`
+    pub fn new(proof_kind: ProofKind) -> Self {
+        Self {
+            proof_kind,
+            ..Self::default()
+        }
+    }
+
+    /// The prover options for the kind, hash function and dev mode of the configuration
+    pub fn prover_opts(&self) -> ProverOpts {
+        let mut opts = self.proof_kind.prover_opts();
+        if let Some(hashfn) = &self.hashfn {
+            opts = opts.with_hashfn(hashfn.clone());
+        }
+        // Without the option, the environment still selects dev mode
+        if self.dev_mode {
+            opts = opts.with_dev_mode(true);
+        }
+        opts
+    }
+
+    /// Whether proving returns fake receipts, by the configuration or by `RISC0_DEV_MODE`
+    pub fn is_dev_mode(&self) -> bool {
+        self.dev_mode || is_dev_mode()
+    }
+
+    /// The executor environment that runs the guest on `input` within the limits of the
+    /// configuration
+    ///
+    /// # Errors
+    /// Returns [`ProveError::Env`] if the input cannot be written to the environment.
+    pub fn executor_env(&self, input: &GuestInput) -> Result<ExecutorEnv<'static>, ProveError> {
+        let mut builder = self.env_builder();
+        builder.write(input).map_err(ProveError::Env)?;
+        builder.build().map_err(ProveError::Env)
+    }
+
+    /// A builder of executor environments within the limits of the configuration, without input
+    fn env_builder(&self) -> ExecutorEnvBuilder<'static> {
+        let mut builder = ExecutorEnv::builder();
+        if let Some(segment_limit_po2) = self.segment_limit_po2 {
+            builder.segment_limit_po2(segment_limit_po2);
+        }
+        builder.session_limit(self.max_cycles);
+        for (name, value) in &self.env_vars {
+            builder.env_var(name, value);
+        }
+        builder
+    }
+}
+
+/// How long a proof took, from the submission of the input to the receipt
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProveTimings {
+    /// The time from the submission of the input to the receipt, including the upload, the
+    /// queueing and the download of a remote proof
+    pub latency: Duration,
+    /// The time spent proving, as reported by the prover, or `None` if it reports none
+    pub proving_time: Option<Duration>,
+}
+
+impl ProveTimings {
+    /// The timings of a local proof, which spends all of its latency proving
+    fn local(latency: Duration) -> Self {
+        Self {
+            latency,
+            proving_time: Some(latency),
+        }
+    }
+
+    /// The time spent outside of proving, i.e. in queues and transfers, if the proving time is
+    /// known
+    pub fn queue_time(&self) -> Option<Duration> {
+        self.proving_time
+            .map(|proving_time| self.latency.saturating_sub(proving_time))
+    }
+}
+
+/// A receipt with the statistics of the execution it proves
+struct Proof {
+    receipt: Receipt,
+    total_cycles: u64,
+    user_cycles: u64,
+    segments: usize,
+    timings: ProveTimings,
+}
+
+/// A proof of an aggregate with what its journal commits
+///
+/// The public inputs are those of the aggregate, or of every aggregate of a batch, see
+/// [`prove_batch`].
+pub struct ProveResult<T = PublicInputs> {
+    /// The receipt, verified against the image id of the guest
+    pub receipt: Receipt,
+    /// The kind of the receipt
+    pub kind: ProofKind,
+    /// The image id of the guest the receipt proves, e.g. [`XMSS_AGGREGATE_ID`]
+    pub image_id: Digest,
+    /// The SHA-256 digest of the journal, which the claim of the receipt commits to
+    pub journal_digest: [u8; 32],
+    /// The public inputs committed to the journal, equal to the submitted ones
+    pub public_inputs: T,
+    /// The size of the seal in bytes, i.e. of all segment seals for a composite receipt
+    ///
+    /// The fake receipts of dev mode have no seal, so the size is 0 and says nothing about the
+    /// size of an actual proof.
+    pub seal_size: usize,
+    /// The size of the journal in bytes
+    pub journal_size_bytes: usize,
+    /// The number of cycles of the execution, including paging and padding
+    pub total_cycles: u64,
+    /// The number of cycles spent in the guest itself
+    pub user_cycles: u64,
+    /// The number of segments the execution was split into, see
+    /// [`ProveConfig::segment_limit_po2`]
+    pub num_segments: usize,
+    /// Whether the receipt is a fake one of dev mode, which proves nothing
+    pub is_dev_mode: bool,
+    /// The backend the receipt was proven with
+    pub backend: ProverBackend,
+    /// How long the proof took
+    pub timings: ProveTimings,
+}
+
+impl<T> ProveResult<T> {
+    /// The result of `proof` of the guest with `image_id` as `config` describes for
+    /// `public_inputs`
+    fn new(proof: Proof, config: &ProveConfig, image_id: [u32; 8], public_inputs: T) -> Self {
+        let receipt = proof.receipt;
+        let is_dev_mode = is_fake(&receipt);
+        Self {
+            image_id: Digest::from(image_id),
+            journal_digest: receipt.journal.digest().into(),
+            seal_size: seal_size(&receipt),
+            journal_size_bytes: receipt.journal.bytes.len(),
+            total_cycles: proof.total_cycles,
+            user_cycles: proof.user_cycles,
+            num_segments: proof.segments,
+            backend: if is_dev_mode {
+                ProverBackend::Local
+            } else {
+                config.backend.clone()
+            },
+            timings: proof.timings,
+            is_dev_mode,
+            receipt,
+            kind: config.proof_kind,
+            public_inputs,
+        }
+    }
+
+    /// The identifiers, sizes and statistics of the proof, without the receipt
+    pub fn summary(&self) -> ProofSummary {
+        let millis = |time: Duration| time.as_millis() as u64;
+        ProofSummary {
+            kind: self.kind.name().to_string(),
+            image_id: self.image_id.to_string(),
+            journal_digest: Digest::from(self.journal_digest).to_string(),
+            seal_size: self.seal_size,
+            journal_size_bytes: self.journal_size_bytes,
+            total_cycles: self.total_cycles,
+            user_cycles: self.user_cycles,
+            segments: self.num_segments,
+            dev_mode: self.is_dev_mode,
+            backend: self.backend.name().to_string(),
+            latency_ms: millis(self.timings.latency),
+            proving_time_ms: self.timings.proving_time.map(millis),
+            queue_time_ms: self.timings.queue_time().map(millis),
+        }
+    }
+
+    /// Writes the receipt to `path` with bincode, after the envelope of a [`PayloadKind::Receipt`]
+    /// in [`RECEIPT_FORMAT_VERSION`]
+    ///
+    /// The public inputs are not written along, so that the verifier checks the receipt against
+    /// the ones it expects, e.g. written by [`PublicInputs::write_to_file`].
+    pub fn write_receipt(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        wire::write_envelope(&mut file, PayloadKind::Receipt, RECEIPT_FORMAT_VERSION)?;
+        bincode::serialize_into(&mut file, &self.receipt).map_err(std::io::Error::other)
+    }
+}
+
+/// What downstream systems record of a proof, see [`ProveResult::summary`]
+///
+/// The image id and the journal digest are hex strings, as are the ones of the RISC0 tooling.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofSummary {
+    /// The name of the [`ProofKind`]
+    pub kind: String,
+    /// The image id of the guest
+    pub image_id: String,
+    /// The SHA-256 digest of the journal
+    pub journal_digest: String,
+    /// The size of the seal in bytes
+    pub seal_size: usize,
+    /// The size of the journal in bytes
+    pub journal_size_bytes: usize,
+    /// The number of cycles of the execution, including paging and padding
+    pub total_cycles: u64,
+    /// The number of cycles spent in the guest itself
+    pub user_cycles: u64,
+    /// The number of segments the execution was split into
+    pub segments: usize,
+    /// Whether the receipt is a fake one of dev mode
+    pub dev_mode: bool,
+    /// The name of the [`ProverBackend`]
+    pub backend: String,
+    /// The latency of the proof in milliseconds
+    pub latency_ms: u64,
+    /// The time spent proving in milliseconds, if known
+    pub proving_time_ms: Option<u64>,
+    /// The time spent in queues and transfers in milliseconds, if known
+    pub queue_time_ms: Option<u64>,
+}
+
+/// The version of the file layout written by [`ProveResult::write_receipt`], bumped whenever
+/// the layout of the receipt changes
+pub const RECEIPT_FORMAT_VERSION: u16 = 2;
+
+/// Reads a receipt written by [`ProveResult::write_receipt`]
+///
+/// # Errors
+/// Returns an error of kind [`std::io::ErrorKind::InvalidData`] if the file holds another kind of
+/// payload or was written in another format version, see [`wire::read_envelope`].
+pub fn load_receipt(path: impl AsRef<Path>) -> std::io::Result<Receipt> {
+    let mut file = std::io::BufReader::new(std::fs::File::open(path)?);
+    wire::read_envelope(&mut file, PayloadKind::Receipt, RECEIPT_FORMAT_VERSION)?;
+    bincode::deserialize_from(&mut file).map_err(std::io::Error::other)
+}
+
+/// The statistics of an execution of the guest without proving it, see [`execute`]
+#[derive(Clone, Debug)]
+pub struct ExecutionStats {
+    /// The number of cycles of the execution, including paging and padding
+    pub total_cycles: u64,
+    /// The number of cycles spent in the guest itself
+    pub user_cycles: u64,
+    /// The number of segments the execution was split into
+    pub segments: usize,
+    /// The journal of the execution, with the result of every validator with
+    /// [`FailMode::CommitResult`]
+    pub journal: AggregateJournal,
+}
+
+/// The reasons [`prove`] and [`compose`] can fail.
+#[derive(Debug)]
+pub enum ProveError {
+    /// The test data could not be written to the executor environment.
+    Env(anyhow::Error),
+    /// The guest did not run to completion, e.g. because it rejected the aggregate or ran past
+    /// [`ProveConfig::max_cycles`].
+    Prove(anyhow::Error),
+    /// The receipt fails [`verify_receipt`] for the submitted public inputs, a shard receipt
+    /// to compose fails it for its own, or the journal of an execution is malformed.
+    Receipt(ReceiptError),
+    /// The shard receipts to compose do not merge into one aggregate.
+    Shards(MergeError),
+    /// The remote prover could not be reached or failed to prove.
+    Remote(RemoteError),
+}
+
+impl fmt::Display for ProveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProveError::Env(err) => write!(f, "failed to build the executor environment: {err}"),
+            ProveError::Prove(err) => write!(f, "failed to prove the aggregate: {err}"),
+            ProveError::Receipt(err) => write!(f, "invalid receipt: {err}"),
+            ProveError::Shards(err) => write!(f, "failed to compose the shards: {err}"),
+            ProveError::Remote(err) => write!(f, "remote proving failed: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for ProveError {}
+
+/// The reasons proving with [`ProverBackend::Remote`] can fail.
+#[derive(Debug)]
+pub enum RemoteError {
+    /// The host was built without the `remote` feature.
+    Disabled,
+    /// The environment variable `env` that should hold the API key is not set.
+    MissingApiKey { env: String },
+    /// The backend has no URL, and `BONSAI_API_URL` is not set.
+    MissingUrl,
+    /// The remote prover cannot prove what was asked.
+    Unsupported(&'static str),
+    /// A request to the remote prover failed, e.g. because it rejected the API key.
+    Request(String),
+    /// The remote prover ended the session in `state` instead of succeeding.
+    Failed { state: String, message: String },
+    /// The session was still running after [`ProveConfig::remote_timeout`].
+    Timeout { after: Duration },
+    /// The downloaded receipt does not decode.
+    MalformedReceipt(bincode::Error),
+}
+
+impl fmt::Display for RemoteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RemoteError::Disabled => write!(f, "built without the remote feature"),
+            RemoteError::MissingApiKey { env } => write!(f, "no API key in {env}"),
+            RemoteError::MissingUrl => write!(f, "no URL, and BONSAI_API_URL is not set"),
+            RemoteError::Unsupported(what) => write!(f, "unsupported: {what}"),
+            RemoteError::Request(err) => write!(f, "request failed: {err}"),
+            RemoteError::Failed { state, message } => write!(f, "session {state}: {message}"),
+            RemoteError::Timeout { after } => write!(f, "still running after {after:?}"),
+            RemoteError::MalformedReceipt(err) => write!(f, "malformed receipt: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for RemoteError {}
+
+/// The reasons [`verify_receipt`] can reject a receipt.
+#[derive(Debug)]
+pub enum ReceiptError {
+    /// The receipt does not verify against the image id of the guest.
+    ImageId(VerificationError),
+    /// The journal of a batch does not decode to the digests and the verification result.
+    Journal(risc0_zkvm::serde::Error),
+    /// The journal of an aggregate does not have the layout of an [`AggregateJournal`].
+    JournalLayout(DecodeError),
+    /// The journal commits a failed verification.
+    VerificationFailed,
+    /// The journal commits the digest of other public inputs.
+    PublicInputsMismatch,
+    /// The journal of a composed receipt commits shards of another guest than the aggregate one.
+    ShardImageId,
+    /// The journal of a batch commits another number of aggregates.
+    BatchLengthMismatch,
+    /// The journal of a batch commits another digest for aggregate `index`.
+    DigestMismatch { index: usize },
+    /// The receipt is a fake one of dev mode, which was not allowed.
+    DevMode,
+    /// The receipt or the expected public inputs could not be read from their file.
+    File(std::io::Error),
+}
+
+impl fmt::Display for ReceiptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReceiptError::ImageId(err) => write!(f, "receipt is not for the guest: {err}"),
+            ReceiptError::Journal(err) => write!(f, "malformed journal: {err}"),
+            ReceiptError::JournalLayout(err) => write!(f, "malformed journal: {err}"),
+            ReceiptError::VerificationFailed => write!(f, "journal commits a failed verification"),
+            ReceiptError::PublicInputsMismatch => {
+                write!(f, "journal commits other public inputs")
+            }
+            ReceiptError::ShardImageId => write!(f, "journal commits shards of another guest"),
+            ReceiptError::BatchLengthMismatch => {
+                write!(f, "journal commits another number of aggregates")
+            }
+            ReceiptError::DigestMismatch { index } => {
+                write!(f, "journal commits another digest for aggregate {index}")
+            }
+            ReceiptError::DevMode => write!(f, "receipt is a fake one of dev mode"),
+            ReceiptError::File(err) => write!(f, "failed to read the receipt: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for ReceiptError {}
+
+/// Checks that `receipt` proves that the guest verified an aggregate for `expected`
+///
+/// The receipt is verified against the image id of the guest, and the journal must be the
+/// [`AggregateJournal`] of a successful verification of `expected`, i.e. commit the
+/// [`PublicInputs::digest`] recomputed from `expected`.
+///
+/// The fake receipts of dev mode are rejected unless `allow_dev_mode`, since they prove nothing.
+pub fn verify_receipt(
+    receipt: &Receipt,
+    expected: &PublicInputs,
+    allow_dev_mode: bool,
+) -> Result<(), ReceiptError> {
+    check_aggregate_journal(&verify_receipt_result(receipt, expected, allow_dev_mode)?)
+}
+
+/// Checks that `receipt` proves that the guest verified an aggregate for `expected`, and
+/// returns its journal whether the verification succeeded or not
+///
+/// Same as [`verify_receipt`], but a journal committed with [`FailMode::CommitResult`] that
+/// proves a failed verification is returned rather than rejected, with the result of every
+/// validator.
+pub fn verify_receipt_result(
+    receipt: &Receipt,
+    expected: &PublicInputs,
+    allow_dev_mode: bool,
+) -> Result<AggregateJournal, ReceiptError> {
+    verify_image_id(receipt, XMSS_AGGREGATE_ID, allow_dev_mode)?;
+    decode_aggregate_journal(&receipt.journal.bytes, expected)
+}
+
+/// Checks that the receipt at `path`, written by [`ProveResult::write_receipt`], proves that the
+/// guest verified an aggregate for the public inputs at `expected_inputs_path`, written by
+/// [`PublicInputs::write_to_file`], see [`verify_receipt`]
+pub fn verify_receipt_file(
+    path: impl AsRef<Path>,
+    expected_inputs_path: impl AsRef<Path>,
+    allow_dev_mode: bool,
+) -> Result<(), ReceiptError> {
+    let receipt = load_receipt(path).map_err(ReceiptError::File)?;
+    let expected =
+        PublicInputs::read_from_file(expected_inputs_path).map_err(ReceiptError::File)?;
+    verify_receipt(&receipt, &expected, allow_dev_mode)
+}
+
+/// Checks that `receipt` proves that the composition guest merged aggregates of the aggregate
+/// guest into one for `expected`, see [`verify_receipt`]
+pub fn verify_composed_receipt(
+    receipt: &Receipt,
+    expected: &PublicInputs,
+    allow_dev_mode: bool,
+) -> Result<(), ReceiptError> {
+    verify_image_id(receipt, XMSS_COMPOSE_ID, allow_dev_mode)?;
+
+    // The guest commits the journal of the merged public inputs followed by the image id of the
+    // shards, as little-endian words
+    let bytes = &receipt.journal.bytes;
+    let Some((journal, shard_image_id)) = bytes.split_at_checked(AggregateJournal::LEN) else {
+        return Err(ReceiptError::JournalLayout(DecodeError::InvalidLength {
+            expected: AggregateJournal::LEN + 32,
+            actual: bytes.len(),
+        }));
+    };
+    check_aggregate_journal(&decode_aggregate_journal(journal, expected)?)?;
+    if shard_image_id != Digest::from(XMSS_AGGREGATE_ID).as_bytes() {
+        return Err(ReceiptError::ShardImageId);
+    }
+    Ok(())
+}
+
+/// Checks that `receipt` proves that the guest verified a batch of aggregates for `expected`,
+/// see [`verify_receipt`]
+///
+/// The journal commits the digests of the public inputs, which are compared in order.
+pub fn verify_batch_receipt(
+    receipt: &Receipt,
+    expected: &[PublicInputs],
+    allow_dev_mode: bool,
+) -> Result<(), ReceiptError> {
+    verify_image_id(receipt, XMSS_AGGREGATE_ID, allow_dev_mode)?;
+
+    // The guest commits the digests followed by the verification result
+    let (digests, verification_result): (Vec<Hash>, bool) =
+        receipt.journal.decode().map_err(ReceiptError::Journal)?;
+    if !verification_result {
+        return Err(ReceiptError::VerificationFailed);
+    }
+    if digests.len() != expected.len() {
+        return Err(ReceiptError::BatchLengthMismatch);
+    }
+    match (digests.iter().zip(expected)).position(|(digest, expected)| *digest != expected.digest())
+    {
+        Some(index) => Err(ReceiptError::DigestMismatch { index }),
+        None => Ok(()),
+    }
+}
+
+/// Decodes `journal` as an [`AggregateJournal`] of either fail mode, which must commit the
+/// digest of `expected`
+fn decode_aggregate_journal(
+    journal: &[u8],
+    expected: &PublicInputs,
+) -> Result<AggregateJournal, ReceiptError> {
+    let journal = AggregateJournal::from_bytes(journal).map_err(ReceiptError::JournalLayout)?;
+    if journal.digest != expected.digest() {
+        return Err(ReceiptError::PublicInputsMismatch);
+    }
+    Ok(journal)
+}
+
+/// Checks that `journal` commits a successful verification
+fn check_aggregate_journal(journal: &AggregateJournal) -> Result<(), ReceiptError> {
+    if !journal.verification_result {
+        return Err(ReceiptError::VerificationFailed);
+    }
+    Ok(())
+}
+
+/// Verifies `receipt` against `image_id`, accepting the fake receipts of dev mode only if
+/// `allow_dev_mode`
+fn verify_image_id(
+    receipt: &Receipt,
+    image_id: [u32; 8],
+    allow_dev_mode: bool,
+) -> Result<(), ReceiptError> {
+    if is_fake(receipt) && !allow_dev_mode {
+        return Err(ReceiptError::DevMode);
+    }
+    let ctx = VerifierContext::default().with_dev_mode(allow_dev_mode);
+    receipt
+        .verify_with_context(&ctx, image_id)
+        .map_err(ReceiptError::ImageId)
+}
+
+/// Whether `receipt` is a fake one of dev mode
+fn is_fake(receipt: &Receipt) -> bool {
+    matches!(receipt.inner, InnerReceipt::Fake(_))
+}
+
+/// Proves with a receipt of `kind` that the aggregated signature of `test_data` verifies for its
+/// public inputs, see [`prove`]
+pub fn prove_xmss_aggregate(
+    test_data: &XmssTestData,
+    kind: ProofKind,
+) -> Result<ProveResult, ProveError> {
+    prove(test_data, &ProveConfig::new(kind))
+}
+
+/// Proves as `config` describes that the aggregated signature of `test_data` verifies for its
+/// public inputs
+///
+/// The proof is generated by [`ProveConfig::backend`], so dev mode, selected by `RISC0_DEV_MODE`
+/// or [`ProveConfig::dev_mode`], skips the actual proving while still running the guest locally.
+/// Groth16 proofs need the Groth16 prover of RISC0, i.e. docker on x86.
+///
+/// With [`FailMode::CommitResult`], an aggregate that fails to verify is proven all the same,
+/// and the journal commits the result of every validator, see [`verify_receipt_result`].
+///
+/// # Errors
+/// Returns an error if the guest rejects the aggregate, if the receipt does not verify, or if
+/// the journal does not commit the submitted public inputs.
+pub fn prove(test_data: &XmssTestData, config: &ProveConfig) -> Result<ProveResult, ProveError> {
+    let input = GuestInput::single_message(test_data.clone(), config.fail_mode);
+    let proof = prove_input(&input, config)?;
+    let journal = verify_receipt_result(
+        &proof.receipt,
+        &test_data.public_inputs,
+        config.is_dev_mode(),
+    )
+    .map_err(ProveError::Receipt)?;
+    if config.fail_mode == FailMode::Abort {
+        check_aggregate_journal(&journal).map_err(ProveError::Receipt)?;
+    }
+
+    Ok(ProveResult::new(
+        proof,
+        config,
+        XMSS_AGGREGATE_ID,
+        test_data.public_inputs.clone(),
+    ))
+}
+
+/// Proves with a composite receipt that every aggregate of `batch` verifies for its public
+/// inputs, see [`prove_batch`]
+pub fn prove_xmss_aggregate_batch(
+    batch: &[XmssTestData],
+) -> Result<ProveResult<Vec<PublicInputs>>, ProveError> {
+    prove_batch(batch, &ProveConfig::default())
+}
+
+/// Proves as `config` describes that every aggregate of `batch` verifies for its public inputs
+///
+/// A single execution of the guest verifies the whole batch, and commits the
+/// [`PublicInputs::digest`] of every aggregate. An aggregate that fails to verify aborts the
+/// proof of the whole batch, so a receipt always attests to all of its aggregates, see
+/// [`verify_batch_receipt`].
+///
+/// # Errors
+/// Returns an error if the batch is empty, if the guest rejects an aggregate, if the receipt
+/// does not verify, or if the journal does not commit the digests of the submitted public inputs.
+pub fn prove_batch(
+    batch: &[XmssTestData],
+    config: &ProveConfig,
+) -> Result<ProveResult<Vec<PublicInputs>>, ProveError> {
+    let proof = prove_input(&GuestInput::Batch(batch.to_vec()), config)?;
+    let public_inputs: Vec<_> = (batch.iter())
+        .map(|test_data| test_data.public_inputs.clone())
+        .collect();
+    verify_batch_receipt(&proof.receipt, &public_inputs, config.is_dev_mode())
+        .map_err(ProveError::Receipt)?;
+
+    Ok(ProveResult::new(
+        proof,
+        config,
+        XMSS_AGGREGATE_ID,
+        public_inputs,
+    ))
+}
+
+/// Proves with a succinct receipt that every receipt of `shards` proves an aggregate for its
+/// public inputs, and that the aggregates merge into one, see [`compose`]
+pub fn compose_aggregates(shards: Vec<(Receipt, PublicInputs)>) -> Result<ProveResult, ProveError> {
+    compose(shards, &ProveConfig::new(ProofKind::Succinct))
+}
+
+/// Proves as `config` describes that every receipt of `shards` proves an aggregate for its
+/// public inputs, and that the aggregates merge into one as [`PublicInputs::merge`] describes
+///
+/// The journals only commit the digests of the public inputs, so the public inputs of every
+/// shard are passed along with its receipt. The receipts are assumptions of the composition
+/// guest, so proving resolves them only for succinct and Groth16 receipts, while a composite
+/// receipt carries them along. The result holds the merged public inputs, which the receipt is
+/// checked against with [`verify_composed_receipt`].
+///
+/// Assumptions cannot be uploaded to the remote prover, so composing is always local. The shards
+/// must be proven with [`FailMode::Abort`], whose journals the composition guest rebuilds.
+///
+/// # Errors
+/// Returns an error if a receipt is not a successful aggregate for its public inputs, if the
+/// aggregates do not merge, if the backend is remote, or if the composed receipt does not verify.
+pub fn compose(
+    shards: Vec<(Receipt, PublicInputs)>,
+    config: &ProveConfig,
+) -> Result<ProveResult, ProveError> {
+    if config.backend != ProverBackend::Local && !config.is_dev_mode() {
+        return Err(ProveError::Remote(RemoteError::Unsupported(
+            "composing receipts remotely",
+        )));
+    }
+    for (receipt, shard_inputs) in &shards {
+        verify_receipt(receipt, shard_inputs, config.is_dev_mode()).map_err(ProveError::Receipt)?;
+    }
+    let (receipts, shard_inputs): (Vec<_>, Vec<_>) = shards.into_iter().unzip();
+    let public_inputs = PublicInputs::merge(&shard_inputs).map_err(ProveError::Shards)?;
+
+    // The guest reads the public inputs, and verifies their journals against the receipts as
+    // assumptions
+    let shard_inputs: Vec<_> = (shard_inputs.into_iter())
+        .map(|shard_inputs| (shard_inputs, XMSS_AGGREGATE_ID))
+        .collect();
+    let mut builder = config.env_builder();
+    builder.write(&shard_inputs).map_err(ProveError::Env)?;
+    for receipt in receipts {
+        builder.add_assumption(receipt);
+    }
+    let env = builder.build().map_err(ProveError::Env)?;
+
+    let proof = prove_locally(env, XMSS_COMPOSE_ELF, config)?;
+    verify_composed_receipt(&proof.receipt, &public_inputs, config.is_dev_mode())
+        .map_err(ProveError::Receipt)?;
+
+    Ok(ProveResult::new(
+        proof,
+        config,
+        XMSS_COMPOSE_ID,
+        public_inputs,
+    ))
+}
+
+/// Proves that the aggregate guest runs to completion on `input` with the backend of `config`
+fn prove_input(input: &GuestInput, config: &ProveConfig) -> Result<Proof, ProveError> {
+    // Fake receipts need no prover, so dev mode never reaches the remote one
+    if config.backend != ProverBackend::Local && !config.is_dev_mode() {
+        return prove_remotely(input, config);
+    }
+    prove_locally(config.executor_env(input)?, XMSS_AGGREGATE_ELF, config)
+}
+
+/// Proves that `elf` runs to completion in `env` with the default prover of this machine
+fn prove_locally(env: ExecutorEnv, elf: &[u8], config: &ProveConfig) -> Result<Proof, ProveError> {
+    let start = Instant::now();
+    let prove_info = default_prover()
+        .prove_with_opts(env, elf, &config.prover_opts())
+        .map_err(ProveError::Prove)?;
+    Ok(Proof {
+        receipt: prove_info.receipt,
+        total_cycles: prove_info.stats.total_cycles,
+        user_cycles: prove_info.stats.user_cycles,
+        segments: prove_info.stats.segments,
+        timings: ProveTimings::local(start.elapsed()),
+    })
+}
+
+/// Proves that the aggregate guest runs to completion on `input` on Bonsai
+#[cfg(feature = "remote")]
+fn prove_remotely(input: &GuestInput, config: &ProveConfig) -> Result<Proof, ProveError> {
+    remote::prove(input, config)
+}
+
+/// Fails, since the host was built without the remote prover
+#[cfg(not(feature = "remote"))]
+fn prove_remotely(_input: &GuestInput, _config: &ProveConfig) -> Result<Proof, ProveError> {
+    Err(ProveError::Remote(RemoteError::Disabled))
+}
+
+/// Runs the guest on `test_data` without proving it, to estimate the cost of a proof
+///
+/// Same as [`execute`] with the default configuration.
+pub fn execute_xmss_aggregate(test_data: &XmssTestData) -> Result<ExecutionStats, ProveError> {
+    execute(test_data, &ProveConfig::default())
+}
+
+/// Runs the guest on `test_data` within the limits of `config` without proving it
+///
+/// With [`FailMode::CommitResult`], an aggregate that fails to verify runs to completion and
+/// commits the failure.
+///
+/// # Errors
+/// Returns an error if the guest rejects the aggregate, runs past the cycle limit, or commits a
+/// malformed journal.
+pub fn execute(
+    test_data: &XmssTestData,
+    config: &ProveConfig,
+) -> Result<ExecutionStats, ProveError> {
+    let input = GuestInput::single_message(test_data.clone(), config.fail_mode);
+    let env = config.executor_env(&input)?;
+    let session = ExecutorImpl::from_elf(env, XMSS_AGGREGATE_ELF)
+        .and_then(|mut executor| executor.run())
+        .map_err(ProveError::Prove)?;
+
+    // A guest that commits nothing has an empty journal, which fails to decode
+    let journal = session.journal.unwrap_or_else(|| Journal::new(Vec::new()));
+    let journal = decode_aggregate_journal(&journal.bytes, &test_data.public_inputs)
+        .map_err(ProveError::Receipt)?;
+    if config.fail_mode == FailMode::Abort {
+        check_aggregate_journal(&journal).map_err(ProveError::Receipt)?;
+    }
+
+    Ok(ExecutionStats {
+        total_cycles: session.total_cycles,
+        user_cycles: session.user_cycles,
+        segments: session.segments.len(),
+        journal,
+    })
+}
+
+/// The size of the seal of `receipt` in bytes, see [`ProveResult::seal_size`]
+pub fn seal_size(receipt: &Receipt) -> usize {
+    match &receipt.inner {
+        InnerReceipt::Composite(composite) => composite.seal_size(),
+        InnerReceipt::Succinct(succinct) => succinct.seal_size(),
+        InnerReceipt::Groth16(groth16) => groth16.seal_size(),
+        // The fake receipts of dev mode carry no seal
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use risc0_zkvm::ReceiptKind;
+
+    use super::*;
+
+    #[test]
+    fn test_prover_opts() {
+        for (kind, receipt_kind) in [
+            (ProofKind::Composite, ReceiptKind::Composite),
+            (ProofKind::Succinct, ReceiptKind::Succinct),
+            (ProofKind::Groth16, ReceiptKind::Groth16),
+        ] {
+            assert_eq!(kind.prover_opts().receipt_kind, receipt_kind);
+        }
+        assert_eq!(ProofKind::default(), ProofKind::Composite);
+    }
+
+    #[test]
+    fn test_prove_config() {
+        let config = ProveConfig::new(ProofKind::Succinct);
+        assert_eq!(config.prover_opts().receipt_kind, ReceiptKind::Succinct);
+        assert_eq!(
+            config.prover_opts().hashfn,
+            ProofKind::Succinct.prover_opts().hashfn
+        );
+
+        let config = ProveConfig {
+            hashfn: Some("sha-256".to_string()),
+            ..config
+        };
+        assert_eq!(config.prover_opts().receipt_kind, ReceiptKind::Succinct);
+        assert_eq!(config.prover_opts().hashfn, "sha-256");
+
+        let config = ProveConfig {
+            dev_mode: true,
+            ..config
+        };
+        assert!(config.is_dev_mode());
+        assert_eq!(config.prover_opts().receipt_kind, ReceiptKind::Succinct);
+    }
+
+    #[test]
+    fn test_remote_credentials() {
+        assert_eq!(ProverBackend::default(), ProverBackend::Local);
+        assert_eq!(ProverBackend::Local.remote_credentials().unwrap(), None);
+
+        let backend = ProverBackend::Remote {
+            api_key_env: "LEANSIG_TEST_REMOTE_CREDENTIALS_KEY".to_string(),
+            url: Some("https://prover.example".to_string()),
+        };
+        assert!(matches!(
+            backend.remote_credentials(),
+            Err(RemoteError::MissingApiKey { env }) if env == "LEANSIG_TEST_REMOTE_CREDENTIALS_KEY"
+        ));
+
+        // SAFETY: no other test reads or writes this variable
+        unsafe { std::env::set_var("LEANSIG_TEST_REMOTE_CREDENTIALS_KEY", "secret") };
+        assert_eq!(
+            backend.remote_credentials().unwrap(),
+            Some(("https://prover.example".to_string(), "secret".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_prove_timings() {
+        let timings = ProveTimings {
+            latency: Duration::from_secs(10),
+            proving_time: Some(Duration::from_secs(7)),
+        };
+        assert_eq!(timings.queue_time(), Some(Duration::from_secs(3)));
+
+        let timings = ProveTimings::local(Duration::from_secs(10));
+        assert_eq!(timings.queue_time(), Some(Duration::ZERO));
+
+        let timings = ProveTimings {
+            proving_time: None,
+            ..timings
+        };
+        assert_eq!(timings.queue_time(), None);
+    }
+
+    #[test]
+    #[cfg(not(feature = "remote"))]
+    fn test_remote_disabled() {
+        let config = ProveConfig {
+            backend: ProverBackend::Remote {
+                api_key_env: "BONSAI_API_KEY".to_string(),
+                url: None,
+            },
+            ..ProveConfig::new(ProofKind::Succinct)
+        };
+        assert!(matches!(
+            prove_remotely(&GuestInput::Batch(Vec::new()), &config),
+            Err(ProveError::Remote(RemoteError::Disabled))
+        ));
+    }
+}
@@ -1,46 +1,229 @@
 // Copyright 2025 Irreducible Inc.
-use leansig_core::{
-    AggregatedVerifier,
-    spec::{self},
-};
-use leansig_shared::{PublicInputs, create_test_data};
-// These constants represent the RISC-V ELF and the image ID generated by risc0-build.
-// The ELF is used for proving and the ID is used for verification.
-use methods::{XMSS_AGGREGATE_ELF, XMSS_AGGREGATE_ID};
-use risc0_zkvm::{ExecutorEnv, default_prover};
-
-fn main() {
+//! Generates, executes, proves and verifies XMSS signature aggregates with RISC0.
+//!
+//! Every command prints its results as text, or as a JSON object with `--json`.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use clap::{Args, Parser, Subcommand};
+use leansig_core::spec::{SPEC_1, SPEC_2, Spec};
+use leansig_shared::{TestDataBuilder, XmssTestData, print_report};
+use risc0_host::{ProofKind, ProveConfig, ProverBackend, execute, prove, verify_receipt_file};
+use serde_json::json;
+
+#[derive(Parser)]
+#[command(about = "Proves XMSS signature aggregates with RISC0")]
+struct Cli {
+    /// Print the results as a JSON object
+    #[arg(long, global = true)]
+    json: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Generates test data of validators signing the same message and writes it to a file
+    Generate {
+        /// The number of validators
+        #[arg(long, default_value_t = 3)]
+        validators: usize,
+        /// The height of the XMSS tree of every validator
+        #[arg(long, default_value_t = 4)]
+        tree_height: usize,
+        /// The spec of the signature scheme, `1` or `2`
+        #[arg(long, default_value = "2", value_parser = parse_spec)]
+        spec: Spec,
+        /// The seed the validators are derived from
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+        /// Where to write the test data
+        #[arg(long)]
+        output: PathBuf,
+        /// Where to also write the public inputs alone, as expected by `verify`
+        #[arg(long)]
+        public_inputs: Option<PathBuf>,
+    },
+    /// Runs the guest on test data without proving it
+    Execute {
+        /// The test data written by `generate`
+        #[arg(long)]
+        input: PathBuf,
+        #[command(flatten)]
+        limits: Limits,
+    },
+    /// Proves test data and writes the receipt to a file
+    Prove {
+        /// The test data written by `generate`
+        #[arg(long)]
+        input: PathBuf,
+        /// Where to write the receipt
+        #[arg(long)]
+        output: PathBuf,
+        /// The kind of receipt, `composite`, `succinct` or `groth16`
+        #[arg(long, default_value = "succinct", value_parser = parse_proof_kind)]
+        kind: ProofKind,
+        /// Skip proving and write a fake receipt, as does `RISC0_DEV_MODE`
+        #[arg(long)]
+        dev_mode: bool,
+        #[command(flatten)]
+        limits: Limits,
+        #[command(flatten)]
+        remote: Remote,
+    },
+    /// Checks that a receipt commits a successful verification of the expected public inputs
+    Verify {
+        /// The receipt written by `prove`
+        #[arg(long)]
+        receipt: PathBuf,
+        /// The public inputs written by `generate`
+        #[arg(long)]
+        public_inputs: PathBuf,
+        /// Accept the fake receipts of dev mode, which prove nothing
+        #[arg(long)]
+        allow_dev_mode: bool,
+    },
+}
+
+/// The limits of the execution, see [`ProveConfig`]
+#[derive(Args)]
+struct Limits {
+    /// The log2 of the maximum number of cycles of a segment
+    #[arg(long)]
+    segment_limit_po2: Option<u32>,
+    /// The maximum number of cycles of the whole execution
+    #[arg(long)]
+    max_cycles: Option<u64>,
+}
+
+impl Limits {
+    fn prove_config(self, proof_kind: ProofKind) -> ProveConfig {
+        ProveConfig {
+            proof_kind,
+            segment_limit_po2: self.segment_limit_po2,
+            max_cycles: self.max_cycles,
+            ..ProveConfig::default()
+        }
+    }
+}
+
+/// The remote prover to prove on instead of this machine, see [`ProverBackend`]
+#[derive(Args)]
+struct Remote {
+    /// Prove on Bonsai, with the API key in this environment variable
+    #[arg(long)]
+    remote_api_key_env: Option<String>,
+    /// The URL of Bonsai, instead of `BONSAI_API_URL`
+    #[arg(long, requires = "remote_api_key_env")]
+    remote_url: Option<String>,
+}
+
+impl Remote {
+    fn backend(self) -> ProverBackend {
+        match self.remote_api_key_env {
+            Some(api_key_env) => ProverBackend::Remote {
+                api_key_env,
+                url: self.remote_url,
+            },
+            None => ProverBackend::Local,
+        }
+    }
+}
+
+fn parse_spec(name: &str) -> Result<Spec, String> {
+    match name {
+        "1" | "SPEC_1" => Ok(SPEC_1),
+        "2" | "SPEC_2" => Ok(SPEC_2),
+        _ => Err(format!("unknown spec {name}, expected 1 or 2")),
+    }
+}
+
+fn parse_proof_kind(name: &str) -> Result<ProofKind, String> {
+    (ProofKind::ALL.into_iter())
+        .find(|kind| kind.name() == name)
+        .ok_or_else(|| format!("unknown proof kind {name}"))
+}
+
+fn main() -> anyhow::Result<()> {
     // Initialize tracing. In order to view logs, run `RUST_LOG=info cargo run`
     tracing_subscriber::fmt()
         .with_env_filter(tracing_subscriber::filter::EnvFilter::from_default_env())
         .init();
 
-    let test_data = create_test_data(3, spec::SPEC_2, 4, 10000, None, None);
-
-    // Sanity check the signature verification
-    let verifier = AggregatedVerifier::new(
-        test_data.public_inputs.validator_roots.clone(),
-        test_data.public_inputs.spec.clone(),
-    );
-    assert!(
-        verifier.verify(
-            &test_data.public_inputs.message,
-            &test_data.aggregated_signature
-        ),
-        "failed to verify aggregated signature"
-    );
-
-    // Send test data to the guest
-    let env = ExecutorEnv::builder()
-        .write(&test_data)
-        .unwrap()
-        .build()
-        .unwrap();
-
-    let prover = default_prover();
-    let prove_info = prover.prove(env, XMSS_AGGREGATE_ELF).unwrap();
-
-    let receipt = prove_info.receipt;
-    let _committed_public_inputs: PublicInputs = receipt.journal.decode().unwrap();
-    receipt.verify(XMSS_AGGREGATE_ID).unwrap();
+    let cli = Cli::parse();
+    let report = match cli.command {
+        Command::Generate {
+            validators,
+            tree_height,
+            spec,
+            seed,
+            output,
+            public_inputs,
+        } => {
+            let test_data = TestDataBuilder::new()
+                .validators(validators)
+                .spec(spec)
+                .tree_height(tree_height)
+                .seed(seed)
+                .build();
+            test_data
+                .write_to_file(&output)
+                .with_context(|| format!("failed to write {}", output.display()))?;
+            if let Some(path) = public_inputs {
+                test_data
+                    .public_inputs
+                    .write_to_file(&path)
+                    .with_context(|| format!("failed to write {}", path.display()))?;
+            }
+            json!({
+                "validators": validators,
+                "tree_height": tree_height,
+                "digest": test_data.public_inputs.digest().to_string(),
+            })
+        }
+        Command::Execute { input, limits } => {
+            let test_data = read_test_data(&input)?;
+            let stats = execute(&test_data, &limits.prove_config(ProofKind::default()))?;
+            json!({
+                "total_cycles": stats.total_cycles,
+                "user_cycles": stats.user_cycles,
+                "segments": stats.segments,
+            })
+        }
+        Command::Prove {
+            input,
+            output,
+            kind,
+            dev_mode,
+            limits,
+            remote,
+        } => {
+            let test_data = read_test_data(&input)?;
+            let config = ProveConfig {
+                dev_mode,
+                backend: remote.backend(),
+                ..limits.prove_config(kind)
+            };
+            let result = prove(&test_data, &config)?;
+            result
+                .write_receipt(&output)
+                .with_context(|| format!("failed to write {}", output.display()))?;
+            serde_json::to_value(result.summary())?
+        }
+        Command::Verify {
+            receipt,
+            public_inputs,
+            allow_dev_mode,
+        } => {
+            verify_receipt_file(&receipt, &public_inputs, allow_dev_mode)?;
+            json!({ "verified": true })
+        }
+    };
+    print_report(cli.json, &report);
+    Ok(())
+}
+
+fn read_test_data(path: &Path) -> anyhow::Result<XmssTestData> {
+    XmssTestData::read_from_file(path).with_context(|| format!("failed to read {}", path.display()))
 }
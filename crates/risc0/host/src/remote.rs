@@ -0,0 +1,89 @@
+// Copyright 2025 Irreducible Inc.
+//! Proving on Bonsai, the remote prover of RISC0.
+
+use std::time::{Duration, Instant};
+
+use bonsai_sdk::{SdkErr, blocking::Client};
+use leansig_shared::GuestInput;
+use methods::{XMSS_AGGREGATE_ELF, XMSS_AGGREGATE_ID};
+use risc0_zkvm::{Digest, Receipt};
+
+use crate::{Proof, ProofKind, ProveConfig, ProveError, ProveTimings, RemoteError};
+
+/// How long to wait between two polls of the status of a session
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Proves that the aggregate guest runs to completion on `input` on Bonsai
+///
+/// Bonsai proves succinct receipts only, with its own segment and cycle limits. The proving time
+/// is the one Bonsai reports for the session, so the latency beyond it is spent in queues and
+/// transfers.
+pub(crate) fn prove(input: &GuestInput, config: &ProveConfig) -> Result<Proof, ProveError> {
+    if config.proof_kind != ProofKind::Succinct {
+        return Err(ProveError::Remote(RemoteError::Unsupported(
+            "receipts other than succinct ones",
+        )));
+    }
+    let (url, api_key) = (config.backend.remote_credentials())
+        .map_err(ProveError::Remote)?
+        .expect("remote backend");
+    let client = Client::from_parts(url, api_key, risc0_zkvm::VERSION).map_err(request_error)?;
+
+    let start = Instant::now();
+    let image_id = Digest::from(XMSS_AGGREGATE_ID).to_string();
+    client
+        .upload_img(&image_id, XMSS_AGGREGATE_ELF.to_vec())
+        .map_err(request_error)?;
+    // The guest reads the words of the RISC0 serialization, as written to an executor environment
+    let words = risc0_zkvm::serde::to_vec(input).map_err(|err| ProveError::Env(err.into()))?;
+    let input_id = client
+        .upload_input(words.iter().flat_map(|word| word.to_le_bytes()).collect())
+        .map_err(request_error)?;
+    let session = client
+        .create_session(image_id, input_id, Vec::new(), false)
+        .map_err(request_error)?;
+
+    let status = loop {
+        let status = session.status(&client).map_err(request_error)?;
+        if status.status != "RUNNING" {
+            break status;
+        }
+        if (config.remote_timeout).is_some_and(|timeout| start.elapsed() > timeout) {
+            return Err(ProveError::Remote(RemoteError::Timeout {
+                after: start.elapsed(),
+            }));
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    };
+    if status.status != "SUCCEEDED" {
+        return Err(ProveError::Remote(RemoteError::Failed {
+            state: status.status,
+            message: status.error_msg.unwrap_or_default(),
+        }));
+    }
+
+    let receipt_url = (status.receipt_url)
+        .ok_or_else(|| ProveError::Remote(RemoteError::Request("no receipt".to_string())))?;
+    let receipt_bytes = client.download(&receipt_url).map_err(request_error)?;
+    let receipt: Receipt = bincode::deserialize(&receipt_bytes)
+        .map_err(|err| ProveError::Remote(RemoteError::MalformedReceipt(err)))?;
+    let latency = start.elapsed();
+
+    let (total_cycles, user_cycles, segments) = (status.stats).map_or((0, 0, 0), |stats| {
+        (stats.total_cycles, stats.cycles, stats.segments)
+    });
+    Ok(Proof {
+        receipt,
+        total_cycles,
+        user_cycles,
+        segments,
+        timings: ProveTimings {
+            latency,
+            proving_time: status.elapsed_time.map(Duration::from_secs_f64),
+        },
+    })
+}
+
+fn request_error(err: SdkErr) -> ProveError {
+    ProveError::Remote(RemoteError::Request(err.to_string()))
+}
@@ -0,0 +1,132 @@
+// Copyright 2025 Irreducible Inc.
+//! Running the command line interface of the host, proving in dev mode.
+
+use std::{
+    path::PathBuf,
+    process::{Command, Output},
+};
+
+use serde_json::Value;
+
+/// A fresh directory for the files of `test`
+fn temp_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("leansig-risc0-cli-{test}-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Runs the host with `args` and `--json` in dev mode
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_risc0-host"))
+        .args(args)
+        .arg("--json")
+        .env("RISC0_DEV_MODE", "1")
+        .output()
+        .unwrap()
+}
+
+/// The JSON report of a successful run of the host with `args`
+fn report(args: &[&str]) -> Value {
+    let output = run(args);
+    assert!(
+        output.status.success(),
+        "{args:?} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+#[test]
+fn test_generate_prove_verify() {
+    let dir = temp_dir("prove");
+    let path = |name: &str| dir.join(name).to_str().unwrap().to_string();
+    let (data, inputs, receipt) = (path("data.bin"), path("inputs.bin"), path("receipt.bin"));
+
+    let generated = report(&[
+        "generate",
+        "--validators",
+        "2",
+        "--tree-height",
+        "3",
+        "--output",
+        &data,
+        "--public-inputs",
+        &inputs,
+    ]);
+    assert_eq!(generated["validators"], 2);
+
+    let executed = report(&["execute", "--input", &data]);
+    assert!(executed["user_cycles"].as_u64().unwrap() > 0);
+
+    let proven = report(&[
+        "prove",
+        "--input",
+        &data,
+        "--output",
+        &receipt,
+        "--kind",
+        "composite",
+    ]);
+    assert_eq!(proven["kind"], "composite");
+    assert!(proven["segments"].as_u64().unwrap() > 0);
+    assert_eq!(proven["dev_mode"], true);
+    assert_eq!(proven["journal_digest"].as_str().unwrap().len(), 64);
+
+    // The receipt is fake, which verification must be told to accept
+    let output = run(&["verify", "--receipt", &receipt, "--public-inputs", &inputs]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("dev mode"));
+    let verified = report(&[
+        "verify",
+        "--receipt",
+        &receipt,
+        "--public-inputs",
+        &inputs,
+        "--allow-dev-mode",
+    ]);
+    assert_eq!(verified["verified"], true);
+
+    // Validators of another seed are not the ones the receipt commits
+    let other_data = path("other_data.bin");
+    let other_inputs = path("other_inputs.bin");
+    report(&[
+        "generate",
+        "--validators",
+        "2",
+        "--tree-height",
+        "3",
+        "--seed",
+        "1",
+        "--output",
+        &other_data,
+        "--public-inputs",
+        &other_inputs,
+    ]);
+    let output = run(&[
+        "verify",
+        "--receipt",
+        &receipt,
+        "--public-inputs",
+        &other_inputs,
+        "--allow-dev-mode",
+    ]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("other public inputs"));
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_unknown_proof_kind() {
+    let output = run(&[
+        "prove",
+        "--input",
+        "data.bin",
+        "--output",
+        "receipt.bin",
+        "--kind",
+        "stark",
+    ]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unknown proof kind stark"));
+}
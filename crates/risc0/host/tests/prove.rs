@@ -0,0 +1,547 @@
+// Copyright 2025 Irreducible Inc.
+//! Proving in dev mode, which runs the guest without generating the actual proof.
+
+use std::time::Duration;
+
+use leansig_core::{Message, hash::Hash, spec};
+use leansig_shared::{
+    AggregateJournal, CorruptionKind, DigestJournal, EpochAssignment, FailMode, GuestInput,
+    MergeError, PublicInputs, TestDataBuilder, ThresholdJournal, WeightedJournal, XmssTestData,
+    corrupt, create_multi_epoch_test_data,
+};
+use methods::{XMSS_AGGREGATE_ELF, XMSS_AGGREGATE_ID};
+use risc0_host::{
+    ProofKind, ProofSummary, ProveConfig, ProveError, ProverBackend, ReceiptError,
+    compose_aggregates, execute, execute_xmss_aggregate, load_receipt, prove, prove_xmss_aggregate,
+    prove_xmss_aggregate_batch, verify_batch_receipt, verify_composed_receipt, verify_receipt,
+    verify_receipt_file, verify_receipt_result,
+};
+use risc0_zkvm::{
+    Digest, ExecutorEnv, default_executor,
+    sha::{Impl, Sha256},
+};
+
+fn test_data() -> XmssTestData {
+    TestDataBuilder::new()
+        .validators(2)
+        .spec(spec::SPEC_2)
+        .tree_height(3)
+        .build()
+}
+
+fn enable_dev_mode() {
+    // SAFETY: every test of this file sets the same value, and nothing reads the environment
+    // concurrently except the prover, which only reads this variable.
+    unsafe { std::env::set_var("RISC0_DEV_MODE", "1") };
+}
+
+#[test]
+fn test_prove_xmss_aggregate() {
+    enable_dev_mode();
+    let test_data = test_data();
+    for kind in ProofKind::ALL {
+        let result = prove_xmss_aggregate(&test_data, kind).unwrap();
+        verify_receipt(&result.receipt, &test_data.public_inputs, true).unwrap();
+        assert!(result.is_dev_mode);
+        assert_eq!(result.kind, kind);
+        assert_eq!(result.public_inputs, test_data.public_inputs);
+        // Dev mode proves with fake receipts, which have no seal
+        assert_eq!(result.seal_size, 0);
+        assert_eq!(
+            result.journal_size_bytes,
+            result.receipt.journal.bytes.len()
+        );
+        // The journal commits the digest of the public inputs and the result
+        assert_eq!(result.journal_size_bytes, AggregateJournal::LEN);
+        // Dev mode still executes the guest
+        assert!(result.user_cycles > 0);
+        assert!(result.total_cycles >= result.user_cycles);
+        assert!(result.num_segments > 0);
+    }
+}
+
+#[test]
+fn test_verify_receipt_for_other_public_inputs() {
+    enable_dev_mode();
+    let test_data = test_data();
+    let receipt = prove_xmss_aggregate(&test_data, ProofKind::Composite)
+        .unwrap()
+        .receipt;
+
+    // The journal only commits a digest, which the host recomputes from the public inputs it
+    // expects, so any field that differs is a mismatch
+    let public_inputs = &test_data.public_inputs;
+    let mut other_message = public_inputs.clone();
+    other_message.message = Message([1; 32]);
+    let mut other_epochs = public_inputs.clone();
+    other_epochs.epochs = EpochAssignment::Uniform(1);
+    let mut other_roots = public_inputs.clone();
+    other_roots.validator_roots.swap(0, 1);
+    let mut other_params = public_inputs.clone();
+    other_params.validator_params.swap(0, 1);
+    let mut other_spec = public_inputs.clone();
+    other_spec.spec = spec::SPEC_1;
+    for other in [
+        other_message,
+        other_epochs,
+        other_roots,
+        other_params,
+        other_spec,
+    ] {
+        assert!(matches!(
+            verify_receipt(&receipt, &other, true),
+            Err(ReceiptError::PublicInputsMismatch)
+        ));
+    }
+}
+
+#[test]
+fn test_prove_corrupted_aggregate() {
+    enable_dev_mode();
+    let corrupted = corrupt(
+        &test_data(),
+        CorruptionKind::FlipSignatureHash {
+            validator: 1,
+            chain: 2,
+        },
+    );
+    assert!(matches!(
+        prove_xmss_aggregate(&corrupted, ProofKind::Composite),
+        Err(ProveError::Prove(_))
+    ));
+}
+
+#[test]
+fn test_guest_rejects_corrupted_input() {
+    let test_data = test_data();
+    for kind in [
+        CorruptionKind::FlipSignatureHash {
+            validator: 0,
+            chain: 0,
+        },
+        CorruptionKind::WrongNonce { validator: 1 },
+        CorruptionKind::SwapMerkleSibling {
+            validator: 0,
+            level: 1,
+        },
+        CorruptionKind::UnregisteredRoot { validator: 1 },
+        CorruptionKind::TruncateChains { validator: 0 },
+    ] {
+        let env = ExecutorEnv::builder()
+            .write(&GuestInput::SingleMessage(corrupt(&test_data, kind)))
+            .unwrap()
+            .build()
+            .unwrap();
+        // The guest panics on a rejected aggregate, which fails the execution
+        assert!(
+            default_executor().execute(env, XMSS_AGGREGATE_ELF).is_err(),
+            "{kind:?}"
+        );
+    }
+}
+
+#[test]
+fn test_guest_rejects_unlisted_spec() {
+    // The signatures are valid, but under a spec that is not on the allow list
+    let unlisted = spec::Spec {
+        target_sum: spec::SPEC_2.target_sum + 1,
+        ..spec::SPEC_2
+    };
+    let test_data = TestDataBuilder::new()
+        .validators(2)
+        .spec(unlisted)
+        .tree_height(3)
+        .build();
+    for input in [
+        GuestInput::SingleMessage(test_data.clone()),
+        GuestInput::single_message(test_data, FailMode::CommitResult),
+    ] {
+        let env = ExecutorEnv::builder()
+            .write(&input)
+            .unwrap()
+            .build()
+            .unwrap();
+        assert!(default_executor().execute(env, XMSS_AGGREGATE_ELF).is_err());
+    }
+}
+
+#[test]
+fn test_guest_commits_digest() {
+    let test_data = test_data();
+    let expected = AggregateJournal::new(&test_data.public_inputs, true);
+    for input in [
+        GuestInput::SingleMessage(test_data.clone()),
+        GuestInput::SingleMessageDigest(test_data.clone()),
+    ] {
+        let env = ExecutorEnv::builder()
+            .write(&input)
+            .unwrap()
+            .build()
+            .unwrap();
+        let session = default_executor().execute(env, XMSS_AGGREGATE_ELF).unwrap();
+        // The digest is recomputed from the public inputs the host already knows
+        let journal = AggregateJournal::from_bytes(&session.journal.bytes).unwrap();
+        assert_eq!(journal, expected);
+    }
+}
+
+#[test]
+fn test_guest_commits_digests_of_other_inputs() {
+    let journal_of = |input: &GuestInput| {
+        let env = ExecutorEnv::builder()
+            .write(input)
+            .unwrap()
+            .build()
+            .unwrap();
+        default_executor()
+            .execute(env, XMSS_AGGREGATE_ELF)
+            .unwrap()
+            .journal
+    };
+    let test_data = test_data();
+    let public_inputs = &test_data.public_inputs;
+
+    let journal = journal_of(&GuestInput::Threshold {
+        test_data: test_data.clone(),
+        threshold: 1,
+    });
+    assert_eq!(
+        journal.decode::<ThresholdJournal>().unwrap(),
+        ThresholdJournal::new(public_inputs, 1, 2, true)
+    );
+
+    let weights = vec![3, 4];
+    let journal = journal_of(&GuestInput::Weighted {
+        test_data: test_data.clone(),
+        weights: weights.clone(),
+        min_weight: 5,
+    });
+    assert_eq!(
+        journal.decode::<WeightedJournal>().unwrap(),
+        WeightedJournal::new(public_inputs, &weights, 5, 7, true)
+    );
+
+    let multi_message = TestDataBuilder::new()
+        .validators(2)
+        .tree_height(3)
+        .build_multi_message();
+    let journal = journal_of(&GuestInput::MultiMessage(multi_message.clone()));
+    assert_eq!(
+        journal.decode::<DigestJournal>().unwrap(),
+        DigestJournal::multi_message(&multi_message.public_inputs, true)
+    );
+
+    let multi_epoch = create_multi_epoch_test_data(spec::SPEC_2, 3, 10000, &[0, 2]);
+    let journal = journal_of(&GuestInput::MultiEpoch(multi_epoch.clone()));
+    assert_eq!(
+        journal.decode::<DigestJournal>().unwrap(),
+        DigestJournal::multi_epoch(&multi_epoch.public_inputs, true)
+    );
+}
+
+#[test]
+fn test_commit_result() {
+    let test_data = test_data();
+    let config = ProveConfig {
+        fail_mode: FailMode::CommitResult,
+        ..ProveConfig::default()
+    };
+    // A valid aggregate commits the success of every validator
+    let stats = execute(&test_data, &config).unwrap();
+    assert!(stats.journal.verification_result);
+    assert_eq!(stats.journal.validator_results, Some(vec![true, true]));
+
+    // A corrupted one runs to completion, and commits the validator that failed
+    let corrupted = corrupt(
+        &test_data,
+        CorruptionKind::FlipSignatureHash {
+            validator: 1,
+            chain: 2,
+        },
+    );
+    let stats = execute(&corrupted, &config).unwrap();
+    assert!(!stats.journal.verification_result);
+    assert_eq!(stats.journal.validator_results, Some(vec![true, false]));
+    assert_eq!(stats.journal.first_failure(), Some(1));
+    assert_eq!(stats.journal.digest, corrupted.public_inputs.digest());
+    // The guest still rejects it by default
+    assert!(matches!(
+        execute(&corrupted, &ProveConfig::default()),
+        Err(ProveError::Prove(_))
+    ));
+
+    // The receipt proves the failure, which is not a successful verification
+    enable_dev_mode();
+    let receipt = prove(&corrupted, &config).unwrap().receipt;
+    let journal = verify_receipt_result(&receipt, &corrupted.public_inputs, true).unwrap();
+    assert_eq!(journal, stats.journal);
+    assert!(matches!(
+        verify_receipt(&receipt, &corrupted.public_inputs, true),
+        Err(ReceiptError::VerificationFailed)
+    ));
+}
+
+#[test]
+fn test_segment_limit() {
+    let test_data = test_data();
+    let num_segments = |segment_limit_po2| {
+        let config = ProveConfig {
+            segment_limit_po2,
+            ..ProveConfig::default()
+        };
+        execute(&test_data, &config).unwrap().segments
+    };
+    // Smaller segments split the same execution into more of them
+    assert!(num_segments(Some(15)) > num_segments(None));
+}
+
+#[test]
+fn test_max_cycles() {
+    enable_dev_mode();
+    let test_data = test_data();
+    let config = ProveConfig {
+        max_cycles: Some(1 << 15),
+        ..ProveConfig::new(ProofKind::Composite)
+    };
+    assert!(matches!(
+        execute(&test_data, &config),
+        Err(ProveError::Prove(_))
+    ));
+    assert!(matches!(
+        prove(&test_data, &config),
+        Err(ProveError::Prove(_))
+    ));
+}
+
+#[test]
+fn test_compose_aggregates() {
+    enable_dev_mode();
+    let shard = |seed| {
+        TestDataBuilder::new()
+            .validators(2)
+            .tree_height(3)
+            .seed(seed)
+            .build()
+    };
+    let shards = [shard(1), shard(2)];
+    let receipts: Vec<_> = (shards.iter())
+        .map(|test_data| {
+            let receipt = prove_xmss_aggregate(test_data, ProofKind::Succinct)
+                .unwrap()
+                .receipt;
+            (receipt, test_data.public_inputs.clone())
+        })
+        .collect();
+
+    let result = compose_aggregates(receipts.clone()).unwrap();
+    let expected = PublicInputs::merge(&[
+        shards[0].public_inputs.clone(),
+        shards[1].public_inputs.clone(),
+    ])
+    .unwrap();
+    assert_eq!(result.public_inputs, expected);
+    assert_eq!(result.public_inputs.validator_roots.len(), 4);
+    verify_composed_receipt(&result.receipt, &expected, true).unwrap();
+    // The composed receipt is not one of the aggregate guest
+    assert!(matches!(
+        verify_receipt(&result.receipt, &expected, true),
+        Err(ReceiptError::ImageId(_))
+    ));
+
+    // A shard must come with the public inputs its receipt commits the digest of
+    assert!(matches!(
+        compose_aggregates(vec![
+            receipts[0].clone(),
+            (receipts[1].0.clone(), shards[0].public_inputs.clone()),
+        ]),
+        Err(ProveError::Receipt(ReceiptError::PublicInputsMismatch))
+    ));
+
+    // Composing a shard with itself counts its validators twice
+    assert!(matches!(
+        compose_aggregates(vec![receipts[0].clone(), receipts[0].clone()]),
+        Err(ProveError::Shards(MergeError::DuplicateRoot { index: 2 }))
+    ));
+}
+
+#[test]
+fn test_execute_xmss_aggregate() {
+    let execute_validators = |num_validators| {
+        let test_data = TestDataBuilder::new()
+            .validators(num_validators)
+            .tree_height(4)
+            .build();
+        let stats = execute_xmss_aggregate(&test_data).unwrap();
+        assert_eq!(
+            stats.journal,
+            AggregateJournal::new(&test_data.public_inputs, true)
+        );
+        assert!(stats.total_cycles >= stats.user_cycles);
+        assert!(stats.segments > 0);
+        stats
+    };
+    // Every validator signature is verified in the guest
+    assert!(execute_validators(4).user_cycles > execute_validators(2).user_cycles);
+}
+
+#[test]
+fn test_batch() {
+    let batch = [
+        test_data(),
+        TestDataBuilder::new()
+            .validators(1)
+            .tree_height(3)
+            .message(Message([7; 32]))
+            .build(),
+    ];
+    let execute_batch = |batch: Vec<XmssTestData>| {
+        let env = ExecutorEnv::builder()
+            .write(&GuestInput::Batch(batch))
+            .unwrap()
+            .build()
+            .unwrap();
+        default_executor().execute(env, XMSS_AGGREGATE_ELF)
+    };
+
+    let session = execute_batch(batch.to_vec()).unwrap();
+    let (digests, verification_result): (Vec<Hash>, bool) = session.journal.decode().unwrap();
+    assert!(verification_result);
+    assert_eq!(
+        digests,
+        [
+            batch[0].public_inputs.digest(),
+            batch[1].public_inputs.digest()
+        ]
+    );
+
+    // A single aggregate that fails to verify aborts the whole batch
+    let corrupted = corrupt(&batch[1], CorruptionKind::WrongNonce { validator: 0 });
+    assert!(execute_batch(vec![batch[0].clone(), corrupted]).is_err());
+    assert!(execute_batch(Vec::new()).is_err());
+
+    enable_dev_mode();
+    let result = prove_xmss_aggregate_batch(&batch).unwrap();
+    let public_inputs = [
+        batch[0].public_inputs.clone(),
+        batch[1].public_inputs.clone(),
+    ];
+    assert_eq!(result.public_inputs, public_inputs);
+    verify_batch_receipt(&result.receipt, &public_inputs, true).unwrap();
+    assert!(matches!(
+        verify_batch_receipt(&result.receipt, &public_inputs[..1], true),
+        Err(ReceiptError::BatchLengthMismatch)
+    ));
+    assert!(matches!(
+        verify_batch_receipt(
+            &result.receipt,
+            &[public_inputs[1].clone(), public_inputs[0].clone()],
+            true
+        ),
+        Err(ReceiptError::DigestMismatch { index: 0 })
+    ));
+}
+
+#[test]
+fn test_dev_mode() {
+    // Dev mode is selected by the configuration alone, which CI can run without a prover
+    let test_data = test_data();
+    let config = ProveConfig {
+        dev_mode: true,
+        ..ProveConfig::new(ProofKind::Succinct)
+    };
+    let result = prove(&test_data, &config).unwrap();
+    assert!(result.is_dev_mode);
+    assert_eq!(result.seal_size, 0);
+    assert_eq!(result.public_inputs, test_data.public_inputs);
+
+    // A fake receipt proves nothing, so it is only accepted when allowed
+    verify_receipt(&result.receipt, &test_data.public_inputs, true).unwrap();
+    assert!(matches!(
+        verify_receipt(&result.receipt, &test_data.public_inputs, false),
+        Err(ReceiptError::DevMode)
+    ));
+}
+
+#[test]
+fn test_journal_digest() {
+    let test_data = test_data();
+    let config = ProveConfig {
+        dev_mode: true,
+        ..ProveConfig::default()
+    };
+    let result = prove(&test_data, &config).unwrap();
+    assert_eq!(result.image_id, Digest::from(XMSS_AGGREGATE_ID));
+    let journal_digest = Impl::hash_bytes(&result.receipt.journal.bytes);
+    assert_eq!(result.journal_digest.as_slice(), journal_digest.as_bytes());
+
+    // The summary holds the same identifiers, and round trips through JSON
+    let summary = result.summary();
+    assert_eq!(summary.image_id, result.image_id.to_string());
+    assert_eq!(summary.journal_digest, journal_digest.to_string());
+    assert_eq!(
+        summary.journal_size_bytes,
+        result.receipt.journal.bytes.len()
+    );
+    let json = serde_json::to_string(&summary).unwrap();
+    assert_eq!(
+        serde_json::from_str::<ProofSummary>(&json).unwrap(),
+        summary
+    );
+}
+
+#[test]
+fn test_remote_backend_in_dev_mode() {
+    // Fake receipts need no prover, so dev mode proves locally without any credentials
+    let test_data = test_data();
+    let config = ProveConfig {
+        dev_mode: true,
+        backend: ProverBackend::Remote {
+            api_key_env: "LEANSIG_TEST_UNSET_API_KEY".to_string(),
+            url: None,
+        },
+        ..ProveConfig::new(ProofKind::Succinct)
+    };
+    let result = prove(&test_data, &config).unwrap();
+    assert_eq!(result.backend, ProverBackend::Local);
+    assert_eq!(result.timings.queue_time(), Some(Duration::ZERO));
+}
+
+#[test]
+fn test_receipt_file() {
+    let dir = std::env::temp_dir();
+    let receipt_path = dir.join(format!("leansig-receipt-{}", std::process::id()));
+    let inputs_path = dir.join(format!("leansig-receipt-inputs-{}", std::process::id()));
+    let test_data = test_data();
+    let config = ProveConfig {
+        dev_mode: true,
+        ..ProveConfig::default()
+    };
+    let result = prove(&test_data, &config).unwrap();
+    result.write_receipt(&receipt_path).unwrap();
+    test_data.public_inputs.write_to_file(&inputs_path).unwrap();
+
+    let receipt = load_receipt(&receipt_path).unwrap();
+    assert_eq!(receipt.journal.bytes, result.receipt.journal.bytes);
+    verify_receipt_file(&receipt_path, &inputs_path, true).unwrap();
+
+    // A corrupted journal fails to decode or to verify, rather than panicking
+    let mut bytes = std::fs::read(&receipt_path).unwrap();
+    let journal = &result.receipt.journal.bytes;
+    let position = (bytes.windows(journal.len()))
+        .position(|window| window == journal.as_slice())
+        .unwrap();
+    bytes[position] ^= 1;
+    std::fs::write(&receipt_path, &bytes).unwrap();
+    assert!(verify_receipt_file(&receipt_path, &inputs_path, true).is_err());
+
+    // So does a receipt of another format version
+    bytes[4] ^= 1;
+    std::fs::write(&receipt_path, &bytes).unwrap();
+    assert!(matches!(
+        verify_receipt_file(&receipt_path, &inputs_path, true),
+        Err(ReceiptError::File(err)) if err.to_string() == "receipt format version 3 instead of 2"
+    ));
+
+    std::fs::remove_file(receipt_path).unwrap();
+    std::fs::remove_file(inputs_path).unwrap();
+}
@@ -1,4 +1,19 @@
 // Copyright 2025 Irreducible Inc.
+use std::collections::HashMap;
+
+use risc0_build::GuestOptionsBuilder;
+
 fn main() {
-    risc0_build::embed_methods();
+    let mut features = Vec::new();
+    if std::env::var_os("CARGO_FEATURE_POSEIDON2").is_some() {
+        features.push("poseidon2".to_string());
+    }
+    let options = GuestOptionsBuilder::default()
+        .features(features)
+        .build()
+        .unwrap();
+    risc0_build::embed_methods_with_options(HashMap::from([
+        ("xmss_aggregate", options.clone()),
+        ("xmss_compose", options),
+    ]));
 }
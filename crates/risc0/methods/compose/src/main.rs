@@ -0,0 +1,32 @@
+// Copyright 2025 Irreducible Inc.
+use leansig_shared::{AggregateJournal, PublicInputs};
+use risc0_zkvm::guest::env;
+
+fn main() {
+    // The public inputs of every shard with the image id of the guest that proved it. The
+    // receipts themselves are assumptions of the host, which resolve the `env::verify` calls
+    let shards: Vec<(PublicInputs, [u32; 8])> = env::read();
+    let image_id = shards.first().expect("no shards to compose").1;
+
+    let shard_inputs: Vec<PublicInputs> = shards
+        .into_iter()
+        .map(|(public_inputs, shard_image_id)| {
+            // Every shard must be an aggregate of the same guest, which is committed below
+            assert_eq!(shard_image_id, image_id, "shards of different guests");
+
+            // The aggregate guest commits the digest of its public inputs with a successful
+            // verification, so only a shard of these public inputs resolves the assumption
+            let journal = AggregateJournal::new(&public_inputs, true);
+            env::verify(shard_image_id, &journal.to_bytes()).expect("unverified shard");
+            public_inputs
+        })
+        .collect();
+
+    // The shards must agree on the message and spec, and register every validator only once
+    let public_inputs = PublicInputs::merge(&shard_inputs).expect("shards do not compose");
+
+    // Commit the journal of the merged public inputs like a single aggregate does, followed by
+    // the image id that the host checks the shards against
+    env::commit_slice(&AggregateJournal::new(&public_inputs, true).to_bytes());
+    env::commit_slice(&image_id);
+}
@@ -1,32 +1,301 @@
 // Copyright 2025 Irreducible Inc.
 use leansig_core::AggregatedVerifier;
-use leansig_shared::XmssTestData;
+use leansig_shared::{
+    AggregateJournal, Backend, DigestJournal, FailMode, GuestInput, ThresholdJournal,
+    WeightedJournal, XmssTestData, is_allowed_spec,
+};
 use risc0_zkvm::guest::env;
 
 fn main() {
     // Read the test data containing both public inputs and aggregated signature
-    let test_data: XmssTestData = env::read();
-    
-    // Extract the components
-    let public_inputs = test_data.public_inputs;
-    let aggregated_signature = test_data.aggregated_signature;
-
-    // Create the aggregated verifier with the validator roots
-    let verifier = AggregatedVerifier::new(
-        public_inputs.validator_roots.clone(),
-        public_inputs.spec.clone(),
-    );
+    let input: GuestInput = env::read();
+    // The prover chooses the spec, so only the allowed ones are proven under
+    for spec in input.specs() {
+        assert!(
+            is_allowed_spec(spec),
+            "spec {} is not in the allowlist",
+            spec.id_with_backend::<Backend>()
+        );
+    }
+    // A compressed aggregate is verified like any other once its params are restored, and an
+    // aggregate committed by digest or with a fail mode only differs in what is committed
+    let verbose_journal = cfg!(feature = "verbose_journal");
+    let (input, verbose_journal, fail_mode) = match input {
+        GuestInput::Compressed(test_data) => (
+            GuestInput::SingleMessage(
+                test_data
+                    .decompress()
+                    .expect("malformed compressed aggregate"),
+            ),
+            verbose_journal,
+            FailMode::Abort,
+        ),
+        GuestInput::SingleMessageDigest(test_data) => {
+            (GuestInput::SingleMessage(test_data), false, FailMode::Abort)
+        }
+        GuestInput::SingleMessageWithFailMode {
+            test_data,
+            fail_mode,
+        } => (
+            GuestInput::SingleMessage(test_data),
+            verbose_journal,
+            fail_mode,
+        ),
+        input => (input, verbose_journal, FailMode::Abort),
+    };
+
+    let verification_result = match input {
+        GuestInput::SingleMessage(test_data) if fail_mode == FailMode::CommitResult => {
+            let public_inputs = &test_data.public_inputs;
+            public_inputs
+                .spec
+                .validate()
+                .expect("invalid signature scheme spec");
+            public_inputs
+                .check_validators()
+                .expect("malformed validator set");
+
+            // Commit the result of every validator instead of rejecting a failed aggregate, so
+            // that the failure is proven as well
+            let results = public_inputs.validator_results(&test_data.aggregated_signature);
+            let journal = AggregateJournal::with_validator_results(public_inputs, results);
+            env::commit_slice(&journal.to_bytes());
+            return;
+        }
+        GuestInput::SingleMessage(test_data) => {
+            let verification_result = verify_aggregate(&test_data);
+
+            // The verification must succeed, otherwise the proof generation will fail
+            assert!(verification_result, "XMSS signature verification failed");
+
+            // Commit the digest of the public inputs with the result, which binds the proof to
+            // the inputs in the same 33 bytes for any number of validators. The verbose journal
+            // commits the public inputs themselves instead, for debugging
+            let public_inputs = test_data.public_inputs;
+            if !verbose_journal {
+                let journal = AggregateJournal::new(&public_inputs, verification_result);
+                env::commit_slice(&journal.to_bytes());
+                return;
+            }
+            env::commit(&public_inputs);
+            verification_result
+        }
+        GuestInput::Batch(batch) => {
+            assert!(!batch.is_empty(), "empty batch");
+
+            // Any aggregate of the batch that fails to verify aborts the proof of the whole batch
+            let digests: Vec<_> = batch
+                .iter()
+                .enumerate()
+                .map(|(index, test_data)| {
+                    assert!(
+                        verify_aggregate(test_data),
+                        "XMSS signature verification failed for batch entry {index}"
+                    );
+                    test_data.public_inputs.digest()
+                })
+                .collect();
+
+            // The journal commits the digests of the public inputs, in the order of the batch
+            env::commit(&digests);
+            true
+        }
+        GuestInput::MultiMessage(test_data) => {
+            let public_inputs = test_data.public_inputs;
+            let aggregated_signature = test_data.aggregated_signature;
+
+            public_inputs
+                .spec
+                .validate()
+                .expect("invalid signature scheme spec");
+            public_inputs
+                .check_validators()
+                .expect("malformed validator set");
+            // The journal commits the epoch, so every validator must sign at it
+            assert!(
+                aggregated_signature.is_at_epoch(public_inputs.epoch),
+                "XMSS signature at the wrong epoch"
+            );
+
+            // Every root is registered together with the message signed under it
+            let verifier = AggregatedVerifier::<Backend>::new_with_backend(
+                public_inputs.validator_registry(),
+                public_inputs.spec.clone(),
+            )
+            .with_strict_roots(true);
+            let verification_result =
+                verifier.verify_multi(&public_inputs.messages, &aggregated_signature);
+            assert!(verification_result, "XMSS signature verification failed");
+
+            // The journal commits the (root, message) pairs, by digest unless it is verbose
+            if !verbose_journal {
+                let journal = DigestJournal::multi_message(&public_inputs, verification_result);
+                env::commit(&journal);
+                return;
+            }
+            env::commit(&public_inputs);
+            verification_result
+        }
+        GuestInput::Threshold {
+            test_data,
+            threshold,
+        } => {
+            let public_inputs = test_data.public_inputs;
+            let aggregated_signature = test_data.aggregated_signature;
+
+            public_inputs
+                .spec
+                .validate()
+                .expect("invalid signature scheme spec");
+            public_inputs
+                .check_validators()
+                .expect("malformed validator set");
+            // The journal commits the epochs, so every validator must sign at its own
+            assert!(
+                public_inputs.is_at_assigned_epochs(&aggregated_signature),
+                "XMSS signature at the wrong epoch"
+            );
+
+            let verifier = AggregatedVerifier::<Backend>::new_with_backend(
+                public_inputs.validator_registry(),
+                public_inputs.spec.clone(),
+            )
+            .with_strict_roots(true);
 
-    // Verify the aggregated signature
-    let verification_result = verifier.verify(&public_inputs.message, &aggregated_signature);
+            // Proof generation fails unless a quorum of distinct validators signed
+            let num_valid = verifier
+                .verify_threshold(&public_inputs.message, &aggregated_signature, threshold)
+                .expect("XMSS threshold verification failed");
 
-    // The verification must succeed, otherwise the proof generation will fail
-    assert!(verification_result, "XMSS signature verification failed");
+            // The journal commits the achieved count so the consumer can check the quorum, with
+            // the public inputs by digest unless it is verbose
+            if !verbose_journal {
+                let journal = ThresholdJournal::new(&public_inputs, threshold, num_valid, true);
+                env::commit(&journal);
+                return;
+            }
+            env::commit(&public_inputs);
+            env::commit(&threshold);
+            env::commit(&num_valid);
+            true
+        }
+        GuestInput::Weighted {
+            test_data,
+            weights,
+            min_weight,
+        } => {
+            let public_inputs = test_data.public_inputs;
+            let aggregated_signature = test_data.aggregated_signature;
 
-    // Commit the public inputs to the journal for the host to verify
-    // This ensures the proof is bound to specific inputs
-    env::commit(&public_inputs);
+            public_inputs
+                .spec
+                .validate()
+                .expect("invalid signature scheme spec");
+            public_inputs
+                .check_validators()
+                .expect("malformed validator set");
+            // The journal commits the epochs, so every validator must sign at its own
+            assert!(
+                public_inputs.is_at_assigned_epochs(&aggregated_signature),
+                "XMSS signature at the wrong epoch"
+            );
+            assert_eq!(
+                weights.len(),
+                public_inputs.validator_roots.len(),
+                "one weight per validator root"
+            );
+
+            let verifier = AggregatedVerifier::<Backend>::new_with_backend(
+                public_inputs.weighted_validator_registry(&weights),
+                public_inputs.spec.clone(),
+            )
+            .with_strict_roots(true);
+
+            // Proof generation fails unless the signers carry enough weight
+            let weight = verifier
+                .verify_weighted(&public_inputs.message, &aggregated_signature, min_weight)
+                .expect("XMSS weighted verification failed");
+
+            // The journal commits the achieved weight so the consumer can check it, with the
+            // public inputs and the weights by digest unless it is verbose
+            if !verbose_journal {
+                let journal =
+                    WeightedJournal::new(&public_inputs, &weights, min_weight, weight, true);
+                env::commit(&journal);
+                return;
+            }
+            env::commit(&public_inputs);
+            env::commit(&weights);
+            env::commit(&min_weight);
+            env::commit(&weight);
+            true
+        }
+        GuestInput::MultiEpoch(test_data) => {
+            let public_inputs = test_data.public_inputs;
+            let signature = test_data.signature;
+
+            public_inputs
+                .spec
+                .validate()
+                .expect("invalid signature scheme spec");
+            // The journal commits the root and the (epoch, message) pairs, which must be the
+            // ones that are verified
+            assert!(
+                signature.root == public_inputs.root
+                    && signature.param == public_inputs.param
+                    && signature.messages() == public_inputs.messages,
+                "public inputs do not match the signature"
+            );
+
+            let verification_result = signature.verify_with_backend::<Backend>(&public_inputs.spec);
+            assert!(verification_result, "XMSS multi-epoch verification failed");
+
+            if !verbose_journal {
+                let journal = DigestJournal::multi_epoch(&public_inputs, verification_result);
+                env::commit(&journal);
+                return;
+            }
+            env::commit(&public_inputs);
+            verification_result
+        }
+        GuestInput::Compressed(_)
+        | GuestInput::SingleMessageDigest(_)
+        | GuestInput::SingleMessageWithFailMode { .. } => {
+            unreachable!("mapped to a single-message input above")
+        }
+        GuestInput::SingleMessageParts { .. } => {
+            panic!("the public inputs and the aggregate are read as one input")
+        }
+    };
 
     // Optionally commit a success flag
     env::commit(&verification_result);
 }
+
+/// Verifies the aggregated signature of `test_data` for its message, with every validator at
+/// its committed epoch
+fn verify_aggregate(test_data: &XmssTestData) -> bool {
+    let public_inputs = &test_data.public_inputs;
+    let aggregated_signature = &test_data.aggregated_signature;
+
+    // Reject malformed specs up front rather than failing somewhere inside verification
+    public_inputs
+        .spec
+        .validate()
+        .expect("invalid signature scheme spec");
+    // Validators are looked up by root, so a repeated root would check the later ones
+    // against the epochs of others
+    public_inputs
+        .check_validators()
+        .expect("malformed validator set");
+
+    // Create the aggregated verifier with the validator roots and their params
+    let verifier = AggregatedVerifier::<Backend>::new_with_backend(
+        public_inputs.validator_registry(),
+        public_inputs.spec.clone(),
+    )
+    .with_strict_roots(true);
+
+    verifier.verify(&public_inputs.message, aggregated_signature)
+        && public_inputs.is_at_assigned_epochs(aggregated_signature)
+}
@@ -0,0 +1,255 @@
+// Copyright 2025 Irreducible Inc.
+//! The results of the benchmark sweeps of the hosts, in a schema shared by RISC0 and SP1.
+//!
+//! Both hosts write a [`BenchRecord`] per point of a [`SweepGrid`], so that their CSV files can
+//! be joined on the `spec`, `hash_backend`, `validators` and `tree_height` columns.
+
+use std::time::Duration;
+
+use leansig_core::{
+    hash::TweakableHash,
+    spec::{SPEC_1, SPEC_2, Spec},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::Backend;
+
+/// The validator counts a sweep runs by default
+pub const SWEEP_VALIDATORS: [usize; 4] = [1, 4, 16, 64];
+/// The tree heights a sweep runs by default
+pub const SWEEP_TREE_HEIGHTS: [usize; 2] = [8, 13];
+
+/// The points a benchmark sweep runs, every validator count at every tree height
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SweepGrid {
+    pub validators: Vec<usize>,
+    pub tree_heights: Vec<usize>,
+}
+
+impl Default for SweepGrid {
+    fn default() -> Self {
+        Self {
+            validators: SWEEP_VALIDATORS.to_vec(),
+            tree_heights: SWEEP_TREE_HEIGHTS.to_vec(),
+        }
+    }
+}
+
+impl SweepGrid {
+    /// The default grid, with the validator counts and tree heights replaced by the
+    /// comma-separated lists of `BENCH_SWEEP_VALIDATORS` and `BENCH_SWEEP_TREE_HEIGHTS`
+    ///
+    /// Lists that don't parse are ignored, like the other settings of the benchmarks.
+    pub fn from_env() -> Self {
+        let list = |name| {
+            let value = std::env::var(name).ok()?;
+            let values: Result<Vec<usize>, _> =
+                value.split(',').map(|value| value.trim().parse()).collect();
+            values.ok().filter(|values| !values.is_empty())
+        };
+        let default = Self::default();
+        Self {
+            validators: list("BENCH_SWEEP_VALIDATORS").unwrap_or(default.validators),
+            tree_heights: list("BENCH_SWEEP_TREE_HEIGHTS").unwrap_or(default.tree_heights),
+        }
+    }
+
+    /// The `(validators, tree_height)` points of the grid, by tree height first
+    pub fn points(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (self.tree_heights.iter()).flat_map(|&tree_height| {
+            (self.validators.iter()).map(move |&validators| (validators, tree_height))
+        })
+    }
+}
+
+/// The name of `spec` in benchmark results, `SPEC_1`, `SPEC_2` or the id of any other spec
+pub fn spec_name(spec: &Spec) -> String {
+    if *spec == SPEC_1 {
+        "SPEC_1".to_string()
+    } else if *spec == SPEC_2 {
+        "SPEC_2".to_string()
+    } else {
+        spec.id().to_string()
+    }
+}
+
+/// The measurements of one point of a benchmark sweep
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BenchRecord {
+    /// `risc0` or `sp1`
+    pub zkvm: String,
+    /// The kind of receipt or the mode of proof, e.g. `succinct` or `core`
+    pub proof_kind: String,
+    /// See [`spec_name`]
+    pub spec: String,
+    /// The name of the hash backend of the test data
+    pub hash_backend: String,
+    pub validators: usize,
+    pub tree_height: usize,
+    /// The cycles of the execution of the guest
+    pub cycles: u64,
+    /// The time of proving, without the execution of the guest where the zkVM separates it
+    pub proving_time_ms: f64,
+    /// The time of verifying the proof against the expected public inputs
+    pub verification_time_ms: f64,
+    /// The bytes of the seal, without the journal
+    pub proof_size_bytes: usize,
+    /// The bytes of the journal, i.e. the public values of SP1
+    pub journal_size_bytes: usize,
+}
+
+impl BenchRecord {
+    /// The header of [`BenchRecord::to_csv_row`]
+    pub const CSV_HEADER: &str = "zkvm,proof_kind,spec,hash_backend,validators,tree_height,cycles,proving_time_ms,verification_time_ms,proof_size_bytes,journal_size_bytes";
+
+    /// A record of a point of the sweep, with the spec and the hash backend of the test data
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        zkvm: &str,
+        proof_kind: &str,
+        spec: &Spec,
+        validators: usize,
+        tree_height: usize,
+        cycles: u64,
+        proving_time: Duration,
+        verification_time: Duration,
+        proof_size_bytes: usize,
+        journal_size_bytes: usize,
+    ) -> Self {
+        Self {
+            zkvm: zkvm.to_string(),
+            proof_kind: proof_kind.to_string(),
+            spec: spec_name(spec),
+            hash_backend: <Backend as TweakableHash>::NAME.to_string(),
+            validators,
+            tree_height,
+            cycles,
+            proving_time_ms: proving_time.as_secs_f64() * 1000.0,
+            verification_time_ms: verification_time.as_secs_f64() * 1000.0,
+            proof_size_bytes,
+            journal_size_bytes,
+        }
+    }
+
+    /// The fields of the record as a line of CSV, in the order of [`BenchRecord::CSV_HEADER`]
+    ///
+    /// None of the fields holds a comma or a quote, so none is quoted.
+    pub fn to_csv_row(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{},{:.3},{:.3},{},{}",
+            self.zkvm,
+            self.proof_kind,
+            self.spec,
+            self.hash_backend,
+            self.validators,
+            self.tree_height,
+            self.cycles,
+            self.proving_time_ms,
+            self.verification_time_ms,
+            self.proof_size_bytes,
+            self.journal_size_bytes
+        )
+    }
+}
+
+/// The directory the sweeps write their results to, `bench-results` in the target directory of
+/// the workspace
+#[cfg(feature = "bench_results")]
+pub fn bench_results_dir() -> std::path::PathBuf {
+    let target_dir = std::env::var_os("CARGO_TARGET_DIR")
+        .map(Into::into)
+        .unwrap_or_else(|| std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../target"));
+    target_dir.join("bench-results")
+}
+
+/// Writes `records` to `<name>.csv` and `<name>.json` in `dir`, which is created if needed
+#[cfg(feature = "bench_results")]
+pub fn write_bench_results(
+    dir: impl AsRef<std::path::Path>,
+    name: &str,
+    records: &[BenchRecord],
+) -> std::io::Result<()> {
+    let dir = dir.as_ref();
+    std::fs::create_dir_all(dir)?;
+
+    let mut csv = String::from(BenchRecord::CSV_HEADER);
+    csv.push('\n');
+    for record in records {
+        csv.push_str(&record.to_csv_row());
+        csv.push('\n');
+    }
+    std::fs::write(dir.join(format!("{name}.csv")), csv)?;
+
+    let json = serde_json::to_string_pretty(records).map_err(std::io::Error::other)?;
+    std::fs::write(dir.join(format!("{name}.json")), json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record() -> BenchRecord {
+        BenchRecord::new(
+            "risc0",
+            "succinct",
+            &SPEC_2,
+            16,
+            13,
+            1_234_567,
+            Duration::from_micros(2_500_250),
+            Duration::from_millis(12),
+            222_668,
+            33,
+        )
+    }
+
+    #[test]
+    fn test_csv_row() {
+        let record = record();
+        let row = record.to_csv_row();
+        let fields: Vec<_> = row.split(',').collect();
+        assert_eq!(fields.len(), BenchRecord::CSV_HEADER.split(',').count());
+        assert_eq!(
+            fields,
+            [
+                "risc0",
+                "succinct",
+                "SPEC_2",
+                <Backend as TweakableHash>::NAME,
+                "16",
+                "13",
+                "1234567",
+                "2500.250",
+                "12.000",
+                "222668",
+                "33"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let records = vec![record(), record()];
+        let json = serde_json::to_string(&records).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Vec<BenchRecord>>(&json).unwrap(),
+            records
+        );
+        // The JSON fields are the CSV columns
+        let object = serde_json::to_value(record()).unwrap();
+        let mut keys: Vec<_> = object.as_object().unwrap().keys().cloned().collect();
+        let mut columns: Vec<_> = BenchRecord::CSV_HEADER.split(',').collect();
+        keys.sort();
+        columns.sort();
+        assert_eq!(keys, columns);
+    }
+
+    #[test]
+    fn test_sweep_grid() {
+        let grid = SweepGrid::default();
+        let points: Vec<_> = grid.points().collect();
+        assert_eq!(points.len(), 8);
+        assert_eq!(points[..2], [(1, 8), (4, 8)]);
+        assert_eq!(points[7], (64, 13));
+    }
+}
@@ -0,0 +1,199 @@
+// Copyright 2025 Irreducible Inc.
+//! Known-bad variants of [`XmssTestData`] for asserting that verifiers reject tampered input.
+//!
+//! Every corruption keeps the data structurally valid, so that it still deserializes in a guest
+//! and is rejected by verification rather than by decoding.
+
+use leansig_core::{AnySignature, Signature, hash::Hash};
+
+use crate::XmssTestData;
+
+/// A way of tampering with one validator signature of an aggregate
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CorruptionKind {
+    /// Flips the bits of the signed hash of `chain`, so the chain no longer ends at the public key
+    FlipSignatureHash { validator: usize, chain: usize },
+    /// Flips the bits of the nonce, so the message no longer encodes to the signed codeword
+    WrongNonce { validator: usize },
+    /// Swaps the sibling at `level` of the Merkle path with the one above it, wrapping around to
+    /// the bottom at the top level, so the path no longer leads to the root
+    SwapMerkleSibling { validator: usize, level: usize },
+    /// Replaces the root of the signature by one that is not among the public inputs
+    UnregisteredRoot { validator: usize },
+    /// Drops the last signed hash, so the signature has one chain too few
+    TruncateChains { validator: usize },
+}
+
+impl CorruptionKind {
+    /// The index of the validator whose signature is tampered with
+    pub fn validator(&self) -> usize {
+        match *self {
+            CorruptionKind::FlipSignatureHash { validator, .. }
+            | CorruptionKind::WrongNonce { validator }
+            | CorruptionKind::SwapMerkleSibling { validator, .. }
+            | CorruptionKind::UnregisteredRoot { validator }
+            | CorruptionKind::TruncateChains { validator } => validator,
+        }
+    }
+}
+
+/// Returns a copy of `test_data` with the signature of one validator tampered with as `kind`
+/// describes
+///
+/// The public inputs are left as they are. For a hypertree signature the bottom signature is
+/// tampered with.
+///
+/// # Panics
+/// Panics if the validator, chain or level of `kind` is out of range, or if a Merkle path is too
+/// short to swap two of its siblings.
+pub fn corrupt(test_data: &XmssTestData, kind: CorruptionKind) -> XmssTestData {
+    let mut test_data = test_data.clone();
+    let num_signatures = test_data.aggregated_signature.signatures.len();
+    let validator_signature = (test_data.aggregated_signature.signatures)
+        .get_mut(kind.validator())
+        .unwrap_or_else(|| {
+            panic!(
+                "validator {} is out of range for {num_signatures} signatures",
+                kind.validator()
+            )
+        });
+
+    match kind {
+        CorruptionKind::FlipSignatureHash { chain, .. } => {
+            let hashes = &mut signature_mut(&mut validator_signature.signature)
+                .signature
+                .hashes;
+            let num_chains = hashes.len();
+            let hash = (hashes.get_mut(chain))
+                .unwrap_or_else(|| panic!("chain {chain} is out of range for {num_chains} chains"));
+            *hash = flipped(hash);
+        }
+        CorruptionKind::WrongNonce { .. } => {
+            let nonce = &mut signature_mut(&mut validator_signature.signature)
+                .signature
+                .nonce;
+            for byte in nonce.0.iter_mut() {
+                *byte = !*byte;
+            }
+        }
+        CorruptionKind::SwapMerkleSibling { level, .. } => {
+            let path = &mut signature_mut(&mut validator_signature.signature)
+                .hash_tree_proof
+                .path;
+            assert!(
+                level < path.len() && path.len() > 1,
+                "level {level} cannot be swapped in a path of {} siblings",
+                path.len()
+            );
+            let above = (level + 1) % path.len();
+            path.swap(level, above);
+        }
+        CorruptionKind::UnregisteredRoot { .. } => {
+            validator_signature.xmss_root = flipped(&validator_signature.xmss_root);
+        }
+        CorruptionKind::TruncateChains { .. } => {
+            signature_mut(&mut validator_signature.signature)
+                .signature
+                .hashes
+                .pop();
+        }
+    }
+    test_data
+}
+
+/// The XMSS signature of `signature` that the message is signed with
+fn signature_mut(signature: &mut AnySignature) -> &mut Signature {
+    match signature {
+        AnySignature::Xmss(signature) => signature,
+        AnySignature::Hyper(signature) => &mut signature.bottom,
+    }
+}
+
+/// `hash` with all of its bits flipped
+fn flipped(hash: &Hash) -> Hash {
+    Hash(hash.0.map(|byte| !byte))
+}
+
+#[cfg(test)]
+mod tests {
+    use leansig_core::{AggregatedVerifier, ValidatorVerification, VerifyError, spec::SPEC_2};
+
+    use super::*;
+    use crate::{Backend, create_test_data};
+
+    #[test]
+    fn test_corruptions_are_rejected() {
+        let test_data = create_test_data(3, SPEC_2, 3, 10000, None, None);
+        let public_inputs = &test_data.public_inputs;
+        let verifier = AggregatedVerifier::<Backend>::new_with_backend(
+            public_inputs.validator_registry(),
+            public_inputs.spec.clone(),
+        );
+        assert!(verifier.verify(&public_inputs.message, &test_data.aggregated_signature));
+
+        let cases = [
+            (
+                CorruptionKind::FlipSignatureHash {
+                    validator: 0,
+                    chain: 3,
+                },
+                ValidatorVerification::InvalidSignature(VerifyError::PublicKeyMismatch),
+            ),
+            (
+                CorruptionKind::SwapMerkleSibling {
+                    validator: 2,
+                    level: 2,
+                },
+                ValidatorVerification::InvalidSignature(VerifyError::InvalidTreeProof),
+            ),
+            (
+                CorruptionKind::UnregisteredRoot { validator: 1 },
+                ValidatorVerification::UnknownRoot,
+            ),
+            (
+                CorruptionKind::TruncateChains { validator: 2 },
+                ValidatorVerification::InvalidSignature(VerifyError::MalformedSignature),
+            ),
+        ];
+        for (kind, expected) in cases {
+            let corrupted = corrupt(&test_data, kind);
+            assert!(!verifier.verify(&public_inputs.message, &corrupted.aggregated_signature));
+            let report =
+                verifier.verify_detailed(&public_inputs.message, &corrupted.aggregated_signature);
+            for (index, result) in report.results.into_iter().enumerate() {
+                if index == kind.validator() {
+                    assert_eq!(result, expected, "{kind:?}");
+                } else {
+                    assert_eq!(result, ValidatorVerification::Ok, "{kind:?}");
+                }
+            }
+        }
+
+        // Another nonce almost never encodes to a valid codeword, and if it does, to another one.
+        let corrupted = corrupt(&test_data, CorruptionKind::WrongNonce { validator: 1 });
+        let report =
+            verifier.verify_detailed(&public_inputs.message, &corrupted.aggregated_signature);
+        assert!(matches!(
+            report.results[1],
+            ValidatorVerification::InvalidSignature(
+                VerifyError::InvalidCodeword | VerifyError::PublicKeyMismatch
+            )
+        ));
+    }
+
+    #[test]
+    fn test_corrupted_data_round_trips() {
+        let test_data = create_test_data(1, SPEC_2, 2, 10000, None, None);
+        let corrupted = corrupt(&test_data, CorruptionKind::TruncateChains { validator: 0 });
+        let bytes = bincode::serialize(&corrupted).unwrap();
+        let decoded: XmssTestData = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(bincode::serialize(&decoded).unwrap(), bytes);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn test_validator_out_of_range() {
+        let test_data = create_test_data(1, SPEC_2, 2, 10000, None, None);
+        corrupt(&test_data, CorruptionKind::WrongNonce { validator: 1 });
+    }
+}
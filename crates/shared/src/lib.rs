@@ -1,17 +1,69 @@
 // Copyright 2025 Irreducible Inc.
+use core::fmt;
+
+#[cfg(feature = "persistence")]
+use leansig_core::wire::{self, PayloadKind};
 use leansig_core::{
-    AggregatedSignature, Message, Param, Signer, ValidatorSignature, hash::Hash, spec::Spec,
+    AggregatedSignature, AggregatedVerifier, DecodeError, Message, Param, Signer,
+    ValidatorSignature, ValidatorVerification,
+    compressed::CompressedAggregate,
+    footprint::{MemoryFootprint, SignerFootprint},
+    hash::{HASH_LEN, Hash},
+    multi_epoch::MultiEpochSignature,
+    registry::ValidatorRegistry,
+    spec::{SPEC_1, SPEC_2, Spec},
 };
 use rand::{SeedableRng, rngs::StdRng};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
+use tiny_keccak::{Hasher, Keccak};
+
+mod bench_results;
+mod corruption;
+#[cfg(feature = "report")]
+mod report;
+#[cfg(feature = "ssz")]
+mod ssz;
+pub use bench_results::{BenchRecord, SWEEP_TREE_HEIGHTS, SWEEP_VALIDATORS, SweepGrid, spec_name};
+#[cfg(feature = "bench_results")]
+pub use bench_results::{bench_results_dir, write_bench_results};
+pub use corruption::{CorruptionKind, corrupt};
+#[cfg(feature = "report")]
+pub use report::{format_report, print_report};
+
+/// The hash backend the test data is generated for, selected by the `poseidon2` feature.
+///
+/// The guests verify with the same backend, so the host and guest features must match.
+#[cfg(not(feature = "poseidon2"))]
+pub type Backend = leansig_core::hash::KeccakBackend;
+#[cfg(feature = "poseidon2")]
+pub type Backend = leansig_core::poseidon2::Poseidon2Backend;
+
+/// The specs the guests accept public inputs over, unless built with the `any_spec` feature
+///
+/// The prover chooses the spec of the public inputs, so a guest that accepted any spec would
+/// prove aggregates under a spec as weak as a target sum of 0. Test data is generated over
+/// [`SPEC_2`] by default.
+pub const ALLOWED_SPECS: [Spec; 2] = [SPEC_1, SPEC_2];
+
+/// Whether the guests accept public inputs over `spec`, i.e. whether it has the id over
+/// [`Backend`] of one of [`ALLOWED_SPECS`], or any spec with the `any_spec` feature
+pub fn is_allowed_spec(spec: &Spec) -> bool {
+    if cfg!(feature = "any_spec") {
+        return true;
+    }
+    let id = spec.id_with_backend::<Backend>();
+    (ALLOWED_SPECS.iter()).any(|allowed| allowed.id_with_backend::<Backend>() == id)
+}
 
 /// Public inputs for RISC0 proof - only this gets committed to the journal
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PublicInputs {
     /// The message being signed by all validators
     pub message: Message,
-    /// The epoch at which all validators sign
-    pub epoch: usize,
+    /// The epochs at which the validators sign
+    pub epochs: EpochAssignment,
     /// Each validator's XMSS tree root hash
     pub validator_roots: Vec<Hash>,
     /// Domain parameters for each validator
@@ -20,15 +72,1422 @@ pub struct PublicInputs {
     pub spec: Spec,
 }
 
+#[cfg(feature = "cbor")]
+impl leansig_core::cbor::CanonicalCbor for PublicInputs {}
+
+/// The epochs at which the validators of [`PublicInputs`] sign
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EpochAssignment {
+    /// Every validator signs at the same epoch
+    Uniform(u64),
+    /// Every validator signs at its own epoch, in the order of the validator roots
+    PerValidator(Vec<u64>),
+}
+
+impl EpochAssignment {
+    /// The epoch of validator `index`, or None if there is no epoch for it
+    pub fn epoch_of(&self, index: usize) -> Option<u64> {
+        match self {
+            EpochAssignment::Uniform(epoch) => Some(*epoch),
+            EpochAssignment::PerValidator(epochs) => epochs.get(index).copied(),
+        }
+    }
+}
+
+impl PublicInputs {
+    /// A keccak-256 commitment to the public inputs, for guests to commit instead of the inputs
+    ///
+    /// The digest is over the following encoding, with every integer a big-endian u64:
+    /// * the domain `"leansig-public-inputs-v1"`
+    /// * the id of the spec over [`Backend`], see [`Spec::id_with_backend`]
+    /// * the 32 bytes of the message
+    /// * the epochs, as the byte 0 and the epoch if uniform, or as the byte 1, the number of
+    ///   epochs and every epoch if per validator
+    /// * the number of validator roots and the 32 bytes of every root, in order
+    /// * the number of validator params and the length and bytes of every param, in order
+    pub fn digest(&self) -> Hash {
+        let mut hasher = digest_hasher(b"leansig-public-inputs-v1", &self.spec);
+        hasher.update(&self.message.0);
+        match &self.epochs {
+            EpochAssignment::Uniform(epoch) => {
+                hasher.update(&[0]);
+                hasher.update(&epoch.to_be_bytes());
+            }
+            EpochAssignment::PerValidator(epochs) => {
+                hasher.update(&[1]);
+                hasher.update(&(epochs.len() as u64).to_be_bytes());
+                for epoch in epochs {
+                    hasher.update(&epoch.to_be_bytes());
+                }
+            }
+        }
+        hasher.update(&(self.validator_roots.len() as u64).to_be_bytes());
+        for root in &self.validator_roots {
+            hasher.update(&root.0);
+        }
+        update_params(&mut hasher, &self.validator_params);
+        finalize_digest(hasher)
+    }
+
+    /// Checks that every validator root has a param and, if the epochs are assigned per
+    /// validator, an epoch, and that no root repeats
+    ///
+    /// The validators are looked up by root, so a repeated root would shift the index of every
+    /// later validator away from its position in the committed roots, params and epochs.
+    pub fn check_validators(&self) -> Result<(), ValidatorSetError> {
+        let roots = self.validator_roots.len();
+        if self.validator_params.len() != roots {
+            return Err(ValidatorSetError::ParamCountMismatch {
+                roots,
+                params: self.validator_params.len(),
+            });
+        }
+        if let EpochAssignment::PerValidator(epochs) = &self.epochs
+            && epochs.len() != roots
+        {
+            return Err(ValidatorSetError::EpochCountMismatch {
+                epochs: epochs.len(),
+                validators: roots,
+            });
+        }
+        match first_repeated_root(&self.validator_roots) {
+            Some(index) => Err(ValidatorSetError::RepeatedRoot { index }),
+            None => Ok(()),
+        }
+    }
+
+    /// Whether every signature of `aggregated` claims the epoch assigned to its validator and
+    /// authenticates the leaf of that epoch
+    ///
+    /// A signature whose root is not among the validator roots is not at any assigned epoch, and
+    /// no signature is if the validators fail [`PublicInputs::check_validators`].
+    pub fn is_at_assigned_epochs(&self, aggregated: &AggregatedSignature) -> bool {
+        if self.check_validators().is_err() {
+            return false;
+        }
+        if let EpochAssignment::Uniform(epoch) = self.epochs {
+            return aggregated.is_at_epoch(epoch);
+        }
+        let registry = self.validator_registry();
+        (aggregated.signatures.iter()).all(|sig| self.is_at_assigned_epoch(&registry, sig))
+    }
+
+    /// Whether every signature of `aggregated` verifies for the message at the epoch assigned to
+    /// its validator, in the order of the signatures
+    ///
+    /// Unlike verifying the aggregate, this does not stop at the first signature that fails, so
+    /// that the failing validators can be told apart from the others. Every signature fails if
+    /// the validators fail [`PublicInputs::check_validators`].
+    pub fn validator_results(&self, aggregated: &AggregatedSignature) -> Vec<bool> {
+        if self.check_validators().is_err() {
+            return vec![false; aggregated.signatures.len()];
+        }
+        let registry = self.validator_registry();
+        let verifier =
+            AggregatedVerifier::<Backend>::new_with_backend(registry.clone(), self.spec.clone())
+                .with_strict_roots(true);
+        let report = verifier.verify_detailed(&self.message, aggregated);
+        (aggregated.signatures.iter())
+            .zip(report.results)
+            .map(|(sig, result)| {
+                result == ValidatorVerification::Ok && self.is_at_assigned_epoch(&registry, sig)
+            })
+            .collect()
+    }
+
+    /// Whether `sig` claims the epoch assigned to its validator in `registry`, and authenticates
+    /// the leaf of that epoch
+    ///
+    /// The index of a validator in `registry` is its position in the roots once they passed
+    /// [`PublicInputs::check_validators`].
+    fn is_at_assigned_epoch(&self, registry: &ValidatorRegistry, sig: &ValidatorSignature) -> bool {
+        (registry.index_of(&sig.xmss_root))
+            .and_then(|index| self.epochs.epoch_of(index))
+            .is_some_and(|epoch| sig.epoch == epoch && sig.signature.leaf_index() as u64 == epoch)
+    }
+
+    /// The validators with their params, in the order of the roots
+    ///
+    /// A repeated root keeps its first param, and roots without a param are left out, see
+    /// [`PublicInputs::check_validators`].
+    pub fn validator_registry(&self) -> ValidatorRegistry {
+        (self.validator_roots.iter().copied())
+            .zip(self.validator_params.iter().cloned())
+            .collect()
+    }
+
+    /// Same as [`PublicInputs::validator_registry`], with `weights` in the order of the roots
+    pub fn weighted_validator_registry(&self, weights: &[u64]) -> ValidatorRegistry {
+        let validators = (self.validator_roots.iter().copied())
+            .zip(self.validator_params.iter().cloned())
+            .zip(weights.iter().copied())
+            .map(|((root, param), weight)| (root, param, weight))
+            .collect();
+        ValidatorRegistry::with_weights(validators)
+    }
+
+    /// The public inputs of an aggregate of all validators of `shards`, which must sign the
+    /// same message over the same spec
+    ///
+    /// The roots and params are concatenated in the order of the shards. The epochs stay uniform
+    /// if every shard signs at the same one, and are assigned per validator otherwise.
+    ///
+    /// # Errors
+    /// Returns an error if there are no shards, if the shards sign different messages or over
+    /// different specs, if a shard does not have a param and an epoch per root, or if a root
+    /// repeats.
+    pub fn merge(shards: &[PublicInputs]) -> Result<PublicInputs, MergeError> {
+        let first = shards.first().ok_or(MergeError::NoShards)?;
+        let mut validator_roots = Vec::new();
+        let mut validator_params = Vec::new();
+        let mut epochs = Vec::new();
+        for (shard, public_inputs) in shards.iter().enumerate() {
+            if public_inputs.message != first.message {
+                return Err(MergeError::MessageMismatch { shard });
+            }
+            if public_inputs.spec != first.spec {
+                return Err(MergeError::SpecMismatch { shard });
+            }
+            let num_roots = public_inputs.validator_roots.len();
+            let num_epochs = match &public_inputs.epochs {
+                EpochAssignment::Uniform(_) => num_roots,
+                EpochAssignment::PerValidator(epochs) => epochs.len(),
+            };
+            if public_inputs.validator_params.len() != num_roots || num_epochs != num_roots {
+                return Err(MergeError::LengthMismatch { shard });
+            }
+            validator_roots.extend_from_slice(&public_inputs.validator_roots);
+            validator_params.extend_from_slice(&public_inputs.validator_params);
+            epochs.extend((0..num_roots).filter_map(|index| public_inputs.epochs.epoch_of(index)));
+        }
+
+        if let Some(index) = first_repeated_root(&validator_roots) {
+            return Err(MergeError::DuplicateRoot { index });
+        }
+
+        let uniform = matches!(first.epochs, EpochAssignment::Uniform(_))
+            && (shards.iter()).all(|public_inputs| public_inputs.epochs == first.epochs);
+        let epochs = if uniform {
+            first.epochs.clone()
+        } else {
+            EpochAssignment::PerValidator(epochs)
+        };
+        Ok(PublicInputs {
+            message: first.message,
+            epochs,
+            validator_roots,
+            validator_params,
+            spec: first.spec.clone(),
+        })
+    }
+}
+
+/// A keccak-256 hasher that has absorbed `domain` and the id of `spec` over [`Backend`], the
+/// prefix of every digest of public inputs
+fn digest_hasher(domain: &[u8], spec: &Spec) -> Keccak {
+    let mut hasher = Keccak::v256();
+    hasher.update(domain);
+    hasher.update(&spec.id_with_backend::<Backend>().0);
+    hasher
+}
+
+/// Absorbs the number of `params` and the length and bytes of every param, in order
+fn update_params(hasher: &mut Keccak, params: &[Param]) {
+    hasher.update(&(params.len() as u64).to_be_bytes());
+    for param in params {
+        hasher.update(&(param.as_ref().len() as u64).to_be_bytes());
+        hasher.update(param.as_ref());
+    }
+}
+
+fn finalize_digest(hasher: Keccak) -> Hash {
+    let mut digest = [0; HASH_LEN];
+    hasher.finalize(&mut digest);
+    Hash(digest)
+}
+
+/// A keccak-256 commitment to the `weights` of the validators of [`GuestInput::Weighted`], over
+/// the domain `"leansig-weights-v1"`, the number of weights and every weight, each a big-endian
+/// u64
+pub fn weights_digest(weights: &[u64]) -> Hash {
+    let mut hasher = Keccak::v256();
+    hasher.update(b"leansig-weights-v1");
+    hasher.update(&(weights.len() as u64).to_be_bytes());
+    for weight in weights {
+        hasher.update(&weight.to_be_bytes());
+    }
+    finalize_digest(hasher)
+}
+
+/// The index of the first of `roots` that repeats an earlier one, if any
+fn first_repeated_root(roots: &[Hash]) -> Option<usize> {
+    // The roots seen so far are kept sorted, which finds the duplicates without a hash set
+    let mut seen = Vec::with_capacity(roots.len());
+    for (index, root) in roots.iter().enumerate() {
+        match seen.binary_search(root) {
+            Ok(_) => return Some(index),
+            Err(position) => seen.insert(position, *root),
+        }
+    }
+    None
+}
+
+/// The reasons [`PublicInputs::check_validators`] can reject the validators of public inputs
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ValidatorSetError {
+    /// The numbers of validator roots and validator params differ.
+    ParamCountMismatch { roots: usize, params: usize },
+    /// The validators are assigned another number of epochs than there are validators.
+    EpochCountMismatch { epochs: usize, validators: usize },
+    /// Validator root `index` repeats an earlier one.
+    RepeatedRoot { index: usize },
+}
+
+impl fmt::Display for ValidatorSetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidatorSetError::ParamCountMismatch { roots, params } => {
+                write!(f, "{roots} validator roots and {params} validator params")
+            }
+            ValidatorSetError::EpochCountMismatch { epochs, validators } => {
+                write!(f, "{epochs} epochs for {validators} validators")
+            }
+            ValidatorSetError::RepeatedRoot { index } => {
+                write!(f, "validator root {index} repeats an earlier one")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ValidatorSetError {}
+
+/// The reasons [`PublicInputs::merge`] can reject shards
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MergeError {
+    /// There are no shards to merge.
+    NoShards,
+    /// Shard `shard` signs another message than the first one.
+    MessageMismatch { shard: usize },
+    /// Shard `shard` is over another spec than the first one.
+    SpecMismatch { shard: usize },
+    /// Shard `shard` does not have a param and an epoch per root.
+    LengthMismatch { shard: usize },
+    /// Root `index` of the merged roots repeats an earlier one.
+    DuplicateRoot { index: usize },
+}
+
+impl fmt::Display for MergeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MergeError::NoShards => write!(f, "no shards to merge"),
+            MergeError::MessageMismatch { shard } => {
+                write!(f, "shard {shard} signs another message")
+            }
+            MergeError::SpecMismatch { shard } => write!(f, "shard {shard} is over another spec"),
+            MergeError::LengthMismatch { shard } => {
+                write!(
+                    f,
+                    "shard {shard} does not have a param and an epoch per root"
+                )
+            }
+            MergeError::DuplicateRoot { index } => {
+                write!(f, "root {index} repeats an earlier one")
+            }
+        }
+    }
+}
+
+impl std::error::Error for MergeError {}
+
 /// Test data structure containing both public inputs and the aggregated signature
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct XmssTestData {
     pub public_inputs: PublicInputs,
     pub aggregated_signature: AggregatedSignature,
+    /// The seeds the validators were generated from, to replay the data with
+    /// [`TestDataBuilder::seeds`]
+    pub seeds: ValidatorSeeds,
+}
+
+/// How the rngs of the validators of generated test data are seeded
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ValidatorSeeds {
+    /// Validator `i` uses the rng seeded with the keccak-256 digest of
+    /// `"leansig-test-data-validator" || seed || i`, and a shared param is drawn from the rng
+    /// seeded with the digest of `"leansig-test-data-param" || seed`, with `seed` and `i` as
+    /// big-endian u64s
+    Derived(u64),
+    /// Validator `i` uses `StdRng::seed_from_u64(i + 1)`, and a shared param is drawn from the
+    /// rng of validator 0, as test data was generated before it had a seed
+    Legacy,
+}
+
+impl ValidatorSeeds {
+    /// The rng validator `index` is generated from
+    pub fn validator_rng(&self, index: usize) -> StdRng {
+        match *self {
+            ValidatorSeeds::Derived(seed) => StdRng::from_seed(derive_seed(
+                b"leansig-test-data-validator",
+                &[seed, index as u64],
+            )),
+            ValidatorSeeds::Legacy => StdRng::seed_from_u64(index as u64 + 1),
+        }
+    }
+
+    /// The rng a param shared by all validators is drawn from
+    fn param_rng(&self) -> StdRng {
+        match *self {
+            ValidatorSeeds::Derived(seed) => {
+                StdRng::from_seed(derive_seed(b"leansig-test-data-param", &[seed]))
+            }
+            ValidatorSeeds::Legacy => self.validator_rng(0),
+        }
+    }
+}
+
+/// The keccak-256 digest of `domain` followed by every value as a big-endian u64
+fn derive_seed(domain: &[u8], values: &[u64]) -> [u8; 32] {
+    let mut hasher = Keccak::v256();
+    hasher.update(domain);
+    for value in values {
+        hasher.update(&value.to_be_bytes());
+    }
+    let mut seed = [0; 32];
+    hasher.finalize(&mut seed);
+    seed
+}
+
+/// The memory held by an [`XmssTestData`], see [`XmssTestData::memory_footprint`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TestDataFootprint {
+    /// The bytes of the public inputs
+    pub public_inputs_bytes: usize,
+    /// The bytes of the aggregated signature
+    pub signature_bytes: usize,
+}
+
+impl TestDataFootprint {
+    /// The bytes of the whole test data
+    pub fn total(&self) -> usize {
+        self.public_inputs_bytes + self.signature_bytes
+    }
+}
+
+impl MemoryFootprint for EpochAssignment {
+    fn heap_bytes(&self) -> usize {
+        match self {
+            EpochAssignment::Uniform(_) => 0,
+            EpochAssignment::PerValidator(epochs) => epochs.len() * size_of::<u64>(),
+        }
+    }
+}
+
+impl MemoryFootprint for PublicInputs {
+    fn heap_bytes(&self) -> usize {
+        self.epochs.heap_bytes()
+            + self.validator_roots.heap_bytes()
+            + self.validator_params.heap_bytes()
+    }
+}
+
+impl XmssTestData {
+    /// The memory held by the test data, which is small next to that of the validators that
+    /// signed it, see [`Signer::memory_footprint`]
+    pub fn memory_footprint(&self) -> TestDataFootprint {
+        TestDataFootprint {
+            public_inputs_bytes: self.public_inputs.memory_footprint(),
+            signature_bytes: self.aggregated_signature.memory_footprint(),
+        }
+    }
+
+    /// Deduplicates the params of the aggregated signature for a smaller guest input
+    pub fn compress(&self) -> CompressedTestData {
+        CompressedTestData {
+            public_inputs: self.public_inputs.clone(),
+            aggregated_signature: self.aggregated_signature.to_compressed(),
+            seeds: self.seeds,
+        }
+    }
+
+    /// The public inputs and the aggregated signature, without the seeds, for a guest to read
+    /// the public statement apart from the aggregate that witnesses it
+    pub fn into_parts(self) -> (PublicInputs, AggregatedSignature) {
+        (self.public_inputs, self.aggregated_signature)
+    }
+
+    /// Checks that the public inputs and the aggregated signature describe the same validators
+    ///
+    /// The validators must pass [`PublicInputs::check_validators`], there must be as many
+    /// signatures as validator roots, every signature must be by one of the validators, and every
+    /// signature must be at the epoch assigned to its validator.
+    pub fn validate_consistency(&self) -> Result<(), TestDataError> {
+        let public_inputs = &self.public_inputs;
+        (public_inputs.check_validators()).map_err(TestDataError::Validators)?;
+        check_signatures(
+            &public_inputs.validator_registry(),
+            public_inputs.validator_roots.len(),
+            public_inputs.validator_params.len(),
+            &self.aggregated_signature,
+            |index| public_inputs.epochs.epoch_of(index),
+        )
+    }
+
+    /// Checks the test data like [`XmssTestData::validate_consistency`] and verifies the
+    /// aggregated signature, as a sanity check before proving
+    pub fn verify(&self) -> Result<(), TestDataError> {
+        self.validate_consistency()?;
+        let public_inputs = &self.public_inputs;
+        let verifier = AggregatedVerifier::<Backend>::new_with_backend(
+            public_inputs.validator_registry(),
+            public_inputs.spec.clone(),
+        )
+        .with_strict_roots(true);
+        match verifier.first_failure(
+            &public_inputs.message,
+            &self.aggregated_signature.signatures,
+        ) {
+            Some((index, reason)) => Err(TestDataError::InvalidSignature { index, reason }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The reasons test data fails [`XmssTestData::verify`] or [`XmssMultiMessageTestData::verify`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TestDataError {
+    /// The aggregated signature holds no signatures.
+    NoSignatures,
+    /// The numbers of validator roots, validator params and signatures differ.
+    LengthMismatch {
+        roots: usize,
+        params: usize,
+        signatures: usize,
+    },
+    /// The validators of the public inputs are malformed.
+    Validators(ValidatorSetError),
+    /// The root of signature `index` is not among the validator roots.
+    UnknownRoot { index: usize },
+    /// An earlier signature has the same root as signature `index`.
+    DuplicateRoot { index: usize },
+    /// Signature `index` is not at the epoch assigned to its validator.
+    WrongEpoch { index: usize },
+    /// Signature `index` does not verify.
+    InvalidSignature {
+        index: usize,
+        reason: ValidatorVerification,
+    },
+}
+
+impl fmt::Display for TestDataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TestDataError::NoSignatures => write!(f, "aggregated signature is empty"),
+            TestDataError::LengthMismatch {
+                roots,
+                params,
+                signatures,
+            } => write!(
+                f,
+                "{roots} validator roots, {params} validator params and {signatures} signatures"
+            ),
+            TestDataError::Validators(err) => write!(f, "malformed validators: {err}"),
+            TestDataError::UnknownRoot { index } => {
+                write!(f, "signature {index} is not by a validator")
+            }
+            TestDataError::DuplicateRoot { index } => {
+                write!(f, "signature {index} repeats the root of an earlier one")
+            }
+            TestDataError::WrongEpoch { index } => {
+                write!(f, "signature {index} is not at the epoch of its validator")
+            }
+            TestDataError::InvalidSignature { index, reason } => {
+                write!(f, "signature {index} does not verify: {reason:?}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TestDataError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TestDataError::Validators(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Checks the signatures of `aggregated` against the validators of `registry`, of which there
+/// are `num_roots` roots and `num_params` params, validator `i` signing at `epoch_of(i)`
+fn check_signatures(
+    registry: &ValidatorRegistry,
+    num_roots: usize,
+    num_params: usize,
+    aggregated: &AggregatedSignature,
+    epoch_of: impl Fn(usize) -> Option<u64>,
+) -> Result<(), TestDataError> {
+    let signatures = &aggregated.signatures;
+    if signatures.is_empty() {
+        return Err(TestDataError::NoSignatures);
+    }
+    if num_roots != num_params || num_roots != signatures.len() {
+        return Err(TestDataError::LengthMismatch {
+            roots: num_roots,
+            params: num_params,
+            signatures: signatures.len(),
+        });
+    }
+    let mut signed = vec![false; registry.len()];
+    for (index, sig) in signatures.iter().enumerate() {
+        let validator =
+            (registry.index_of(&sig.xmss_root)).ok_or(TestDataError::UnknownRoot { index })?;
+        if std::mem::replace(&mut signed[validator], true) {
+            return Err(TestDataError::DuplicateRoot { index });
+        }
+        if epoch_of(validator)
+            .is_none_or(|epoch| sig.epoch != epoch || sig.signature.leaf_index() as u64 != epoch)
+        {
+            return Err(TestDataError::WrongEpoch { index });
+        }
+    }
+    Ok(())
+}
+
+/// The version of the file layout written by [`XmssTestData::write_to_file`] and
+/// [`PublicInputs::write_to_file`], bumped whenever the layout of the test data changes
+#[cfg(feature = "persistence")]
+pub const TEST_DATA_FORMAT_VERSION: u16 = 5;
+
+/// The header following the envelope of the test data or public inputs in a file.
+#[cfg(feature = "persistence")]
+#[derive(Serialize, Deserialize)]
+struct TestDataHeader {
+    /// The id of the spec over [`Backend`], see [`Spec::id_with_backend`]
+    spec_id: Hash,
+}
+
+#[cfg(feature = "persistence")]
+impl XmssTestData {
+    /// Writes the test data to `path` with bincode, after the envelope of a
+    /// [`PayloadKind::TestData`] in [`TEST_DATA_FORMAT_VERSION`] and a header holding the id of
+    /// the spec
+    pub fn write_to_file(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        write_with_header(path, PayloadKind::TestData, &self.public_inputs.spec, self)
+    }
+
+    /// Reads test data written by [`XmssTestData::write_to_file`]
+    ///
+    /// # Errors
+    /// Returns an error of kind [`std::io::ErrorKind::InvalidData`] if the file holds another
+    /// kind of payload or was written in another format version, see
+    /// [`leansig_core::wire::read_envelope`], or for another spec or hash backend than the one it
+    /// holds.
+    pub fn read_from_file(path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        read_with_header(path, PayloadKind::TestData, |test_data: &Self| {
+            &test_data.public_inputs.spec
+        })
+    }
+
+    /// Whether the test data is what `builder` builds.
+    fn matches(&self, builder: &TestDataBuilder) -> bool {
+        let public_inputs = &self.public_inputs;
+        let heights_match = (self.aggregated_signature.signatures.iter()).all(|sig| {
+            matches!(&sig.signature, leansig_core::AnySignature::Xmss(signature)
+                if signature.hash_tree_proof.height() == builder.tree_height)
+        });
+        let signatures = &self.aggregated_signature.signatures;
+        let params_shared = signatures.windows(2).all(|w| w[0].param == w[1].param);
+        public_inputs.validator_roots.len() == builder.num_validators
+            && public_inputs.spec.id_with_backend::<Backend>()
+                == builder.spec.id_with_backend::<Backend>()
+            && public_inputs.message == builder.message
+            && public_inputs.epochs == builder.epochs
+            && heights_match
+            && self.seeds == builder.seeds
+            && (builder.num_validators < 2 || params_shared == builder.shared_param)
+    }
+}
+
+#[cfg(feature = "persistence")]
+impl PublicInputs {
+    /// Writes the public inputs to `path` with bincode, after the envelope of a
+    /// [`PayloadKind::PublicInputs`] and the same header as [`XmssTestData::write_to_file`]
+    ///
+    /// A verifier reads them with [`PublicInputs::read_from_file`] to check a proof against,
+    /// without the signatures.
+    pub fn write_to_file(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        write_with_header(path, PayloadKind::PublicInputs, &self.spec, self)
+    }
+
+    /// Reads public inputs written by [`PublicInputs::write_to_file`]
+    ///
+    /// # Errors
+    /// Same as [`XmssTestData::read_from_file`].
+    pub fn read_from_file(path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        read_with_header(path, PayloadKind::PublicInputs, |public_inputs: &Self| {
+            &public_inputs.spec
+        })
+    }
+}
+
+/// Writes `value` of `kind` over `spec` to `path` with bincode, after the envelope and a
+/// [`TestDataHeader`]
+#[cfg(feature = "persistence")]
+fn write_with_header(
+    path: impl AsRef<std::path::Path>,
+    kind: PayloadKind,
+    spec: &Spec,
+    value: &impl Serialize,
+) -> std::io::Result<()> {
+    let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    wire::write_envelope(&mut file, kind, TEST_DATA_FORMAT_VERSION)?;
+    let header = TestDataHeader {
+        spec_id: spec.id_with_backend::<Backend>(),
+    };
+    bincode::serialize_into(&mut file, &header).map_err(std::io::Error::other)?;
+    bincode::serialize_into(&mut file, value).map_err(std::io::Error::other)
+}
+
+/// Reads a value of `kind` written by [`write_with_header`], which is over the spec `spec_of`
+/// returns
+#[cfg(feature = "persistence")]
+fn read_with_header<T: serde::de::DeserializeOwned>(
+    path: impl AsRef<std::path::Path>,
+    kind: PayloadKind,
+    spec_of: impl Fn(&T) -> &Spec,
+) -> std::io::Result<T> {
+    let mut file = std::io::BufReader::new(std::fs::File::open(path)?);
+    wire::read_envelope(&mut file, kind, TEST_DATA_FORMAT_VERSION)?;
+    let header: TestDataHeader =
+        bincode::deserialize_from(&mut file).map_err(std::io::Error::other)?;
+    let value: T = bincode::deserialize_from(&mut file).map_err(std::io::Error::other)?;
+    if header.spec_id != spec_of(&value).id_with_backend::<Backend>() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("{kind} for another spec or hash backend"),
+        ));
+    }
+    Ok(value)
+}
+
+/// [`XmssTestData`] with the aggregated signature in its compressed form
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CompressedTestData {
+    pub public_inputs: PublicInputs,
+    pub aggregated_signature: CompressedAggregate,
+    pub seeds: ValidatorSeeds,
+}
+
+impl CompressedTestData {
+    /// Restores the test data from [`XmssTestData::compress`]
+    pub fn decompress(self) -> Result<XmssTestData, DecodeError> {
+        Ok(XmssTestData {
+            public_inputs: self.public_inputs,
+            aggregated_signature: AggregatedSignature::from_compressed(self.aggregated_signature)?,
+            seeds: self.seeds,
+        })
+    }
+}
+
+/// Public inputs when every validator signs its own message, committed to the journal like
+/// [`PublicInputs`]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MultiMessagePublicInputs {
+    /// Each validator's XMSS tree root hash with the message signed under it
+    pub messages: Vec<(Hash, Message)>,
+    /// The epoch at which all validators sign
+    pub epoch: u64,
+    /// Domain parameters for each validator
+    pub validator_params: Vec<Param>,
+    /// Specification for the signature scheme
+    pub spec: Spec,
+}
+
+impl MultiMessagePublicInputs {
+    /// A keccak-256 commitment to the public inputs, like [`PublicInputs::digest`]
+    ///
+    /// The digest is over the domain `"leansig-multi-message-public-inputs-v1"`, the id of the
+    /// spec, the epoch, the number of messages and every root followed by its message, then the
+    /// params as in [`PublicInputs::digest`].
+    pub fn digest(&self) -> Hash {
+        let mut hasher = digest_hasher(b"leansig-multi-message-public-inputs-v1", &self.spec);
+        hasher.update(&self.epoch.to_be_bytes());
+        hasher.update(&(self.messages.len() as u64).to_be_bytes());
+        for (root, message) in &self.messages {
+            hasher.update(&root.0);
+            hasher.update(&message.0);
+        }
+        update_params(&mut hasher, &self.validator_params);
+        finalize_digest(hasher)
+    }
+
+    /// The registered validator roots, in the order of the messages
+    pub fn validator_roots(&self) -> Vec<Hash> {
+        self.messages.iter().map(|(root, _)| *root).collect()
+    }
+
+    /// Same as [`PublicInputs::check_validators`], with a root per message and one epoch
+    pub fn check_validators(&self) -> Result<(), ValidatorSetError> {
+        if self.validator_params.len() != self.messages.len() {
+            return Err(ValidatorSetError::ParamCountMismatch {
+                roots: self.messages.len(),
+                params: self.validator_params.len(),
+            });
+        }
+        match first_repeated_root(&self.validator_roots()) {
+            Some(index) => Err(ValidatorSetError::RepeatedRoot { index }),
+            None => Ok(()),
+        }
+    }
+
+    /// The validators with their params, in the order of the messages
+    pub fn validator_registry(&self) -> ValidatorRegistry {
+        (self.messages.iter().map(|(root, _)| *root))
+            .zip(self.validator_params.iter().cloned())
+            .collect()
+    }
+}
+
+/// Test data for an aggregate in which every validator signs its own message
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct XmssMultiMessageTestData {
+    pub public_inputs: MultiMessagePublicInputs,
+    pub aggregated_signature: AggregatedSignature,
+}
+
+impl XmssMultiMessageTestData {
+    /// Same as [`XmssTestData::validate_consistency`], with a message per validator root and
+    /// every signature at the one epoch
+    pub fn validate_consistency(&self) -> Result<(), TestDataError> {
+        let public_inputs = &self.public_inputs;
+        (public_inputs.check_validators()).map_err(TestDataError::Validators)?;
+        check_signatures(
+            &public_inputs.validator_registry(),
+            public_inputs.messages.len(),
+            public_inputs.validator_params.len(),
+            &self.aggregated_signature,
+            |_| Some(public_inputs.epoch),
+        )
+    }
+
+    /// Same as [`XmssTestData::verify`], with every validator verified against its own message
+    pub fn verify(&self) -> Result<(), TestDataError> {
+        self.validate_consistency()?;
+        let public_inputs = &self.public_inputs;
+        let verifier = AggregatedVerifier::<Backend>::new_with_backend(
+            public_inputs.validator_registry(),
+            public_inputs.spec.clone(),
+        )
+        .with_strict_roots(true);
+        for (index, sig) in self.aggregated_signature.signatures.iter().enumerate() {
+            let (_, message) = (public_inputs.messages.iter())
+                .find(|(root, _)| *root == sig.xmss_root)
+                .expect("checked to be a validator root");
+            if let Some((_, reason)) = verifier.first_failure(message, [sig]) {
+                return Err(TestDataError::InvalidSignature { index, reason });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Public inputs when one validator signs a message at each of several epochs, committed to the
+/// journal like [`PublicInputs`]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MultiEpochPublicInputs {
+    /// The validator's XMSS tree root hash
+    pub root: Hash,
+    /// Domain parameter of the validator
+    pub param: Param,
+    /// Every epoch with the message signed at it, in increasing order of the epochs
+    pub messages: Vec<(u64, Message)>,
+    /// Specification for the signature scheme
+    pub spec: Spec,
+}
+
+impl MultiEpochPublicInputs {
+    /// A keccak-256 commitment to the public inputs, like [`PublicInputs::digest`]
+    ///
+    /// The digest is over the domain `"leansig-multi-epoch-public-inputs-v1"`, the id of the
+    /// spec, the root, the length and bytes of the param, then the number of messages and every
+    /// epoch followed by its message.
+    pub fn digest(&self) -> Hash {
+        let mut hasher = digest_hasher(b"leansig-multi-epoch-public-inputs-v1", &self.spec);
+        hasher.update(&self.root.0);
+        hasher.update(&(self.param.as_ref().len() as u64).to_be_bytes());
+        hasher.update(self.param.as_ref());
+        hasher.update(&(self.messages.len() as u64).to_be_bytes());
+        for (epoch, message) in &self.messages {
+            hasher.update(&epoch.to_be_bytes());
+            hasher.update(&message.0);
+        }
+        finalize_digest(hasher)
+    }
+}
+
+/// Test data for one validator signing at several epochs
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct XmssMultiEpochTestData {
+    pub public_inputs: MultiEpochPublicInputs,
+    pub signature: MultiEpochSignature,
+}
+
+/// The input the guests read, in either shape of test data
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum GuestInput {
+    /// All validators sign the same message; the guest commits an [`AggregateJournal`], or
+    /// [`PublicInputs`] and the result when built with its `verbose_journal` feature
+    SingleMessage(XmssTestData),
+    /// Every validator signs its own message; the guest commits a [`DigestJournal`], or
+    /// [`MultiMessagePublicInputs`] and the result when built with its `verbose_journal` feature
+    MultiMessage(XmssMultiMessageTestData),
+    /// At least `threshold` of the registered validators sign the same message; the guest commits
+    /// a [`ThresholdJournal`], or [`PublicInputs`], the threshold, the number of valid signatures
+    /// and the result when built with its `verbose_journal` feature
+    Threshold {
+        test_data: XmssTestData,
+        threshold: usize,
+    },
+    /// The registered validators that sign the same message weigh at least `min_weight`, with
+    /// `weights` in the order of the validator roots; the guest commits a [`WeightedJournal`], or
+    /// [`PublicInputs`], the weights, the minimum, the achieved weight and the result when built
+    /// with its `verbose_journal` feature
+    Weighted {
+        test_data: XmssTestData,
+        weights: Vec<u64>,
+        min_weight: u64,
+    },
+    /// Same as [`GuestInput::SingleMessage`], but the guest commits an [`AggregateJournal`] even
+    /// when built with its `verbose_journal` feature
+    SingleMessageDigest(XmssTestData),
+    /// Same as [`GuestInput::SingleMessage`], but with [`FailMode::CommitResult`] the guest
+    /// commits an [`AggregateJournal`] with the result of every validator instead of rejecting an
+    /// aggregate that fails to verify
+    SingleMessageWithFailMode {
+        test_data: XmssTestData,
+        fail_mode: FailMode,
+    },
+    /// Same as [`GuestInput::SingleMessageWithFailMode`], with the [`PublicInputs`] and the
+    /// [`AggregatedSignature`] as the next two inputs of the guest, see
+    /// [`XmssTestData::into_parts`]
+    ///
+    /// Only the SP1 guest reads its inputs this way.
+    SingleMessageParts { fail_mode: FailMode },
+    /// Same as [`GuestInput::SingleMessage`], with the params of the aggregated signature
+    /// deduplicated
+    Compressed(CompressedTestData),
+    /// One validator signs at several epochs; the guest commits a [`DigestJournal`], or
+    /// [`MultiEpochPublicInputs`] and the result when built with its `verbose_journal` feature
+    MultiEpoch(XmssMultiEpochTestData),
+    /// Several aggregates, each of validators signing the same message, proven at once; the guest
+    /// commits the [`PublicInputs::digest`] of every aggregate in order
+    ///
+    /// A batch is proven only if every aggregate verifies, so an aggregate that fails aborts the
+    /// proof of the whole batch rather than being committed as failed.
+    Batch(Vec<XmssTestData>),
+}
+
+impl GuestInput {
+    /// The specs of the public inputs of the input, which the guests check with
+    /// [`is_allowed_spec`]
+    ///
+    /// Empty for [`GuestInput::SingleMessageParts`], whose public inputs are the next input.
+    pub fn specs(&self) -> Vec<&Spec> {
+        match self {
+            GuestInput::SingleMessage(test_data)
+            | GuestInput::SingleMessageDigest(test_data)
+            | GuestInput::SingleMessageWithFailMode { test_data, .. }
+            | GuestInput::Threshold { test_data, .. }
+            | GuestInput::Weighted { test_data, .. } => vec![&test_data.public_inputs.spec],
+            GuestInput::MultiMessage(test_data) => vec![&test_data.public_inputs.spec],
+            GuestInput::Compressed(test_data) => vec![&test_data.public_inputs.spec],
+            GuestInput::MultiEpoch(test_data) => vec![&test_data.public_inputs.spec],
+            GuestInput::Batch(batch) => (batch.iter())
+                .map(|test_data| &test_data.public_inputs.spec)
+                .collect(),
+            GuestInput::SingleMessageParts { .. } => Vec::new(),
+        }
+    }
+
+    /// The input of a single-message aggregate that the guest handles as `fail_mode` describes
+    pub fn single_message(test_data: XmssTestData, fail_mode: FailMode) -> Self {
+        match fail_mode {
+            FailMode::Abort => GuestInput::SingleMessage(test_data),
+            fail_mode => GuestInput::SingleMessageWithFailMode {
+                test_data,
+                fail_mode,
+            },
+        }
+    }
+}
+
+/// What a guest does with a single-message aggregate that fails to verify
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FailMode {
+    /// Panic, so that no proof of a failed verification can be generated
+    #[default]
+    Abort,
+    /// Commit the result of every validator and the overall result, so that a failed
+    /// verification is proven like a successful one, e.g. as evidence for slashing
+    CommitResult,
+}
+
+/// The journal the guests commit for a single-message aggregate: the [`PublicInputs::digest`]
+/// of the aggregate and the result of its verification, followed by the result of every
+/// validator with [`FailMode::CommitResult`]
+///
+/// The journal has the fixed layout of [`AggregateJournal::to_bytes`], so that it is the same
+/// 33 bytes in every zkVM and for every number of validators with [`FailMode::Abort`]. A
+/// consumer recomputes the digest from the public inputs it expects instead of reading them back
+/// from the journal.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AggregateJournal {
+    pub digest: Hash,
+    pub verification_result: bool,
+    /// Whether the signature of every validator verifies, in the order of the signatures of the
+    /// aggregate, or `None` with [`FailMode::Abort`]
+    pub validator_results: Option<Vec<bool>>,
+}
+
+impl AggregateJournal {
+    /// The length of [`AggregateJournal::to_bytes`] without the results of the validators
+    pub const LEN: usize = HASH_LEN + 1;
+
+    pub fn new(public_inputs: &PublicInputs, verification_result: bool) -> Self {
+        Self {
+            digest: public_inputs.digest(),
+            verification_result,
+            validator_results: None,
+        }
+    }
+
+    /// The journal of [`FailMode::CommitResult`], which is successful if there are results and
+    /// all of them are
+    pub fn with_validator_results(public_inputs: &PublicInputs, results: Vec<bool>) -> Self {
+        Self {
+            digest: public_inputs.digest(),
+            verification_result: !results.is_empty() && results.iter().all(|&result| result),
+            validator_results: Some(results),
+        }
+    }
+
+    /// The index of the first validator whose signature fails, if the journal has the results of
+    /// the validators
+    pub fn first_failure(&self) -> Option<usize> {
+        self.validator_results
+            .as_ref()?
+            .iter()
+            .position(|&result| !result)
+    }
+
+    /// The 32 bytes of the digest followed by the result as the byte 0 or 1
+    ///
+    /// The results of the validators, if any, follow as their number as a little-endian u32 and a
+    /// bitmap of the results, with the result of validator `i` in bit `i % 8` of byte `i / 8`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(Self::LEN);
+        bytes.extend_from_slice(&self.digest.0);
+        bytes.push(self.verification_result as u8);
+        if let Some(results) = &self.validator_results {
+            bytes.extend_from_slice(&(results.len() as u32).to_le_bytes());
+            let mut bitmap = vec![0; results.len().div_ceil(8)];
+            for (index, _) in results.iter().enumerate().filter(|(_, result)| **result) {
+                bitmap[index / 8] |= 1 << (index % 8);
+            }
+            bytes.extend_from_slice(&bitmap);
+        }
+        bytes
+    }
+
+    /// Decodes [`AggregateJournal::to_bytes`], with or without the results of the validators
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let invalid_length = |expected| DecodeError::InvalidLength {
+            expected,
+            actual: bytes.len(),
+        };
+        if bytes.len() < Self::LEN {
+            return Err(invalid_length(Self::LEN));
+        }
+        let (head, tail) = bytes.split_at(Self::LEN);
+        let verification_result = match head[HASH_LEN] {
+            0 => false,
+            1 => true,
+            byte => return Err(DecodeError::InvalidBool(byte)),
+        };
+        let mut digest = [0; HASH_LEN];
+        digest.copy_from_slice(&head[..HASH_LEN]);
+
+        let validator_results = if tail.is_empty() {
+            None
+        } else {
+            let (num_results, bitmap) = tail
+                .split_first_chunk::<4>()
+                .ok_or_else(|| invalid_length(Self::LEN + 4))?;
+            let num_results = u32::from_le_bytes(*num_results) as usize;
+            if bitmap.len() != num_results.div_ceil(8) {
+                return Err(invalid_length(Self::LEN + 4 + num_results.div_ceil(8)));
+            }
+            let results = (0..num_results)
+                .map(|index| bitmap[index / 8] & (1 << (index % 8)) != 0)
+                .collect();
+            Some(results)
+        };
+        Ok(Self {
+            digest: Hash(digest),
+            verification_result,
+            validator_results,
+        })
+    }
+}
+
+/// The journal of [`GuestInput::MultiMessage`] and [`GuestInput::MultiEpoch`] without the
+/// `verbose_journal` feature of the guests, which commit the digest of the public inputs in place
+/// of the public inputs themselves
+///
+/// The guests commit the fields in order, so the journal decodes as this struct in either zkVM.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DigestJournal {
+    /// The [`MultiMessagePublicInputs::digest`] or [`MultiEpochPublicInputs::digest`]
+    pub digest: Hash,
+    pub verification_result: bool,
+}
+
+impl DigestJournal {
+    pub fn multi_message(
+        public_inputs: &MultiMessagePublicInputs,
+        verification_result: bool,
+    ) -> Self {
+        Self {
+            digest: public_inputs.digest(),
+            verification_result,
+        }
+    }
+
+    pub fn multi_epoch(public_inputs: &MultiEpochPublicInputs, verification_result: bool) -> Self {
+        Self {
+            digest: public_inputs.digest(),
+            verification_result,
+        }
+    }
+}
+
+/// The journal of [`GuestInput::Threshold`] without the `verbose_journal` feature of the guests,
+/// committed in the order of the fields like [`DigestJournal`]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThresholdJournal {
+    /// The [`PublicInputs::digest`]
+    pub digest: Hash,
+    pub threshold: u64,
+    /// The number of distinct validators whose signature verifies
+    pub num_valid: u64,
+    pub verification_result: bool,
+}
+
+impl ThresholdJournal {
+    pub fn new(
+        public_inputs: &PublicInputs,
+        threshold: usize,
+        num_valid: usize,
+        verification_result: bool,
+    ) -> Self {
+        Self {
+            digest: public_inputs.digest(),
+            threshold: threshold as u64,
+            num_valid: num_valid as u64,
+            verification_result,
+        }
+    }
+}
+
+/// The journal of [`GuestInput::Weighted`] without the `verbose_journal` feature of the guests,
+/// committed in the order of the fields like [`DigestJournal`]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WeightedJournal {
+    /// The [`PublicInputs::digest`]
+    pub digest: Hash,
+    /// The [`weights_digest`] of the weights of the validators
+    pub weights_digest: Hash,
+    pub min_weight: u64,
+    /// The total weight of the distinct validators whose signature verifies
+    pub weight: u64,
+    pub verification_result: bool,
+}
+
+impl WeightedJournal {
+    pub fn new(
+        public_inputs: &PublicInputs,
+        weights: &[u64],
+        min_weight: u64,
+        weight: u64,
+        verification_result: bool,
+    ) -> Self {
+        Self {
+            digest: public_inputs.digest(),
+            weights_digest: weights_digest(weights),
+            min_weight,
+            weight,
+            verification_result,
+        }
+    }
+}
+
+/// Builds [`XmssTestData`] for an aggregate of validators signing the same message
+///
+/// Every setting that is not set keeps its default: one validator over [`SPEC_2`] with a tree of
+/// height 13, up to 10000 nonce grinding attempts, the message `[42; 32]` signed at epoch 0,
+/// seed 0 and a param per validator.
+#[derive(Clone, Debug)]
+pub struct TestDataBuilder {
+    num_validators: usize,
+    spec: Spec,
+    tree_height: usize,
+    max_retries: usize,
+    message: Message,
+    epochs: EpochAssignment,
+    seeds: ValidatorSeeds,
+    shared_param: bool,
+    distinct_messages: bool,
+}
+
+impl Default for TestDataBuilder {
+    fn default() -> Self {
+        Self {
+            num_validators: 1,
+            spec: SPEC_2,
+            tree_height: 13,
+            max_retries: 10000,
+            message: Message([42; 32]),
+            epochs: EpochAssignment::Uniform(0),
+            seeds: ValidatorSeeds::Derived(0),
+            shared_param: false,
+            distinct_messages: false,
+        }
+    }
+}
+
+impl TestDataBuilder {
+    /// A builder with every setting at its default
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the number of validators
+    pub fn validators(mut self, num_validators: usize) -> Self {
+        self.num_validators = num_validators;
+        self
+    }
+
+    /// Sets the specification for the signature scheme
+    pub fn spec(mut self, spec: Spec) -> Self {
+        self.spec = spec;
+        self
+    }
+
+    /// Sets the height of the XMSS trees, which have a leaf per epoch
+    pub fn tree_height(mut self, tree_height: usize) -> Self {
+        self.tree_height = tree_height;
+        self
+    }
+
+    /// Sets the maximum number of retries for nonce grinding
+    pub fn max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Sets the message every validator signs
+    pub fn message(mut self, message: Message) -> Self {
+        self.message = message;
+        self
+    }
+
+    /// Sets the epoch every validator signs at
+    pub fn epoch(mut self, epoch: u64) -> Self {
+        self.epochs = EpochAssignment::Uniform(epoch);
+        self
+    }
+
+    /// Sets the epoch of every validator, validator `i` signing at `epochs[i]`
+    ///
+    /// This also sets the number of validators to that of the epochs.
+    pub fn epochs(mut self, epochs: Vec<u64>) -> Self {
+        self.num_validators = epochs.len();
+        self.epochs = EpochAssignment::PerValidator(epochs);
+        self
+    }
+
+    /// Sets the seed the rngs of the validators are derived from, see
+    /// [`ValidatorSeeds::Derived`]
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seeds = ValidatorSeeds::Derived(seed);
+        self
+    }
+
+    /// Sets how the rngs of the validators are seeded, e.g. to [`ValidatorSeeds::Legacy`] to
+    /// reproduce test data generated before it had a seed, or to the seeds of earlier test data
+    /// to replay it
+    pub fn seeds(mut self, seeds: ValidatorSeeds) -> Self {
+        self.seeds = seeds;
+        self
+    }
+
+    /// Sets whether all validators share one param rather than drawing their own
+    pub fn shared_param(mut self, shared_param: bool) -> Self {
+        self.shared_param = shared_param;
+        self
+    }
+
+    /// Sets whether every validator signs its own message, see
+    /// [`TestDataBuilder::build_multi_message`], rather than all signing the same one
+    pub fn distinct_messages(mut self, distinct_messages: bool) -> Self {
+        self.distinct_messages = distinct_messages;
+        self
+    }
+
+    /// Creates the validators and signs the message with every one of them
+    ///
+    /// # Panics
+    /// Panics if distinct messages are set, if the number of validators was changed after
+    /// [`TestDataBuilder::epochs`], or if an epoch is past the lifetime of `2^tree_height`
+    /// epochs.
+    pub fn build(&self) -> XmssTestData {
+        assert!(
+            !self.distinct_messages,
+            "distinct messages are built by build_multi_message"
+        );
+        let (validators, aggregated_signature) = self.sign(|_| self.message);
+
+        let test_data = XmssTestData {
+            public_inputs: PublicInputs {
+                message: self.message,
+                epochs: self.epochs.clone(),
+                validator_roots: validators.iter().map(|v| v.root).collect(),
+                validator_params: validators.iter().map(|v| v.param.clone()).collect(),
+                spec: self.spec.clone(),
+            },
+            aggregated_signature,
+            seeds: self.seeds,
+        };
+        let footprint = test_data.memory_footprint();
+        tracing::info!(
+            public_inputs_bytes = footprint.public_inputs_bytes,
+            signature_bytes = footprint.signature_bytes,
+            "test data memory footprint"
+        );
+        test_data
+    }
+
+    /// Creates the validators and signs a message per validator, whether or not distinct
+    /// messages are set
+    ///
+    /// Validator `i` signs the message with `i` added to every byte, so `[42 + i; 32]` for the
+    /// default message.
+    ///
+    /// # Panics
+    /// Panics if the validators are assigned epochs of their own, which the public inputs cannot
+    /// express, or if the epoch is past the lifetime of `2^tree_height` epochs.
+    pub fn build_multi_message(&self) -> XmssMultiMessageTestData {
+        let EpochAssignment::Uniform(epoch) = self.epochs else {
+            panic!("multi-message test data needs the same epoch for every validator");
+        };
+        let message_for = |i: usize| Message(self.message.0.map(|b| b.wrapping_add(i as u8)));
+        let (validators, aggregated_signature) = self.sign(message_for);
+
+        XmssMultiMessageTestData {
+            public_inputs: MultiMessagePublicInputs {
+                messages: (validators.iter().enumerate())
+                    .map(|(i, v)| (v.root, message_for(i)))
+                    .collect(),
+                epoch,
+                validator_params: validators.iter().map(|v| v.param.clone()).collect(),
+                spec: self.spec.clone(),
+            },
+            aggregated_signature,
+        }
+    }
+
+    /// Builds the test data in the shape the settings select, i.e. with
+    /// [`TestDataBuilder::build_multi_message`] if distinct messages are set and with
+    /// [`TestDataBuilder::build`] otherwise
+    pub fn build_guest_input(&self) -> GuestInput {
+        if self.distinct_messages {
+            GuestInput::MultiMessage(self.build_multi_message())
+        } else {
+            GuestInput::SingleMessage(self.build())
+        }
+    }
+
+    /// Creates the validators and signs `message_for(i)` with validator `i` at its epoch
+    fn sign(
+        &self,
+        message_for: impl Fn(usize) -> Message + Sync,
+    ) -> (Vec<Signer<Backend>>, AggregatedSignature) {
+        if let EpochAssignment::PerValidator(epochs) = &self.epochs {
+            assert_eq!(
+                epochs.len(),
+                self.num_validators,
+                "{} validators but {} epochs",
+                self.num_validators,
+                epochs.len()
+            );
+        }
+        let epoch_for = |i| self.epochs.epoch_of(i).expect("an epoch per validator");
+        let lifetime = 1 << self.tree_height;
+        if let Some(epoch) = (0..self.num_validators)
+            .map(epoch_for)
+            .find(|&epoch| epoch >= lifetime)
+        {
+            panic!("epoch {epoch} is past the lifetime of {lifetime} epochs");
+        }
+
+        let mut validators = create_validators(
+            self.num_validators,
+            &self.spec,
+            self.tree_height,
+            self.max_retries,
+            self.seeds,
+            self.shared_param,
+        );
+        let footprint: SignerFootprint = validators.iter().map(Signer::memory_footprint).sum();
+        tracing::info!(
+            validators = self.num_validators,
+            secret_bytes = footprint.secret_bytes,
+            public_key_bytes = footprint.public_key_bytes,
+            tree_bytes = footprint.tree_bytes,
+            total_bytes = footprint.total(),
+            "validator memory footprint"
+        );
+        let aggregated_signature = sign_with_validators(&mut validators, epoch_for, message_for);
+        (validators, aggregated_signature)
+    }
+
+    /// Same as [`TestDataBuilder::build`], but reads the test data from `cache_path` if it holds
+    /// test data for the same settings
+    ///
+    /// Otherwise, e.g. when the file is missing, unreadable or for other settings, the test data
+    /// is generated and written to `cache_path` for the next run. The maximum number of retries
+    /// only affects how the data is generated, so it is not compared.
+    #[cfg(feature = "persistence")]
+    pub fn build_or_load(&self, cache_path: impl AsRef<std::path::Path>) -> XmssTestData {
+        let cache_path = cache_path.as_ref();
+        match XmssTestData::read_from_file(cache_path) {
+            Ok(test_data) if test_data.matches(self) => return test_data,
+            Ok(_) => tracing::info!(?cache_path, "cached test data is for other settings"),
+            Err(err) => tracing::info!(?cache_path, %err, "no usable cached test data"),
+        }
+
+        let test_data = self.build();
+        if let Err(err) = test_data.write_to_file(cache_path) {
+            tracing::warn!(?cache_path, %err, "failed to cache test data");
+        }
+        test_data
+    }
 }
 
 /// Create test data for XMSS aggregate signatures
 ///
+/// A shorthand for [`TestDataBuilder`] with [`ValidatorSeeds::Legacy`], so that the data is the
+/// same as before test data had a seed.
+///
 /// # Arguments
 /// * `num_validators` - Number of validators to create
 /// * `spec` - Specification for the signature scheme
@@ -45,52 +1504,1099 @@ pub fn create_test_data(
     tree_height: usize,
     max_retries: usize,
     message: Option<Message>,
-    epoch: Option<usize>,
+    epoch: Option<u64>,
 ) -> XmssTestData {
-    let message = message.unwrap_or(Message([42; 32]));
-    let epoch = epoch.unwrap_or(0);
+    test_data_builder(
+        num_validators,
+        spec,
+        tree_height,
+        max_retries,
+        message,
+        epoch,
+    )
+    .build()
+}
 
-    // Calculate lifetime from tree height (2^height)
-    let lifetime = 1 << tree_height;
+/// Same as [`create_test_data`], but validator `i` signs at `epochs[i]`
+///
+/// There is a validator per epoch, and the epochs need not be distinct.
+///
+/// # Panics
+/// Panics if an epoch is past the lifetime of `2^tree_height` epochs.
+pub fn create_test_data_with_epochs(
+    spec: Spec,
+    tree_height: usize,
+    max_retries: usize,
+    message: Option<Message>,
+    epochs: &[u64],
+) -> XmssTestData {
+    test_data_builder(epochs.len(), spec, tree_height, max_retries, message, None)
+        .epochs(epochs.to_vec())
+        .build()
+}
+
+/// Same as [`create_test_data`], but reads the test data from `cache_path` if it holds test data
+/// for the same arguments, see [`TestDataBuilder::build_or_load`]
+#[cfg(feature = "persistence")]
+pub fn create_or_load_test_data(
+    cache_path: impl AsRef<std::path::Path>,
+    num_validators: usize,
+    spec: Spec,
+    tree_height: usize,
+    max_retries: usize,
+    message: Option<Message>,
+    epoch: Option<u64>,
+) -> XmssTestData {
+    test_data_builder(
+        num_validators,
+        spec,
+        tree_height,
+        max_retries,
+        message,
+        epoch,
+    )
+    .build_or_load(cache_path)
+}
+
+/// The builder for the arguments of [`create_test_data`]
+fn test_data_builder(
+    num_validators: usize,
+    spec: Spec,
+    tree_height: usize,
+    max_retries: usize,
+    message: Option<Message>,
+    epoch: Option<u64>,
+) -> TestDataBuilder {
+    let builder = TestDataBuilder::new()
+        .validators(num_validators)
+        .spec(spec)
+        .tree_height(tree_height)
+        .max_retries(max_retries)
+        .epoch(epoch.unwrap_or(0))
+        .seeds(ValidatorSeeds::Legacy);
+    match message {
+        Some(message) => builder.message(message),
+        None => builder,
+    }
+}
 
-    let mut validators: Vec<Signer> = (0..num_validators)
-        .map(|i| {
-            Signer::new(
-                StdRng::seed_from_u64(i as u64 + 1),
+/// Create test data for an aggregate in which validator `i` signs the message `[42 + i; 32]`
+///
+/// The arguments are those of [`create_test_data`], see
+/// [`TestDataBuilder::build_multi_message`].
+pub fn create_multi_message_test_data(
+    num_validators: usize,
+    spec: Spec,
+    tree_height: usize,
+    max_retries: usize,
+    epoch: Option<u64>,
+) -> XmssMultiMessageTestData {
+    test_data_builder(num_validators, spec, tree_height, max_retries, None, epoch)
+        .build_multi_message()
+}
+
+/// Create test data for one validator signing the message `[42 + epoch; 32]` at each of `epochs`
+///
+/// The other arguments are those of [`create_test_data`].
+///
+/// # Panics
+/// Panics if an epoch is past the lifetime of the tree.
+pub fn create_multi_epoch_test_data(
+    spec: Spec,
+    tree_height: usize,
+    max_retries: usize,
+    epochs: &[u64],
+) -> XmssMultiEpochTestData {
+    let mut signer = Signer::<Backend>::with_tree_height_and_backend(
+        StdRng::seed_from_u64(1),
+        max_retries,
+        spec.clone(),
+        tree_height as u32,
+    )
+    .expect("Failed to create signer");
+    let message_for = |epoch: u64| Message([42u8.wrapping_add(epoch as u8); 32]);
+
+    let entries = (epochs.iter())
+        .map(|&epoch| {
+            let message = message_for(epoch);
+            let signature = signer
+                .sign(epoch as usize, &message)
+                .expect("Failed to sign");
+            (epoch, message, signature)
+        })
+        .collect();
+    let signature = MultiEpochSignature {
+        root: signer.root,
+        param: signer.param.clone(),
+        entries,
+    };
+
+    XmssMultiEpochTestData {
+        public_inputs: MultiEpochPublicInputs {
+            root: signature.root,
+            param: signature.param.clone(),
+            messages: signature.messages(),
+            spec,
+        },
+        signature,
+    }
+}
+
+/// Creates `num_validators` validators from `seeds`, with a param per validator unless
+/// `shared_param`
+///
+/// The seeds are per validator, so the validators are the same whether or not the `parallel`
+/// feature builds them concurrently.
+fn create_validators(
+    num_validators: usize,
+    spec: &Spec,
+    tree_height: usize,
+    max_retries: usize,
+    seeds: ValidatorSeeds,
+    shared_param: bool,
+) -> Vec<Signer<Backend>> {
+    let param = shared_param.then(|| Param::random(spec.param_len, &mut seeds.param_rng()));
+
+    let new_validator = |i: usize| {
+        let rng = seeds.validator_rng(i);
+        match &param {
+            // Calculate lifetime from tree height (2^height)
+            Some(param) => Signer::new_with_param_and_backend(
+                rng,
                 max_retries,
                 spec.clone(),
-                lifetime,
-            )
-        })
+                1 << tree_height,
+                param.clone(),
+            ),
+            None => Signer::with_tree_height_and_backend(
+                rng,
+                max_retries,
+                spec.clone(),
+                tree_height as u32,
+            ),
+        }
+        .expect("Failed to create signer")
+    };
+    #[cfg(feature = "parallel")]
+    let validators = (0..num_validators)
+        .into_par_iter()
+        .map(new_validator)
         .collect();
+    #[cfg(not(feature = "parallel"))]
+    let validators = (0..num_validators).map(new_validator).collect();
+    validators
+}
+
+/// Signs `message_for(i)` with validator `i` at `epoch_for(i)`
+fn sign_with_validators(
+    validators: &mut [Signer<Backend>],
+    epoch_for: impl Fn(usize) -> u64 + Sync,
+    message_for: impl Fn(usize) -> Message + Sync,
+) -> AggregatedSignature {
+    // Each validator signs its message
+    let sign = |(i, validator): (usize, &mut Signer<Backend>)| {
+        let epoch = epoch_for(i);
+        let signature = validator
+            .sign(epoch as usize, &message_for(i))
+            .expect("Failed to sign");
+        let signature = ValidatorSignature {
+            epoch,
+            signature: signature.into(),
+            xmss_root: validator.root,
+            param: validator.param.clone(),
+            validator_index: Some(i as u32),
+        };
+        (signature, validator.last_grind_attempts())
+    };
+    #[cfg(feature = "parallel")]
+    let (validator_signatures, grind_attempts): (Vec<ValidatorSignature>, Vec<_>) =
+        validators.par_iter_mut().enumerate().map(sign).unzip();
+    #[cfg(not(feature = "parallel"))]
+    let (validator_signatures, grind_attempts): (Vec<ValidatorSignature>, Vec<_>) =
+        validators.iter_mut().enumerate().map(sign).unzip();
+    let grind_attempts: Vec<usize> = grind_attempts.into_iter().flatten().collect();
 
-    let validator_roots: Vec<_> = validators.iter().map(|v| v.root).collect();
-    let validator_params: Vec<_> = validators.iter().map(|v| v.param.clone()).collect();
+    if let (Some(min), Some(max)) = (grind_attempts.iter().min(), grind_attempts.iter().max()) {
+        let total: usize = grind_attempts.iter().sum();
+        tracing::info!(
+            min,
+            max,
+            mean = total as f64 / grind_attempts.len() as f64,
+            total,
+            "nonce grinding attempts"
+        );
+    }
 
-    // Each validator signs the message
-    let validator_signatures: Vec<ValidatorSignature> = validators
-        .iter_mut()
-        .map(|validator| {
-            let signature = validator.sign(epoch, &message).expect("Failed to sign");
-            ValidatorSignature {
-                epoch,
-                signature,
-                xmss_root: validator.root,
-                param: validator.param.clone(),
+    AggregatedSignature::new(validator_signatures)
+}
+
+#[cfg(test)]
+mod tests {
+    use leansig_core::VerifyError;
+
+    use super::*;
+
+    #[cfg(feature = "cbor")]
+    #[test]
+    fn test_public_inputs_cbor_round_trip() {
+        use leansig_core::cbor::{CanonicalCbor, CborError};
+
+        let public_inputs = TestDataBuilder::new()
+            .validators(3)
+            .tree_height(2)
+            .epochs(vec![0, 3, 1])
+            .build()
+            .public_inputs;
+        let bytes = public_inputs.to_canonical_cbor();
+        assert_eq!(PublicInputs::from_cbor(&bytes).unwrap(), public_inputs);
+        assert_eq!(
+            PublicInputs::from_cbor(&bytes[..bytes.len() - 1]).map(drop),
+            Err(CborError::Malformed("unexpected end of input".to_string()))
+        );
+    }
+
+    /// The layout a 32-bit guest decodes the test data with, spelled with fixed-width integers
+    /// only, so that it cannot depend on the width of `usize`
+    mod guest_layout {
+        use serde::Deserialize;
+
+        #[derive(Deserialize)]
+        pub struct PublicInputs {
+            pub message: [u8; 32],
+            pub epochs: EpochAssignment,
+            pub validator_roots: Vec<[u8; 32]>,
+            pub validator_params: Vec<Param>,
+            pub spec: Spec,
+        }
+
+        #[derive(Debug, PartialEq, Deserialize)]
+        pub enum EpochAssignment {
+            Uniform(u64),
+            PerValidator(Vec<u64>),
+        }
+
+        #[derive(Deserialize)]
+        pub struct Param {
+            pub data: Vec<u8>,
+        }
+
+        #[derive(Debug, PartialEq, Deserialize)]
+        pub struct Spec {
+            pub message_hash_len: u64,
+            pub coordinate_resolution_bits: u64,
+            pub param_len: u64,
+            pub target_sum: u64,
+            pub acceptance: Acceptance,
+        }
+
+        #[derive(Debug, PartialEq, Deserialize)]
+        pub enum Acceptance {
+            Exact,
+        }
+
+        #[derive(Deserialize)]
+        pub struct AggregatedSignature {
+            pub signatures: Vec<ValidatorSignature>,
+        }
+
+        #[derive(Deserialize)]
+        pub struct ValidatorSignature {
+            pub epoch: u64,
+            pub signature: AnySignature,
+            pub xmss_root: [u8; 32],
+            pub param: Param,
+            pub validator_index: Option<u32>,
+        }
+
+        /// Only the XMSS variant, which the test data signs with
+        #[derive(Deserialize)]
+        pub enum AnySignature {
+            Xmss(Signature),
+        }
+
+        #[derive(Deserialize)]
+        pub struct Signature {
+            pub nonce: [u8; 23],
+            pub hashes: Vec<[u8; 32]>,
+            pub leaf_index: u32,
+            pub path: Vec<[u8; 32]>,
+            pub param: Param,
+            pub end_hashes: Vec<[u8; 32]>,
+        }
+    }
+
+    #[test]
+    fn test_guest_layout_has_fixed_widths() {
+        let test_data = TestDataBuilder::new()
+            .validators(2)
+            .tree_height(3)
+            .epochs(vec![5, 2])
+            .build();
+        let public_inputs: guest_layout::PublicInputs =
+            bincode::deserialize(&bincode::serialize(&test_data.public_inputs).unwrap()).unwrap();
+        assert_eq!(
+            public_inputs.epochs,
+            guest_layout::EpochAssignment::PerValidator(vec![5, 2])
+        );
+        assert_eq!(
+            public_inputs.spec,
+            guest_layout::Spec {
+                message_hash_len: SPEC_2.message_hash_len as u64,
+                coordinate_resolution_bits: SPEC_2.coordinate_resolution_bits as u64,
+                param_len: SPEC_2.param_len as u64,
+                target_sum: SPEC_2.target_sum as u64,
+                acceptance: guest_layout::Acceptance::Exact,
             }
-        })
-        .collect();
+        );
+        assert_eq!(public_inputs.message, test_data.public_inputs.message.0);
+        assert_eq!(public_inputs.validator_roots.len(), 2);
+        assert_eq!(
+            public_inputs.validator_params[0].data.len(),
+            SPEC_2.param_len
+        );
 
-    let aggregated_signature = AggregatedSignature::new(validator_signatures);
+        let bytes = bincode::serialize(&test_data.aggregated_signature).unwrap();
+        let aggregated: guest_layout::AggregatedSignature = bincode::deserialize(&bytes).unwrap();
+        let signatures = aggregated
+            .signatures
+            .iter()
+            .zip(&test_data.aggregated_signature.signatures);
+        for (sig, expected) in signatures {
+            let guest_layout::AnySignature::Xmss(signature) = &sig.signature;
+            let leansig_core::AnySignature::Xmss(expected_signature) = &expected.signature else {
+                unreachable!("the test data signs with XMSS");
+            };
+            assert_eq!(sig.epoch, expected.epoch);
+            assert_eq!(signature.leaf_index as u64, expected.epoch);
+            assert_eq!(sig.xmss_root, expected.xmss_root.0);
+            assert_eq!(sig.param.data, expected.param.as_ref());
+            assert_eq!(sig.validator_index, expected.validator_index);
+            assert_eq!(signature.nonce, expected_signature.signature.nonce.0);
+            assert_eq!(
+                signature.hashes.len(),
+                expected_signature.signature.hashes.len()
+            );
+            assert_eq!(signature.path.len(), 3);
+            assert_eq!(signature.param.data, expected.param.as_ref());
+            assert_eq!(signature.end_hashes.len(), signature.hashes.len());
+        }
+    }
 
-    XmssTestData {
-        public_inputs: PublicInputs {
-            message,
-            epoch,
-            validator_roots,
-            validator_params,
-            spec,
-        },
-        aggregated_signature,
+    #[test]
+    fn test_per_validator_epochs() {
+        let test_data = create_test_data_with_epochs(SPEC_2, 3, 10000, None, &[0, 3, 7]);
+        let public_inputs = &test_data.public_inputs;
+        let aggregated = &test_data.aggregated_signature;
+        let epochs: Vec<_> = aggregated.signatures.iter().map(|sig| sig.epoch).collect();
+        assert_eq!(epochs, [0, 3, 7]);
+
+        let verifier = AggregatedVerifier::<Backend>::new_with_backend(
+            public_inputs.validator_registry(),
+            public_inputs.spec.clone(),
+        );
+        assert!(verifier.verify(&public_inputs.message, aggregated));
+        assert!(public_inputs.is_at_assigned_epochs(aggregated));
+
+        // The epochs must be the assigned ones, in the order of the validators.
+        let mut swapped = public_inputs.clone();
+        swapped.epochs = EpochAssignment::PerValidator(vec![0, 7, 3]);
+        assert!(!swapped.is_at_assigned_epochs(aggregated));
+        swapped.epochs = EpochAssignment::Uniform(3);
+        assert!(!swapped.is_at_assigned_epochs(aggregated));
+    }
+
+    #[test]
+    fn test_repeated_validator_root() {
+        let test_data = create_test_data_with_epochs(SPEC_2, 3, 10000, None, &[0, 3, 7]);
+        assert_eq!(test_data.public_inputs.check_validators(), Ok(()));
+
+        // Repeating the first root would check the third validator against the epoch of the
+        // second, as the registry drops the repeated root
+        let mut repeated = test_data.clone();
+        repeated.public_inputs.validator_roots[1] = repeated.public_inputs.validator_roots[0];
+        repeated.public_inputs.epochs = EpochAssignment::PerValidator(vec![0, 7, 9]);
+        let public_inputs = &repeated.public_inputs;
+        assert_eq!(
+            public_inputs.check_validators(),
+            Err(ValidatorSetError::RepeatedRoot { index: 1 })
+        );
+        let aggregated = AggregatedSignature {
+            signatures: vec![
+                test_data.aggregated_signature.signatures[0].clone(),
+                test_data.aggregated_signature.signatures[2].clone(),
+            ],
+        };
+        assert!(!public_inputs.is_at_assigned_epochs(&aggregated));
+        assert_eq!(public_inputs.validator_results(&aggregated), [false; 2]);
+        assert_eq!(
+            repeated.validate_consistency(),
+            Err(TestDataError::Validators(ValidatorSetError::RepeatedRoot {
+                index: 1
+            }))
+        );
+
+        let mut missing_epoch = test_data.public_inputs.clone();
+        missing_epoch.epochs = EpochAssignment::PerValidator(vec![0, 3]);
+        assert_eq!(
+            missing_epoch.check_validators(),
+            Err(ValidatorSetError::EpochCountMismatch {
+                epochs: 2,
+                validators: 3
+            })
+        );
+        assert!(!missing_epoch.is_at_assigned_epochs(&test_data.aggregated_signature));
+    }
+
+    #[test]
+    fn test_digest() {
+        let param = |byte: &str| byte.repeat(SPEC_2.param_len).parse::<Param>().unwrap();
+        let public_inputs = PublicInputs {
+            message: Message([1; 32]),
+            epochs: EpochAssignment::PerValidator(vec![3, 5]),
+            validator_roots: vec![Hash([2; 32]), Hash([4; 32])],
+            validator_params: vec![param("06"), param("07")],
+            spec: SPEC_2,
+        };
+        // Pins the encoding, which the consumers of the digest recompute.
+        #[cfg(not(feature = "poseidon2"))]
+        let expected = "670552458ade5e13aacabbbee438dd3a035d9b44543f43d0ba6a9276c5bf9f8a";
+        #[cfg(feature = "poseidon2")]
+        let expected = "a4718ff36837cf9e3ee802afc3dc0fd011f368b1eebf7413748a8ea2b50ae742";
+        assert_eq!(public_inputs.digest().to_string(), expected);
+
+        // Every field is committed to.
+        let mut changed = public_inputs.clone();
+        changed.epochs = EpochAssignment::Uniform(3);
+        assert_ne!(changed.digest(), public_inputs.digest());
+        let mut changed = public_inputs.clone();
+        changed.validator_roots.swap(0, 1);
+        assert_ne!(changed.digest(), public_inputs.digest());
+        let mut changed = public_inputs.clone();
+        changed.validator_params[1] = param("08");
+        assert_ne!(changed.digest(), public_inputs.digest());
+        let mut changed = public_inputs.clone();
+        changed.message = Message([9; 32]);
+        assert_ne!(changed.digest(), public_inputs.digest());
+        let mut changed = public_inputs.clone();
+        changed.spec = SPEC_1;
+        assert_ne!(changed.digest(), public_inputs.digest());
+    }
+
+    #[test]
+    fn test_multi_digests() {
+        let multi_message = create_multi_message_test_data(2, SPEC_2, 2, 10000, Some(1));
+        let public_inputs = &multi_message.public_inputs;
+        let mut changed = public_inputs.clone();
+        changed.epoch = 2;
+        assert_ne!(changed.digest(), public_inputs.digest());
+        let mut changed = public_inputs.clone();
+        changed.messages.swap(0, 1);
+        assert_ne!(changed.digest(), public_inputs.digest());
+        let mut changed = public_inputs.clone();
+        changed.validator_params.swap(0, 1);
+        assert_ne!(changed.digest(), public_inputs.digest());
+
+        let multi_epoch = create_multi_epoch_test_data(SPEC_2, 2, 10000, &[0, 2]);
+        let public_inputs = &multi_epoch.public_inputs;
+        let mut changed = public_inputs.clone();
+        changed.messages[1].0 = 3;
+        assert_ne!(changed.digest(), public_inputs.digest());
+        let mut changed = public_inputs.clone();
+        changed.root = Hash([0; 32]);
+        assert_ne!(changed.digest(), public_inputs.digest());
+
+        assert_ne!(weights_digest(&[1, 2]), weights_digest(&[2, 1]));
+        assert_ne!(weights_digest(&[1]), weights_digest(&[1, 0]));
+    }
+
+    #[test]
+    fn test_journals_decode_as_committed() {
+        // The guests commit the fields one after the other, which must decode as the journal
+        let public_inputs = create_test_data(2, SPEC_2, 2, 10000, None, Some(1)).public_inputs;
+        let threshold = ThresholdJournal::new(&public_inputs, 2, 2, true);
+        let committed = [
+            bincode::serialize(&public_inputs.digest()).unwrap(),
+            bincode::serialize(&2u64).unwrap(),
+            bincode::serialize(&2u64).unwrap(),
+            bincode::serialize(&true).unwrap(),
+        ]
+        .concat();
+        assert_eq!(
+            bincode::deserialize::<ThresholdJournal>(&committed).unwrap(),
+            threshold
+        );
+        // The journal does not grow with the number of validators
+        assert_eq!(bincode::serialize(&threshold).unwrap().len(), HASH_LEN + 17);
+
+        let weighted = WeightedJournal::new(&public_inputs, &[3, 4], 5, 7, true);
+        assert_eq!(weighted.weights_digest, weights_digest(&[3, 4]));
+        let bytes = bincode::serialize(&weighted).unwrap();
+        assert_eq!(bytes.len(), 2 * HASH_LEN + 17);
+        assert_eq!(
+            bincode::deserialize::<WeightedJournal>(&bytes).unwrap(),
+            weighted
+        );
+
+        let multi_epoch = create_multi_epoch_test_data(SPEC_2, 2, 10000, &[0, 2]);
+        let journal = DigestJournal::multi_epoch(&multi_epoch.public_inputs, true);
+        let committed = [
+            bincode::serialize(&multi_epoch.public_inputs.digest()).unwrap(),
+            bincode::serialize(&true).unwrap(),
+        ]
+        .concat();
+        assert_eq!(
+            bincode::deserialize::<DigestJournal>(&committed).unwrap(),
+            journal
+        );
+    }
+
+    #[test]
+    fn test_test_data_memory_footprint() {
+        let footprint = |num_validators| {
+            let test_data = TestDataBuilder::new()
+                .validators(num_validators)
+                .tree_height(2)
+                .build();
+            test_data.memory_footprint()
+        };
+        let (one, three) = (footprint(1), footprint(3));
+        let per_validator = size_of::<Hash>() + size_of::<Param>() + SPEC_2.param_len;
+        assert_eq!(
+            one.public_inputs_bytes,
+            size_of::<PublicInputs>() + per_validator
+        );
+        assert_eq!(
+            three.public_inputs_bytes - one.public_inputs_bytes,
+            2 * per_validator
+        );
+        // Signatures of the same spec and height take the same memory
+        assert_eq!(
+            three.signature_bytes - one.signature_bytes,
+            2 * (one.signature_bytes - size_of::<AggregatedSignature>())
+        );
+    }
+
+    #[test]
+    fn test_aggregate_journal() {
+        let public_inputs = TestDataBuilder::new().tree_height(2).build().public_inputs;
+        let journal = AggregateJournal::new(&public_inputs, true);
+        let bytes = journal.to_bytes();
+        assert_eq!(bytes.len(), 33);
+        assert_eq!(bytes[..32], public_inputs.digest().0);
+        assert_eq!(bytes[32], 1);
+        assert_eq!(AggregateJournal::from_bytes(&bytes), Ok(journal));
+        let failed = AggregateJournal::new(&public_inputs, false);
+        assert_eq!(AggregateJournal::from_bytes(&failed.to_bytes()), Ok(failed));
+
+        // The results of the validators follow as their number and a bitmap
+        let mut results = vec![true; 10];
+        results[3] = false;
+        let detailed = AggregateJournal::with_validator_results(&public_inputs, results);
+        assert!(!detailed.verification_result);
+        assert_eq!(detailed.first_failure(), Some(3));
+        let detailed_bytes = detailed.to_bytes();
+        assert_eq!(detailed_bytes.len(), 33 + 4 + 2);
+        assert_eq!(detailed_bytes[33..], [10, 0, 0, 0, 0b1111_0111, 0b11]);
+        assert_eq!(AggregateJournal::from_bytes(&detailed_bytes), Ok(detailed));
+        let all_valid = AggregateJournal::with_validator_results(&public_inputs, vec![true; 3]);
+        assert!(all_valid.verification_result);
+        assert_eq!(all_valid.first_failure(), None);
+        assert!(
+            !AggregateJournal::with_validator_results(&public_inputs, Vec::new())
+                .verification_result
+        );
+        assert_eq!(
+            AggregateJournal::from_bytes(&detailed_bytes[..38]),
+            Err(DecodeError::InvalidLength {
+                expected: 39,
+                actual: 38
+            })
+        );
+
+        assert_eq!(
+            AggregateJournal::from_bytes(&bytes[..32]),
+            Err(DecodeError::InvalidLength {
+                expected: 33,
+                actual: 32
+            })
+        );
+        let mut invalid = bytes;
+        invalid[32] = 2;
+        assert_eq!(
+            AggregateJournal::from_bytes(&invalid),
+            Err(DecodeError::InvalidBool(2))
+        );
+    }
+
+    #[test]
+    fn test_allowed_specs() {
+        assert!(ALLOWED_SPECS.iter().all(is_allowed_spec));
+        let test_data = TestDataBuilder::new().tree_height(2).build();
+        assert!(is_allowed_spec(&test_data.public_inputs.spec));
+
+        // A spec with a different target sum is off the list
+        let other = Spec {
+            target_sum: SPEC_2.target_sum + 1,
+            ..SPEC_2
+        };
+        assert_eq!(is_allowed_spec(&other), cfg!(feature = "any_spec"));
+        let input = GuestInput::Batch(vec![test_data.clone(), test_data]);
+        assert_eq!(input.specs(), [&SPEC_2, &SPEC_2]);
+    }
+
+    #[test]
+    fn test_validator_results() {
+        let test_data = TestDataBuilder::new()
+            .validators(3)
+            .tree_height(3)
+            .epochs(vec![0, 2, 5])
+            .build();
+        let public_inputs = &test_data.public_inputs;
+        assert_eq!(
+            public_inputs.validator_results(&test_data.aggregated_signature),
+            [true; 3]
+        );
+
+        // Only the corrupted validator fails, and the others are still verified
+        let corrupted = corrupt(&test_data, CorruptionKind::WrongNonce { validator: 1 });
+        assert_eq!(
+            public_inputs.validator_results(&corrupted.aggregated_signature),
+            [true, false, true]
+        );
+        // So does a validator at another epoch than its assigned one
+        let mut other_epochs = public_inputs.clone();
+        other_epochs.epochs = EpochAssignment::PerValidator(vec![0, 2, 4]);
+        assert_eq!(
+            other_epochs.validator_results(&test_data.aggregated_signature),
+            [true, true, false]
+        );
+    }
+
+    #[test]
+    fn test_merge() {
+        let shard = |seed, epoch| {
+            TestDataBuilder::new()
+                .validators(2)
+                .tree_height(3)
+                .epoch(epoch)
+                .seed(seed)
+                .build()
+                .public_inputs
+        };
+        let (first, second) = (shard(1, 2), shard(2, 2));
+        let merged = PublicInputs::merge(&[first.clone(), second.clone()]).unwrap();
+        assert_eq!(merged.message, first.message);
+        assert_eq!(merged.spec, first.spec);
+        assert_eq!(merged.epochs, EpochAssignment::Uniform(2));
+        assert_eq!(merged.validator_roots[..2], first.validator_roots);
+        assert_eq!(merged.validator_roots[2..], second.validator_roots);
+        assert_eq!(merged.validator_params[2..], second.validator_params);
+
+        // Shards at different epochs merge into an epoch per validator
+        let merged = PublicInputs::merge(&[first.clone(), shard(2, 5)]).unwrap();
+        assert_eq!(
+            merged.epochs,
+            EpochAssignment::PerValidator(vec![2, 2, 5, 5])
+        );
+
+        assert_eq!(PublicInputs::merge(&[]), Err(MergeError::NoShards));
+        let mut other = second.clone();
+        other.message = Message([1; 32]);
+        assert_eq!(
+            PublicInputs::merge(&[first.clone(), other]),
+            Err(MergeError::MessageMismatch { shard: 1 })
+        );
+        let mut other = second.clone();
+        other.spec = SPEC_1;
+        assert_eq!(
+            PublicInputs::merge(&[first.clone(), other]),
+            Err(MergeError::SpecMismatch { shard: 1 })
+        );
+        let mut other = second.clone();
+        other.epochs = EpochAssignment::PerValidator(vec![2]);
+        assert_eq!(
+            PublicInputs::merge(&[first.clone(), other]),
+            Err(MergeError::LengthMismatch { shard: 1 })
+        );
+        assert_eq!(
+            PublicInputs::merge(&[first.clone(), second, first]),
+            Err(MergeError::DuplicateRoot { index: 4 })
+        );
+    }
+
+    #[test]
+    fn test_verify_test_data() {
+        let test_data = create_test_data(3, SPEC_2, 2, 10000, None, Some(1));
+        assert_eq!(test_data.validate_consistency(), Ok(()));
+        assert_eq!(test_data.verify(), Ok(()));
+
+        let mut missing_param = test_data.clone();
+        missing_param.public_inputs.validator_params.pop();
+        assert_eq!(
+            missing_param.verify(),
+            Err(TestDataError::Validators(
+                ValidatorSetError::ParamCountMismatch {
+                    roots: 3,
+                    params: 2
+                }
+            ))
+        );
+
+        let mut missing_signature = test_data.clone();
+        missing_signature.aggregated_signature.signatures.pop();
+        assert_eq!(
+            missing_signature.verify(),
+            Err(TestDataError::LengthMismatch {
+                roots: 3,
+                params: 3,
+                signatures: 2
+            })
+        );
+
+        let mut repeated = test_data.clone();
+        repeated.aggregated_signature.signatures[2] =
+            repeated.aggregated_signature.signatures[0].clone();
+        assert_eq!(
+            repeated.validate_consistency(),
+            Err(TestDataError::DuplicateRoot { index: 2 })
+        );
+
+        let mut other_epoch = test_data.clone();
+        other_epoch.public_inputs.epochs = EpochAssignment::PerValidator(vec![1, 2, 1]);
+        assert_eq!(
+            other_epoch.validate_consistency(),
+            Err(TestDataError::WrongEpoch { index: 1 })
+        );
+
+        let corrupted = corrupt(&test_data, CorruptionKind::TruncateChains { validator: 1 });
+        assert_eq!(corrupted.validate_consistency(), Ok(()));
+        assert_eq!(
+            corrupted.verify(),
+            Err(TestDataError::InvalidSignature {
+                index: 1,
+                reason: ValidatorVerification::InvalidSignature(VerifyError::MalformedSignature)
+            })
+        );
+        let corrupted = corrupt(
+            &test_data,
+            CorruptionKind::UnregisteredRoot { validator: 0 },
+        );
+        assert_eq!(
+            corrupted.verify(),
+            Err(TestDataError::UnknownRoot { index: 0 })
+        );
+
+        // Multi-message test data is checked against the message of every validator.
+        let multi_message = create_multi_message_test_data(2, SPEC_2, 2, 10000, None);
+        assert_eq!(multi_message.verify(), Ok(()));
+        let mut swapped = multi_message.clone();
+        let (first, second) = (
+            swapped.public_inputs.messages[0].1,
+            swapped.public_inputs.messages[1].1,
+        );
+        swapped.public_inputs.messages[0].1 = second;
+        swapped.public_inputs.messages[1].1 = first;
+        assert!(matches!(
+            swapped.verify(),
+            Err(TestDataError::InvalidSignature { index: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_builder_defaults() {
+        let builder = TestDataBuilder::new();
+        assert_eq!(builder.num_validators, 1);
+        assert_eq!(builder.spec, SPEC_2);
+        assert_eq!(builder.tree_height, 13);
+        assert_eq!(builder.max_retries, 10000);
+        assert_eq!(builder.message, Message([42; 32]));
+        assert_eq!(builder.epochs, EpochAssignment::Uniform(0));
+        assert_eq!(builder.seeds, ValidatorSeeds::Derived(0));
+        assert!(!builder.shared_param);
+        assert!(!builder.distinct_messages);
+
+        // The wrapper builds the same data as the builder with legacy seeds.
+        let test_data = TestDataBuilder::new()
+            .tree_height(2)
+            .seeds(ValidatorSeeds::Legacy)
+            .build();
+        let wrapped = create_test_data(1, SPEC_2, 2, 10000, None, None);
+        assert_eq!(
+            test_data.public_inputs.validator_roots,
+            wrapped.public_inputs.validator_roots
+        );
+    }
+
+    #[test]
+    fn test_builder_setters() {
+        let builder = TestDataBuilder::new()
+            .validators(3)
+            .spec(SPEC_1)
+            .tree_height(2)
+            .max_retries(20000)
+            .message(Message([7; 32]))
+            .epoch(3);
+        assert_eq!(builder.max_retries, 20000);
+        let test_data = builder.clone().build();
+        let public_inputs = &test_data.public_inputs;
+        let aggregated = &test_data.aggregated_signature;
+        assert_eq!(public_inputs.validator_roots.len(), 3);
+        assert_eq!(public_inputs.spec, SPEC_1);
+        assert_eq!(public_inputs.message, Message([7; 32]));
+        assert!(aggregated.is_at_epoch(3));
+        assert!(aggregated.signatures.iter().all(|sig| {
+            matches!(&sig.signature, leansig_core::AnySignature::Xmss(signature)
+                if signature.hash_tree_proof.height() == 2)
+        }));
+        let verifier = AggregatedVerifier::<Backend>::new_with_backend(
+            public_inputs.validator_registry(),
+            public_inputs.spec.clone(),
+        );
+        assert!(verifier.verify(&public_inputs.message, aggregated));
+
+        // Per-validator epochs set the number of validators.
+        let per_validator = builder.clone().epochs(vec![1, 2]).build();
+        assert_eq!(per_validator.public_inputs.validator_roots.len(), 2);
+        assert!(
+            per_validator
+                .public_inputs
+                .is_at_assigned_epochs(&per_validator.aggregated_signature)
+        );
+
+        // Another seed yields other validators.
+        let reseeded = builder.clone().seed(5).build();
+        assert_ne!(
+            reseeded.public_inputs.validator_roots,
+            public_inputs.validator_roots
+        );
+
+        // A shared param is used by every validator.
+        let shared = builder.shared_param(true).build();
+        let params = &shared.public_inputs.validator_params;
+        assert!(params.iter().all(|param| *param == params[0]));
+        assert_ne!(
+            public_inputs.validator_params[0],
+            public_inputs.validator_params[1]
+        );
+        let verifier = AggregatedVerifier::<Backend>::new_with_backend(
+            shared.public_inputs.validator_registry(),
+            shared.public_inputs.spec.clone(),
+        );
+        assert!(verifier.verify(&shared.public_inputs.message, &shared.aggregated_signature));
+    }
+
+    #[test]
+    fn test_seeds() {
+        let builder = TestDataBuilder::new().validators(2).tree_height(2);
+        let test_data = builder.clone().seed(7).build();
+        assert_eq!(test_data.seeds, ValidatorSeeds::Derived(7));
+
+        // The recorded seeds replay the same validators, other seeds give other ones.
+        let replayed = builder.clone().seeds(test_data.seeds).build();
+        assert_eq!(
+            replayed.public_inputs.validator_roots,
+            test_data.public_inputs.validator_roots
+        );
+        for other in [ValidatorSeeds::Derived(8), ValidatorSeeds::Legacy] {
+            let other = builder.clone().seeds(other).build();
+            for root in &other.public_inputs.validator_roots {
+                assert!(!test_data.public_inputs.validator_roots.contains(root));
+            }
+        }
+
+        // Legacy seeds are the ones of test data generated before it had a seed.
+        let legacy = builder.seeds(ValidatorSeeds::Legacy).build();
+        let legacy_root =
+            Signer::<Backend>::new_with_backend(StdRng::seed_from_u64(2), 10000, SPEC_2, 1 << 2)
+                .unwrap()
+                .root;
+        assert_eq!(legacy.public_inputs.validator_roots[1], legacy_root);
+    }
+
+    #[test]
+    fn test_distinct_messages() {
+        let builder = TestDataBuilder::new()
+            .validators(3)
+            .tree_height(2)
+            .distinct_messages(true);
+        let GuestInput::MultiMessage(test_data) = builder.build_guest_input() else {
+            panic!("distinct messages build multi-message test data");
+        };
+        let public_inputs = &test_data.public_inputs;
+        let messages: Vec<_> = public_inputs.messages.iter().map(|(_, m)| *m).collect();
+        assert_eq!(
+            messages,
+            [Message([42; 32]), Message([43; 32]), Message([44; 32])]
+        );
+
+        let verifier = AggregatedVerifier::<Backend>::new_with_backend(
+            public_inputs.validator_registry(),
+            public_inputs.spec.clone(),
+        );
+        assert!(verifier.verify_multi(&public_inputs.messages, &test_data.aggregated_signature));
+
+        // Swapping the messages of two validators breaks the aggregate.
+        let mut swapped = public_inputs.messages.clone();
+        let (first, second) = (swapped[0].1, swapped[2].1);
+        swapped[0].1 = second;
+        swapped[2].1 = first;
+        assert!(!verifier.verify_multi(&swapped, &test_data.aggregated_signature));
+    }
+
+    #[test]
+    #[should_panic(expected = "build_multi_message")]
+    fn test_distinct_messages_not_built_as_single_message() {
+        TestDataBuilder::new()
+            .tree_height(2)
+            .distinct_messages(true)
+            .build();
+    }
+
+    #[test]
+    #[should_panic(expected = "3 validators but 2 epochs")]
+    fn test_builder_epochs_mismatch() {
+        TestDataBuilder::new()
+            .tree_height(2)
+            .epochs(vec![0, 1])
+            .validators(3)
+            .build();
+    }
+
+    #[test]
+    #[should_panic(expected = "past the lifetime")]
+    fn test_epoch_past_lifetime() {
+        create_test_data_with_epochs(SPEC_2, 2, 10000, None, &[0, 4]);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_matches_serial() {
+        let test_data = create_test_data(4, SPEC_2, 3, 10000, None, None);
+        let serial_roots: Vec<_> = (0..4)
+            .map(|i| {
+                Signer::<Backend>::new_with_backend(
+                    StdRng::seed_from_u64(i + 1),
+                    10000,
+                    SPEC_2,
+                    1 << 3,
+                )
+                .unwrap()
+                .root
+            })
+            .collect();
+        assert_eq!(test_data.public_inputs.validator_roots, serial_roots);
+    }
+
+    #[cfg(feature = "persistence")]
+    fn cache_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("leansig-{name}-{}", std::process::id()))
+    }
+
+    #[cfg(feature = "persistence")]
+    #[test]
+    fn test_version_mismatch() {
+        let path = cache_path("test-data-version");
+        let test_data = create_test_data(2, SPEC_2, 2, 10000, None, None);
+        test_data.write_to_file(&path).unwrap();
+        let read = XmssTestData::read_from_file(&path).unwrap();
+        assert_eq!(
+            read.public_inputs.validator_roots,
+            test_data.public_inputs.validator_roots
+        );
+
+        // Public inputs are not test data, even with the same layout after the envelope
+        let bytes = std::fs::read(&path).unwrap();
+        let mut envelope = Vec::new();
+        wire::write_envelope(
+            &mut envelope,
+            PayloadKind::PublicInputs,
+            TEST_DATA_FORMAT_VERSION,
+        )
+        .unwrap();
+        std::fs::write(&path, [&envelope, &bytes[wire::ENVELOPE_LEN..]].concat()).unwrap();
+        let err = XmssTestData::read_from_file(&path).map(drop).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "payload kind public inputs instead of test data"
+        );
+
+        let mut envelope = Vec::new();
+        wire::write_envelope(
+            &mut envelope,
+            PayloadKind::TestData,
+            TEST_DATA_FORMAT_VERSION + 1,
+        )
+        .unwrap();
+        std::fs::write(&path, [&envelope, &bytes[wire::ENVELOPE_LEN..]].concat()).unwrap();
+        let err = XmssTestData::read_from_file(&path).map(drop).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "test data format version {} instead of {TEST_DATA_FORMAT_VERSION}",
+                TEST_DATA_FORMAT_VERSION + 1
+            )
+        );
+
+        // The stale file is replaced by freshly generated data.
+        let regenerated = create_or_load_test_data(&path, 2, SPEC_2, 2, 10000, None, None);
+        assert_eq!(
+            regenerated.public_inputs.validator_roots,
+            test_data.public_inputs.validator_roots
+        );
+        assert!(XmssTestData::read_from_file(&path).is_ok());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(feature = "persistence")]
+    #[test]
+    fn test_public_inputs_file() {
+        let path = cache_path("public-inputs");
+        let public_inputs = create_test_data(2, SPEC_2, 2, 10000, None, None).public_inputs;
+        public_inputs.write_to_file(&path).unwrap();
+        assert_eq!(PublicInputs::read_from_file(&path).unwrap(), public_inputs);
+
+        // A truncated file fails to decode
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::write(&path, &bytes[..bytes.len() - 1]).unwrap();
+        assert!(PublicInputs::read_from_file(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(feature = "persistence")]
+    #[test]
+    fn test_parameter_mismatch() {
+        let path = cache_path("test-data-parameters");
+        let mut test_data = create_test_data(2, SPEC_2, 2, 10000, None, None);
+        // A marker that tells cached data apart from generated data.
+        test_data.public_inputs.validator_params.clear();
+        test_data.write_to_file(&path).unwrap();
+
+        let cached = create_or_load_test_data(&path, 2, SPEC_2, 2, 10000, None, None);
+        assert!(cached.public_inputs.validator_params.is_empty());
+
+        for (num_validators, tree_height, epoch) in [(3, 2, 0), (2, 3, 0), (2, 2, 1)] {
+            let generated = create_or_load_test_data(
+                &path,
+                num_validators,
+                SPEC_2,
+                tree_height,
+                10000,
+                None,
+                Some(epoch),
+            );
+            assert_eq!(
+                generated.public_inputs.validator_params.len(),
+                num_validators
+            );
+            assert_eq!(
+                generated.public_inputs.epochs,
+                EpochAssignment::Uniform(epoch)
+            );
+        }
+        let other_message =
+            create_or_load_test_data(&path, 2, SPEC_2, 2, 10000, Some(Message([1; 32])), Some(1));
+        assert_eq!(other_message.public_inputs.message, Message([1; 32]));
+        std::fs::remove_file(&path).unwrap();
     }
 }
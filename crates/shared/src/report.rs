@@ -0,0 +1,44 @@
+// Copyright 2025 Irreducible Inc.
+//! The results the command line tools print, in the same shape for the CLI and both hosts.
+
+use serde_json::Value;
+
+/// Formats the fields of `report` as a JSON object, or as a `name: value` line per field
+pub fn format_report(json: bool, report: &Value) -> String {
+    if json {
+        return format!("{report}\n");
+    }
+    let mut lines = String::new();
+    for (name, value) in report.as_object().into_iter().flatten() {
+        let line = match value {
+            // Strings without the quotes of JSON
+            Value::String(value) => format!("{name}: {value}\n"),
+            value => format!("{name}: {value}\n"),
+        };
+        lines.push_str(&line);
+    }
+    lines
+}
+
+/// Prints [`format_report`] to stdout
+pub fn print_report(json: bool, report: &Value) {
+    print!("{}", format_report(json, report));
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_format_report() {
+        let report = json!({ "root": "0x2a", "valid": true, "epoch": 3 });
+        assert_eq!(format_report(true, &report), format!("{report}\n"));
+        let text = format_report(false, &report);
+        for line in ["root: 0x2a\n", "valid: true\n", "epoch: 3\n"] {
+            assert!(text.contains(line), "{text}");
+        }
+        assert_eq!(text.lines().count(), 3);
+    }
+}
@@ -0,0 +1,177 @@
+// Copyright 2025 Irreducible Inc.
+//! SimpleSerialize encodings of the public inputs, on top of those of `leansig_core::ssz`.
+//!
+//! | Type                | SSZ type                                                            |
+//! |---------------------|---------------------------------------------------------------------|
+//! | [`EpochAssignment`] | `Union[uint64, List[uint64, MAX_VALIDATORS]]`                       |
+//! | [`PublicInputs`]    | `{message, epochs, validator_roots: List[Hash, MAX_VALIDATORS], validator_params: List[Param, MAX_VALIDATORS], spec}` |
+
+use leansig_core::{
+    Message, Param,
+    hash::Hash,
+    spec::Spec,
+    ssz::{
+        Decode, DecodeError, Encode, HashTreeRoot, MAX_VALIDATORS, container_root, decode_list,
+        list_root, merkleize, mix_in_length, mix_in_selector, pack, uint_chunk,
+    },
+};
+use ssz::{BYTES_PER_LENGTH_OFFSET, SszDecoderBuilder, SszEncoder, split_union_bytes};
+
+use crate::{EpochAssignment, PublicInputs};
+
+impl Encode for EpochAssignment {
+    fn is_ssz_fixed_len() -> bool {
+        false
+    }
+
+    fn ssz_append(&self, buf: &mut Vec<u8>) {
+        match self {
+            EpochAssignment::Uniform(epoch) => {
+                buf.push(0);
+                epoch.ssz_append(buf);
+            }
+            EpochAssignment::PerValidator(epochs) => {
+                buf.push(1);
+                epochs.ssz_append(buf);
+            }
+        }
+    }
+
+    fn ssz_bytes_len(&self) -> usize {
+        1 + match self {
+            EpochAssignment::Uniform(_) => 8,
+            EpochAssignment::PerValidator(epochs) => 8 * epochs.len(),
+        }
+    }
+}
+
+impl Decode for EpochAssignment {
+    fn is_ssz_fixed_len() -> bool {
+        false
+    }
+
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let (selector, body) = split_union_bytes(bytes)?;
+        match selector.into() {
+            0 => Ok(EpochAssignment::Uniform(u64::from_ssz_bytes(body)?)),
+            1 => Ok(EpochAssignment::PerValidator(decode_list(
+                body,
+                MAX_VALIDATORS,
+            )?)),
+            other => Err(DecodeError::UnionSelectorInvalid(other)),
+        }
+    }
+}
+
+impl HashTreeRoot for EpochAssignment {
+    fn hash_tree_root(&self) -> Hash {
+        Hash(match self {
+            EpochAssignment::Uniform(epoch) => mix_in_selector(uint_chunk(*epoch), 0),
+            EpochAssignment::PerValidator(epochs) => {
+                let bytes = epochs.as_ssz_bytes();
+                let root = merkleize(pack(&bytes), MAX_VALIDATORS * 8 / 32);
+                mix_in_selector(mix_in_length(root, epochs.len()), 1)
+            }
+        })
+    }
+}
+
+impl Encode for PublicInputs {
+    fn is_ssz_fixed_len() -> bool {
+        false
+    }
+
+    fn ssz_append(&self, buf: &mut Vec<u8>) {
+        let num_fixed_bytes = <Message as Encode>::ssz_fixed_len()
+            + 3 * BYTES_PER_LENGTH_OFFSET
+            + <Spec as Encode>::ssz_fixed_len();
+        let mut encoder = SszEncoder::container(buf, num_fixed_bytes);
+        encoder.append(&self.message);
+        encoder.append(&self.epochs);
+        encoder.append(&self.validator_roots);
+        encoder.append(&self.validator_params);
+        encoder.append(&self.spec);
+        encoder.finalize();
+    }
+
+    fn ssz_bytes_len(&self) -> usize {
+        self.message.ssz_bytes_len()
+            + 3 * BYTES_PER_LENGTH_OFFSET
+            + self.epochs.ssz_bytes_len()
+            + self.validator_roots.ssz_bytes_len()
+            + self.validator_params.ssz_bytes_len()
+            + self.spec.ssz_bytes_len()
+    }
+}
+
+impl Decode for PublicInputs {
+    fn is_ssz_fixed_len() -> bool {
+        false
+    }
+
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut builder = SszDecoderBuilder::new(bytes);
+        builder.register_type::<Message>()?;
+        builder.register_type::<EpochAssignment>()?;
+        builder.register_anonymous_variable_length_item()?;
+        builder.register_anonymous_variable_length_item()?;
+        builder.register_type::<Spec>()?;
+        let mut decoder = builder.build()?;
+        Ok(PublicInputs {
+            message: decoder.decode_next()?,
+            epochs: decoder.decode_next()?,
+            validator_roots: decoder
+                .decode_next_with(|bytes| decode_list(bytes, MAX_VALIDATORS))?,
+            validator_params: decoder
+                .decode_next_with(|bytes| decode_list::<Param>(bytes, MAX_VALIDATORS))?,
+            spec: decoder.decode_next()?,
+        })
+    }
+}
+
+impl HashTreeRoot for PublicInputs {
+    fn hash_tree_root(&self) -> Hash {
+        Hash(container_root(&[
+            self.message.hash_tree_root().0,
+            self.epochs.hash_tree_root().0,
+            list_root(&self.validator_roots, MAX_VALIDATORS),
+            list_root(&self.validator_params, MAX_VALIDATORS),
+            self.spec.hash_tree_root().0,
+        ]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TestDataBuilder;
+
+    #[test]
+    fn test_public_inputs_round_trip() {
+        for epochs in [None, Some(vec![0, 3, 1])] {
+            let mut builder = TestDataBuilder::new().validators(3).tree_height(2);
+            if let Some(epochs) = epochs {
+                builder = builder.epochs(epochs);
+            }
+            let public_inputs = builder.build().public_inputs;
+            let bytes = public_inputs.as_ssz_bytes();
+            assert_eq!(bytes.len(), public_inputs.ssz_bytes_len());
+            let decoded = PublicInputs::from_ssz_bytes(&bytes).unwrap();
+            assert_eq!(decoded, public_inputs);
+            assert_eq!(decoded.hash_tree_root(), public_inputs.hash_tree_root());
+        }
+    }
+
+    #[test]
+    fn test_epoch_assignment_vectors() {
+        let uniform = EpochAssignment::Uniform(7);
+        assert_eq!(uniform.as_ssz_bytes(), [0, 7, 0, 0, 0, 0, 0, 0, 0]);
+        let per_validator = EpochAssignment::PerValidator(vec![1, 2]);
+        assert_eq!(
+            per_validator.as_ssz_bytes(),
+            [1, 1, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0]
+        );
+        assert!(EpochAssignment::from_ssz_bytes(&[2]).is_err());
+        assert!(EpochAssignment::from_ssz_bytes(&[1, 1, 0]).is_err());
+    }
+}
@@ -2,33 +2,334 @@
 #![no_main]
 sp1_zkvm::entrypoint!(main);
 
-use leansig_core::AggregatedVerifier;
-use leansig_shared::XmssTestData;
+use leansig_core::{spec::Spec, AggregatedSignature, AggregatedVerifier};
+use leansig_shared::{
+    is_allowed_spec, AggregateJournal, Backend, DigestJournal, FailMode, GuestInput, PublicInputs,
+    ThresholdJournal, WeightedJournal,
+};
 
 pub fn main() {
-    // Read the test data containing both public inputs and aggregated signature
-    let test_data = sp1_zkvm::io::read::<XmssTestData>();
+    // Read the shape of the input, which for a single-message aggregate is followed by the
+    // public inputs and the aggregated signature as separate inputs
+    let input = sp1_zkvm::io::read::<GuestInput>();
+    // The prover chooses the spec, so only the allowed ones are proven under
+    input.specs().into_iter().for_each(assert_allowed_spec);
+    // A compressed aggregate is verified like any other once its params are restored, and an
+    // aggregate committed by digest or with a fail mode only differs in what is committed
+    let verbose_journal = cfg!(feature = "verbose_journal");
+    let (input, verbose_journal, fail_mode) = match input {
+        GuestInput::Compressed(test_data) => (
+            GuestInput::SingleMessage(
+                test_data
+                    .decompress()
+                    .expect("malformed compressed aggregate"),
+            ),
+            verbose_journal,
+            FailMode::Abort,
+        ),
+        GuestInput::SingleMessageDigest(test_data) => {
+            (GuestInput::SingleMessage(test_data), false, FailMode::Abort)
+        }
+        GuestInput::SingleMessageWithFailMode {
+            test_data,
+            fail_mode,
+        } => (
+            GuestInput::SingleMessage(test_data),
+            verbose_journal,
+            fail_mode,
+        ),
+        input => (input, verbose_journal, FailMode::Abort),
+    };
 
-    // Extract the components
-    let public_inputs = test_data.public_inputs;
-    let aggregated_signature = test_data.aggregated_signature;
+    let verification_result = match input {
+        GuestInput::SingleMessageParts { fail_mode } => {
+            // The public statement is read apart from the aggregate that witnesses it
+            let public_inputs = sp1_zkvm::io::read::<PublicInputs>();
+            assert_allowed_spec(&public_inputs.spec);
+            let aggregated_signature = sp1_zkvm::io::read::<AggregatedSignature>();
+            return commit_aggregate(
+                &public_inputs,
+                &aggregated_signature,
+                verbose_journal,
+                fail_mode,
+            );
+        }
+        GuestInput::SingleMessage(test_data) => {
+            let (public_inputs, aggregated_signature) = test_data.into_parts();
+            return commit_aggregate(
+                &public_inputs,
+                &aggregated_signature,
+                verbose_journal,
+                fail_mode,
+            );
+        }
+        GuestInput::Batch(batch) => {
+            assert!(!batch.is_empty(), "empty batch");
 
-    // Create the aggregated verifier with the validator roots
-    let verifier = AggregatedVerifier::new(
-        public_inputs.validator_roots.clone(),
-        public_inputs.spec.clone(),
-    );
+            // Any aggregate of the batch that fails to verify aborts the proof of the whole batch
+            let digests: Vec<_> = batch
+                .iter()
+                .enumerate()
+                .map(|(index, test_data)| {
+                    assert!(
+                        verify_aggregate(&test_data.public_inputs, &test_data.aggregated_signature),
+                        "XMSS signature verification failed for batch entry {index}"
+                    );
+                    test_data.public_inputs.digest()
+                })
+                .collect();
 
-    // Verify the aggregated signature
-    let verification_result = verifier.verify(&public_inputs.message, &aggregated_signature);
+            // The journal commits the digests of the public inputs, in the order of the batch
+            sp1_zkvm::io::commit(&digests);
+            true
+        }
+        GuestInput::MultiMessage(test_data) => {
+            let public_inputs = test_data.public_inputs;
+            let aggregated_signature = test_data.aggregated_signature;
 
-    // The verification must succeed, otherwise the proof generation will fail
-    assert!(verification_result, "XMSS signature verification failed");
+            public_inputs
+                .spec
+                .validate()
+                .expect("invalid signature scheme spec");
+            public_inputs
+                .check_validators()
+                .expect("malformed validator set");
+            // The journal commits the epoch, so every validator must sign at it
+            assert!(
+                aggregated_signature.is_at_epoch(public_inputs.epoch),
+                "XMSS signature at the wrong epoch"
+            );
+
+            // Every root is registered together with the message signed under it
+            let verifier = AggregatedVerifier::<Backend>::new_with_backend(
+                public_inputs.validator_registry(),
+                public_inputs.spec.clone(),
+            )
+            .with_strict_roots(true);
+            let verification_result =
+                verifier.verify_multi(&public_inputs.messages, &aggregated_signature);
+            assert!(verification_result, "XMSS signature verification failed");
+
+            // The journal commits the (root, message) pairs, by digest unless it is verbose
+            if !verbose_journal {
+                let journal = DigestJournal::multi_message(&public_inputs, verification_result);
+                sp1_zkvm::io::commit(&journal);
+                return;
+            }
+            sp1_zkvm::io::commit(&public_inputs);
+            verification_result
+        }
+        GuestInput::Threshold {
+            test_data,
+            threshold,
+        } => {
+            let public_inputs = test_data.public_inputs;
+            let aggregated_signature = test_data.aggregated_signature;
+
+            public_inputs
+                .spec
+                .validate()
+                .expect("invalid signature scheme spec");
+            public_inputs
+                .check_validators()
+                .expect("malformed validator set");
+            // The journal commits the epochs, so every validator must sign at its own
+            assert!(
+                public_inputs.is_at_assigned_epochs(&aggregated_signature),
+                "XMSS signature at the wrong epoch"
+            );
+
+            let verifier = AggregatedVerifier::<Backend>::new_with_backend(
+                public_inputs.validator_registry(),
+                public_inputs.spec.clone(),
+            )
+            .with_strict_roots(true);
+
+            // Proof generation fails unless a quorum of distinct validators signed
+            let num_valid = verifier
+                .verify_threshold(&public_inputs.message, &aggregated_signature, threshold)
+                .expect("XMSS threshold verification failed");
 
-    // Commit the public inputs to the journal for the host to verify
-    // This ensures the proof is bound to specific inputs
-    sp1_zkvm::io::commit(&public_inputs);
+            // The journal commits the achieved count so the consumer can check the quorum, with
+            // the public inputs by digest unless it is verbose
+            if !verbose_journal {
+                let journal = ThresholdJournal::new(&public_inputs, threshold, num_valid, true);
+                sp1_zkvm::io::commit(&journal);
+                return;
+            }
+            sp1_zkvm::io::commit(&public_inputs);
+            sp1_zkvm::io::commit(&threshold);
+            sp1_zkvm::io::commit(&num_valid);
+            true
+        }
+        GuestInput::Weighted {
+            test_data,
+            weights,
+            min_weight,
+        } => {
+            let public_inputs = test_data.public_inputs;
+            let aggregated_signature = test_data.aggregated_signature;
+
+            public_inputs
+                .spec
+                .validate()
+                .expect("invalid signature scheme spec");
+            public_inputs
+                .check_validators()
+                .expect("malformed validator set");
+            // The journal commits the epochs, so every validator must sign at its own
+            assert!(
+                public_inputs.is_at_assigned_epochs(&aggregated_signature),
+                "XMSS signature at the wrong epoch"
+            );
+            assert_eq!(
+                weights.len(),
+                public_inputs.validator_roots.len(),
+                "one weight per validator root"
+            );
+
+            let verifier = AggregatedVerifier::<Backend>::new_with_backend(
+                public_inputs.weighted_validator_registry(&weights),
+                public_inputs.spec.clone(),
+            )
+            .with_strict_roots(true);
+
+            // Proof generation fails unless the signers carry enough weight
+            let weight = verifier
+                .verify_weighted(&public_inputs.message, &aggregated_signature, min_weight)
+                .expect("XMSS weighted verification failed");
+
+            // The journal commits the achieved weight so the consumer can check it, with the
+            // public inputs and the weights by digest unless it is verbose
+            if !verbose_journal {
+                let journal =
+                    WeightedJournal::new(&public_inputs, &weights, min_weight, weight, true);
+                sp1_zkvm::io::commit(&journal);
+                return;
+            }
+            sp1_zkvm::io::commit(&public_inputs);
+            sp1_zkvm::io::commit(&weights);
+            sp1_zkvm::io::commit(&min_weight);
+            sp1_zkvm::io::commit(&weight);
+            true
+        }
+        GuestInput::MultiEpoch(test_data) => {
+            let public_inputs = test_data.public_inputs;
+            let signature = test_data.signature;
+
+            public_inputs
+                .spec
+                .validate()
+                .expect("invalid signature scheme spec");
+            // The journal commits the root and the (epoch, message) pairs, which must be the
+            // ones that are verified
+            assert!(
+                signature.root == public_inputs.root
+                    && signature.param == public_inputs.param
+                    && signature.messages() == public_inputs.messages,
+                "public inputs do not match the signature"
+            );
+
+            let verification_result = signature.verify_with_backend::<Backend>(&public_inputs.spec);
+            assert!(verification_result, "XMSS multi-epoch verification failed");
+
+            if !verbose_journal {
+                let journal = DigestJournal::multi_epoch(&public_inputs, verification_result);
+                sp1_zkvm::io::commit(&journal);
+                return;
+            }
+            sp1_zkvm::io::commit(&public_inputs);
+            verification_result
+        }
+        GuestInput::Compressed(_)
+        | GuestInput::SingleMessageDigest(_)
+        | GuestInput::SingleMessageWithFailMode { .. } => {
+            unreachable!("mapped to a single-message input above")
+        }
+    };
 
     // Optionally commit a success flag
     sp1_zkvm::io::commit(&verification_result);
 }
+
+/// Verifies `aggregated_signature` for `public_inputs`, and commits the journal of a
+/// single-message aggregate as `fail_mode` describes
+///
+/// The public values are the 33 bytes of an [`AggregateJournal`], followed by the result of
+/// every validator with [`FailMode::CommitResult`]. The verbose journal instead commits the
+/// public inputs and the result with bincode, for debugging.
+fn commit_aggregate(
+    public_inputs: &PublicInputs,
+    aggregated_signature: &AggregatedSignature,
+    verbose_journal: bool,
+    fail_mode: FailMode,
+) {
+    if fail_mode == FailMode::CommitResult {
+        public_inputs
+            .spec
+            .validate()
+            .expect("invalid signature scheme spec");
+        public_inputs
+            .check_validators()
+            .expect("malformed validator set");
+
+        // Commit the result of every validator instead of rejecting a failed aggregate, so that
+        // the failure is proven as well
+        let results = public_inputs.validator_results(aggregated_signature);
+        let journal = AggregateJournal::with_validator_results(public_inputs, results);
+        sp1_zkvm::io::commit_slice(&journal.to_bytes());
+        return;
+    }
+
+    let verification_result = verify_aggregate(public_inputs, aggregated_signature);
+
+    // The verification must succeed, otherwise the proof generation will fail
+    assert!(verification_result, "XMSS signature verification failed");
+
+    // Commit the digest of the public inputs with the result, which binds the proof to the
+    // inputs in the same 33 bytes for any number of validators
+    if !verbose_journal {
+        let journal = AggregateJournal::new(public_inputs, verification_result);
+        sp1_zkvm::io::commit_slice(&journal.to_bytes());
+        return;
+    }
+    sp1_zkvm::io::commit(public_inputs);
+    sp1_zkvm::io::commit(&verification_result);
+}
+
+/// Verifies `aggregated_signature` for the message of `public_inputs`, with every validator at
+/// its committed epoch
+fn verify_aggregate(
+    public_inputs: &PublicInputs,
+    aggregated_signature: &AggregatedSignature,
+) -> bool {
+    // Reject malformed specs up front rather than failing somewhere inside verification
+    public_inputs
+        .spec
+        .validate()
+        .expect("invalid signature scheme spec");
+    // Validators are looked up by root, so a repeated root would check the later ones
+    // against the epochs of others
+    public_inputs
+        .check_validators()
+        .expect("malformed validator set");
+
+    // Create the aggregated verifier with the validator roots and their params
+    let verifier = AggregatedVerifier::<Backend>::new_with_backend(
+        public_inputs.validator_registry(),
+        public_inputs.spec.clone(),
+    )
+    .with_strict_roots(true);
+
+    verifier.verify(&public_inputs.message, aggregated_signature)
+        && public_inputs.is_at_assigned_epochs(aggregated_signature)
+}
+
+/// Aborts unless the guest accepts public inputs over `spec`, see [`is_allowed_spec`]
+fn assert_allowed_spec(spec: &Spec) {
+    assert!(
+        is_allowed_spec(spec),
+        "spec {} is not in the allowlist",
+        spec.id_with_backend::<Backend>()
+    );
+}
@@ -1,18 +1,28 @@
 // Copyright 2025 Irreducible Inc.
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use leansig_core::spec::{Spec, SPEC_1, SPEC_2};
-use leansig_shared::{create_test_data, XmssTestData};
-use sp1_sdk::{ProverClient, SP1Stdin};
+use std::time::Instant;
 
-const ELF: &[u8] = include_bytes!(
-    "../../../../target/elf-compilation/riscv32im-succinct-zkvm-elf/release/sp1-guest"
-);
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use leansig_core::{
+    hash::TweakableHash,
+    spec::{Spec, SPEC_1, SPEC_2},
+};
+use leansig_shared::{
+    bench_results_dir, write_bench_results, Backend, BenchRecord, SweepGrid, TestDataBuilder,
+    ValidatorSeeds, XmssTestData,
+};
+use sp1_host::{
+    execute_xmss_aggregate, phase_table, prove_xmss_aggregate, verify_proof, ProofMode,
+};
 
 /// Configuration parameters for benchmarking
+#[derive(Clone)]
 struct BenchmarkConfig {
     num_validators: usize,
     tree_height: usize,
     spec: Spec,
+    proof_mode: ProofMode,
+    /// Run every point of a [`SweepGrid`] once instead of benchmarking a single point
+    sweep: bool,
 }
 
 impl Default for BenchmarkConfig {
@@ -21,6 +31,8 @@ impl Default for BenchmarkConfig {
             num_validators: 16,
             tree_height: 13,
             spec: SPEC_2,
+            proof_mode: ProofMode::Core,
+            sweep: false,
         }
     }
 }
@@ -49,42 +61,112 @@ impl BenchmarkConfig {
             };
         }
 
+        if let Ok(val) = std::env::var("BENCH_PROOF_MODE") {
+            if let Some(mode) = ProofMode::from_name(&val) {
+                config.proof_mode = mode;
+            }
+        }
+
+        if let Ok(val) = std::env::var("BENCH_SWEEP") {
+            config.sweep = val == "1" || val == "true";
+        }
+
         config
     }
 }
 
+/// Where the test data is cached between runs, overridden by `BENCH_TEST_DATA_CACHE`
+///
+/// The file is regenerated whenever the configuration changes, so one path serves all of them.
+fn test_data_cache_path() -> std::path::PathBuf {
+    std::env::var_os("BENCH_TEST_DATA_CACHE")
+        .map(Into::into)
+        .unwrap_or_else(|| std::env::temp_dir().join("leansig-xmss-bench-test-data.bin"))
+}
+
+/// Where the test data of a point of the sweep is cached between runs, next to
+/// [`test_data_cache_path`]
+fn sweep_cache_path(num_validators: usize, tree_height: usize) -> std::path::PathBuf {
+    let path = test_data_cache_path();
+    path.with_file_name(format!(
+        "{}-{num_validators}-{tree_height}.bin",
+        path.file_stem().unwrap_or_default().to_string_lossy()
+    ))
+}
+
 /// Job structure for benchmarking XMSS signatures with SP1
 struct Job {
     test_data: XmssTestData,
 }
 
 impl Job {
-    fn new(config: BenchmarkConfig) -> Self {
-        // Create test data with specified parameters
-        let test_data = create_test_data(
-            config.num_validators,
-            config.spec.clone(),
-            config.tree_height,
-            10000, // max_retries for nonce grinding
-            None,  // use default message [42; 32]
-            None,  // use default epoch 0
-        );
+    fn new(config: BenchmarkConfig, cache_path: std::path::PathBuf) -> Self {
+        // Load the test data from the cache, or create it once for the specified parameters
+        // The message [42; 32], epoch 0 and 10000 nonce grinding retries are the defaults, and
+        // the legacy seeds keep the validators of earlier recorded runs
+        let test_data = TestDataBuilder::new()
+            .validators(config.num_validators)
+            .spec(config.spec.clone())
+            .tree_height(config.tree_height)
+            .seeds(ValidatorSeeds::Legacy)
+            .build_or_load(cache_path);
+        // Cached data in particular must still verify before it is proven
+        test_data
+            .verify()
+            .expect("failed to verify aggregated signature");
 
         Self { test_data }
     }
+}
+
+/// Proves every point of the sweep grid once, and writes the results to `sp1.csv` and
+/// `sp1.json` in `target/bench-results`
+fn run_sweep(config: &BenchmarkConfig) {
+    let grid = SweepGrid::from_env();
+    println!("{}", BenchRecord::CSV_HEADER);
 
-    /// Execute witness generation phase (SP1 setup + stdin preparation)
-    fn exec_compute(&self) -> SP1Stdin {
-        let mut stdin = SP1Stdin::new();
-        stdin.write(&self.test_data);
+    let mut records = Vec::new();
+    for (num_validators, tree_height) in grid.points() {
+        let point = BenchmarkConfig {
+            num_validators,
+            tree_height,
+            ..config.clone()
+        };
+        let job = Job::new(point, sweep_cache_path(num_validators, tree_height));
+        let result = prove_xmss_aggregate(&job.test_data, config.proof_mode).unwrap();
 
-        stdin
+        let start = Instant::now();
+        verify_proof(&result.proof, &result.vk, &job.test_data.public_inputs).unwrap();
+        let verification_time = start.elapsed();
+
+        let record = BenchRecord::new(
+            "sp1",
+            config.proof_mode.name(),
+            &config.spec,
+            num_validators,
+            tree_height,
+            result.cycles,
+            result.proof_generation_time,
+            verification_time,
+            result.seal_size,
+            result.proof.public_values.as_slice().len(),
+        );
+        println!("{}", record.to_csv_row());
+        records.push(record);
     }
+
+    let dir = bench_results_dir();
+    write_bench_results(&dir, "sp1", &records).expect("failed to write the results");
+    println!("\nResults written to {}", dir.join("sp1.csv").display());
 }
 
 /// Main benchmarking function
 fn xmss_benchmarks(c: &mut Criterion) {
     let config = BenchmarkConfig::from_env();
+    if config.sweep {
+        run_sweep(&config);
+        return;
+    }
 
     println!("\n════════════════════════════════════════════════");
     println!("SP1 XMSS Signature Benchmark Configuration:");
@@ -102,24 +184,23 @@ fn xmss_benchmarks(c: &mut Criterion) {
             "SPEC_2"
         }
     );
+    println!("  Hash Backend: {}", <Backend as TweakableHash>::NAME);
+    println!("  Proof Mode: {}", config.proof_mode.name());
     println!("════════════════════════════════════════════════\n");
 
-    // Setup client and keys once for all benchmarks
-    let client = ProverClient::from_env();
-    let (pk, vk) = client.setup(ELF);
-
     let mut group = c.benchmark_group("sp1_xmss_signature");
 
     // Configure the benchmark group
     group.sample_size(100);
 
-    let job = Job::new(config);
+    let proof_mode = config.proof_mode;
+    let job = Job::new(config, test_data_cache_path());
 
-    // Benchmark 1: Witness Generation (setup + stdin preparation)
+    // Benchmark 1: Witness Generation (execution of the guest)
     group.bench_function("witness_generation", |b| {
         b.iter(|| {
-            let stdin = job.exec_compute();
-            black_box(stdin);
+            let stats = execute_xmss_aggregate(&job.test_data).unwrap();
+            black_box(stats);
         });
     });
 
@@ -130,20 +211,20 @@ fn xmss_benchmarks(c: &mut Criterion) {
     let mut group = c.benchmark_group("sp1_xmss_signature_proving");
     group.sample_size(10);
 
-    // Pre-compute stdin once - it gets cloned internally by SP1, not consumed
-    let mut stdin = SP1Stdin::new();
-    stdin.write(&job.test_data);
-
-    // Benchmark 2: Proof Generation
+    // Benchmark 2: Proof Generation, without the execution and verification around it
     group.bench_function("proof_generation", |b| {
-        b.iter(|| {
-            let proof = client.prove(&pk, &stdin).run().unwrap();
-            black_box(proof);
+        b.iter_custom(|iters| {
+            (0..iters)
+                .map(|_| {
+                    let result = prove_xmss_aggregate(&job.test_data, proof_mode).unwrap();
+                    result.proof_generation_time
+                })
+                .sum()
         });
     });
 
-    // Generate proof for verification benchmark (reuse the same stdin)
-    let proof = client.prove(&pk, &stdin).run().unwrap();
+    // Generate proof for verification benchmark
+    let result = prove_xmss_aggregate(&job.test_data, proof_mode).unwrap();
 
     group.finish();
 
@@ -153,18 +234,39 @@ fn xmss_benchmarks(c: &mut Criterion) {
 
     group.bench_function("proof_verification", |b| {
         b.iter(|| {
-            client.verify(&proof, &vk).unwrap();
+            verify_proof(&result.proof, &result.vk, &job.test_data.public_inputs).unwrap();
         });
     });
 
-    // Print additional metrics
+    // Print additional metrics, the cycles being what the hash backends are compared on
     println!("\nSP1 Additional Metrics:");
-    let proof_size_bytes = bincode::serialize(&proof).unwrap().len();
+    println!("  Total Cycles: {}", result.cycles);
     println!(
         "  Proof Size: {:.2} KiB ({} bytes)",
-        proof_size_bytes as f64 / 1024.0,
-        proof_size_bytes
+        result.proof_size_bytes as f64 / 1024.0,
+        result.proof_size_bytes
     );
+    println!(
+        "  Seal Size: {:.2} KiB ({} bytes)",
+        result.seal_size as f64 / 1024.0,
+        result.seal_size
+    );
+    println!(
+        "  Public Values Size: {} bytes",
+        result.proof.public_values.as_slice().len()
+    );
+
+    // The phases are only tracked by a guest built with the `cycle_tracker` feature
+    let stats = execute_xmss_aggregate(&job.test_data).unwrap();
+    if stats.phase_cycles.is_empty() {
+        println!("  Phase Cycles: build the guest with `--features cycle_tracker` to track them");
+    } else {
+        println!("\nSP1 Cycles per Verification Phase:");
+        print!(
+            "{}",
+            phase_table(&[(job.test_data.public_inputs.validator_roots.len(), &stats)])
+        );
+    }
 
     group.finish();
 }
@@ -0,0 +1,907 @@
+// Copyright 2025 Irreducible Inc.
+//! Running and proving the SP1 guest on XMSS signature aggregates.
+
+use core::fmt;
+use std::{
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use leansig_core::{cycle_tracker, DecodeError};
+use leansig_shared::{AggregateJournal, FailMode, GuestInput, PublicInputs, XmssTestData};
+use serde::{Deserialize, Serialize};
+use sp1_sdk::{
+    HashableKey, ProverClient, SP1Proof, SP1ProofMode, SP1ProofWithPublicValues, SP1Stdin,
+    SP1VerificationError, SP1VerifyingKey,
+};
+
+#[cfg(feature = "remote")]
+mod remote;
+
+/// The guest, built by `cargo prove build` in `crates/sp1/guest`
+pub const ELF: &[u8] = include_bytes!(
+    "../../../../target/elf-compilation/riscv32im-succinct-zkvm-elf/release/sp1-guest"
+);
+
+/// The statistics of an execution of the guest without proving it
+#[derive(Clone, Debug)]
+pub struct ExecutionStats {
+    /// The number of instructions the guest executed
+    pub total_cycles: u64,
+    /// The cycles of every phase of signature verification over all validators, in the order of
+    /// [`cycle_tracker::PHASES`]
+    ///
+    /// Empty unless the guest was built with the `cycle_tracker` feature.
+    pub phase_cycles: Vec<(&'static str, u64)>,
+    /// The journal committed to the public values, with the result of every validator with
+    /// [`FailMode::CommitResult`]
+    pub journal: AggregateJournal,
+}
+
+/// The reasons [`execute_xmss_aggregate`] can fail.
+#[derive(Debug)]
+pub enum ExecuteError {
+    /// The guest did not run to completion, e.g. because it rejected the aggregate.
+    Execute(anyhow::Error),
+    /// The public values do not have the layout of an [`AggregateJournal`].
+    PublicValues(DecodeError),
+    /// The public values commit a failed verification.
+    VerificationFailed,
+}
+
+impl fmt::Display for ExecuteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExecuteError::Execute(err) => write!(f, "failed to execute the guest: {err}"),
+            ExecuteError::PublicValues(err) => write!(f, "malformed public values: {err}"),
+            ExecuteError::VerificationFailed => {
+                write!(f, "public values commit a failed verification")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ExecuteError {}
+
+/// Runs the guest on `test_data` without proving it, to estimate the cost of a proof
+///
+/// Same as [`execute`] with [`FailMode::Abort`].
+pub fn execute_xmss_aggregate(test_data: &XmssTestData) -> Result<ExecutionStats, ExecuteError> {
+    execute(test_data, FailMode::Abort)
+}
+
+/// Runs the guest on `test_data` with `fail_mode` without proving it
+///
+/// With [`FailMode::CommitResult`], an aggregate that fails to verify runs to completion and
+/// commits the failure.
+///
+/// # Errors
+/// Returns an error if the guest rejects the aggregate or commits malformed public values.
+pub fn execute(
+    test_data: &XmssTestData,
+    fail_mode: FailMode,
+) -> Result<ExecutionStats, ExecuteError> {
+    let client = ProverClient::from_env();
+    let stdin = aggregate_stdin(test_data, fail_mode);
+    let (public_values, report) = client
+        .execute(ELF, &stdin)
+        .run()
+        .map_err(ExecuteError::Execute)?;
+
+    // The guest commits the digest of the public inputs followed by the verification result
+    let journal = AggregateJournal::from_bytes(public_values.as_slice())
+        .map_err(ExecuteError::PublicValues)?;
+    if fail_mode == FailMode::Abort && !journal.verification_result {
+        return Err(ExecuteError::VerificationFailed);
+    }
+
+    Ok(ExecutionStats {
+        total_cycles: report.total_instruction_count(),
+        phase_cycles: (cycle_tracker::PHASES.into_iter())
+            .filter_map(|phase| Some((phase, *report.cycle_tracker.get(phase)?)))
+            .collect(),
+        journal,
+    })
+}
+
+/// The input of the guest for the aggregate of `test_data` with `fail_mode`
+///
+/// The guest reads the public inputs and the aggregated signature as separate inputs, after a
+/// [`GuestInput::SingleMessageParts`] header, so the public statement is written once and apart
+/// from its witness.
+pub fn aggregate_stdin(test_data: &XmssTestData, fail_mode: FailMode) -> SP1Stdin {
+    let (public_inputs, aggregated_signature) = test_data.clone().into_parts();
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&GuestInput::SingleMessageParts { fail_mode });
+    stdin.write(&public_inputs);
+    stdin.write(&aggregated_signature);
+    stdin
+}
+
+/// Formats the cycles of every phase of signature verification as a table, with a row of
+/// `(number of validators, stats)` per entry of `rows`
+///
+/// Every row lists the cycles of each phase and their share of the total cycles. A phase the
+/// stats have no cycles for, since the guest was built without the `cycle_tracker` feature, is
+/// left blank.
+pub fn phase_table(rows: &[(usize, &ExecutionStats)]) -> String {
+    let mut table = format!("{:>10}", "validators");
+    for phase in cycle_tracker::PHASES {
+        table += &format!(" | {phase:>20}");
+    }
+    table += &format!(" | {:>12}\n", "total");
+    for (num_validators, stats) in rows {
+        table += &format!("{num_validators:>10}");
+        for phase in cycle_tracker::PHASES {
+            let cell = match stats.phase_cycles.iter().find(|(name, _)| *name == phase) {
+                Some((_, cycles)) => {
+                    let share = 100.0 * *cycles as f64 / stats.total_cycles as f64;
+                    format!("{cycles} ({share:.1}%)")
+                }
+                None => String::new(),
+            };
+            table += &format!(" | {cell:>20}");
+        }
+        table += &format!(" | {:>12}\n", stats.total_cycles);
+    }
+    table
+}
+
+/// The mode of proof to prove an aggregate with
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ProofMode {
+    /// A STARK per shard of the execution
+    #[default]
+    Core,
+    /// The shard proofs recursively compressed into a single STARK
+    Compressed,
+    /// The compressed proof wrapped into a Groth16 proof
+    Groth16,
+    /// The compressed proof wrapped into a PLONK proof
+    Plonk,
+}
+
+impl ProofMode {
+    /// Every mode, from the cheapest to prove to the cheapest to verify on-chain
+    pub const ALL: [ProofMode; 4] = [
+        ProofMode::Core,
+        ProofMode::Compressed,
+        ProofMode::Groth16,
+        ProofMode::Plonk,
+    ];
+
+    /// The mode named `name`, see [`ProofMode::name`]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|mode| mode.name() == name)
+    }
+
+    /// Whether proofs of this mode can be verified on-chain, see [`ProveResult::write_evm_proof`]
+    pub fn is_onchain(self) -> bool {
+        matches!(self, ProofMode::Groth16 | ProofMode::Plonk)
+    }
+
+    /// The proof mode of SP1 that produces proofs of this mode
+    pub fn sp1_mode(self) -> SP1ProofMode {
+        match self {
+            ProofMode::Core => SP1ProofMode::Core,
+            ProofMode::Compressed => SP1ProofMode::Compressed,
+            ProofMode::Groth16 => SP1ProofMode::Groth16,
+            ProofMode::Plonk => SP1ProofMode::Plonk,
+        }
+    }
+
+    /// The lowercase name of the mode
+    pub fn name(self) -> &'static str {
+        match self {
+            ProofMode::Core => "core",
+            ProofMode::Compressed => "compressed",
+            ProofMode::Groth16 => "groth16",
+            ProofMode::Plonk => "plonk",
+        }
+    }
+}
+
+/// Where proofs are generated
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum ProverBackend {
+    /// The prover selected by `SP1_PROVER`, on this machine by default
+    #[default]
+    Local,
+    /// The Succinct prover network, which requires the `remote` feature
+    Remote {
+        /// The environment variable holding the private key of the requester
+        api_key_env: String,
+        /// The RPC URL of the network, or the default one of SP1 if `None`
+        url: Option<String>,
+    },
+}
+
+impl ProverBackend {
+    /// The lowercase name of the backend
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProverBackend::Local => "local",
+            ProverBackend::Remote { .. } => "remote",
+        }
+    }
+
+    /// The key of a remote backend, read from the environment, or `None` for the local one
+    ///
+    /// # Errors
+    /// Returns an error if the key is not set.
+    pub fn api_key(&self) -> Result<Option<String>, RemoteError> {
+        let ProverBackend::Remote { api_key_env, .. } = self else {
+            return Ok(None);
+        };
+        std::env::var(api_key_env)
+            .map(Some)
+            .map_err(|_| RemoteError::MissingApiKey {
+                env: api_key_env.clone(),
+            })
+    }
+}
+
+/// How to prove the execution of the guest
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProveConfig {
+    /// The mode of proof to prove
+    pub proof_mode: ProofMode,
+    /// Where to prove, with the prover of `SP1_PROVER` by default
+    pub backend: ProverBackend,
+    /// How long to wait for a remote proof before giving up, or the default of SP1 if `None`
+    pub remote_timeout: Option<Duration>,
+    /// What the guest does with an aggregate that fails to verify, see [`prove`]
+    pub fail_mode: FailMode,
+}
+
+impl ProveConfig {
+    /// The default configuration for proofs of `proof_mode`
+    pub fn new(proof_mode: ProofMode) -> Self {
+        Self {
+            proof_mode,
+            ..Self::default()
+        }
+    }
+}
+
+/// How long a proof took, from the submission of the input to the proof
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProveTimings {
+    /// The time from the submission of the input to the proof, including the queueing and the
+    /// transfers of a remote proof
+    pub latency: Duration,
+    /// The time spent proving, or `None` if the prover reports none
+    pub proving_time: Option<Duration>,
+}
+
+impl ProveTimings {
+    /// The timings of a local proof, which spends all of its latency proving
+    fn local(latency: Duration) -> Self {
+        Self {
+            latency,
+            proving_time: Some(latency),
+        }
+    }
+
+    /// The time spent outside of proving, i.e. in queues and transfers, if the proving time is
+    /// known
+    pub fn queue_time(&self) -> Option<Duration> {
+        self.proving_time
+            .map(|proving_time| self.latency.saturating_sub(proving_time))
+    }
+}
+
+/// A proof with the verifying key of the guest and how long it took
+struct Proof {
+    proof: SP1ProofWithPublicValues,
+    vk: SP1VerifyingKey,
+    timings: ProveTimings,
+}
+
+/// A proof of an aggregate with the public inputs its public values commit
+pub struct ProveResult {
+    /// The proof, with the public values committed by the guest
+    pub proof: SP1ProofWithPublicValues,
+    /// The verifying key of the guest, which the proof verifies against
+    pub vk: SP1VerifyingKey,
+    /// The mode of the proof
+    pub mode: ProofMode,
+    /// The number of instructions the guest executed
+    pub cycles: u64,
+    /// The time spent executing the guest to count its cycles, before proving
+    pub witness_generation_time: Duration,
+    /// The time spent generating the proof, i.e. the latency of [`ProveResult::timings`]
+    pub proof_generation_time: Duration,
+    /// The time spent verifying the proof with [`verify_proof`]
+    pub verification_time: Duration,
+    /// The size of the proof with its public values in bytes, as saved to a file
+    pub proof_size_bytes: usize,
+    /// The size of the seal in bytes, i.e. of the bytes verified on-chain for Groth16 and PLONK
+    /// proofs, or of the STARKs otherwise
+    pub seal_size: usize,
+    /// The hash of the verifying key of the guest, as a `0x` prefixed hex string of the BN254
+    /// field element that on-chain verifiers expect
+    pub vk_hash: String,
+    /// The SHA-256 digest of the public values
+    pub public_values_digest: [u8; 32],
+    /// The public inputs committed to the public values, equal to the submitted ones
+    pub public_inputs: PublicInputs,
+    /// The backend the proof was proven with
+    pub backend: ProverBackend,
+    /// How long the proof took
+    pub timings: ProveTimings,
+}
+
+impl ProveResult {
+    /// The identifiers, sizes and timings of the proof, without the proof itself
+    pub fn summary(&self) -> ProofSummary {
+        let millis = |time: Duration| time.as_millis() as u64;
+        ProofSummary {
+            mode: self.mode.name().to_string(),
+            vk_hash: self.vk_hash.clone(),
+            public_values_digest: (self.public_values_digest.iter())
+                .map(|byte| format!("{byte:02x}"))
+                .collect(),
+            cycles: self.cycles,
+            seal_size: self.seal_size,
+            proof_size_bytes: self.proof_size_bytes,
+            public_values_size_bytes: self.proof.public_values.as_slice().len(),
+            backend: self.backend.name().to_string(),
+            witness_generation_time_ms: millis(self.witness_generation_time),
+            latency_ms: millis(self.timings.latency),
+            proving_time_ms: self.timings.proving_time.map(millis),
+            queue_time_ms: self.timings.queue_time().map(millis),
+            verification_time_ms: millis(self.verification_time),
+        }
+    }
+
+    /// Writes the bytes an EVM verifier checks to `proof_path`, and the hash of the verifying key
+    /// it checks them against to `vk_path`, as a `0x` prefixed hex string
+    ///
+    /// The public values the verifier needs along are those of [`ProveResult::proof`].
+    ///
+    /// # Errors
+    /// Returns an error of kind [`std::io::ErrorKind::InvalidInput`] unless the proof is a
+    /// Groth16 or PLONK one.
+    pub fn write_evm_proof(
+        &self,
+        proof_path: impl AsRef<Path>,
+        vk_path: impl AsRef<Path>,
+    ) -> std::io::Result<()> {
+        if !self.mode.is_onchain() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("{} proofs are not verified on-chain", self.mode.name()),
+            ));
+        }
+        std::fs::write(proof_path, self.proof.bytes())?;
+        std::fs::write(vk_path, &self.vk_hash)
+    }
+
+    /// The fixture an EVM verifier checks the proof with, or `None` unless the proof is a Groth16
+    /// or PLONK one
+    pub fn evm_fixture(&self) -> Option<EvmFixture> {
+        self.mode.is_onchain().then(|| EvmFixture {
+            vkey: self.vk_hash.clone(),
+            public_values: to_hex(self.proof.public_values.as_slice()),
+            proof: to_hex(&self.proof.bytes()),
+        })
+    }
+}
+
+/// The arguments of `verifyProof` of the SP1 Solidity verifier for a proof, as written to JSON
+/// fixtures by the SP1 project templates
+///
+/// Every field is a `0x` prefixed hex string: the hash of the verifying key of the guest, which is
+/// the `programVKey` of the verifier, the public values the guest committed, and the proof bytes.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EvmFixture {
+    /// The hash of the verifying key of the guest
+    pub vkey: String,
+    /// The public values, laid out as [`encode_public_values`] does
+    pub public_values: String,
+    /// The proof bytes, prefixed by the selector of the verifier
+    pub proof: String,
+}
+
+impl EvmFixture {
+    /// Writes the fixture to `path` as pretty-printed JSON
+    pub fn write_to_file(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let json = serde_json::to_string_pretty(self).map_err(std::io::Error::other)?;
+        std::fs::write(path, json)
+    }
+
+    /// Reads a fixture written by [`EvmFixture::write_to_file`]
+    pub fn read_from_file(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let json = std::fs::read(path)?;
+        serde_json::from_slice(&json)
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))
+    }
+
+    /// The bytes of the public values, or `None` if they are not a `0x` prefixed hex string
+    pub fn public_values_bytes(&self) -> Option<Vec<u8>> {
+        from_hex(&self.public_values)
+    }
+}
+
+/// The public values the guest commits for an aggregate of `public_inputs` with
+/// `verification_result`, i.e. the 33 bytes of their [`AggregateJournal`]
+///
+/// These are the bytes an EVM verifier receives along with the proof, see [`EvmFixture`]. A
+/// contract recomputes the [`PublicInputs::digest`] of the inputs it expects and compares it
+/// with the first 32 bytes.
+pub fn encode_public_values(public_inputs: &PublicInputs, verification_result: bool) -> Vec<u8> {
+    AggregateJournal::new(public_inputs, verification_result).to_bytes()
+}
+
+/// `bytes` as a `0x` prefixed hex string
+fn to_hex(bytes: &[u8]) -> String {
+    let digits: String = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+    format!("0x{digits}")
+}
+
+/// The bytes of the `0x` prefixed hex string `hex`, or `None` if it is malformed
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    let digits = hex.strip_prefix("0x")?;
+    if digits.len() % 2 != 0 {
+        return None;
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(digits.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// The size of the seal of `proof` in bytes, see [`ProveResult::seal_size`]
+pub fn seal_size(proof: &SP1ProofWithPublicValues) -> usize {
+    match &proof.proof {
+        SP1Proof::Groth16(_) | SP1Proof::Plonk(_) => proof.bytes().len(),
+        inner => bincode::serialized_size(inner).map_or(0, |size| size as usize),
+    }
+}
+
+/// What downstream systems record of a proof, see [`ProveResult::summary`]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofSummary {
+    /// The name of the [`ProofMode`]
+    pub mode: String,
+    /// The hash of the verifying key of the guest
+    pub vk_hash: String,
+    /// The SHA-256 digest of the public values, as a hex string
+    pub public_values_digest: String,
+    /// The number of instructions the guest executed
+    pub cycles: u64,
+    /// The size of the seal in bytes
+    pub seal_size: usize,
+    /// The size of the proof with its public values in bytes
+    pub proof_size_bytes: usize,
+    /// The size of the public values in bytes
+    pub public_values_size_bytes: usize,
+    /// The name of the [`ProverBackend`]
+    pub backend: String,
+    /// The time spent executing the guest before proving in milliseconds
+    pub witness_generation_time_ms: u64,
+    /// The latency of the proof in milliseconds
+    pub latency_ms: u64,
+    /// The time spent proving in milliseconds, if known
+    pub proving_time_ms: Option<u64>,
+    /// The time spent in queues and transfers in milliseconds, if known
+    pub queue_time_ms: Option<u64>,
+    /// The time spent verifying the proof in milliseconds
+    pub verification_time_ms: u64,
+}
+
+/// The reasons [`prove`] can fail.
+#[derive(Debug)]
+pub enum ProveError {
+    /// The guest did not run to completion, e.g. because it rejected the aggregate.
+    Prove(anyhow::Error),
+    /// The proof fails [`verify_proof`] for the submitted public inputs.
+    Verify(VerifyError),
+    /// The prover network could not be reached or failed to prove.
+    Remote(RemoteError),
+}
+
+impl fmt::Display for ProveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProveError::Prove(err) => write!(f, "failed to prove the aggregate: {err}"),
+            ProveError::Verify(err) => write!(f, "invalid proof: {err}"),
+            ProveError::Remote(err) => write!(f, "remote proving failed: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for ProveError {}
+
+/// The reasons [`verify_proof`] can reject a proof.
+#[derive(Debug)]
+pub enum VerifyError {
+    /// The proof does not verify against the verifying key.
+    Proof(SP1VerificationError),
+    /// The public values do not have the layout of an [`AggregateJournal`].
+    PublicValues(DecodeError),
+    /// The public values commit a failed verification.
+    VerificationFailed,
+    /// The public values commit the digest of other public inputs than the expected ones.
+    PublicInputsMismatch,
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyError::Proof(err) => write!(f, "proof does not verify: {err}"),
+            VerifyError::PublicValues(err) => write!(f, "malformed public values: {err}"),
+            VerifyError::VerificationFailed => {
+                write!(f, "public values commit a failed verification")
+            }
+            VerifyError::PublicInputsMismatch => {
+                write!(f, "public values commit other public inputs")
+            }
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+/// The reasons proving with [`ProverBackend::Remote`] can fail.
+#[derive(Debug)]
+pub enum RemoteError {
+    /// The host was built without the `remote` feature.
+    Disabled,
+    /// The environment variable `env` that should hold the private key is not set.
+    MissingApiKey { env: String },
+    /// A request to the network failed, e.g. because it rejected the key, or no prover
+    /// fulfilled it.
+    Request(anyhow::Error),
+    /// The request was not fulfilled within [`ProveConfig::remote_timeout`].
+    Timeout,
+}
+
+impl fmt::Display for RemoteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RemoteError::Disabled => write!(f, "built without the remote feature"),
+            RemoteError::MissingApiKey { env } => write!(f, "no private key in {env}"),
+            RemoteError::Request(err) => write!(f, "request failed: {err}"),
+            RemoteError::Timeout => write!(f, "request timed out"),
+        }
+    }
+}
+
+impl std::error::Error for RemoteError {}
+
+/// Proves with a proof of `mode` that the aggregated signature of `test_data` verifies for its
+/// public inputs, see [`prove`]
+pub fn prove_xmss_aggregate(
+    test_data: &XmssTestData,
+    mode: ProofMode,
+) -> Result<ProveResult, ProveError> {
+    prove(test_data, &ProveConfig::new(mode))
+}
+
+/// Proves as `config` describes that the aggregated signature of `test_data` verifies for its
+/// public inputs
+///
+/// The guest is first executed locally to count its cycles, and the proof is verified with
+/// [`verify_proof`] once generated, each timed apart from the proof generation.
+///
+/// With [`FailMode::CommitResult`], an aggregate that fails to verify is proven all the same,
+/// and the public values commit the result of every validator, see [`verify_proof_result`].
+///
+/// # Errors
+/// Returns an error if the guest rejects the aggregate, if the remote prover fails, or if the
+/// proof does not verify for the submitted public inputs.
+pub fn prove(test_data: &XmssTestData, config: &ProveConfig) -> Result<ProveResult, ProveError> {
+    let stdin = aggregate_stdin(test_data, config.fail_mode);
+
+    let start = Instant::now();
+    let (_, report) = ProverClient::from_env()
+        .execute(ELF, &stdin)
+        .run()
+        .map_err(ProveError::Prove)?;
+    let witness_generation_time = start.elapsed();
+
+    let Proof { proof, vk, timings } = match config.backend {
+        ProverBackend::Local => prove_locally(&stdin, config)?,
+        ProverBackend::Remote { .. } => prove_remotely(&stdin, config)?,
+    };
+
+    let start = Instant::now();
+    let journal =
+        verify_proof_result(&proof, &vk, &test_data.public_inputs).map_err(ProveError::Verify)?;
+    let verification_time = start.elapsed();
+    if config.fail_mode == FailMode::Abort {
+        check_journal(&journal).map_err(ProveError::Verify)?;
+    }
+
+    let public_values_digest = (proof.public_values.hash())
+        .try_into()
+        .expect("SHA-256 digest");
+    Ok(ProveResult {
+        cycles: report.total_instruction_count(),
+        witness_generation_time,
+        proof_generation_time: timings.latency,
+        verification_time,
+        proof_size_bytes: bincode::serialized_size(&proof).map_or(0, |size| size as usize),
+        seal_size: seal_size(&proof),
+        vk_hash: vk.bytes32(),
+        public_values_digest,
+        proof,
+        vk,
+        mode: config.proof_mode,
+        public_inputs: test_data.public_inputs.clone(),
+        backend: config.backend.clone(),
+        timings,
+    })
+}
+
+/// Checks that `proof` verifies against `vk`, and that its public values commit a successful
+/// verification of an aggregate for `expected`
+///
+/// The verification runs on the prover selected by `SP1_PROVER`, so the mock prover accepts the
+/// mock proofs it generates.
+pub fn verify_proof(
+    proof: &SP1ProofWithPublicValues,
+    vk: &SP1VerifyingKey,
+    expected: &PublicInputs,
+) -> Result<(), VerifyError> {
+    check_journal(&verify_proof_result(proof, vk, expected)?)
+}
+
+/// Checks that `proof` verifies against `vk` for an aggregate of `expected`, and returns its
+/// journal whether the verification of the aggregate succeeded or not
+///
+/// Same as [`verify_proof`], but public values committed with [`FailMode::CommitResult`] that
+/// prove a failed verification are returned rather than rejected, with the result of every
+/// validator.
+pub fn verify_proof_result(
+    proof: &SP1ProofWithPublicValues,
+    vk: &SP1VerifyingKey,
+    expected: &PublicInputs,
+) -> Result<AggregateJournal, VerifyError> {
+    ProverClient::from_env()
+        .verify(proof, vk)
+        .map_err(VerifyError::Proof)?;
+    decode_public_values(proof.public_values.as_slice(), expected)
+}
+
+/// Checks that `public_values` commit a successful verification of an aggregate for `expected`
+///
+/// The public values only commit the [`PublicInputs::digest`], which is recomputed from
+/// `expected`.
+pub fn check_public_values(
+    public_values: &[u8],
+    expected: &PublicInputs,
+) -> Result<(), VerifyError> {
+    check_journal(&decode_public_values(public_values, expected)?)
+}
+
+/// Decodes `public_values` as an [`AggregateJournal`] of either fail mode, which must commit the
+/// digest of `expected`
+pub fn decode_public_values(
+    public_values: &[u8],
+    expected: &PublicInputs,
+) -> Result<AggregateJournal, VerifyError> {
+    // The guest commits the digest of the public inputs followed by the verification result,
+    // and the result of every validator with `FailMode::CommitResult`
+    let journal = AggregateJournal::from_bytes(public_values).map_err(VerifyError::PublicValues)?;
+    if journal.digest != expected.digest() {
+        return Err(VerifyError::PublicInputsMismatch);
+    }
+    Ok(journal)
+}
+
+/// Checks that `journal` commits a successful verification
+fn check_journal(journal: &AggregateJournal) -> Result<(), VerifyError> {
+    if !journal.verification_result {
+        return Err(VerifyError::VerificationFailed);
+    }
+    Ok(())
+}
+
+/// Proves the guest on `stdin` with the prover selected by `SP1_PROVER`
+fn prove_locally(stdin: &SP1Stdin, config: &ProveConfig) -> Result<Proof, ProveError> {
+    let client = ProverClient::from_env();
+    let (pk, vk) = client.setup(ELF);
+    let start = Instant::now();
+    let proof = client
+        .prove(&pk, stdin)
+        .mode(config.proof_mode.sp1_mode())
+        .run()
+        .map_err(ProveError::Prove)?;
+    Ok(Proof {
+        proof,
+        vk,
+        timings: ProveTimings::local(start.elapsed()),
+    })
+}
+
+/// Proves the guest on `stdin` on the prover network
+#[cfg(feature = "remote")]
+fn prove_remotely(stdin: &SP1Stdin, config: &ProveConfig) -> Result<Proof, ProveError> {
+    remote::prove(stdin, config).map_err(ProveError::Remote)
+}
+
+/// Fails, since the host was built without the prover network
+#[cfg(not(feature = "remote"))]
+fn prove_remotely(_stdin: &SP1Stdin, _config: &ProveConfig) -> Result<Proof, ProveError> {
+    Err(ProveError::Remote(RemoteError::Disabled))
+}
+
+#[cfg(test)]
+mod tests {
+    use leansig_core::AggregatedSignature;
+    use leansig_shared::TestDataBuilder;
+
+    use super::*;
+
+    #[test]
+    fn test_phase_table() {
+        let journal = TestDataBuilder::new()
+            .validators(2)
+            .tree_height(3)
+            .build()
+            .public_inputs;
+        let journal = AggregateJournal::new(&journal, true);
+        let tracked = ExecutionStats {
+            total_cycles: 1000,
+            phase_cycles: vec![
+                (cycle_tracker::CODEWORD, 100),
+                (cycle_tracker::HASH_CHAINS, 500),
+                (cycle_tracker::MERKLE_PROOF, 250),
+            ],
+            journal: journal.clone(),
+        };
+        let untracked = ExecutionStats {
+            total_cycles: 2000,
+            phase_cycles: Vec::new(),
+            journal,
+        };
+
+        let table = phase_table(&[(2, &tracked), (4, &untracked)]);
+        let lines: Vec<_> = table.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(cycle_tracker::PHASES
+            .iter()
+            .all(|phase| lines[0].contains(phase)));
+        assert!(lines[1].contains("100 (10.0%)") && lines[1].contains("500 (50.0%)"));
+        assert!(lines[1].trim_end().ends_with("1000"));
+        assert!(!lines[2].contains('%') && lines[2].trim_end().ends_with("2000"));
+    }
+
+    #[test]
+    fn test_check_public_values() {
+        let public_inputs = TestDataBuilder::new()
+            .validators(2)
+            .tree_height(3)
+            .build()
+            .public_inputs;
+        let other_inputs = TestDataBuilder::new()
+            .validators(2)
+            .tree_height(3)
+            .seed(1)
+            .build()
+            .public_inputs;
+
+        check_public_values(&encode_public_values(&public_inputs, true), &public_inputs).unwrap();
+        assert!(matches!(
+            check_public_values(&encode_public_values(&public_inputs, false), &public_inputs),
+            Err(VerifyError::VerificationFailed)
+        ));
+        assert!(matches!(
+            check_public_values(&encode_public_values(&other_inputs, true), &public_inputs),
+            Err(VerifyError::PublicInputsMismatch)
+        ));
+        let truncated = encode_public_values(&public_inputs, true);
+        assert!(matches!(
+            check_public_values(&truncated[..truncated.len() - 1], &public_inputs),
+            Err(VerifyError::PublicValues(_))
+        ));
+    }
+
+    #[test]
+    fn test_encode_public_values() {
+        let public_inputs = TestDataBuilder::new()
+            .validators(2)
+            .tree_height(3)
+            .build()
+            .public_inputs;
+        // The guest commits the digest followed by the result, for any number of validators
+        let public_values = encode_public_values(&public_inputs, true);
+        assert_eq!(public_values.len(), 33);
+        assert_eq!(public_values[..32], public_inputs.digest().0);
+        assert_eq!(public_values[32], 1);
+
+        assert_eq!(to_hex(&[0x00, 0xab, 0x7f]), "0x00ab7f");
+        assert_eq!(from_hex("0x00ab7f"), Some(vec![0x00, 0xab, 0x7f]));
+        assert_eq!(from_hex(&to_hex(&public_values)), Some(public_values));
+        assert_eq!(from_hex("00ab"), None);
+        assert_eq!(from_hex("0xabc"), None);
+        assert_eq!(from_hex("0xzz"), None);
+    }
+
+    #[test]
+    fn test_aggregate_stdin() {
+        let test_data = TestDataBuilder::new().validators(2).tree_height(3).build();
+        let stdin = aggregate_stdin(&test_data, FailMode::CommitResult);
+
+        // The header, the public inputs and the aggregated signature, each written apart
+        assert_eq!(stdin.buffer.len(), 3);
+        let header: GuestInput = bincode::deserialize(&stdin.buffer[0]).unwrap();
+        assert!(matches!(
+            header,
+            GuestInput::SingleMessageParts {
+                fail_mode: FailMode::CommitResult
+            }
+        ));
+        let public_inputs: PublicInputs = bincode::deserialize(&stdin.buffer[1]).unwrap();
+        assert_eq!(public_inputs, test_data.public_inputs);
+        let aggregated_signature: AggregatedSignature =
+            bincode::deserialize(&stdin.buffer[2]).unwrap();
+        assert_eq!(
+            bincode::serialize(&aggregated_signature).unwrap(),
+            bincode::serialize(&test_data.aggregated_signature).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_proof_mode() {
+        for mode in ProofMode::ALL {
+            assert_eq!(ProofMode::from_name(mode.name()), Some(mode));
+        }
+        assert_eq!(ProofMode::from_name("stark"), None);
+        assert_eq!(ProofMode::default(), ProofMode::Core);
+        assert!(ProofMode::Groth16.is_onchain() && ProofMode::Plonk.is_onchain());
+        assert!(!ProofMode::Core.is_onchain() && !ProofMode::Compressed.is_onchain());
+    }
+
+    #[test]
+    fn test_api_key() {
+        assert_eq!(ProverBackend::default(), ProverBackend::Local);
+        assert_eq!(ProverBackend::Local.api_key().unwrap(), None);
+
+        let backend = ProverBackend::Remote {
+            api_key_env: "LEANSIG_TEST_SP1_API_KEY".to_string(),
+            url: None,
+        };
+        assert!(matches!(
+            backend.api_key(),
+            Err(RemoteError::MissingApiKey { env }) if env == "LEANSIG_TEST_SP1_API_KEY"
+        ));
+
+        // No other test reads or writes this variable
+        std::env::set_var("LEANSIG_TEST_SP1_API_KEY", "secret");
+        assert_eq!(backend.api_key().unwrap(), Some("secret".to_string()));
+    }
+
+    #[test]
+    fn test_prove_timings() {
+        let timings = ProveTimings {
+            latency: Duration::from_secs(10),
+            proving_time: Some(Duration::from_secs(7)),
+        };
+        assert_eq!(timings.queue_time(), Some(Duration::from_secs(3)));
+        assert_eq!(
+            ProveTimings::local(Duration::from_secs(10)).queue_time(),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "remote"))]
+    fn test_remote_disabled() {
+        let config = ProveConfig {
+            backend: ProverBackend::Remote {
+                api_key_env: "NETWORK_PRIVATE_KEY".to_string(),
+                url: None,
+            },
+            ..ProveConfig::new(ProofMode::Compressed)
+        };
+        assert!(matches!(
+            prove_remotely(&SP1Stdin::new(), &config),
+            Err(ProveError::Remote(RemoteError::Disabled))
+        ));
+    }
+}
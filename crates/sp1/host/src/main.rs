@@ -1,58 +1,230 @@
 // Copyright 2025 Irreducible Inc.
-use leansig_core::{spec, AggregatedVerifier};
-use leansig_shared::create_test_data;
-use sp1_sdk::{ProverClient, SP1Stdin};
-use tracing_subscriber;
+//! Generates, executes, proves and verifies XMSS signature aggregates with SP1.
+//!
+//! Every command prints its results as text, or as a JSON object with `--json`. The prover is
+//! selected by `SP1_PROVER`, e.g. `mock` for fast fake proofs.
 
-const ELF: &[u8] = include_bytes!(
-    "../../../../target/elf-compilation/riscv32im-succinct-zkvm-elf/release/sp1-guest"
-);
+use std::path::{Path, PathBuf};
 
-fn main() {
-    // Initialize tracing. In order to view logs, run `RUST_LOG=info cargo run`
-    tracing_subscriber::fmt()
-        .with_env_filter(tracing_subscriber::filter::EnvFilter::from_default_env())
-        .init();
+use anyhow::{ensure, Context};
+use clap::{Args, Parser, Subcommand};
+use leansig_core::spec::{Spec, SPEC_1, SPEC_2};
+use leansig_shared::{print_report, PublicInputs, TestDataBuilder, XmssTestData};
+use serde_json::json;
+use sp1_host::{
+    execute_xmss_aggregate, prove, verify_proof, ProofMode, ProveConfig, ProverBackend, ELF,
+};
+use sp1_sdk::{ProverClient, SP1ProofWithPublicValues};
 
-    let test_data = create_test_data(3, spec::SPEC_2, 13, 10000, None, None);
-
-    // Sanity check the signature verification
-    let verifier = AggregatedVerifier::new(
-        test_data.public_inputs.validator_roots.clone(),
-        test_data.public_inputs.spec.clone(),
-    );
-    assert!(
-        verifier.verify(
-            &test_data.public_inputs.message,
-            &test_data.aggregated_signature
-        ),
-        "failed to verify aggregated signature"
-    );
-
-    // Setup the prover client.
-    let client = ProverClient::from_env();
+#[derive(Parser)]
+#[command(about = "Proves XMSS signature aggregates with SP1")]
+struct Cli {
+    /// Print the results as a JSON object
+    #[arg(long, global = true)]
+    json: bool,
+    #[command(subcommand)]
+    command: Command,
+}
 
-    // Setup the inputs.
-    let mut stdin = SP1Stdin::new();
-    stdin.write(&test_data);
+#[derive(Subcommand)]
+enum Command {
+    /// Generates test data of validators signing the same message and writes it to a file
+    Generate {
+        /// The number of validators
+        #[arg(long, default_value_t = 3)]
+        validators: usize,
+        /// The height of the XMSS tree of every validator
+        #[arg(long, default_value_t = 4)]
+        tree_height: usize,
+        /// The spec of the signature scheme, `1` or `2`
+        #[arg(long, default_value = "2", value_parser = parse_spec)]
+        spec: Spec,
+        /// The seed the validators are derived from
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+        /// Where to write the test data
+        #[arg(long)]
+        output: PathBuf,
+        /// Where to also write the public inputs alone, as expected by `verify`
+        #[arg(long)]
+        public_inputs: Option<PathBuf>,
+    },
+    /// Runs the guest on test data without proving it
+    Execute {
+        /// The test data written by `generate`
+        #[arg(long)]
+        input: PathBuf,
+    },
+    /// Proves test data and writes the proof to a file
+    Prove {
+        /// The test data written by `generate`
+        #[arg(long)]
+        input: PathBuf,
+        /// Where to write the proof
+        #[arg(long)]
+        output: PathBuf,
+        /// The mode of proof
+        #[arg(long, value_enum, default_value_t = ProofMode::Core)]
+        mode: ProofMode,
+        /// Prove a Groth16 proof, and write the JSON fixture the SP1 Solidity verifier checks it
+        /// with to this path
+        #[arg(long, conflicts_with = "mode")]
+        evm: Option<PathBuf>,
+        /// Where to write the bytes an EVM verifier checks, for Groth16 and PLONK proofs
+        #[arg(long, requires = "evm_vk")]
+        evm_proof: Option<PathBuf>,
+        /// Where to write the hash of the verifying key an EVM verifier checks against
+        #[arg(long, requires = "evm_proof")]
+        evm_vk: Option<PathBuf>,
+        #[command(flatten)]
+        remote: Remote,
+    },
+    /// Checks that a proof commits a successful verification of the expected public inputs
+    Verify {
+        /// The proof written by `prove`
+        #[arg(long)]
+        proof: PathBuf,
+        /// The public inputs written by `generate`
+        #[arg(long)]
+        public_inputs: PathBuf,
+    },
+}
 
-    println!("Generated proof");
+/// The prover network to prove on instead of the prover of `SP1_PROVER`, see [`ProverBackend`]
+#[derive(Args)]
+struct Remote {
+    /// Prove on the Succinct prover network, with the private key in this environment variable
+    #[arg(long)]
+    remote_api_key_env: Option<String>,
+    /// The RPC URL of the network, instead of the default one
+    #[arg(long, requires = "remote_api_key_env")]
+    remote_url: Option<String>,
+}
 
-    // Generate the proof for the given program and input.
-    let (pk, vk) = client.setup(ELF);
-    let mut proof = client.prove(&pk, &stdin).run().unwrap();
+impl Remote {
+    fn backend(self) -> ProverBackend {
+        match self.remote_api_key_env {
+            Some(api_key_env) => ProverBackend::Remote {
+                api_key_env,
+                url: self.remote_url,
+            },
+            None => ProverBackend::Local,
+        }
+    }
+}
 
-    println!("Successfully generated proof!");
+fn parse_spec(name: &str) -> Result<Spec, String> {
+    match name {
+        "1" | "SPEC_1" => Ok(SPEC_1),
+        "2" | "SPEC_2" => Ok(SPEC_2),
+        _ => Err(format!("unknown spec {name}, expected 1 or 2")),
+    }
+}
 
-    // Verify proof and public values
-    client.verify(&proof, &vk).expect("verification failed");
+fn main() -> anyhow::Result<()> {
+    // Initialize tracing. In order to view logs, run `RUST_LOG=info cargo run`
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::filter::EnvFilter::from_default_env())
+        .init();
 
-    // Get the public values from the proof as committed by the guest.
-    let _committed_public_inputs = proof.public_values.read::<leansig_shared::PublicInputs>();
-    let committed_verification_result = proof.public_values.read::<bool>();
+    let cli = Cli::parse();
+    let report = match cli.command {
+        Command::Generate {
+            validators,
+            tree_height,
+            spec,
+            seed,
+            output,
+            public_inputs,
+        } => {
+            let test_data = TestDataBuilder::new()
+                .validators(validators)
+                .spec(spec)
+                .tree_height(tree_height)
+                .seed(seed)
+                .build();
+            test_data
+                .write_to_file(&output)
+                .with_context(|| format!("failed to write {}", output.display()))?;
+            if let Some(path) = public_inputs {
+                test_data
+                    .public_inputs
+                    .write_to_file(&path)
+                    .with_context(|| format!("failed to write {}", path.display()))?;
+            }
+            json!({
+                "validators": validators,
+                "tree_height": tree_height,
+                "digest": test_data.public_inputs.digest().to_string(),
+            })
+        }
+        Command::Execute { input } => {
+            let test_data = read_test_data(&input)?;
+            let stats = execute_xmss_aggregate(&test_data)?;
+            json!({ "total_cycles": stats.total_cycles })
+        }
+        Command::Prove {
+            input,
+            output,
+            mode,
+            evm,
+            evm_proof,
+            evm_vk,
+            remote,
+        } => {
+            let test_data = read_test_data(&input)?;
+            let mode = if evm.is_some() {
+                ProofMode::Groth16
+            } else {
+                mode
+            };
+            ensure!(
+                evm_proof.is_none() || mode.is_onchain(),
+                "{} proofs are not verified on-chain",
+                mode.name()
+            );
+            let config = ProveConfig {
+                backend: remote.backend(),
+                ..ProveConfig::new(mode)
+            };
+            let result = prove(&test_data, &config)?;
+            result
+                .proof
+                .save(&output)
+                .with_context(|| format!("failed to write {}", output.display()))?;
+            if let Some(path) = evm {
+                let fixture = result
+                    .evm_fixture()
+                    .expect("Groth16 proofs are verified on-chain");
+                fixture
+                    .write_to_file(&path)
+                    .with_context(|| format!("failed to write {}", path.display()))?;
+            }
+            if let (Some(proof_path), Some(vk_path)) = (evm_proof, evm_vk) {
+                result
+                    .write_evm_proof(&proof_path, &vk_path)
+                    .with_context(|| format!("failed to write {}", proof_path.display()))?;
+            }
+            serde_json::to_value(result.summary())?
+        }
+        Command::Verify {
+            proof,
+            public_inputs,
+        } => {
+            let proof = SP1ProofWithPublicValues::load(&proof)
+                .with_context(|| format!("failed to read {}", proof.display()))?;
+            let expected = PublicInputs::read_from_file(&public_inputs)
+                .with_context(|| format!("failed to read {}", public_inputs.display()))?;
 
-    println!("Verification result: {}", committed_verification_result);
-    assert!(committed_verification_result, "Guest verification failed");
+            let (_, vk) = ProverClient::from_env().setup(ELF);
+            verify_proof(&proof, &vk, &expected)?;
+            json!({ "verified": true })
+        }
+    };
+    print_report(cli.json, &report);
+    Ok(())
+}
 
-    println!("Successfully verified proof!");
+fn read_test_data(path: &Path) -> anyhow::Result<XmssTestData> {
+    XmssTestData::read_from_file(path).with_context(|| format!("failed to read {}", path.display()))
 }
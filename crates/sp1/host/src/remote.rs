@@ -0,0 +1,45 @@
+// Copyright 2025 Irreducible Inc.
+//! Proving on the Succinct prover network.
+
+use std::time::Instant;
+
+use sp1_sdk::{network::Error as NetworkError, ProverClient, SP1Stdin};
+
+use crate::{Proof, ProveConfig, ProveTimings, ProverBackend, RemoteError, ELF};
+
+/// Proves the guest on `stdin` on the prover network
+///
+/// The network does not report how long the proof itself took, so the timings hold the latency
+/// only.
+pub(crate) fn prove(stdin: &SP1Stdin, config: &ProveConfig) -> Result<Proof, RemoteError> {
+    let ProverBackend::Remote { url, .. } = &config.backend else {
+        unreachable!("proving remotely with the local backend")
+    };
+    let private_key = config.backend.api_key()?.expect("remote backend");
+    let mut builder = ProverClient::builder().network().private_key(&private_key);
+    if let Some(url) = url {
+        builder = builder.rpc_url(url);
+    }
+    let client = builder.build();
+    let (pk, vk) = client.setup(ELF);
+
+    let start = Instant::now();
+    let mut request = client.prove(&pk, stdin).mode(config.proof_mode.sp1_mode());
+    if let Some(timeout) = config.remote_timeout {
+        request = request.timeout(timeout);
+    }
+    let proof = request
+        .run()
+        .map_err(|err| match err.downcast_ref::<NetworkError>() {
+            Some(NetworkError::RequestTimedOut { .. }) => RemoteError::Timeout,
+            _ => RemoteError::Request(err),
+        })?;
+    Ok(Proof {
+        proof,
+        vk,
+        timings: ProveTimings {
+            latency: start.elapsed(),
+            proving_time: None,
+        },
+    })
+}
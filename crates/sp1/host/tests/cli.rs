@@ -0,0 +1,155 @@
+// Copyright 2025 Irreducible Inc.
+//! Running the command line interface of the host with the mock prover.
+
+use std::{
+    path::PathBuf,
+    process::{Command, Output},
+};
+
+use serde_json::Value;
+
+/// A fresh directory for the files of `test`
+fn temp_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("leansig-sp1-cli-{test}-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Runs the host with `args` and `--json` with the mock prover
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_sp1-host"))
+        .args(args)
+        .arg("--json")
+        .env("SP1_PROVER", "mock")
+        .output()
+        .unwrap()
+}
+
+/// The JSON report of a successful run of the host with `args`
+fn report(args: &[&str]) -> Value {
+    let output = run(args);
+    assert!(
+        output.status.success(),
+        "{args:?} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+#[test]
+fn test_generate_prove_verify() {
+    let dir = temp_dir("prove");
+    let path = |name: &str| dir.join(name).to_str().unwrap().to_string();
+    let (data, inputs, proof) = (path("data.bin"), path("inputs.bin"), path("proof.bin"));
+
+    let generated = report(&[
+        "generate",
+        "--validators",
+        "2",
+        "--tree-height",
+        "3",
+        "--output",
+        &data,
+        "--public-inputs",
+        &inputs,
+    ]);
+    assert_eq!(generated["validators"], 2);
+
+    let executed = report(&["execute", "--input", &data]);
+    assert!(executed["total_cycles"].as_u64().unwrap() > 0);
+
+    let proven = report(&["prove", "--input", &data, "--output", &proof]);
+    assert_eq!(proven["mode"], "core");
+
+    let verified = report(&["verify", "--proof", &proof, "--public-inputs", &inputs]);
+    assert_eq!(verified["verified"], true);
+
+    // Validators of another seed are not the ones the proof commits
+    let other_data = path("other_data.bin");
+    let other_inputs = path("other_inputs.bin");
+    report(&[
+        "generate",
+        "--validators",
+        "2",
+        "--tree-height",
+        "3",
+        "--seed",
+        "1",
+        "--output",
+        &other_data,
+        "--public-inputs",
+        &other_inputs,
+    ]);
+    let output = run(&[
+        "verify",
+        "--proof",
+        &proof,
+        "--public-inputs",
+        &other_inputs,
+    ]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("other public inputs"));
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_evm_proof() {
+    let dir = temp_dir("evm");
+    let path = |name: &str| dir.join(name).to_str().unwrap().to_string();
+    let (data, proof) = (path("data.bin"), path("proof.bin"));
+    let (evm_proof, evm_vk) = (path("evm_proof.bin"), path("evm_vk.txt"));
+    report(&[
+        "generate",
+        "--validators",
+        "2",
+        "--tree-height",
+        "3",
+        "--output",
+        &data,
+    ]);
+
+    let proven = report(&[
+        "prove",
+        "--input",
+        &data,
+        "--output",
+        &proof,
+        "--mode",
+        "groth16",
+        "--evm-proof",
+        &evm_proof,
+        "--evm-vk",
+        &evm_vk,
+    ]);
+    assert_eq!(proven["mode"], "groth16");
+    assert!(std::path::Path::new(&evm_proof).exists());
+    assert_eq!(std::fs::read_to_string(&evm_vk).unwrap(), proven["vk_hash"]);
+
+    // The fixture holds a Groth16 proof for the verifying key of the guest
+    let fixture = path("fixture.json");
+    let proven = report(&[
+        "prove", "--input", &data, "--output", &proof, "--evm", &fixture,
+    ]);
+    assert_eq!(proven["mode"], "groth16");
+    let fixture: Value = serde_json::from_slice(&std::fs::read(&fixture).unwrap()).unwrap();
+    assert_eq!(fixture["vkey"], proven["vk_hash"]);
+    assert!(fixture["publicValues"].as_str().unwrap().starts_with("0x"));
+
+    // Core proofs are not verified on-chain
+    let output = run(&[
+        "prove",
+        "--input",
+        &data,
+        "--output",
+        &proof,
+        "--evm-proof",
+        &evm_proof,
+        "--evm-vk",
+        &evm_vk,
+    ]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("not verified on-chain"));
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
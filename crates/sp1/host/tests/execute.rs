@@ -0,0 +1,116 @@
+// Copyright 2025 Irreducible Inc.
+//! Executing the guest without proving it.
+
+use leansig_core::spec::{Spec, SPEC_2};
+use leansig_shared::{
+    corrupt, create_multi_epoch_test_data, AggregateJournal, CorruptionKind, DigestJournal,
+    FailMode, GuestInput, TestDataBuilder, ThresholdJournal, WeightedJournal,
+};
+use sp1_host::{execute, execute_xmss_aggregate, ELF};
+use sp1_sdk::{ProverClient, SP1Stdin};
+
+#[test]
+fn test_execute_xmss_aggregate() {
+    let execute_validators = |num_validators| {
+        let test_data = TestDataBuilder::new()
+            .validators(num_validators)
+            .tree_height(4)
+            .build();
+        let stats = execute_xmss_aggregate(&test_data).unwrap();
+        assert_eq!(
+            stats.journal,
+            AggregateJournal::new(&test_data.public_inputs, true)
+        );
+        stats
+    };
+    // Every validator signature is verified in the guest
+    assert!(execute_validators(4).total_cycles > execute_validators(2).total_cycles);
+}
+
+#[test]
+fn test_commit_result() {
+    let test_data = TestDataBuilder::new().validators(3).tree_height(3).build();
+    let stats = execute(&test_data, FailMode::CommitResult).unwrap();
+    assert!(stats.journal.verification_result);
+    assert_eq!(stats.journal.validator_results, Some(vec![true; 3]));
+
+    // A corrupted aggregate runs to completion, and commits the validator that failed
+    let corrupted = corrupt(&test_data, CorruptionKind::WrongNonce { validator: 2 });
+    let stats = execute(&corrupted, FailMode::CommitResult).unwrap();
+    assert!(!stats.journal.verification_result);
+    assert_eq!(
+        stats.journal.validator_results,
+        Some(vec![true, true, false])
+    );
+    assert_eq!(stats.journal.first_failure(), Some(2));
+    assert_eq!(stats.journal.digest, corrupted.public_inputs.digest());
+    // The guest still rejects it by default
+    assert!(execute_xmss_aggregate(&corrupted).is_err());
+}
+
+#[test]
+fn test_unlisted_spec() {
+    // The signatures are valid, but under a spec that is not on the allow list
+    let unlisted = Spec {
+        target_sum: SPEC_2.target_sum + 1,
+        ..SPEC_2
+    };
+    let test_data = TestDataBuilder::new()
+        .validators(2)
+        .spec(unlisted)
+        .tree_height(3)
+        .build();
+    // The guest rejects the spec before it verifies anything, whatever the fail mode
+    assert!(execute_xmss_aggregate(&test_data).is_err());
+    assert!(execute(&test_data, FailMode::CommitResult).is_err());
+}
+
+#[test]
+fn test_guest_commits_digests_of_other_inputs() {
+    let client = ProverClient::from_env();
+    let execute_input = |input: &GuestInput| {
+        let mut stdin = SP1Stdin::new();
+        stdin.write(input);
+        let (public_values, _) = client.execute(ELF, &stdin).run().unwrap();
+        public_values
+    };
+    let test_data = TestDataBuilder::new().validators(2).tree_height(3).build();
+    let public_inputs = &test_data.public_inputs;
+
+    let mut public_values = execute_input(&GuestInput::Threshold {
+        test_data: test_data.clone(),
+        threshold: 1,
+    });
+    assert_eq!(
+        public_values.read::<ThresholdJournal>(),
+        ThresholdJournal::new(public_inputs, 1, 2, true)
+    );
+
+    let weights = vec![3, 4];
+    let mut public_values = execute_input(&GuestInput::Weighted {
+        test_data: test_data.clone(),
+        weights: weights.clone(),
+        min_weight: 5,
+    });
+    assert_eq!(
+        public_values.read::<WeightedJournal>(),
+        WeightedJournal::new(public_inputs, &weights, 5, 7, true)
+    );
+
+    let multi_message = TestDataBuilder::new()
+        .validators(2)
+        .tree_height(3)
+        .build_multi_message();
+    let mut public_values = execute_input(&GuestInput::MultiMessage(multi_message.clone()));
+    assert_eq!(
+        public_values.read::<DigestJournal>(),
+        DigestJournal::multi_message(&multi_message.public_inputs, true)
+    );
+
+    let multi_epoch = create_multi_epoch_test_data(SPEC_2, 3, 10000, &[0, 2]);
+    let mut public_values = execute_input(&GuestInput::MultiEpoch(multi_epoch.clone()));
+    assert_eq!(
+        public_values.read::<DigestJournal>(),
+        DigestJournal::multi_epoch(&multi_epoch.public_inputs, true)
+    );
+}
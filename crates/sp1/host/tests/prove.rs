@@ -0,0 +1,103 @@
+// Copyright 2025 Irreducible Inc.
+//! Proving with the mock prover, which runs the guest without generating the actual proof.
+
+use leansig_shared::{corrupt, CorruptionKind, FailMode, TestDataBuilder};
+use sha2::{Digest, Sha256};
+use sp1_host::{
+    check_public_values, decode_public_values, encode_public_values, prove, EvmFixture, ProofMode,
+    ProofSummary, ProveConfig, ProverBackend, VerifyError,
+};
+use sp1_sdk::SP1Proof;
+
+#[test]
+fn test_public_values_digest() {
+    std::env::set_var("SP1_PROVER", "mock");
+    let test_data = TestDataBuilder::new().validators(2).tree_height(3).build();
+    let result = prove(&test_data, &ProveConfig::new(ProofMode::Core)).unwrap();
+    assert_eq!(result.public_inputs, test_data.public_inputs);
+    assert_eq!(result.backend, ProverBackend::Local);
+
+    let digest: [u8; 32] = Sha256::digest(result.proof.public_values.as_slice()).into();
+    assert_eq!(result.public_values_digest, digest);
+    // The public values only commit the digest of the public inputs and the result
+    assert_eq!(result.proof.public_values.as_slice().len(), 33);
+    assert!(result.vk_hash.starts_with("0x"));
+
+    // The summary holds the same identifiers, and round trips through JSON
+    let summary = result.summary();
+    assert_eq!(summary.vk_hash, result.vk_hash);
+    assert_eq!(summary.public_values_digest.len(), 64);
+    let json = serde_json::to_string(&summary).unwrap();
+    assert_eq!(
+        serde_json::from_str::<ProofSummary>(&json).unwrap(),
+        summary
+    );
+}
+
+#[test]
+fn test_proof_modes() {
+    std::env::set_var("SP1_PROVER", "mock");
+    let test_data = TestDataBuilder::new().validators(2).tree_height(3).build();
+    for mode in ProofMode::ALL {
+        let result = prove(&test_data, &ProveConfig::new(mode)).unwrap();
+        assert_eq!(result.mode, mode);
+        // Every mode produces the proof of its own SP1 mode
+        let matches_mode = match mode {
+            ProofMode::Core => matches!(result.proof.proof, SP1Proof::Core(_)),
+            ProofMode::Compressed => matches!(result.proof.proof, SP1Proof::Compressed(_)),
+            ProofMode::Groth16 => matches!(result.proof.proof, SP1Proof::Groth16(_)),
+            ProofMode::Plonk => matches!(result.proof.proof, SP1Proof::Plonk(_)),
+        };
+        assert!(matches_mode, "{} proof of another mode", mode.name());
+        assert_eq!(result.summary().mode, mode.name());
+    }
+}
+
+#[test]
+fn test_evm_fixture() {
+    std::env::set_var("SP1_PROVER", "mock");
+    let test_data = TestDataBuilder::new().validators(2).tree_height(3).build();
+    let result = prove(&test_data, &ProveConfig::new(ProofMode::Groth16)).unwrap();
+    let fixture = result.evm_fixture().unwrap();
+    assert_eq!(fixture.vkey, result.vk_hash);
+
+    let path = std::env::temp_dir().join(format!("leansig-evm-fixture-{}", std::process::id()));
+    fixture.write_to_file(&path).unwrap();
+    let decoded = EvmFixture::read_from_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(decoded, fixture);
+
+    // The public values are the bytes the guest committed, in the layout of the helper
+    let public_values = decoded.public_values_bytes().unwrap();
+    assert_eq!(public_values, result.proof.public_values.as_slice());
+    assert_eq!(
+        public_values,
+        encode_public_values(&test_data.public_inputs, true)
+    );
+    check_public_values(&public_values, &test_data.public_inputs).unwrap();
+
+    // Only proofs verified on-chain have a fixture
+    let result = prove(&test_data, &ProveConfig::new(ProofMode::Compressed)).unwrap();
+    assert_eq!(result.evm_fixture(), None);
+}
+
+#[test]
+fn test_prove_failed_aggregate() {
+    std::env::set_var("SP1_PROVER", "mock");
+    let test_data = TestDataBuilder::new().validators(2).tree_height(3).build();
+    let corrupted = corrupt(&test_data, CorruptionKind::WrongNonce { validator: 1 });
+    let config = ProveConfig {
+        fail_mode: FailMode::CommitResult,
+        ..ProveConfig::new(ProofMode::Core)
+    };
+    // The failure is proven rather than aborting the proof
+    let result = prove(&corrupted, &config).unwrap();
+    let public_values = result.proof.public_values.as_slice();
+    let journal = decode_public_values(public_values, &corrupted.public_inputs).unwrap();
+    assert!(!journal.verification_result);
+    assert_eq!(journal.first_failure(), Some(1));
+    assert!(matches!(
+        check_public_values(public_values, &corrupted.public_inputs),
+        Err(VerifyError::VerificationFailed)
+    ));
+}
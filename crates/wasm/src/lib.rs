@@ -0,0 +1,369 @@
+// Copyright 2025 Irreducible Inc.
+//! Verification of leansig signatures in the browser, through `wasm-bindgen`.
+//!
+//! ```text
+//! wasm-pack build crates/wasm --target web
+//! ```
+//!
+//! The crate builds on the `no_std` verification path of `leansig-core`, so it compiles for
+//! `wasm32-unknown-unknown` without any source of randomness. Signatures are passed in the
+//! fixed-layout encoding of [`Signature::to_bytes`], while specs are passed as JSON and hashes,
+//! params and messages as hex. Malformed inputs throw a JS `Error` with the reason, and a
+//! well-formed signature that does not verify returns `false`.
+
+use std::{fmt, str::FromStr};
+
+use js_sys::Uint8Array;
+use leansig_core::{
+    AggregatedSignature, AggregatedVerifier, DecodeError, Message, Param, Signature,
+    ValidatorSignature,
+    hash::Hash,
+    hex::ParseHexError,
+    registry::ValidatorRegistry,
+    spec::{Spec, SpecError},
+};
+use wasm_bindgen::prelude::*;
+
+/// The reasons the inputs of a verification can be rejected before verifying anything.
+#[derive(Debug)]
+pub enum InputError {
+    /// The spec is not the JSON of a [`Spec`].
+    SpecJson(serde_json::Error),
+    /// The spec parses but does not define a usable scheme.
+    InvalidSpec(SpecError),
+    /// An input is not valid hex, or does not have the length of its type.
+    InvalidHex {
+        name: &'static str,
+        error: ParseHexError,
+    },
+    /// A param does not have the length of the params of the spec.
+    ParamLength { expected: usize, actual: usize },
+    /// No tree height gives a signature of this length under the spec.
+    SignatureLength { len: usize },
+    /// The signature has the length of a signature, but does not decode.
+    InvalidSignature(DecodeError),
+    /// A validator index is past the end of the validator set.
+    UnknownValidator { index: u32, validators: usize },
+    /// Root `index` of the validator set repeats an earlier one.
+    DuplicateValidator { index: usize },
+    /// Two lists that go together have different lengths.
+    LengthMismatch {
+        name: &'static str,
+        expected: usize,
+        actual: usize,
+    },
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::SpecJson(err) => write!(f, "invalid spec JSON: {err}"),
+            InputError::InvalidSpec(err) => write!(f, "invalid spec: {err}"),
+            InputError::InvalidHex { name, error } => write!(f, "invalid {name}: {error}"),
+            InputError::ParamLength { expected, actual } => {
+                write!(f, "param has {actual} bytes, the spec expects {expected}")
+            }
+            InputError::SignatureLength { len } => {
+                write!(f, "no tree height gives a signature of {len} bytes")
+            }
+            InputError::InvalidSignature(err) => write!(f, "invalid signature: {err}"),
+            InputError::UnknownValidator { index, validators } => {
+                write!(
+                    f,
+                    "validator {index} is not in the set of {validators} validators"
+                )
+            }
+            InputError::DuplicateValidator { index } => {
+                write!(f, "validator root {index} repeats an earlier one")
+            }
+            InputError::LengthMismatch {
+                name,
+                expected,
+                actual,
+            } => write!(f, "expected {expected} {name}, got {actual}"),
+        }
+    }
+}
+
+impl std::error::Error for InputError {}
+
+fn parse_spec(spec_json: &str) -> Result<Spec, InputError> {
+    let spec: Spec = serde_json::from_str(spec_json).map_err(InputError::SpecJson)?;
+    spec.validate().map_err(InputError::InvalidSpec)?;
+    Ok(spec)
+}
+
+fn parse_hex<T: FromStr<Err = ParseHexError>>(
+    name: &'static str,
+    hex: &str,
+) -> Result<T, InputError> {
+    hex.parse()
+        .map_err(|error| InputError::InvalidHex { name, error })
+}
+
+fn parse_param(spec: &Spec, hex: &str) -> Result<Param, InputError> {
+    let param: Param = parse_hex("param", hex)?;
+    if param.as_ref().len() != spec.param_len {
+        return Err(InputError::ParamLength {
+            expected: spec.param_len,
+            actual: param.as_ref().len(),
+        });
+    }
+    Ok(param)
+}
+
+/// Decodes a signature of whatever tree height its length implies.
+fn decode_signature(spec: &Spec, bytes: &[u8]) -> Result<Signature, InputError> {
+    let height = Signature::height_for_encoded_len(spec, bytes.len())
+        .ok_or(InputError::SignatureLength { len: bytes.len() })?;
+    Signature::from_bytes(bytes, spec, height).map_err(InputError::InvalidSignature)
+}
+
+/// Verifies a signature encoded by [`Signature::to_bytes`] against the root and param of its
+/// signer, see [`leansig_core::verify_signature`]
+///
+/// # Errors
+///
+/// Returns an error if an input is malformed, but `Ok(false)` for a signature that does not
+/// verify.
+pub fn verify_signature(
+    spec_json: &str,
+    param_hex: &str,
+    message_hex: &str,
+    signature_bytes: &[u8],
+    root_hex: &str,
+) -> Result<bool, InputError> {
+    let spec = parse_spec(spec_json)?;
+    let param = parse_param(&spec, param_hex)?;
+    let message: Message = parse_hex("message", message_hex)?;
+    let root: Hash = parse_hex("root", root_hex)?;
+    let signature = decode_signature(&spec, signature_bytes)?;
+    Ok(leansig_core::verify_signature(
+        &spec, &param, &message, &signature, &root,
+    ))
+}
+
+/// Verifies the signatures of validators `validator_indices` of the validator set given by
+/// `roots_hex` and `params_hex`, see [`AggregatedVerifier::verify`]
+///
+/// Every signature is encoded by [`Signature::to_bytes`] and signed at the epoch of the leaf its
+/// proof opens.
+///
+/// # Errors
+///
+/// Returns an error if an input is malformed, the lists don't have matching lengths, a root of
+/// the set repeats or an index is out of the set, but `Ok(false)` for an aggregate that does not
+/// verify.
+pub fn verify_aggregate(
+    spec_json: &str,
+    message_hex: &str,
+    roots_hex: &[String],
+    params_hex: &[String],
+    validator_indices: &[u32],
+    signatures: &[Vec<u8>],
+) -> Result<bool, InputError> {
+    let spec = parse_spec(spec_json)?;
+    let message: Message = parse_hex("message", message_hex)?;
+    if params_hex.len() != roots_hex.len() {
+        return Err(InputError::LengthMismatch {
+            name: "params",
+            expected: roots_hex.len(),
+            actual: params_hex.len(),
+        });
+    }
+    if signatures.len() != validator_indices.len() {
+        return Err(InputError::LengthMismatch {
+            name: "signatures",
+            expected: validator_indices.len(),
+            actual: signatures.len(),
+        });
+    }
+    let validators = (roots_hex.iter().zip(params_hex))
+        .map(|(root, param)| Ok((parse_hex("root", root)?, parse_param(&spec, param)?)))
+        .collect::<Result<Vec<(Hash, Param)>, InputError>>()?;
+    // A repeated root would be dropped from the registry, shifting the indices of the later
+    // validators away from their positions in the set
+    let mut registry = ValidatorRegistry::default();
+    for (index, (root, param)) in validators.iter().enumerate() {
+        if !registry.insert(*root, param.clone(), 1) {
+            return Err(InputError::DuplicateValidator { index });
+        }
+    }
+
+    let signatures = (validator_indices.iter().zip(signatures))
+        .map(|(&index, bytes)| {
+            let signature = decode_signature(&spec, bytes)?;
+            let (root, param) =
+                (validators.get(index as usize)).ok_or(InputError::UnknownValidator {
+                    index,
+                    validators: validators.len(),
+                })?;
+            Ok(ValidatorSignature {
+                epoch: signature.hash_tree_proof.leaf_index() as u64,
+                signature: signature.into(),
+                xmss_root: *root,
+                param: param.clone(),
+                validator_index: Some(index),
+            })
+        })
+        .collect::<Result<Vec<_>, InputError>>()?;
+
+    let verifier = AggregatedVerifier::new(registry, spec);
+    Ok(verifier.verify(&message, &AggregatedSignature::new(signatures)))
+}
+
+/// [`verify_signature`] for JavaScript, throwing an `Error` on malformed inputs
+#[wasm_bindgen]
+pub fn verify_signature_js(
+    spec_json: &str,
+    param_hex: &str,
+    message_hex: &str,
+    signature_bytes: &[u8],
+    root_hex: &str,
+) -> Result<bool, JsError> {
+    Ok(verify_signature(
+        spec_json,
+        param_hex,
+        message_hex,
+        signature_bytes,
+        root_hex,
+    )?)
+}
+
+/// [`verify_aggregate`] for JavaScript, throwing an `Error` on malformed inputs
+#[wasm_bindgen]
+pub fn verify_aggregate_js(
+    spec_json: &str,
+    message_hex: &str,
+    roots_hex: Vec<String>,
+    params_hex: Vec<String>,
+    validator_indices: Vec<u32>,
+    signatures: Vec<Uint8Array>,
+) -> Result<bool, JsError> {
+    let signatures: Vec<_> = signatures.iter().map(Uint8Array::to_vec).collect();
+    Ok(verify_aggregate(
+        spec_json,
+        message_hex,
+        &roots_hex,
+        &params_hex,
+        &validator_indices,
+        &signatures,
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use super::*;
+
+    /// Signatures of two validators signed natively, see `examples/test_vector.rs` of
+    /// `leansig-core`
+    const VECTOR: &str = include_str!("../tests/vector.json");
+
+    struct Vector {
+        spec: String,
+        message: String,
+        roots: Vec<String>,
+        params: Vec<String>,
+        signatures: Vec<Vec<u8>>,
+    }
+
+    fn from_hex(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    fn vector() -> Vector {
+        let vector: Value = serde_json::from_str(VECTOR).unwrap();
+        let validators = vector["validators"].as_array().unwrap();
+        let field = |name: &str| {
+            (validators.iter())
+                .map(|validator| validator[name].as_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+        Vector {
+            spec: vector["spec"].to_string(),
+            message: vector["message"].as_str().unwrap().to_string(),
+            roots: field("root"),
+            params: field("param"),
+            signatures: field("signature").iter().map(|hex| from_hex(hex)).collect(),
+        }
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        let v = vector();
+        let verify = |message: &str, signature: &[u8], root: &str| {
+            verify_signature(&v.spec, &v.params[0], message, signature, root)
+        };
+        assert!(verify(&v.message, &v.signatures[0], &v.roots[0]).unwrap());
+        assert!(!verify(&v.message, &v.signatures[0], &v.roots[1]).unwrap());
+        assert!(!verify(&"00".repeat(32), &v.signatures[0], &v.roots[0]).unwrap());
+        let mut tampered = v.signatures[0].clone();
+        tampered[40] ^= 1;
+        assert!(!verify(&v.message, &tampered, &v.roots[0]).unwrap());
+
+        let err = verify(&v.message, &v.signatures[0][1..], &v.roots[0]).unwrap_err();
+        assert!(matches!(err, InputError::SignatureLength { .. }));
+        let err = verify(&v.message, &v.signatures[0], "0xzz").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid root: invalid hex character 'z' at position 2"
+        );
+        let err = verify_signature(
+            "{}",
+            &v.params[0],
+            &v.message,
+            &v.signatures[0],
+            &v.roots[0],
+        )
+        .unwrap_err();
+        assert!(err.to_string().starts_with("invalid spec JSON"));
+        let err =
+            verify_signature(&v.spec, "00", &v.message, &v.signatures[0], &v.roots[0]).unwrap_err();
+        assert!(matches!(err, InputError::ParamLength { actual: 1, .. }));
+    }
+
+    #[test]
+    fn test_verify_aggregate() {
+        let v = vector();
+        let verify = |indices: &[u32], signatures: &[Vec<u8>]| {
+            verify_aggregate(
+                &v.spec, &v.message, &v.roots, &v.params, indices, signatures,
+            )
+        };
+        assert!(verify(&[0, 1], &v.signatures).unwrap());
+        assert!(verify(&[1], &v.signatures[1..]).unwrap());
+        assert!(!verify(&[1, 0], &v.signatures).unwrap());
+
+        let err = verify(&[0, 2], &v.signatures).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "validator 2 is not in the set of 2 validators"
+        );
+        let err = verify(&[0], &v.signatures).unwrap_err();
+        assert!(matches!(
+            err,
+            InputError::LengthMismatch {
+                name: "signatures",
+                ..
+            }
+        ));
+        let err = verify_aggregate(&v.spec, &v.message, &v.roots, &[], &[], &[]).unwrap_err();
+        assert_eq!(err.to_string(), "expected 2 params, got 0");
+
+        // A repeated root is rejected rather than shifting the indices of the later validators
+        let roots = [v.roots[0].clone(), v.roots[0].clone(), v.roots[1].clone()];
+        let params = [
+            v.params[0].clone(),
+            v.params[0].clone(),
+            v.params[1].clone(),
+        ];
+        let err = verify_aggregate(&v.spec, &v.message, &roots, &params, &[0, 2], &v.signatures)
+            .unwrap_err();
+        assert!(matches!(err, InputError::DuplicateValidator { index: 1 }));
+    }
+}
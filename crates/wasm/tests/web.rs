@@ -0,0 +1,66 @@
+// Copyright 2025 Irreducible Inc.
+//! Runs the JS bindings in a JS engine, with `wasm-pack test --node crates/wasm`.
+#![cfg(target_arch = "wasm32")]
+
+use js_sys::Uint8Array;
+use leansig_wasm::{verify_aggregate_js, verify_signature_js};
+use serde_json::Value;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+/// Signatures of two validators signed natively, see `examples/test_vector.rs` of `leansig-core`
+const VECTOR: &str = include_str!("vector.json");
+
+fn from_hex(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+fn validator_field(vector: &Value, name: &str) -> Vec<String> {
+    (vector["validators"].as_array().unwrap().iter())
+        .map(|validator| validator[name].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[wasm_bindgen_test]
+fn test_verify_signature_js() {
+    let vector: Value = serde_json::from_str(VECTOR).unwrap();
+    let spec = vector["spec"].to_string();
+    let message = vector["message"].as_str().unwrap();
+    let params = validator_field(&vector, "param");
+    let roots = validator_field(&vector, "root");
+    let signature = from_hex(&validator_field(&vector, "signature")[0]);
+
+    assert!(verify_signature_js(&spec, &params[0], message, &signature, &roots[0]).unwrap());
+    assert!(!verify_signature_js(&spec, &params[0], message, &signature, &roots[1]).unwrap());
+    // Malformed inputs throw instead of trapping
+    assert!(verify_signature_js(&spec, &params[0], message, &signature[1..], &roots[0]).is_err());
+    assert!(verify_signature_js("{}", &params[0], message, &signature, &roots[0]).is_err());
+}
+
+#[wasm_bindgen_test]
+fn test_verify_aggregate_js() {
+    let vector: Value = serde_json::from_str(VECTOR).unwrap();
+    let spec = vector["spec"].to_string();
+    let message = vector["message"].as_str().unwrap();
+    let signatures = || {
+        (validator_field(&vector, "signature").iter())
+            .map(|hex| Uint8Array::from(&from_hex(hex)[..]))
+            .collect::<Vec<_>>()
+    };
+    let verify = |indices: Vec<u32>| {
+        verify_aggregate_js(
+            &spec,
+            message,
+            validator_field(&vector, "root"),
+            validator_field(&vector, "param"),
+            indices,
+            signatures(),
+        )
+    };
+
+    assert!(verify(vec![0, 1]).unwrap());
+    assert!(!verify(vec![1, 0]).unwrap());
+    assert!(verify(vec![0, 2]).is_err());
+}